@@ -0,0 +1,108 @@
+-- Runs on SQLite and PostgreSQL alike, so only portable types are used:
+-- timestamps are RFC 3339 text, flags are 0/1 integers and role lists are
+-- JSON arrays.
+
+CREATE TABLE cameras (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    source_url TEXT NOT NULL,
+    permanent_stream_url TEXT,
+    -- the stream metadata is present when stream_probed_at is set
+    stream_video_codec TEXT,
+    stream_width BIGINT,
+    stream_height BIGINT,
+    stream_framerate DOUBLE PRECISION,
+    stream_has_audio BIGINT,
+    stream_audio_codec TEXT,
+    stream_probed_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- the primary key serves the per-user queries
+CREATE TABLE camera_temp_blockings (
+    user_id TEXT NOT NULL,
+    camera_id TEXT NOT NULL,
+    start_date TEXT,
+    end_date TEXT NOT NULL,
+    reason TEXT,
+    share_reason BIGINT NOT NULL DEFAULT 0,
+    created_by TEXT,
+    created_at TEXT,
+    updated_at TEXT,
+    PRIMARY KEY (user_id, camera_id)
+);
+
+CREATE INDEX camera_temp_blockings_by_camera ON camera_temp_blockings (camera_id, user_id);
+
+CREATE TABLE recurring_camera_blockings (
+    user_id TEXT NOT NULL,
+    id TEXT NOT NULL,
+    camera_id TEXT NOT NULL,
+    rrule TEXT NOT NULL,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    timezone TEXT NOT NULL,
+    PRIMARY KEY (user_id, id)
+);
+
+CREATE INDEX recurring_camera_blockings_by_camera ON recurring_camera_blockings (camera_id, id);
+
+CREATE TABLE camera_privacy_shutters (
+    camera_id TEXT PRIMARY KEY,
+    start_date TEXT,
+    end_date TEXT NOT NULL,
+    reason TEXT,
+    share_reason BIGINT NOT NULL DEFAULT 0,
+    created_by TEXT,
+    created_at TEXT
+);
+
+CREATE TABLE users (
+    user_id TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    name TEXT NOT NULL,
+    roles TEXT NOT NULL DEFAULT '[]',
+    granted_roles TEXT NOT NULL DEFAULT '[]',
+    revoked_roles TEXT NOT NULL DEFAULT '[]',
+    deactivated_at TEXT,
+    removed_at TEXT,
+    synced_at TEXT
+);
+
+CREATE TABLE temp_stream_sessions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    camera_id TEXT NOT NULL,
+    stream_id TEXT NOT NULL,
+    stream_url TEXT NOT NULL,
+    hls_url TEXT,
+    whep_url TEXT,
+    quality TEXT NOT NULL,
+    token TEXT NOT NULL,
+    privacy_shutter_exempt BIGINT NOT NULL DEFAULT 0,
+    issued_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX temp_stream_sessions_by_user ON temp_stream_sessions (user_id, id);
+
+-- id starts with the blocking's start in UTC, so ordering by it is
+-- chronological
+CREATE TABLE blocking_history (
+    id TEXT PRIMARY KEY,
+    camera_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    start_date TEXT,
+    end_date TEXT NOT NULL,
+    end_date_utc TEXT NOT NULL,
+    reason TEXT,
+    created_by TEXT,
+    created_at TEXT,
+    archived_at TEXT NOT NULL,
+    archive_reason TEXT NOT NULL,
+    archived_by TEXT
+);
+
+CREATE INDEX blocking_history_by_camera ON blocking_history (camera_id, id);
+CREATE INDEX blocking_history_by_user ON blocking_history (user_id, id);
@@ -0,0 +1,3 @@
+-- active sessions are counted per camera and overall by expiry
+CREATE INDEX temp_stream_sessions_by_camera ON temp_stream_sessions (camera_id, expires_at);
+CREATE INDEX temp_stream_sessions_by_expiry ON temp_stream_sessions (expires_at);
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use crate::layers::{
+    business::shared::{
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        recurrence::{next_free_time, RecurringSchedule},
+    },
+    ewm::main_database::qc_collection::{
+        camera_temp_blocking_qc_collection::{
+            CameraPrivacyShutter, CameraTempBlocking, ICameraTempBlockingQCCollection,
+            RecurringCameraBlocking,
+        },
+        temp_stream_session_qc_collection::TempStreamSession,
+    },
+};
+
+/// A blocking is in force from its start date, or right away without one,
+/// until its end date has passed; started blockings with an unreadable end
+/// date are treated as active.
+pub fn is_blocking_active(blocking: &CameraTempBlocking, now: DateTime<Utc>) -> bool {
+    let started = start_date(blocking.start_date.as_deref()).is_none_or(|start| start <= now);
+    let ended = DateTime::parse_from_rfc3339(&blocking.end_date)
+        .is_ok_and(|end| end.with_timezone(&Utc) <= now);
+    started && !ended
+}
+
+/// The start of a blocking or shutter window; a missing or unreadable start
+/// means the window is already running.
+fn start_date(start_date: Option<&str>) -> Option<DateTime<Utc>> {
+    start_date
+        .and_then(|start| DateTime::parse_from_rfc3339(start).ok())
+        .map(|start| start.with_timezone(&Utc))
+}
+
+/// The reason of an active blocking, if the admin chose to share it with the
+/// blocked user.
+pub fn shared_reason(blocking: &CameraTempBlocking, now: DateTime<Utc>) -> Option<String> {
+    blocking
+        .reason
+        .clone()
+        .filter(|_| blocking.share_reason && is_blocking_active(blocking, now))
+}
+
+/// Users with this role see cameras behind a privacy shutter.
+pub const PRIVACY_SHUTTER_EXEMPT_ROLE: &str = "Admin";
+
+pub fn is_exempt_from_privacy_shutter(roles: &[String]) -> bool {
+    roles.iter().any(|role| role == PRIVACY_SHUTTER_EXEMPT_ROLE)
+}
+
+/// A shutter is closed from its start date, or right away without one,
+/// until its end date. Shutters with an unreadable end date are treated as
+/// closed, like one-off blockings.
+pub fn is_privacy_shutter_closed(shutter: &CameraPrivacyShutter, now: DateTime<Utc>) -> bool {
+    let started = start_date(shutter.start_date.as_deref()).is_none_or(|start| start <= now);
+    let ended = DateTime::parse_from_rfc3339(&shutter.end_date)
+        .is_ok_and(|end| end.with_timezone(&Utc) <= now);
+    started && !ended
+}
+
+/// The reason shown to blocked users: the one of their own blocking if
+/// shared, otherwise the one of a closed privacy shutter if shared.
+pub fn unavailable_reason(
+    blocking: Option<&CameraTempBlocking>,
+    privacy_shutter: Option<&CameraPrivacyShutter>,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    blocking.and_then(|b| shared_reason(b, now)).or_else(|| {
+        privacy_shutter
+            .filter(|shutter| shutter.share_reason && is_privacy_shutter_closed(shutter, now))
+            .and_then(|shutter| shutter.reason.clone())
+    })
+}
+
+/// When the user may use the camera again given its one-off blocking, the
+/// camera's privacy shutter and recurring blockings, or `None` if they may
+/// use it now. Pass no shutter for users exempt from it. The result is an
+/// RFC 3339 timestamp, except for blockings with an unreadable end date,
+/// whose raw end date is returned.
+pub fn available_at(
+    blocking: Option<&CameraTempBlocking>,
+    privacy_shutter: Option<&CameraPrivacyShutter>,
+    recurring: &[RecurringCameraBlocking],
+    now: DateTime<Utc>,
+) -> Option<String> {
+    // (start, end) of the one-off blocking and the shutter; a missing start
+    // means the window is already running
+    let mut windows: Vec<(Option<DateTime<Utc>>, DateTime<Utc>)> = Vec::new();
+    if let Some(blocking) = blocking {
+        let start = start_date(blocking.start_date.as_deref());
+        match DateTime::parse_from_rfc3339(&blocking.end_date) {
+            Ok(end_date) => windows.push((start, end_date.with_timezone(&Utc))),
+            Err(_) if is_blocking_active(blocking, now) => return Some(blocking.end_date.clone()),
+            Err(_) => {}
+        }
+    }
+    if let Some(shutter) = privacy_shutter {
+        let start = start_date(shutter.start_date.as_deref());
+        match DateTime::parse_from_rfc3339(&shutter.end_date) {
+            Ok(end_date) => windows.push((start, end_date.with_timezone(&Utc))),
+            Err(_) if is_privacy_shutter_closed(shutter, now) => return Some(shutter.end_date.clone()),
+            Err(_) => {}
+        }
+    }
+    let schedules: Vec<RecurringSchedule> = recurring
+        .iter()
+        .filter_map(|r| {
+            RecurringSchedule::parse(&r.rrule, &r.start_time, &r.end_time, &r.timezone)
+                .inspect_err(|err| tracing::error!("skipping invalid recurring blocking {}: {:?}", r.id, err))
+                .ok()
+        })
+        .collect();
+
+    // blockings, the shutter and schedules can hand over to each other, so
+    // keep moving forward until none of them covers the moment; bounded like
+    // next_free_time for schedules that never end
+    let mut at = now;
+    for _ in 0..8 {
+        let mut moved = false;
+        for (start, end) in &windows {
+            if start.is_none_or(|start| start <= at) && *end > at {
+                at = *end;
+                moved = true;
+            }
+        }
+        if let Some(free) = next_free_time(&schedules, at) {
+            at = free;
+            moved = true;
+        }
+        if !moved {
+            break;
+        }
+    }
+    (at > now).then(|| at.to_rfc3339())
+}
+
+/// Fails with a business error on `camera_id` while the user is blocked from
+/// the camera by a one-off or recurring blocking, or, unless exempt, by the
+/// camera's privacy shutter.
+pub async fn ensure_camera_not_blocked<IICameraTempBlockingQCCollection>(
+    camera_temp_blocking_qc_collection: &IICameraTempBlockingQCCollection,
+    camera_id: &str,
+    user_id: &str,
+    exempt_from_privacy_shutter: bool,
+) -> Result<(), UseCaseError>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    let to_use_case_error = |err: String| {
+        tracing::error!("{}", err);
+        UseCaseError::InternalDependencyError(InternalDependencyError::new(
+            "failed to load camera temp blocking from database".to_string(),
+            err,
+        ))
+    };
+    let blocking = camera_temp_blocking_qc_collection
+        .get_temp_blocking(camera_id, user_id)
+        .await
+        .map_err(|err| to_use_case_error(format!("{:?}", err)))?;
+    let privacy_shutter = match exempt_from_privacy_shutter {
+        true => None,
+        false => camera_temp_blocking_qc_collection
+            .get_privacy_shutter(camera_id)
+            .await
+            .map_err(|err| to_use_case_error(format!("{:?}", err)))?,
+    };
+    let recurring: Vec<RecurringCameraBlocking> = camera_temp_blocking_qc_collection
+        .list_recurring_blockings_for_user(user_id)
+        .await
+        .map_err(|err| to_use_case_error(format!("{:?}", err)))?
+        .into_iter()
+        .filter(|r| r.camera_id == camera_id)
+        .collect();
+
+    let now = Utc::now();
+    match available_at(blocking.as_ref(), privacy_shutter.as_ref(), &recurring, now) {
+        Some(available_at) => {
+            let mut messages = vec![format!("access to this camera is blocked until {}", available_at)];
+            if let Some(reason) = unavailable_reason(blocking.as_ref(), privacy_shutter.as_ref(), now) {
+                messages.push(format!("reason: {}", reason));
+            }
+            let mut details = HashMap::new();
+            details.insert("camera_id".to_string(), messages);
+            Err(UseCaseError::BusinessError(BusinessError::new(
+                "access to the camera is temporarily blocked".to_string(),
+                details,
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Token handed to clients for a temporary stream session, in the form
+/// `<session id>.<session secret>`. The media layer sends it back so the
+/// session can be looked up and checked on every read.
+pub fn session_access_token(session: &TempStreamSession) -> String {
+    format!("{}.{}", session.id, session.token)
+}
+
+pub fn split_session_access_token(access_token: &str) -> Option<(&str, &str)> {
+    access_token
+        .split_once('.')
+        .filter(|(session_id, secret)| !session_id.is_empty() && !secret.is_empty())
+}
+
+/// Appends the session access token to a stream url as the `token` query
+/// parameter.
+pub fn with_access_token(stream_url: &str, access_token: &str) -> String {
+    match Url::parse(stream_url) {
+        Ok(mut url) => {
+            url.query_pairs_mut().append_pair("token", access_token);
+            url.to_string()
+        }
+        Err(_) => stream_url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocking(end_date: &str, reason: Option<&str>, share_reason: bool) -> CameraTempBlocking {
+        CameraTempBlocking {
+            id: "cam".to_string(),
+            camera_id: "cam".to_string(),
+            user_id: "alice".to_string(),
+            start_date: None,
+            end_date: end_date.to_string(),
+            reason: reason.map(str::to_owned),
+            share_reason,
+            created_by: Some("admin".to_string()),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_reason_is_only_shared_when_allowed_and_active() {
+        let now: DateTime<Utc> = "2025-01-01T00:00:00Z".parse().unwrap();
+        let active = "2025-01-02T00:00:00Z";
+        assert_eq!(shared_reason(&blocking(active, Some("homework"), true), now).as_deref(), Some("homework"));
+        assert_eq!(shared_reason(&blocking(active, Some("homework"), false), now), None);
+        assert_eq!(shared_reason(&blocking("2024-12-31T00:00:00Z", Some("homework"), true), now), None);
+    }
+
+    #[test]
+    fn test_available_at_follows_one_off_into_recurring() {
+        let now: DateTime<Utc> = "2025-01-14T20:00:00Z".parse().unwrap();
+        let one_off = blocking("2025-01-14T22:00:00Z", None, false);
+        let nightly = RecurringCameraBlocking {
+            id: "r1".to_string(),
+            camera_id: "cam".to_string(),
+            user_id: "alice".to_string(),
+            rrule: "FREQ=DAILY".to_string(),
+            start_time: "22:00".to_string(),
+            end_time: "07:00".to_string(),
+            timezone: "UTC".to_string(),
+        };
+        assert_eq!(
+            available_at(Some(&one_off), None, &[nightly], now).as_deref(),
+            Some("2025-01-15T07:00:00+00:00")
+        );
+        assert_eq!(available_at(Some(&blocking("soon", None, false)), None, &[], now).as_deref(), Some("soon"));
+        assert_eq!(available_at(None, None, &[], now), None);
+    }
+
+    #[test]
+    fn test_one_off_blocking_waits_for_its_start_date() {
+        let now: DateTime<Utc> = "2025-01-14T20:00:00Z".parse().unwrap();
+        let later = CameraTempBlocking {
+            start_date: Some("2025-01-14T21:00:00Z".to_string()),
+            ..blocking("2025-01-14T23:00:00Z", Some("homework"), true)
+        };
+        assert!(!is_blocking_active(&later, now));
+        assert_eq!(available_at(Some(&later), None, &[], now), None);
+        assert_eq!(shared_reason(&later, now), None);
+
+        let started_at = "2025-01-14T21:30:00Z".parse().unwrap();
+        assert!(is_blocking_active(&later, started_at));
+        assert_eq!(
+            available_at(Some(&later), None, &[], started_at).as_deref(),
+            Some("2025-01-14T23:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn test_privacy_shutter_window() {
+        let now: DateTime<Utc> = "2025-01-14T20:00:00Z".parse().unwrap();
+        let shutter = |start_date: Option<&str>| CameraPrivacyShutter {
+            camera_id: "cam".to_string(),
+            start_date: start_date.map(str::to_owned),
+            end_date: "2025-01-15T02:00:00Z".to_string(),
+            reason: Some("party".to_string()),
+            share_reason: true,
+            created_by: Some("admin".to_string()),
+            created_at: None,
+        };
+        let later = shutter(Some("2025-01-14T21:00:00Z"));
+        assert!(!is_privacy_shutter_closed(&later, now));
+        assert_eq!(available_at(None, Some(&later), &[], now), None);
+        assert_eq!(unavailable_reason(None, Some(&later), now), None);
+        // a one-off blocking running into the shutter hands over to it
+        let one_off = blocking("2025-01-14T21:30:00Z", None, false);
+        assert_eq!(
+            available_at(Some(&one_off), Some(&later), &[], now).as_deref(),
+            Some("2025-01-15T02:00:00+00:00")
+        );
+        let closed = shutter(None);
+        assert!(is_privacy_shutter_closed(&closed, now));
+        assert_eq!(unavailable_reason(None, Some(&closed), now).as_deref(), Some("party"));
+    }
+}
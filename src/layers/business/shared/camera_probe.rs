@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::{
+        errors::{BusinessError, UseCaseError},
+        validation_rules::source_url::{check_source_url, SourceUrlPolicy},
+    },
+    ewm::{
+        main_database::qc_collection::camera_qc_collection::CameraStreamMetadata,
+        rtsp_prober::{IRtspProber, RtspProbeError},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct CameraProbePolicy {
+    pub probe_on_save: bool,
+    pub reject_unreachable: bool,
+}
+
+/// Probes the stream at the addresses its host resolves to now, after
+/// checking them against the source url policy again.
+pub async fn probe_stream_metadata<IIRtspProber>(
+    rtsp_prober: &IIRtspProber,
+    source_url_policy: &SourceUrlPolicy,
+    source_url: &str,
+) -> Result<CameraStreamMetadata, RtspProbeError>
+where
+    IIRtspProber: IRtspProber + Sync,
+{
+    let addresses = check_source_url(source_url, source_url_policy)
+        .await
+        .map_err(|message| RtspProbeError {
+            message: format!("stream address is not allowed: {}", message),
+            debug_message: message,
+        })?;
+    let description = rtsp_prober.describe(source_url, &addresses).await?;
+    Ok(CameraStreamMetadata {
+        video_codec: description.video_codec,
+        width: description.width,
+        height: description.height,
+        framerate: description.framerate,
+        has_audio: description.has_audio,
+        audio_codec: description.audio_codec,
+        probed_at: Utc::now(),
+    })
+}
+
+/// Probes the stream when the policy asks for it on save. Unreachable streams
+/// are rejected as a business error on `source_url` only when the policy says
+/// so; otherwise the camera is saved without metadata.
+pub async fn probe_on_save<IIRtspProber>(
+    rtsp_prober: &IIRtspProber,
+    policy: &CameraProbePolicy,
+    source_url_policy: &SourceUrlPolicy,
+    source_url: &str,
+) -> Result<Option<CameraStreamMetadata>, UseCaseError>
+where
+    IIRtspProber: IRtspProber + Sync,
+{
+    if !policy.probe_on_save {
+        return Ok(None);
+    }
+
+    match probe_stream_metadata(rtsp_prober, source_url_policy, source_url).await {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(err) if policy.reject_unreachable => {
+            tracing::info!("rejecting unreachable stream: {:?}", err);
+            let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+            feedback.insert(
+                "source_url".to_string(),
+                vec![format!("stream could not be reached: {}", err.message)],
+            );
+            Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation because the camera stream is unreachable"
+                    .to_string(),
+                feedback,
+            )))
+        }
+        Err(err) => {
+            tracing::warn!("saving camera without stream metadata: {:?}", err);
+            Ok(None)
+        }
+    }
+}
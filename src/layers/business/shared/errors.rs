@@ -33,5 +33,8 @@ impl InternalDependencyError {
 #[derive(Debug, Clone)]
 pub enum UseCaseError {
     BusinessError(BusinessError),
+    /// The request is valid but a usage limit is reached; callers may retry
+    /// once usage goes down.
+    LimitExceeded(BusinessError),
     InternalDependencyError(InternalDependencyError)
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+};
+
+use tokio::sync::OwnedMutexGuard;
+
+/// Async mutexes created on demand per key, so work on the same key is
+/// serialized while different keys proceed in parallel. Entries are dropped
+/// once nobody holds or waits for their lock. Clones share the same locks.
+#[derive(Clone, Default)]
+pub struct KeyedLocks {
+    locks: Arc<Mutex<HashMap<String, Weak<tokio::sync::Mutex<()>>>>>,
+}
+
+impl KeyedLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn lock(&self, key: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            match locks.get(key).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(tokio::sync::Mutex::new(()));
+                    locks.insert(key.to_string(), Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_same_key_is_serialized_and_other_keys_are_not() {
+        let locks = KeyedLocks::new();
+        let guard = locks.lock("a").await;
+
+        let other = tokio::time::timeout(Duration::from_millis(50), locks.lock("b")).await;
+        assert!(other.is_ok());
+
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock("a")).await;
+        assert!(same.is_err());
+
+        drop(guard);
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock("a")).await;
+        assert!(same.is_ok());
+    }
+}
@@ -1,4 +1,10 @@
 pub mod errors;
 pub mod business_rules;
+pub mod camera_access;
+pub mod camera_probe;
+pub mod keyed_locks;
+pub mod recurrence;
 pub mod sanitization_rules;
-pub mod validation_rules;
\ No newline at end of file
+pub mod secrets;
+pub mod validation_rules;
+pub mod user_search;
\ No newline at end of file
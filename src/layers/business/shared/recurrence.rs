@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// The subset of RFC 5545 RRULEs that blocking schedules need:
+/// `FREQ=DAILY|WEEKLY`, optionally restricted with `BYDAY=MO,TU,...` and
+/// ended with `UNTIL=20250131T000000Z`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub by_day: Vec<Weekday>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+        let mut frequency = None;
+        let mut by_day = Vec::new();
+        let mut until = None;
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or(format!("expected NAME=VALUE, got {:?}", part))?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        other => return Err(format!("unsupported FREQ {:?}, use DAILY or WEEKLY", other)),
+                    })
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        by_day.push(parse_weekday(day)?);
+                    }
+                }
+                "UNTIL" => {
+                    let until_date = chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+                        .map_err(|_| format!("UNTIL must look like 20250131T000000Z, got {:?}", value))?;
+                    until = Some(until_date.and_utc());
+                }
+                other => return Err(format!("unsupported rule part {:?}", other)),
+            }
+        }
+        Ok(RecurrenceRule {
+            frequency: frequency.ok_or("FREQ is required".to_string())?,
+            by_day,
+            until,
+        })
+    }
+}
+
+fn parse_weekday(day: &str) -> Result<Weekday, String> {
+    match day.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        other => Err(format!("unknown weekday {:?}", other)),
+    }
+}
+
+impl RecurrenceRule {
+    /// Whether an occurrence starts on the given local date. Weekly rules
+    /// without `BYDAY` have nothing to anchor on and are rejected when the
+    /// schedule is parsed.
+    fn occurs_on(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.contains(&date.weekday())
+    }
+}
+
+/// A recurring window of local wall-clock time, e.g. 22:00 to 07:00 on
+/// weeknights in Europe/Berlin. Windows whose end is not after their start
+/// run into the next day; equal times block the whole day.
+#[derive(Debug, Clone)]
+pub struct RecurringSchedule {
+    pub rule: RecurrenceRule,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub timezone: Tz,
+}
+
+impl RecurringSchedule {
+    pub fn parse(rrule: &str, start: &str, end: &str, timezone: &str) -> Result<Self, Vec<(String, String)>> {
+        let mut errors = Vec::new();
+        let rule = rrule
+            .parse::<RecurrenceRule>()
+            .map_err(|err| errors.push(("rrule".to_string(), err)))
+            .ok();
+        if let Some(RecurrenceRule { frequency: Frequency::Weekly, by_day, .. }) = &rule {
+            if by_day.is_empty() {
+                errors.push(("rrule".to_string(), "weekly rules need BYDAY".to_string()));
+            }
+        }
+        let start = parse_local_time(start)
+            .map_err(|err| errors.push(("start_time".to_string(), err)))
+            .ok();
+        let end = parse_local_time(end)
+            .map_err(|err| errors.push(("end_time".to_string(), err)))
+            .ok();
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|_| errors.push(("timezone".to_string(), format!("unknown timezone {:?}", timezone))))
+            .ok();
+        match (rule, start, end, timezone) {
+            (Some(rule), Some(start), Some(end), Some(timezone)) if errors.is_empty() => {
+                Ok(RecurringSchedule { rule, start, end, timezone })
+            }
+            _ => Err(errors),
+        }
+    }
+
+    /// The occurrence that covers `at`, as a UTC start and end.
+    pub fn window_containing(&self, at: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let today = at.with_timezone(&self.timezone).date_naive();
+        // an occurrence may have started the day before and still be running
+        [today.pred_opt(), Some(today)]
+            .into_iter()
+            .flatten()
+            .filter(|date| self.rule.occurs_on(*date))
+            .filter_map(|date| self.window_on(date))
+            .find(|(start, end)| *start <= at && at < *end)
+    }
+
+    fn window_on(&self, date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = self.resolve(date, self.start)?;
+        if self.rule.until.is_some_and(|until| start > until) {
+            return None;
+        }
+        let end_date = if self.end > self.start { date } else { date.succ_opt()? };
+        let end = self.resolve(end_date, self.end)?;
+        Some((start, end))
+    }
+
+    /// Local times skipped by a DST change are moved forward by the gap.
+    fn resolve(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        let local = date.and_time(time);
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| self.timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+fn parse_local_time(input: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(input, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M:%S"))
+        .map_err(|_| format!("expected a local time like 22:00, got {:?}", input))
+}
+
+/// The first moment from `now` on that no schedule covers, or `None` when
+/// none of them is active right now. Back to back windows are followed, so a
+/// blocking from Friday 22:00 that continues into an all-day Saturday rule
+/// reports Sunday.
+pub fn next_free_time(schedules: &[RecurringSchedule], now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let mut at = now;
+    // bounded so an always-on schedule cannot loop forever
+    for _ in 0..64 {
+        match schedules.iter().filter_map(|s| s.window_containing(at)).map(|(_, end)| end).max() {
+            Some(end) => at = end,
+            None => break,
+        }
+    }
+    (at > now).then_some(at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_rejects_unsupported_rules() {
+        assert!("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR".parse::<RecurrenceRule>().is_ok());
+        assert!("FREQ=MONTHLY".parse::<RecurrenceRule>().is_err());
+        assert!("BYDAY=MO".parse::<RecurrenceRule>().is_err());
+        let errors = RecurringSchedule::parse("FREQ=WEEKLY", "25:00", "07:00", "Mars/Base").unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|(field, _)| field.as_str()).collect();
+        assert_eq!(fields, vec!["rrule", "start_time", "timezone"]);
+    }
+
+    #[test]
+    fn test_weeknight_window_crosses_midnight() {
+        let schedule =
+            RecurringSchedule::parse("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR", "22:00", "07:00", "Europe/Berlin").unwrap();
+        let schedules = [schedule];
+        // Tuesday 2025-01-14 23:30 in Berlin
+        assert_eq!(next_free_time(&schedules, utc("2025-01-14T22:30:00Z")), Some(utc("2025-01-15T06:00:00Z")));
+        // Saturday 2025-01-18 01:00 in Berlin still belongs to Friday night
+        assert_eq!(next_free_time(&schedules, utc("2025-01-18T00:00:00Z")), Some(utc("2025-01-18T06:00:00Z")));
+        // Sunday 2025-01-19 01:00 in Berlin is free
+        assert_eq!(next_free_time(&schedules, utc("2025-01-19T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_adjacent_windows_are_followed() {
+        let nights = RecurringSchedule::parse("FREQ=DAILY", "22:00", "07:00", "UTC").unwrap();
+        let mornings = RecurringSchedule::parse("FREQ=DAILY;UNTIL=20250201T000000Z", "07:00", "09:00", "UTC").unwrap();
+        assert_eq!(
+            next_free_time(&[nights.clone(), mornings.clone()], utc("2025-01-14T23:00:00Z")),
+            Some(utc("2025-01-15T09:00:00Z"))
+        );
+        assert_eq!(
+            next_free_time(&[nights, mornings], utc("2025-03-01T23:00:00Z")),
+            Some(utc("2025-03-02T07:00:00Z"))
+        );
+    }
+}
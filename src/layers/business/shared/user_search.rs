@@ -0,0 +1,101 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::layers::{
+    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::user_qc_collection::{SearchUsersQuery, SearchUsersQueryError},
+};
+
+pub const DEFAULT_USER_PAGE_SIZE: usize = 50;
+pub const MAX_USER_PAGE_SIZE: usize = 100;
+
+/// Search, filters and paging shared by the user listings. Empty values are
+/// ignored.
+#[derive(Default)]
+pub struct UserSearchInput {
+    /// Prefix of the email, the name or any word of the name
+    pub search: Option<String>,
+    /// Users holding at least one of these roles
+    pub roles: Vec<String>,
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+fn invalid_input(details: HashMap<String, Vec<String>>) -> UseCaseError {
+    UseCaseError::BusinessError(BusinessError::new(
+        "could not complete operation due to invalid data, please check feedback".to_string(),
+        details,
+    ))
+}
+
+pub fn build_search_users_query(
+    input: UserSearchInput,
+    active_only: bool,
+    exclude_user_ids: HashSet<String>,
+) -> Result<SearchUsersQuery, UseCaseError> {
+    let limit = input.limit.unwrap_or(DEFAULT_USER_PAGE_SIZE);
+    if !(1..=MAX_USER_PAGE_SIZE).contains(&limit) {
+        return Err(invalid_input(HashMap::from([(
+            "limit".to_string(),
+            vec![format!("limit must be between 1 and {}", MAX_USER_PAGE_SIZE)],
+        )])));
+    }
+    let non_empty = |value: String| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+    Ok(SearchUsersQuery {
+        search: input.search.and_then(non_empty),
+        roles: input.roles.into_iter().filter_map(non_empty).collect(),
+        active_only,
+        exclude_user_ids,
+        limit,
+        cursor: input.cursor.and_then(non_empty),
+    })
+}
+
+pub fn map_search_users_error(err: SearchUsersQueryError) -> UseCaseError {
+    match err {
+        SearchUsersQueryError::InvalidCursor => invalid_input(HashMap::from([(
+            "cursor".to_string(),
+            vec!["cursor is not a next_cursor returned by this endpoint".to_string()],
+        )])),
+        SearchUsersQueryError::Database(err) => {
+            tracing::error!("{:?}", err);
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "failed to search users".to_string(),
+                format!("{:?}", err),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_trims_and_defaults() {
+        let query = build_search_users_query(
+            UserSearchInput {
+                search: Some("  ".to_string()),
+                roles: vec![" Admin ".to_string(), "".to_string()],
+                cursor: Some("".to_string()),
+                ..Default::default()
+            },
+            true,
+            HashSet::new(),
+        )
+        .ok()
+        .unwrap();
+        assert!(query.search.is_none());
+        assert_eq!(query.roles, vec!["Admin"]);
+        assert_eq!(query.limit, DEFAULT_USER_PAGE_SIZE);
+        assert!(query.cursor.is_none());
+    }
+
+    #[test]
+    fn test_build_query_rejects_limit_out_of_range() {
+        for limit in [0, MAX_USER_PAGE_SIZE + 1] {
+            let input = UserSearchInput { limit: Some(limit), ..Default::default() };
+            assert!(build_search_users_query(input, false, HashSet::new()).is_err());
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+
+use crate::layers::business::shared::business_rules::FieldValidationResult;
+
+/// Validates a blocking period given as RFC 3339 timestamps: both ends must
+/// parse, `end_time` must come after `start_time` and must not already have
+/// passed.
+pub fn blocking_period(start_time: &str, end_time: &str, now: DateTime<Utc>) -> Vec<FieldValidationResult> {
+    let start = DateTime::parse_from_rfc3339(start_time);
+    let end = DateTime::parse_from_rfc3339(end_time);
+    let mut results = Vec::new();
+    if start.is_err() {
+        results.push(invalid_timestamp("start_time"));
+    }
+    if end.is_err() {
+        results.push(invalid_timestamp("end_time"));
+    }
+    if let (Ok(start), Ok(end)) = (start, end) {
+        if end <= start {
+            results.push(FieldValidationResult::Invalid(
+                "end_time".to_string(),
+                "end_time must be after start_time".to_string(),
+            ));
+        } else if end.with_timezone(&Utc) <= now {
+            results.push(FieldValidationResult::Invalid(
+                "end_time".to_string(),
+                "end_time must be in the future".to_string(),
+            ));
+        }
+    }
+    results
+}
+
+fn invalid_timestamp(field_name: &str) -> FieldValidationResult {
+    FieldValidationResult::Invalid(
+        field_name.to_string(),
+        format!("{} must be an RFC 3339 timestamp, e.g. 2025-01-31T18:00:00Z", field_name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_fields(results: Vec<FieldValidationResult>) -> Vec<String> {
+        results
+            .into_iter()
+            .filter_map(|r| match r {
+                FieldValidationResult::Invalid(field, _) => Some(field),
+                FieldValidationResult::Valid => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_blocking_period() {
+        let now = "2025-01-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(invalid_fields(blocking_period("2025-01-01T10:00:00Z", "2025-01-02T00:00:00+01:00", now)).is_empty());
+        assert_eq!(invalid_fields(blocking_period("yesterday", "2025-01-02T00:00:00Z", now)), vec!["start_time"]);
+        assert_eq!(invalid_fields(blocking_period("2025-01-03T00:00:00Z", "2025-01-02T00:00:00Z", now)), vec!["end_time"]);
+        assert_eq!(invalid_fields(blocking_period("2025-01-01T00:00:00Z", "2025-01-01T11:00:00Z", now)), vec!["end_time"]);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod strings;
-pub mod rtsp_url;
\ No newline at end of file
+pub mod rtsp_url;
+pub mod source_url;
+pub mod dates;
+
+pub mod user;
\ No newline at end of file
@@ -0,0 +1,227 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use ipnet::IpNet;
+use url::{Host, Url};
+
+use std::collections::HashMap;
+
+use crate::layers::business::shared::{
+    business_rules::FieldValidationResult,
+    errors::{BusinessError, UseCaseError},
+};
+
+/// Ranges a camera source must never point at unless explicitly allowed:
+/// this host, link-local (including cloud metadata services), multicast and
+/// reserved addresses. Private LAN ranges are allowed since that is where
+/// cameras usually live.
+const DEFAULT_DENIED_CIDRS: [&str; 11] = [
+    "0.0.0.0/8",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "100.100.100.200/32",
+    "224.0.0.0/4",
+    "240.0.0.0/4",
+    "::/128",
+    "::1/128",
+    "fe80::/10",
+    "ff00::/8",
+    "fd00:ec2::254/128",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    fn contains(&self, port: u16) -> bool {
+        self.start <= port && port <= self.end
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once('-').unwrap_or((s, s));
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<u16>()
+                .map_err(|err| format!("invalid port {:?}: {}", value, err))
+        };
+        let range = PortRange {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start == 0 || range.start > range.end {
+            return Err(format!("invalid port range {:?}", s));
+        }
+        Ok(range)
+    }
+}
+
+/// `denied_cidrs` always win. When `allowed_cidrs` is not empty it becomes a
+/// strict allow list; the built-in deny list still applies except to allowed
+/// ranges that lie within one of its ranges, such as `127.0.0.1/32`. An
+/// empty `allowed_ports` accepts any non-zero port.
+#[derive(Debug, Clone, Default)]
+pub struct SourceUrlPolicy {
+    pub allowed_cidrs: Vec<IpNet>,
+    pub denied_cidrs: Vec<IpNet>,
+    pub allowed_ports: Vec<PortRange>,
+}
+
+impl SourceUrlPolicy {
+    fn check_port(&self, port: u16) -> Result<(), String> {
+        if port == 0 {
+            return Err("port 0 is not allowed".to_string());
+        }
+        if !self.allowed_ports.is_empty() && !self.allowed_ports.iter().any(|r| r.contains(port)) {
+            return Err(format!("port {} is not allowed", port));
+        }
+        Ok(())
+    }
+
+    fn check_address(&self, address: IpAddr) -> Result<(), String> {
+        let address = address.to_canonical();
+        if self.denied_cidrs.iter().any(|net| net.contains(&address)) {
+            return Err(format!("address {} is not allowed", address));
+        }
+        let allowed: Vec<&IpNet> = self.allowed_cidrs.iter().filter(|net| net.contains(&address)).collect();
+        if !self.allowed_cidrs.is_empty() && allowed.is_empty() {
+            return Err(format!("address {} is not in the allowed ranges", address));
+        }
+        // a broad allowed range like 0.0.0.0/0 does not name loopback or
+        // metadata addresses, so those stay denied
+        let denied_by_default = DEFAULT_DENIED_CIDRS
+            .iter()
+            .filter_map(|cidr| cidr.parse::<IpNet>().ok())
+            .filter(|net| net.contains(&address))
+            .any(|denied| !allowed.iter().any(|net| denied.contains(*net)));
+        match denied_by_default {
+            true => Err(format!("address {} is not allowed", address)),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Checks that the url names a host on an allowed port and that every address
+/// the host resolves to passes the policy. Stream servers connect to this url
+/// on our behalf, so it must not reach internal services.
+pub async fn safe_source_url(
+    input: &str,
+    field_name: &str,
+    policy: &SourceUrlPolicy,
+) -> FieldValidationResult {
+    match check_source_url(input, policy).await {
+        Ok(_) => FieldValidationResult::Valid,
+        Err(message) => FieldValidationResult::Invalid(field_name.to_string(), message),
+    }
+}
+
+/// Checks the url again right before it is handed to a stream server, since
+/// its host may resolve differently than when the input was validated.
+pub async fn recheck_source_url(input: &str, policy: &SourceUrlPolicy) -> Result<(), UseCaseError> {
+    check_source_url(input, policy).await.map(|_| ()).map_err(|message| {
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        feedback.insert("source_url".to_string(), vec![message]);
+        UseCaseError::BusinessError(BusinessError::new(
+            "could not complete operation due to invalid data, please check feedback".to_string(),
+            feedback,
+        ))
+    })
+}
+
+/// Resolves the url's host and returns its addresses with the port, once
+/// all of them pass the policy. Connecting to these instead of resolving
+/// the host again leaves no room for DNS rebinding.
+pub async fn check_source_url(input: &str, policy: &SourceUrlPolicy) -> Result<Vec<SocketAddr>, String> {
+    let url = Url::parse(input).map_err(|_| "must be a valid url".to_string())?;
+    let default_port = match url.scheme() {
+        "rtsps" => 322,
+        _ => 554,
+    };
+    let port = url.port().unwrap_or(default_port);
+    policy.check_port(port)?;
+
+    let addresses: Vec<IpAddr> = match url.host() {
+        None => return Err("must include a host".to_string()),
+        Some(Host::Ipv4(address)) => vec![IpAddr::V4(address)],
+        Some(Host::Ipv6(address)) => vec![IpAddr::V6(address)],
+        Some(Host::Domain(domain)) => tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|_| format!("host {} could not be resolved", domain))?
+            .map(|address: SocketAddr| address.ip())
+            .collect(),
+    };
+    if addresses.is_empty() {
+        return Err("host did not resolve to any address".to_string());
+    }
+    addresses
+        .into_iter()
+        .map(|address| policy.check_address(address).map(|_| SocketAddr::new(address, port)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_blocks_internal_addresses() {
+        let policy = SourceUrlPolicy::default();
+        assert!(policy.check_address("127.0.0.1".parse().unwrap()).is_err());
+        assert!(policy.check_address("169.254.169.254".parse().unwrap()).is_err());
+        assert!(policy.check_address("::ffff:127.0.0.1".parse().unwrap()).is_err());
+        assert!(policy.check_address("fe80::1".parse().unwrap()).is_err());
+        assert!(policy.check_address("192.168.1.20".parse().unwrap()).is_ok());
+        assert!(policy.check_address("8.8.8.8".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_configured_ranges_and_ports() {
+        let policy = SourceUrlPolicy {
+            allowed_cidrs: vec!["10.0.0.0/8".parse().unwrap()],
+            denied_cidrs: vec!["10.9.0.0/16".parse().unwrap()],
+            allowed_ports: vec!["554".parse().unwrap(), "8000-8999".parse().unwrap()],
+        };
+        assert!(policy.check_address("10.1.2.3".parse().unwrap()).is_ok());
+        assert!(policy.check_address("10.9.2.3".parse().unwrap()).is_err());
+        assert!(policy.check_address("192.168.1.20".parse().unwrap()).is_err());
+        assert!(policy.check_port(554).is_ok());
+        assert!(policy.check_port(8554).is_ok());
+        assert!(policy.check_port(22).is_err());
+        assert!("0-10".parse::<PortRange>().is_err());
+    }
+
+    #[test]
+    fn test_allowed_ranges_keep_the_default_deny_list() {
+        let policy = SourceUrlPolicy {
+            allowed_cidrs: vec!["0.0.0.0/0".parse().unwrap(), "127.0.0.1/32".parse().unwrap()],
+            ..SourceUrlPolicy::default()
+        };
+        assert!(policy.check_address("8.8.8.8".parse().unwrap()).is_ok());
+        assert!(policy.check_address("169.254.169.254".parse().unwrap()).is_err());
+        assert!(policy.check_address("127.0.0.2".parse().unwrap()).is_err());
+        // named explicitly
+        assert!(policy.check_address("127.0.0.1".parse().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_source_url_feedback() {
+        let policy = SourceUrlPolicy::default();
+        assert_eq!(
+            check_source_url("rtsp://192.168.1.20/live", &policy).await,
+            Ok(vec!["192.168.1.20:554".parse().unwrap()])
+        );
+        assert!(check_source_url("rtsp://localhost/live", &policy).await.is_err());
+        assert!(check_source_url("rtsp://[::1]:8554/live", &policy).await.is_err());
+        assert!(check_source_url("rtsp://192.168.1.20:0/live", &policy).await.is_err());
+        assert!(check_source_url("rtsp:/live", &policy).await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::layers::business::shared::business_rules::FieldValidationResult;
+
+pub const MAX_USER_NAME_LENGTH: usize = 100;
+pub const MAX_ROLE_NAME_LENGTH: usize = 64;
+
+/// A pragmatic email check: one `@` with a non-empty local part and a domain
+/// containing a dot, and no whitespace. Whether the address exists is up to
+/// the identity provider.
+pub fn email(input: &str) -> FieldValidationResult {
+    let valid = match input.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !input.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+    match valid {
+        true => FieldValidationResult::Valid,
+        false => FieldValidationResult::Invalid(
+            "email".to_string(),
+            "email must be an address like jane@example.com".to_string(),
+        ),
+    }
+}
+
+pub fn user_name(input: &str) -> FieldValidationResult {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        FieldValidationResult::Invalid("name".to_string(), "name cannot be empty".to_string())
+    } else if trimmed.chars().count() > MAX_USER_NAME_LENGTH {
+        FieldValidationResult::Invalid(
+            "name".to_string(),
+            format!("name cannot be longer than {} characters", MAX_USER_NAME_LENGTH),
+        )
+    } else if trimmed.chars().any(char::is_control) {
+        FieldValidationResult::Invalid("name".to_string(), "name cannot contain control characters".to_string())
+    } else {
+        FieldValidationResult::Valid
+    }
+}
+
+/// Commas are rejected because role filters are passed as comma separated
+/// lists.
+pub fn role_name(input: &str, field_name: &str) -> FieldValidationResult {
+    let invalid = |message: String| FieldValidationResult::Invalid(field_name.to_string(), message);
+    if input.trim().is_empty() {
+        invalid("role names cannot be empty".to_string())
+    } else if input.chars().count() > MAX_ROLE_NAME_LENGTH {
+        invalid(format!("role names cannot be longer than {} characters", MAX_ROLE_NAME_LENGTH))
+    } else if input.chars().any(|c| c == ',' || c.is_control()) {
+        invalid(format!("role name {:?} cannot contain commas or control characters", input))
+    } else {
+        FieldValidationResult::Valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_valid(result: FieldValidationResult) -> bool {
+        matches!(result, FieldValidationResult::Valid)
+    }
+
+    #[test]
+    fn test_email() {
+        assert!(is_valid(email("jane@example.com")));
+        assert!(is_valid(email("jane.doe+cams@mail.example.co.uk")));
+        assert!(!is_valid(email("jane")));
+        assert!(!is_valid(email("@example.com")));
+        assert!(!is_valid(email("jane@localhost")));
+        assert!(!is_valid(email("jane@@example.com")));
+        assert!(!is_valid(email("jane doe@example.com")));
+    }
+
+    #[test]
+    fn test_user_name() {
+        assert!(is_valid(user_name("Jane Doe")));
+        assert!(!is_valid(user_name("   ")));
+        assert!(!is_valid(user_name(&"x".repeat(MAX_USER_NAME_LENGTH + 1))));
+        assert!(!is_valid(user_name("Jane\nDoe")));
+    }
+
+    #[test]
+    fn test_role_name() {
+        assert!(is_valid(role_name("Camera Operator", "roles")));
+        assert!(!is_valid(role_name(" ", "roles")));
+        assert!(!is_valid(role_name("Admin,Viewer", "roles")));
+        assert!(!is_valid(role_name(&"x".repeat(MAX_ROLE_NAME_LENGTH + 1), "roles")));
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::{
+        blocking_history_qc_collection::{BlockingHistoryEntry, IBlockingHistoryQCCollection},
+        camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+    },
+};
+
+use super::interface::IArchiveExpiredTempBlockingsUseCase;
+
+pub struct ArchiveExpiredTempBlockingsUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
+    ArchiveExpiredTempBlockingsUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+{
+    pub fn new(
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+    ) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+            blocking_history_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection> IArchiveExpiredTempBlockingsUseCase
+    for ArchiveExpiredTempBlockingsUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+{
+    async fn execute(&self) -> Result<usize, UseCaseError> {
+        let now = Utc::now();
+        let blockings = self
+            .camera_temp_blocking_qc_collection
+            .list_all_temp_blockings()
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list camera temp blockings".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        let mut archived = 0;
+        for blocking in blockings {
+            let expired = DateTime::parse_from_rfc3339(&blocking.end_date).is_ok_and(|end| end <= now);
+            if !expired {
+                continue;
+            }
+            let (camera_id, user_id, end_date) =
+                (blocking.camera_id.clone(), blocking.user_id.clone(), blocking.end_date.clone());
+            // one failing blocking should not keep the others around; it is
+            // retried on the next run
+            let entry = BlockingHistoryEntry::archive(blocking, now, "expired", None);
+            if let Err(err) = self.blocking_history_qc_collection.archive(entry).await {
+                tracing::error!("failed to archive expired blocking of {} on {}: {:?}", user_id, camera_id, err);
+                continue;
+            }
+            // a blocking extended since it was listed keeps running; its
+            // history entry is overwritten once it really expires
+            match self
+                .camera_temp_blocking_qc_collection
+                .delete_temp_blocking_ending_at(&camera_id, &user_id, &end_date)
+                .await
+            {
+                Ok(true) => archived += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::error!("failed to delete expired blocking of {} on {}: {:?}", user_id, camera_id, err)
+                }
+            }
+        }
+        Ok(archived)
+    }
+}
@@ -0,0 +1,7 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+/// Moves one-off blockings whose end date has passed into the blocking
+/// history. Returns the number of archived blockings.
+pub trait IArchiveExpiredTempBlockingsUseCase {
+    fn execute(&self) -> impl std::future::Future<Output = Result<usize, UseCaseError>> + Send;
+}
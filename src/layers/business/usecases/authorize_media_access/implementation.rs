@@ -0,0 +1,205 @@
+use chrono::Utc;
+use url::Url;
+
+use crate::layers::{
+    business::shared::{
+        camera_access::{ensure_camera_not_blocked, split_session_access_token},
+        errors::{InternalDependencyError, UseCaseError},
+        secrets::constant_time_eq,
+    },
+    ewm::main_database::qc_collection::{
+        camera_qc_collection::ICameraQCCollection,
+        camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+        temp_stream_session_qc_collection::ITempStreamSessionQCCollection,
+    },
+};
+
+use super::interface::{
+    AuthorizeMediaAccessInput, IAuthorizeMediaAccessUseCase, MediaAccessDecision, MediaAuthPolicy,
+};
+
+pub struct AuthorizeMediaAccessUseCaseImp<
+    IITempStreamSessionQCCollection,
+    IICameraQCCollection,
+    IICameraTempBlockingQCCollection,
+> where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection,
+    IICameraQCCollection: ICameraQCCollection,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    camera_qc_collection: IICameraQCCollection,
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    policy: MediaAuthPolicy,
+}
+
+impl<IITempStreamSessionQCCollection, IICameraQCCollection, IICameraTempBlockingQCCollection>
+    AuthorizeMediaAccessUseCaseImp<
+        IITempStreamSessionQCCollection,
+        IICameraQCCollection,
+        IICameraTempBlockingQCCollection,
+    >
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        camera_qc_collection: IICameraQCCollection,
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        policy: MediaAuthPolicy,
+    ) -> Self {
+        Self {
+            temp_stream_session_qc_collection,
+            camera_qc_collection,
+            camera_temp_blocking_qc_collection,
+            policy,
+        }
+    }
+
+    async fn authorize_read(
+        &self,
+        input: &AuthorizeMediaAccessInput,
+    ) -> Result<MediaAccessDecision, UseCaseError> {
+        let Some(access_token) = access_token(input) else {
+            return Ok(MediaAccessDecision::Deny("missing session token".to_string()));
+        };
+        let Some((session_id, secret)) = split_session_access_token(&access_token) else {
+            return Ok(MediaAccessDecision::Deny("malformed session token".to_string()));
+        };
+
+        let session = self
+            .temp_stream_session_qc_collection
+            .find_session_by_id(session_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to find temporary stream session".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        let Some(session) = session.filter(|s| !s.token.is_empty() && constant_time_eq(&s.token, secret))
+        else {
+            return Ok(MediaAccessDecision::Deny("unknown session".to_string()));
+        };
+        if session.expires_at <= Utc::now() {
+            return Ok(MediaAccessDecision::Deny("session has expired".to_string()));
+        }
+        if stream_path(&session.stream_url) != input.path.trim_matches('/') {
+            return Ok(MediaAccessDecision::Deny("session is for another stream".to_string()));
+        }
+
+        let camera_exists = self
+            .camera_qc_collection
+            .camera_exists_by_id(&session.camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to check if camera exists in the database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        if !camera_exists {
+            return Ok(MediaAccessDecision::Deny("camera no longer exists".to_string()));
+        }
+
+        match ensure_camera_not_blocked(
+            &self.camera_temp_blocking_qc_collection,
+            &session.camera_id,
+            &session.user_id,
+            session.privacy_shutter_exempt,
+        )
+        .await
+        {
+            Ok(()) => Ok(MediaAccessDecision::Allow),
+            Err(UseCaseError::BusinessError(err)) => Ok(MediaAccessDecision::Deny(err.message)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn access_token(input: &AuthorizeMediaAccessInput) -> Option<String> {
+    input
+        .token
+        .clone()
+        .filter(|t| !t.is_empty())
+        .or_else(|| {
+            url::form_urlencoded::parse(input.query.as_deref().unwrap_or_default().as_bytes())
+                .find(|(name, _)| name == "token")
+                .map(|(_, value)| value.into_owned())
+        })
+}
+
+/// Path the media server knows the stream by, e.g. `stream-1` for
+/// `rtsp://relay:8554/stream-1`.
+fn stream_path(stream_url: &str) -> String {
+    Url::parse(stream_url)
+        .map(|url| url.path().trim_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+impl<IITempStreamSessionQCCollection, IICameraQCCollection, IICameraTempBlockingQCCollection>
+    IAuthorizeMediaAccessUseCase
+    for AuthorizeMediaAccessUseCaseImp<
+        IITempStreamSessionQCCollection,
+        IICameraQCCollection,
+        IICameraTempBlockingQCCollection,
+    >
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, input: AuthorizeMediaAccessInput) -> Result<MediaAccessDecision, UseCaseError> {
+        let Some(shared_secret) = self.policy.shared_secret.as_deref() else {
+            return Ok(MediaAccessDecision::Deny("media auth hook is not configured".to_string()));
+        };
+        let hook_secret = input.hook_secret.as_deref().unwrap_or_default();
+        if !constant_time_eq(hook_secret, shared_secret) {
+            return Ok(MediaAccessDecision::Deny("invalid hook secret".to_string()));
+        }
+
+        match input.action.as_str() {
+            "read" | "playback" => self.authorize_read(&input).await,
+            "publish" => match input.password.as_deref() {
+                Some(password) if constant_time_eq(password, shared_secret) => {
+                    Ok(MediaAccessDecision::Allow)
+                }
+                _ => Ok(MediaAccessDecision::Deny("publishing is reserved for the stream servers".to_string())),
+            },
+            other => Ok(MediaAccessDecision::Deny(format!("action {} is not allowed", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(token: Option<&str>, query: Option<&str>) -> AuthorizeMediaAccessInput {
+        AuthorizeMediaAccessInput {
+            hook_secret: None,
+            action: "read".to_string(),
+            path: "stream-1".to_string(),
+            token: token.map(str::to_owned),
+            query: query.map(str::to_owned),
+            password: None,
+        }
+    }
+
+    #[test]
+    fn access_token_prefers_field_then_query() {
+        assert_eq!(access_token(&input(Some("a.b"), Some("token=c.d"))).as_deref(), Some("a.b"));
+        assert_eq!(access_token(&input(Some(""), Some("x=1&token=c.d"))).as_deref(), Some("c.d"));
+        assert_eq!(access_token(&input(None, None)), None);
+    }
+
+    #[test]
+    fn stream_path_strips_slashes() {
+        assert_eq!(stream_path("rtsp://relay:8554/stream-1"), "stream-1");
+        assert_eq!(stream_path("not a url"), "");
+    }
+}
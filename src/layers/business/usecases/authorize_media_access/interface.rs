@@ -0,0 +1,34 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+/// One access attempt reported by a stream server. `token` and `query` are
+/// both checked for the session access token since media servers pass it
+/// either way depending on the protocol.
+pub struct AuthorizeMediaAccessInput {
+    pub hook_secret: Option<String>,
+    pub action: String,
+    pub path: String,
+    pub token: Option<String>,
+    pub query: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MediaAccessDecision {
+    Allow,
+    Deny(String),
+}
+
+/// `shared_secret` authenticates the stream servers calling the hook and is
+/// also the password internal publishers use. Without it every attempt is
+/// denied.
+#[derive(Debug, Clone)]
+pub struct MediaAuthPolicy {
+    pub shared_secret: Option<String>,
+}
+
+pub trait IAuthorizeMediaAccessUseCase {
+    fn execute(
+        &self,
+        input: AuthorizeMediaAccessInput,
+    ) -> impl std::future::Future<Output = Result<MediaAccessDecision, UseCaseError>> + Send;
+}
@@ -9,14 +9,20 @@ use crate::layers::{
         business_rules::{
             FieldValidationResult, UseCaseInputValidationResult, UseCaseInvalidInputResult,
         },
+        camera_probe::{probe_on_save, CameraProbePolicy},
         errors::{BusinessError, InternalDependencyError, UseCaseError},
-        validation_rules::{rtsp_url::rtsp_url, strings::non_empty},
+        validation_rules::{
+                rtsp_url::rtsp_url,
+                source_url::{recheck_source_url, safe_source_url, SourceUrlPolicy},
+                strings::non_empty,
+            },
     },
     ewm::{
         main_database::qc_collection::camera_qc_collection::{
-            ICameraQCCollection, PutCameraCommandInput,
+            CameraStreamMetadata, ICameraQCCollection, PutCameraCommandInput,
         },
         permanent_stream_server::{AddStreamInput, IPermanentStreamServer},
+        rtsp_prober::IRtspProber,
     },
 };
 #[derive(Debug)]
@@ -29,6 +35,7 @@ pub struct CreateCameraOutput {
     pub id: String,
     pub name: String,
     pub source_url: String,
+    pub stream_metadata: Option<CameraStreamMetadata>,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
 }
@@ -39,37 +46,49 @@ pub trait ICreateCameraUseCase {
     ) -> impl std::future::Future<Output = Result<CreateCameraOutput, UseCaseError>> + Send;
 }
 
-pub struct CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+pub struct CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection,
     IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
 {
     camera_qc_collection: IICamercaQCCollection,
     permanent_stream_server: IIPermanentStreamServer,
+    rtsp_prober: IIRtspProber,
+    probe_policy: CameraProbePolicy,
+    source_url_policy: SourceUrlPolicy,
 }
 
-impl<IICamercaQCCollection, IIPermanentStreamServer>
-    CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+    CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection,
     IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
 {
     pub fn new(
         camera_qc_collection: IICamercaQCCollection,
         permanent_stream_server: IIPermanentStreamServer,
+        rtsp_prober: IIRtspProber,
+        probe_policy: CameraProbePolicy,
+        source_url_policy: SourceUrlPolicy,
     ) -> Self {
         Self {
             camera_qc_collection,
             permanent_stream_server,
+            rtsp_prober,
+            probe_policy,
+            source_url_policy,
         }
     }
 }
 
-impl<IICamercaQCCollection, IIPermanentStreamServer>
-    CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+    CreateCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection,
     IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
 {
     pub async fn execute(
         &self,
@@ -89,6 +108,15 @@ where
                 invalid_result.feedback,
             )));
         }
+        let stream_metadata = probe_on_save(
+            &self.rtsp_prober,
+            &self.probe_policy,
+            &self.source_url_policy,
+            &sanitized_input.0.source_url,
+        )
+        .await?;
+        recheck_source_url(&sanitized_input.0.source_url, &self.source_url_policy).await?;
+
         let id = ulid::Ulid::new().to_string();
 
         let add_stream_request = AddStreamInput {
@@ -114,6 +142,7 @@ where
             name: sanitized_input.0.name,
             source_url: sanitized_input.0.source_url,
             permanent_stream_url: Some(permanent_server_response.url),
+            stream_metadata,
         };
 
         let camera_command_result = self
@@ -131,6 +160,7 @@ where
             id: camera_command_result.id,
             name: camera_command_result.name,
             source_url: camera_command_result.source_url,
+            stream_metadata: camera_command_result.stream_metadata,
             created_at: camera_command_result.created_at,
             updated_at: camera_command_result.updated_at,
         };
@@ -142,7 +172,7 @@ where
         input: &CreateCameraSanitizedInput,
     ) -> UseCaseInputValidationResult {
         tracing::info!("{:?}", input.0);
-        let fields_validation_result: Vec<FieldValidationResult> = vec![
+        let mut fields_validation_result: Vec<FieldValidationResult> = vec![
             non_empty(&input.0.name, "name", format!("{} cannot be empty", "name")),
             non_empty(
                 &input.0.source_url,
@@ -155,6 +185,11 @@ where
                 "must be a valid rtmp url",
             ),
         ];
+        if !input.0.source_url.is_empty() {
+            fields_validation_result.push(
+                safe_source_url(&input.0.source_url, "source_url", &self.source_url_policy).await,
+            );
+        }
         let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
         for vr in fields_validation_result {
             if let FieldValidationResult::Invalid(field_name, message) = vr {
@@ -1,65 +1,169 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::layers::{
-    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
-    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::{
-        CreateCameraTempBlockingInput as QCCreateInput, ICameraTempBlockingQCCollection,
+    business::shared::{
+        business_rules::FieldValidationResult,
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        validation_rules::dates::blocking_period,
+    },
+    ewm::main_database::qc_collection::{
+        camera_qc_collection::ICameraQCCollection,
+        camera_temp_blocking_qc_collection::{
+            CreateCameraTempBlockingError, CreateCameraTempBlockingInput as QCCreateInput,
+            ICameraTempBlockingQCCollection, MAX_USERS_PER_TEMP_BLOCKING,
+        },
+        user_qc_collection::IUserQCCollection,
     },
 };
 
 use super::interface::{CreateCameraTempBlockingInput, ICreateCameraTempBlockingUseCase};
 
-pub struct CreateCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
-where
+pub struct CreateCameraTempBlockingUseCaseImp<
+    IICameraTempBlockingQCCollection,
+    IICameraQCCollection,
+    IIUserQCCollection,
+> where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IICameraQCCollection: ICameraQCCollection,
+    IIUserQCCollection: IUserQCCollection,
 {
     camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    camera_qc_collection: IICameraQCCollection,
+    user_qc_collection: IIUserQCCollection,
 }
 
-impl<IICameraTempBlockingQCCollection>
-    CreateCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+impl<IICameraTempBlockingQCCollection, IICameraQCCollection, IIUserQCCollection>
+    CreateCameraTempBlockingUseCaseImp<
+        IICameraTempBlockingQCCollection,
+        IICameraQCCollection,
+        IIUserQCCollection,
+    >
 where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIUserQCCollection: IUserQCCollection + Sync,
 {
-    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+    pub fn new(
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        camera_qc_collection: IICameraQCCollection,
+        user_qc_collection: IIUserQCCollection,
+    ) -> Self {
         Self {
             camera_temp_blocking_qc_collection,
+            camera_qc_collection,
+            user_qc_collection,
         }
     }
-}
-
-impl<IICameraTempBlockingQCCollection> ICreateCameraTempBlockingUseCase
-    for CreateCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
-where
-    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
-{
-    async fn execute(&self, input: CreateCameraTempBlockingInput) -> Result<(), UseCaseError> {
-        let mut already_blocked_users = Vec::new();
 
-        for user_id in &input.user_ids {
-            let existing = self
-                .camera_temp_blocking_qc_collection
-                .get_temp_blocking(&input.camera_id, user_id)
+    async fn check_references(
+        &self,
+        camera_id: &str,
+        user_ids: &[String],
+        created_by: &str,
+    ) -> Result<HashMap<String, Vec<String>>, UseCaseError> {
+        let camera_exists = match camera_id.is_empty() {
+            true => false,
+            false => self
+                .camera_qc_collection
+                .camera_exists_by_id(camera_id)
                 .await
                 .map_err(|err| {
                     tracing::error!("{:?}", err);
                     UseCaseError::InternalDependencyError(InternalDependencyError::new(
-                        "failed to check existing temp blocking".to_string(),
+                        "failed to check if camera exists in the database".to_string(),
                         format!("{:?}", err),
                     ))
-                })?;
+                })?,
+        };
+        let found_users = self
+            .user_qc_collection
+            .find_users_by_ids(user_ids.to_vec())
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to find users".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        let found_user_ids: HashSet<String> = found_users.into_iter().map(|u| u.user_id).collect();
+        Ok(reference_feedback(camera_exists, user_ids, &found_user_ids, created_by))
+    }
+}
 
-            if existing.is_some() {
-                already_blocked_users.push(user_id.clone());
-            }
+/// Feedback for a camera that does not exist, user ids that match no user
+/// and an admin trying to block themselves.
+fn reference_feedback(
+    camera_exists: bool,
+    user_ids: &[String],
+    found_user_ids: &HashSet<String>,
+    created_by: &str,
+) -> HashMap<String, Vec<String>> {
+    let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+    if !camera_exists {
+        feedback
+            .entry("camera_id".to_string())
+            .or_default()
+            .push("camera not found in database".to_string());
+    }
+    for user_id in user_ids {
+        if user_id == created_by {
+            feedback
+                .entry("user_ids".to_string())
+                .or_default()
+                .push("you cannot block yourself".to_string());
+        } else if !found_user_ids.contains(user_id) {
+            feedback
+                .entry("user_ids".to_string())
+                .or_default()
+                .push(format!("user {} not found in database", user_id));
         }
+    }
+    feedback
+}
 
-        if !already_blocked_users.is_empty() {
-            let mut details = HashMap::new();
-            details.insert("already_blocked_users".to_string(), already_blocked_users);
+impl<IICameraTempBlockingQCCollection, IICameraQCCollection, IIUserQCCollection>
+    ICreateCameraTempBlockingUseCase
+    for CreateCameraTempBlockingUseCaseImp<
+        IICameraTempBlockingQCCollection,
+        IICameraQCCollection,
+        IIUserQCCollection,
+    >
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self, input: CreateCameraTempBlockingInput) -> Result<(), UseCaseError> {
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        for vr in blocking_period(&input.start_time, &input.end_time, chrono::Utc::now()) {
+            if let FieldValidationResult::Invalid(field_name, message) = vr {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+        // the same user twice would make the transaction touch one item twice
+        let mut user_ids = input.user_ids.clone();
+        user_ids.sort();
+        user_ids.dedup();
+        if user_ids.is_empty() {
+            feedback.entry("user_ids".to_string()).or_default().push("user_ids cannot be empty".to_string());
+        } else if user_ids.len() > MAX_USERS_PER_TEMP_BLOCKING {
+            feedback.entry("user_ids".to_string()).or_default().push(format!(
+                "at most {} users can be blocked at once",
+                MAX_USERS_PER_TEMP_BLOCKING
+            ));
+        } else {
+            for (field_name, messages) in self
+                .check_references(&input.camera_id, &user_ids, &input.created_by)
+                .await?
+            {
+                feedback.entry(field_name).or_default().extend(messages);
+            }
+        }
+        if !feedback.is_empty() {
             return Err(UseCaseError::BusinessError(BusinessError::new(
-                "Some users already have a temp blocking for this camera".to_string(),
-                details,
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                feedback,
             )));
         }
 
@@ -67,20 +171,53 @@ where
             camera_id: input.camera_id,
             start_time: input.start_time,
             end_time: input.end_time,
-            user_ids: input.user_ids,
+            user_ids,
+            reason: input.reason.filter(|r| !r.trim().is_empty()),
+            share_reason: input.share_reason,
+            created_by: input.created_by,
         };
 
         self.camera_temp_blocking_qc_collection
             .create_temp_blocking(qc_input)
             .await
-            .map_err(|err| {
-                tracing::error!("{:?}", err);
-                UseCaseError::InternalDependencyError(InternalDependencyError::new(
-                    "failed to create camera temp blocking".to_string(),
-                    format!("{:?}", err),
-                ))
+            .map_err(|err| match err {
+                CreateCameraTempBlockingError::AlreadyBlocked(already_blocked_users) => {
+                    let mut details = HashMap::new();
+                    details.insert("already_blocked_users".to_string(), already_blocked_users);
+                    UseCaseError::BusinessError(BusinessError::new(
+                        "Some users already have a temp blocking for this camera".to_string(),
+                        details,
+                    ))
+                }
+                CreateCameraTempBlockingError::Database(err) => {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to create camera temp blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                }
             })?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_feedback_reports_unknown_ids_and_self_blocking() {
+        let user_ids = vec!["admin".to_string(), "alice".to_string(), "bob".to_string()];
+        let found: HashSet<String> = ["admin".to_string(), "alice".to_string()].into_iter().collect();
+
+        let feedback = reference_feedback(false, &user_ids, &found, "admin");
+        assert_eq!(feedback["camera_id"], vec!["camera not found in database".to_string()]);
+        assert_eq!(
+            feedback["user_ids"],
+            vec!["you cannot block yourself".to_string(), "user bob not found in database".to_string()]
+        );
+
+        assert!(reference_feedback(true, &user_ids[1..2], &found, "admin").is_empty());
+    }
+}
@@ -5,6 +5,11 @@ pub struct CreateCameraTempBlockingInput {
     pub start_time: String,
     pub end_time: String,
     pub user_ids: Vec<String>,
+    pub reason: Option<String>,
+    /// Whether the blocked users may see `reason`
+    pub share_reason: bool,
+    /// Id of the admin creating the blocking
+    pub created_by: String,
 }
 
 pub trait ICreateCameraTempBlockingUseCase {
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::shared::{
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        recurrence::RecurringSchedule,
+    },
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::{
+        ICameraTempBlockingQCCollection, RecurringCameraBlocking,
+    },
+};
+
+use super::interface::{CreateRecurringCameraBlockingInput, ICreateRecurringCameraBlockingUseCase};
+
+pub struct CreateRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    CreateRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection> ICreateRecurringCameraBlockingUseCase
+    for CreateRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(
+        &self,
+        input: CreateRecurringCameraBlockingInput,
+    ) -> Result<Vec<RecurringCameraBlocking>, UseCaseError> {
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        if input.camera_id.is_empty() {
+            feedback.entry("camera_id".to_string()).or_default().push("camera_id cannot be empty".to_string());
+        }
+        if input.user_ids.is_empty() {
+            feedback.entry("user_ids".to_string()).or_default().push("user_ids cannot be empty".to_string());
+        }
+        if let Err(errors) = RecurringSchedule::parse(&input.rrule, &input.start_time, &input.end_time, &input.timezone) {
+            for (field_name, message) in errors {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid date, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        let mut created = Vec::new();
+        for user_id in input.user_ids {
+            let blocking = RecurringCameraBlocking {
+                id: ulid::Ulid::new().to_string(),
+                camera_id: input.camera_id.clone(),
+                user_id,
+                rrule: input.rrule.clone(),
+                start_time: input.start_time.clone(),
+                end_time: input.end_time.clone(),
+                timezone: input.timezone.clone(),
+            };
+            self.camera_temp_blocking_qc_collection
+                .create_recurring_blocking(blocking.clone())
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to create recurring camera blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+            created.push(blocking);
+        }
+        Ok(created)
+    }
+}
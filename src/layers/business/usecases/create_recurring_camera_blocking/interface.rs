@@ -0,0 +1,23 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking,
+};
+
+/// `rrule` supports `FREQ=DAILY|WEEKLY` with `BYDAY` and `UNTIL`;
+/// `start_time` and `end_time` are wall-clock times like `22:00` in the IANA
+/// `timezone`. A window ending before it starts runs into the next day.
+pub struct CreateRecurringCameraBlockingInput {
+    pub camera_id: String,
+    pub user_ids: Vec<String>,
+    pub rrule: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub timezone: String,
+}
+
+pub trait ICreateRecurringCameraBlockingUseCase {
+    fn execute(
+        &self,
+        input: CreateRecurringCameraBlockingInput,
+    ) -> impl std::future::Future<Output = Result<Vec<RecurringCameraBlocking>, UseCaseError>> + Send;
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::{
+        shared::{
+            business_rules::FieldValidationResult,
+            errors::{BusinessError, InternalDependencyError, UseCaseError},
+            validation_rules::{strings::non_empty, user},
+        },
+        usecases::list_users::interface::UserListItem,
+    },
+    ewm::main_database::qc_collection::user_qc_collection::{
+        CreateUserCommandError, CreateUserCommandInput, IUserQCCollection,
+    },
+};
+
+use super::interface::{CreateUserInput, ICreateUserUseCase};
+
+pub struct CreateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection,
+{
+    user_qc_collection: IIUserQCCollection,
+}
+
+impl<IIUserQCCollection> CreateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    pub fn new(user_qc_collection: IIUserQCCollection) -> Self {
+        Self { user_qc_collection }
+    }
+}
+
+impl<IIUserQCCollection> ICreateUserUseCase for CreateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self, input: CreateUserInput) -> Result<UserListItem, UseCaseError> {
+        let user_id = input.user_id.trim().to_string();
+        let email = input.email.trim().to_lowercase();
+        let name = input.name.trim().to_string();
+
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        let results = [
+            non_empty(&user_id, "user_id", "user_id cannot be empty".to_string()),
+            user::email(&email),
+            user::user_name(&name),
+        ];
+        for result in results {
+            if let FieldValidationResult::Invalid(field_name, message) = result {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        let created = self
+            .user_qc_collection
+            .create_user(CreateUserCommandInput { user_id, email, name })
+            .await
+            .map_err(|err| match err {
+                CreateUserCommandError::AlreadyExists => {
+                    let mut details = HashMap::new();
+                    details.insert("user_id".to_string(), vec!["a user with this id already exists".to_string()]);
+                    UseCaseError::BusinessError(BusinessError::new(
+                        "could not complete operation due to invalid data, please check feedback".to_string(),
+                        details,
+                    ))
+                }
+                CreateUserCommandError::Database(err) => {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to create user".to_string(),
+                        format!("{:?}", err),
+                    ))
+                }
+            })?;
+        Ok(created.into())
+    }
+}
@@ -0,0 +1,15 @@
+use crate::layers::business::{shared::errors::UseCaseError, usecases::list_users::interface::UserListItem};
+
+/// `user_id` is the identity provider's subject, e.g. `auth0|123`.
+pub struct CreateUserInput {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+}
+
+pub trait ICreateUserUseCase {
+    fn execute(
+        &self,
+        input: CreateUserInput,
+    ) -> impl std::future::Future<Output = Result<UserListItem, UseCaseError>> + Send;
+}
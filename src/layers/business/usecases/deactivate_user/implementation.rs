@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
+    ewm::{
+        main_database::qc_collection::{
+            blocking_history_qc_collection::{BlockingHistoryEntry, IBlockingHistoryQCCollection},
+            camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+            temp_stream_session_qc_collection::ITempStreamSessionQCCollection,
+            user_qc_collection::IUserQCCollection,
+        },
+        temporary_stream_server::ITemporaryStreamServer,
+    },
+};
+
+use super::interface::{DeactivateUserInput, DeactivateUserOutput, IDeactivateUserUseCase};
+
+pub struct DeactivateUserUseCaseImp<
+    IIUserQCCollection,
+    IICameraTempBlockingQCCollection,
+    IIBlockingHistoryQCCollection,
+    IITempStreamSessionQCCollection,
+    IITemporaryStreamServer,
+> where
+    IIUserQCCollection: IUserQCCollection,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection,
+    IITemporaryStreamServer: ITemporaryStreamServer,
+{
+    user_qc_collection: IIUserQCCollection,
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    temporary_stream_server: IITemporaryStreamServer,
+}
+
+impl<
+        IIUserQCCollection,
+        IICameraTempBlockingQCCollection,
+        IIBlockingHistoryQCCollection,
+        IITempStreamSessionQCCollection,
+        IITemporaryStreamServer,
+    >
+    DeactivateUserUseCaseImp<
+        IIUserQCCollection,
+        IICameraTempBlockingQCCollection,
+        IIBlockingHistoryQCCollection,
+        IITempStreamSessionQCCollection,
+        IITemporaryStreamServer,
+    >
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    pub fn new(
+        user_qc_collection: IIUserQCCollection,
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        temporary_stream_server: IITemporaryStreamServer,
+    ) -> Self {
+        Self {
+            user_qc_collection,
+            camera_temp_blocking_qc_collection,
+            blocking_history_qc_collection,
+            temp_stream_session_qc_collection,
+            temporary_stream_server,
+        }
+    }
+}
+
+impl<
+        IIUserQCCollection,
+        IICameraTempBlockingQCCollection,
+        IIBlockingHistoryQCCollection,
+        IITempStreamSessionQCCollection,
+        IITemporaryStreamServer,
+    > IDeactivateUserUseCase
+    for DeactivateUserUseCaseImp<
+        IIUserQCCollection,
+        IICameraTempBlockingQCCollection,
+        IIBlockingHistoryQCCollection,
+        IITempStreamSessionQCCollection,
+        IITemporaryStreamServer,
+    >
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    async fn execute(&self, input: DeactivateUserInput) -> Result<DeactivateUserOutput, UseCaseError> {
+        let user_id = input.user_id.trim();
+        if user_id == input.deactivated_by {
+            let mut details = HashMap::new();
+            details.insert("user_id".to_string(), vec!["admins cannot deactivate themselves".to_string()]);
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                details,
+            )));
+        }
+
+        let now = Utc::now();
+        // deactivated first so the middleware turns the user away while the
+        // cleanup runs
+        let user = self
+            .user_qc_collection
+            .deactivate_user(user_id, now)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to deactivate user".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        let Some(user) = user else {
+            let mut details = HashMap::new();
+            details.insert("user_id".to_string(), vec!["user not found in database".to_string()]);
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "user not found".to_string(),
+                details,
+            )));
+        };
+
+        let sessions = self
+            .temp_stream_session_qc_collection
+            .list_sessions_for_user(user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list temporary stream sessions".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        for session in &sessions {
+            self.temporary_stream_server
+                .delete_stream(&session.stream_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to delete temporary stream".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+            self.temp_stream_session_qc_collection
+                .delete_session(&session.user_id, &session.id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to delete temporary stream session".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+        }
+
+        let blockings = self
+            .camera_temp_blocking_qc_collection
+            .list_temp_blocking_for_user(user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list camera temp blockings".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        let removed_blockings = blockings.len();
+        for blocking in blockings {
+            let camera_id = blocking.camera_id.clone();
+            let entry = BlockingHistoryEntry::archive(blocking, now, "deleted", Some(input.deactivated_by.clone()));
+            self.blocking_history_qc_collection
+                .archive(entry)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to archive camera temp blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+            self.camera_temp_blocking_qc_collection
+                .delete_temp_blocking(&camera_id, user_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to delete camera temp blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+        }
+
+        let recurring = self
+            .camera_temp_blocking_qc_collection
+            .list_recurring_blockings_for_user(user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list recurring camera blockings".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        for blocking in &recurring {
+            self.camera_temp_blocking_qc_collection
+                .delete_recurring_blocking(user_id, &blocking.id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to delete recurring camera blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+        }
+
+        Ok(DeactivateUserOutput {
+            user: user.into(),
+            removed_blockings,
+            removed_recurring_blockings: recurring.len(),
+            revoked_sessions: sessions.len(),
+        })
+    }
+}
@@ -0,0 +1,24 @@
+use crate::layers::business::{shared::errors::UseCaseError, usecases::list_users::interface::UserListItem};
+
+pub struct DeactivateUserInput {
+    pub user_id: String,
+    /// Admin performing the deactivation, recorded in the blocking history
+    pub deactivated_by: String,
+}
+
+/// What was cleaned up along with the deactivation.
+pub struct DeactivateUserOutput {
+    pub user: UserListItem,
+    pub removed_blockings: usize,
+    pub removed_recurring_blockings: usize,
+    pub revoked_sessions: usize,
+}
+
+/// Deactivating an already deactivated user repeats the cleanup, so a
+/// deactivation that failed halfway can simply be retried.
+pub trait IDeactivateUserUseCase {
+    fn execute(
+        &self,
+        input: DeactivateUserInput,
+    ) -> impl std::future::Future<Output = Result<DeactivateUserOutput, UseCaseError>> + Send;
+}
@@ -1,4 +1,4 @@
-use crate::layers::{business::{shared::errors::{InternalDependencyError, UseCaseError}, usecases::delete_camera::{interface::IDeleteCameraUseCase, sanitization_rules::DeleteCameraSanitizedInput}}, ewi::error::AppError, ewm::{
+use crate::layers::{business::{shared::errors::{InternalDependencyError, UseCaseError}, usecases::delete_camera::{interface::IDeleteCameraUseCase, sanitization_rules::DeleteCameraSanitizedInput}}, ewm::{
     main_database::qc_collection::camera_qc_collection::ICameraQCCollection,
     permanent_stream_server::IPermanentStreamServer,
 }};
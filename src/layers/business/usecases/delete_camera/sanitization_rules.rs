@@ -1,4 +1,4 @@
-use crate::layers::business::{shared::sanitization_rules::{pipe_all, strings::{remove_double_spaces, trim_both_sides}}, usecases::put_camera::interface::PutCameraInput};
+use crate::layers::business::shared::sanitization_rules::{pipe_all, strings::{remove_double_spaces, trim_both_sides}};
 
 #[derive(Debug)]
 pub struct DeleteCameraSanitizedInput(pub String);
@@ -0,0 +1,44 @@
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+};
+
+use super::interface::IDeleteCameraPrivacyShutterUseCase;
+
+pub struct DeleteCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    DeleteCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection> IDeleteCameraPrivacyShutterUseCase
+    for DeleteCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, camera_id: &str) -> Result<(), UseCaseError> {
+        self.camera_temp_blocking_qc_collection
+            .delete_privacy_shutter(camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to delete camera privacy shutter".to_string(),
+                    format!("{:?}", err),
+                ))
+            })
+    }
+}
@@ -0,0 +1,8 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+pub trait IDeleteCameraPrivacyShutterUseCase {
+    fn execute(
+        &self,
+        camera_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), UseCaseError>> + Send;
+}
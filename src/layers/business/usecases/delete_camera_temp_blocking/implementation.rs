@@ -1,35 +1,76 @@
+use chrono::Utc;
+
 use crate::layers::{
     business::shared::errors::{InternalDependencyError, UseCaseError},
-    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+    ewm::main_database::qc_collection::{
+        blocking_history_qc_collection::{BlockingHistoryEntry, IBlockingHistoryQCCollection},
+        camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+    },
 };
 
 use super::interface::{DeleteCameraTempBlockingInput, IDeleteCameraTempBlockingUseCase};
 
-pub struct DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+pub struct DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
 where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection,
 {
     camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    blocking_history_qc_collection: IIBlockingHistoryQCCollection,
 }
 
-impl<IICameraTempBlockingQCCollection>
-    DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+impl<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
+    DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
 where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
 {
-    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+    pub fn new(
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+    ) -> Self {
         Self {
             camera_temp_blocking_qc_collection,
+            blocking_history_qc_collection,
         }
     }
 }
 
-impl<IICameraTempBlockingQCCollection> IDeleteCameraTempBlockingUseCase
-    for DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+impl<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection> IDeleteCameraTempBlockingUseCase
+    for DeleteCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection, IIBlockingHistoryQCCollection>
 where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
 {
     async fn execute(&self, input: DeleteCameraTempBlockingInput) -> Result<(), UseCaseError> {
+        let existing = self
+            .camera_temp_blocking_qc_collection
+            .get_temp_blocking(&input.camera_id, &input.user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load camera temp blocking".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        // archived before deleting, so a failure leaves the blocking in place
+        // rather than removing it without a trace
+        if let Some(blocking) = existing {
+            let entry = BlockingHistoryEntry::archive(blocking, Utc::now(), "deleted", Some(input.deleted_by));
+            self.blocking_history_qc_collection
+                .archive(entry)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to archive camera temp blocking".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?;
+        }
+
         self.camera_temp_blocking_qc_collection
             .delete_temp_blocking(&input.camera_id, &input.user_id)
             .await
@@ -1,8 +1,10 @@
 use crate::layers::business::shared::errors::UseCaseError;
 
+/// `deleted_by` is recorded in the blocking history.
 pub struct DeleteCameraTempBlockingInput {
     pub camera_id: String,
     pub user_id: String,
+    pub deleted_by: String,
 }
 
 pub trait IDeleteCameraTempBlockingUseCase {
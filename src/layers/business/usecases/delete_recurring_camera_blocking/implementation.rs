@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+};
+
+use super::interface::{DeleteRecurringCameraBlockingInput, IDeleteRecurringCameraBlockingUseCase};
+
+pub struct DeleteRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    DeleteRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection> IDeleteRecurringCameraBlockingUseCase
+    for DeleteRecurringCameraBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, input: DeleteRecurringCameraBlockingInput) -> Result<(), UseCaseError> {
+        let blockings = self
+            .camera_temp_blocking_qc_collection
+            .list_recurring_blockings_for_user(&input.user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load recurring camera blockings from database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        if !blockings.iter().any(|b| b.id == input.id && b.camera_id == input.camera_id) {
+            let mut details = HashMap::new();
+            details.insert(
+                "id".to_string(),
+                vec!["recurring blocking not found for this camera and user".to_string()],
+            );
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "recurring blocking not found".to_string(),
+                details,
+            )));
+        }
+
+        self.camera_temp_blocking_qc_collection
+            .delete_recurring_blocking(&input.user_id, &input.id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to delete recurring camera blocking".to_string(),
+                    format!("{:?}", err),
+                ))
+            })
+    }
+}
@@ -0,0 +1,14 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+pub struct DeleteRecurringCameraBlockingInput {
+    pub camera_id: String,
+    pub user_id: String,
+    pub id: String,
+}
+
+pub trait IDeleteRecurringCameraBlockingUseCase {
+    fn execute(
+        &self,
+        input: DeleteRecurringCameraBlockingInput,
+    ) -> impl std::future::Future<Output = Result<(), UseCaseError>> + Send;
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use url::Url;
+
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::{
+        main_database::qc_collection::camera_qc_collection::ICameraQCCollection,
+        onvif::{DiscoveredDevice, IOnvifClient, OnvifCredentials},
+    },
+};
+
+use super::interface::{
+    DiscoverCamerasInput, DiscoverCamerasOutput, DiscoveredCameraCandidate,
+    IDiscoverCamerasUseCase,
+};
+
+const ONVIF_NAME_SCOPE: &str = "onvif://www.onvif.org/name/";
+
+pub struct DiscoverCamerasUseCaseImp<IICameraQCCollection, IIOnvifClient>
+where
+    IICameraQCCollection: ICameraQCCollection,
+    IIOnvifClient: IOnvifClient,
+{
+    camera_qc_collection: IICameraQCCollection,
+    onvif_client: IIOnvifClient,
+}
+
+impl<IICameraQCCollection, IIOnvifClient> DiscoverCamerasUseCaseImp<IICameraQCCollection, IIOnvifClient>
+where
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIOnvifClient: IOnvifClient + Sync,
+{
+    pub fn new(camera_qc_collection: IICameraQCCollection, onvif_client: IIOnvifClient) -> Self {
+        Self {
+            camera_qc_collection,
+            onvif_client,
+        }
+    }
+
+    async fn candidates_for_device(
+        &self,
+        device: &DiscoveredDevice,
+        credentials: Option<&OnvifCredentials>,
+    ) -> Option<Vec<DiscoveredCameraCandidate>> {
+        for device_address in device.xaddrs.iter().filter(|a| a.starts_with("http")) {
+            let stream_uris = match self
+                .onvif_client
+                .get_stream_uris(device_address, credentials)
+                .await
+            {
+                Ok(stream_uris) => stream_uris,
+                Err(err) => {
+                    tracing::info!("could not read stream uris from {}: {:?}", device_address, err);
+                    continue;
+                }
+            };
+
+            let information = self
+                .onvif_client
+                .get_device_information(device_address, credentials)
+                .await
+                .inspect_err(|err| {
+                    tracing::info!("could not read device information from {}: {:?}", device_address, err)
+                })
+                .unwrap_or_default();
+            let device_name = device_display_name(device, &information.manufacturer, &information.model);
+
+            return Some(
+                stream_uris
+                    .into_iter()
+                    .map(|stream_uri| DiscoveredCameraCandidate {
+                        name: format!("{} - {}", device_name, stream_uri.profile_name),
+                        source_url: with_credentials(&stream_uri.uri, credentials),
+                        device_address: device_address.clone(),
+                        manufacturer: information.manufacturer.clone(),
+                        model: information.model.clone(),
+                        profile_name: stream_uri.profile_name,
+                    })
+                    .collect(),
+            );
+        }
+        None
+    }
+}
+
+impl<IICameraQCCollection, IIOnvifClient> IDiscoverCamerasUseCase
+    for DiscoverCamerasUseCaseImp<IICameraQCCollection, IIOnvifClient>
+where
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIOnvifClient: IOnvifClient + Sync,
+{
+    async fn execute(&self, input: DiscoverCamerasInput) -> Result<DiscoverCamerasOutput, UseCaseError> {
+        let credentials = match (input.username, input.password) {
+            (Some(username), Some(password)) if !username.trim().is_empty() => Some(OnvifCredentials {
+                username: username.trim().to_string(),
+                password,
+            }),
+            _ => None,
+        };
+
+        let registered_streams: HashSet<String> = self
+            .camera_qc_collection
+            .list_cameras()
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list cameras".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?
+            .iter()
+            .map(|camera| stream_identity(&camera.source_url))
+            .collect();
+
+        let devices = self.onvif_client.probe().await.map_err(|err| {
+            tracing::error!("{:?}", err);
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "failed to run network discovery".to_string(),
+                format!("{:?}", err),
+            ))
+        })?;
+
+        let mut candidates = Vec::new();
+        let mut unreachable_devices = Vec::new();
+        let mut seen = registered_streams;
+        for device in devices {
+            match self.candidates_for_device(&device, credentials.as_ref()).await {
+                Some(device_candidates) => candidates.extend(
+                    device_candidates
+                        .into_iter()
+                        .filter(|candidate| seen.insert(stream_identity(&candidate.source_url))),
+                ),
+                None => unreachable_devices.push(
+                    device
+                        .xaddrs
+                        .first()
+                        .cloned()
+                        .unwrap_or(device.endpoint_reference),
+                ),
+            }
+        }
+
+        Ok(DiscoverCamerasOutput {
+            candidates,
+            unreachable_devices,
+        })
+    }
+}
+
+fn device_display_name(device: &DiscoveredDevice, manufacturer: &str, model: &str) -> String {
+    let scope_name = device
+        .scopes
+        .iter()
+        .find_map(|scope| scope.strip_prefix(ONVIF_NAME_SCOPE))
+        .map(|name| name.replace("%20", " "));
+    let hardware_name = format!("{} {}", manufacturer, model).trim().to_string();
+    match (scope_name, hardware_name.is_empty()) {
+        (Some(name), _) => name,
+        (None, false) => hardware_name,
+        (None, true) => device.endpoint_reference.clone(),
+    }
+}
+
+fn with_credentials(stream_uri: &str, credentials: Option<&OnvifCredentials>) -> String {
+    let (Some(credentials), Ok(mut url)) = (credentials, Url::parse(stream_uri)) else {
+        return stream_uri.to_string();
+    };
+    if !url.username().is_empty()
+        || url.set_username(&credentials.username).is_err()
+        || url.set_password(Some(&credentials.password)).is_err()
+    {
+        return stream_uri.to_string();
+    }
+    url.to_string()
+}
+
+/// Registered cameras usually carry credentials in their URL while ONVIF
+/// reports bare URIs, so streams are compared without user info.
+fn stream_identity(stream_url: &str) -> String {
+    match Url::parse(stream_url) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        Err(_) => stream_url.to_string(),
+    }
+}
@@ -0,0 +1,30 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+pub struct DiscoverCamerasInput {
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// A stream found on the network that is not registered yet. `name` and
+/// `source_url` are shaped so they can be posted to the camera creation
+/// endpoint as they are.
+pub struct DiscoveredCameraCandidate {
+    pub name: String,
+    pub source_url: String,
+    pub device_address: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub profile_name: String,
+}
+
+pub struct DiscoverCamerasOutput {
+    pub candidates: Vec<DiscoveredCameraCandidate>,
+    pub unreachable_devices: Vec<String>,
+}
+
+pub trait IDiscoverCamerasUseCase {
+    fn execute(
+        &self,
+        input: DiscoverCamerasInput,
+    ) -> impl std::future::Future<Output = Result<DiscoverCamerasOutput, UseCaseError>> + Send;
+}
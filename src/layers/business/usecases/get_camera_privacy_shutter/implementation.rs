@@ -0,0 +1,46 @@
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::{
+        CameraPrivacyShutter, ICameraTempBlockingQCCollection,
+    },
+};
+
+use super::interface::IGetCameraPrivacyShutterUseCase;
+
+pub struct GetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    GetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection> IGetCameraPrivacyShutterUseCase
+    for GetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, UseCaseError> {
+        self.camera_temp_blocking_qc_collection
+            .get_privacy_shutter(camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load camera privacy shutter".to_string(),
+                    format!("{:?}", err),
+                ))
+            })
+    }
+}
@@ -0,0 +1,11 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::CameraPrivacyShutter,
+};
+
+pub trait IGetCameraPrivacyShutterUseCase {
+    fn execute(
+        &self,
+        camera_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<CameraPrivacyShutter>, UseCaseError>> + Send;
+}
@@ -1,7 +1,4 @@
-use std::collections::HashMap;
-
-use chrono::{DateTime, Utc};
-use tracing::field::Field;
+use std::collections::{HashMap, HashSet};
 
 use crate::layers::{
     business::{
@@ -10,47 +7,240 @@ use crate::layers::{
                 FieldValidationResult, UseCaseInputValidationResult, UseCaseInvalidInputResult,
             },
             errors::{BusinessError, InternalDependencyError, UseCaseError},
-            validation_rules::strings::non_empty,
+            camera_access::{
+                ensure_camera_not_blocked, is_exempt_from_privacy_shutter, session_access_token,
+                with_access_token,
+            },
+            keyed_locks::KeyedLocks,
+            secrets::random_token,
+            validation_rules::{
+                source_url::{recheck_source_url, SourceUrlPolicy},
+                strings::non_empty,
+            },
         },
         usecases::get_camera_stream_url::{
-            interface::{GetCameraStreamURLOutput, IGetCameraStremaURLUseCase},
+            interface::{
+                GetCameraStreamURLOutput, GetCameraStreamUrlInput, IGetCameraStremaURLUseCase,
+                TempStreamPolicy, TempStreamUsage,
+            },
             sanitization_rules::GetCameraStreamUrlSanitizedInput,
         },
     },
-    ewi::error::UserInputError,
     ewm::{
-        main_database::qc_collection::camera_qc_collection::ICameraQCCollection,
-        temporary_stream_server::ITemporaryStreamServer,
+        main_database::qc_collection::{
+            camera_qc_collection::ICameraQCCollection,
+            camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+            temp_stream_session_qc_collection::{ITempStreamSessionQCCollection, TempStreamSession},
+        },
+        temporary_stream_server::{
+            ITemporaryStreamServer, StreamProtocol, StreamQuality, TemporaryStreamOptions,
+        },
     },
 };
 
-
-pub struct GetCameraStreamUrlUseCase<IICameraCommandQueryCollection, IITemporaryStreamServer>
+pub struct GetCameraStreamUrlUseCase<
+    IICameraCommandQueryCollection,
+    IITemporaryStreamServer,
+    IITempStreamSessionQCCollection,
+    IICameraTempBlockingQCCollection,
+>
 where
     IICameraCommandQueryCollection: ICameraQCCollection + Sync,
     IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
 {
     camera_qc_collection: IICameraCommandQueryCollection,
     temporary_stream_server: IITemporaryStreamServer,
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    stream_locks: KeyedLocks,
+    stream_policy: TempStreamPolicy,
+    source_url_policy: SourceUrlPolicy,
 }
 
-impl<IICameraCommandQueryCollection, IITemporaryStreamServer>
-    GetCameraStreamUrlUseCase<IICameraCommandQueryCollection, IITemporaryStreamServer>
+impl<IICameraCommandQueryCollection, IITemporaryStreamServer, IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection>
+    GetCameraStreamUrlUseCase<IICameraCommandQueryCollection, IITemporaryStreamServer, IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection>
 where
     IICameraCommandQueryCollection: ICameraQCCollection + Sync,
     IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
 {
     pub fn new(
         camera_qc_collection: IICameraCommandQueryCollection,
         temporary_stream_server: IITemporaryStreamServer,
-        
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        stream_locks: KeyedLocks,
+        stream_policy: TempStreamPolicy,
+        source_url_policy: SourceUrlPolicy,
     ) -> Self {
         Self {
             camera_qc_collection,
             temporary_stream_server,
+            temp_stream_session_qc_collection,
+            camera_temp_blocking_qc_collection,
+            stream_locks,
+            stream_policy,
+            source_url_policy,
         }
     }
 
+    fn resolve_stream_options(
+        &self,
+        input: &GetCameraStreamUrlInput,
+    ) -> Result<(TemporaryStreamOptions, Option<StreamProtocol>), UseCaseError> {
+        let (max_quality, _) = self.stream_policy.limits_for(&input.user_roles);
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+
+        let quality = match input.quality.as_deref().map(str::parse::<StreamQuality>) {
+            None => max_quality,
+            Some(Ok(quality)) if quality <= max_quality => quality,
+            Some(Ok(quality)) => {
+                feedback.entry("quality".to_string()).or_default().push(format!(
+                    "quality {} is not allowed for your roles, the maximum is {}",
+                    quality.as_str(),
+                    max_quality.as_str()
+                ));
+                max_quality
+            }
+            Some(Err(_)) => {
+                feedback
+                    .entry("quality".to_string())
+                    .or_default()
+                    .push("quality must be one of: original, downscaled".to_string());
+                max_quality
+            }
+        };
+
+        let ttl_seconds = self
+            .stream_policy
+            .ttl_for(&input.user_roles, input.ttl_seconds)
+            .unwrap_or_else(|message| {
+                feedback.entry("ttl".to_string()).or_default().push(message);
+                None
+            });
+
+        let protocol = match input.protocol.as_deref().map(str::parse::<StreamProtocol>) {
+            None => None,
+            Some(Ok(protocol)) => Some(protocol),
+            Some(Err(_)) => {
+                feedback
+                    .entry("protocol".to_string())
+                    .or_default()
+                    .push("protocol must be one of: rtsp, hls, whep".to_string());
+                None
+            }
+        };
+
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback"
+                    .to_string(),
+                feedback,
+            )));
+        }
+        let options = TemporaryStreamOptions {
+            quality,
+            ttl_seconds,
+        };
+        Ok((options, protocol))
+    }
+
+    async fn find_reusable_session(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+        options: &TemporaryStreamOptions,
+        privacy_shutter_exempt: bool,
+    ) -> Result<Option<TempStreamSession>, UseCaseError> {
+        let sessions = self
+            .temp_stream_session_qc_collection
+            .list_sessions_for_user(user_id)
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError {
+                    message: "Failed to list temporary stream sessions".to_owned(),
+                    debug_details: format!("{:?}", e),
+                })
+            })?;
+        let requested_half = chrono::Duration::seconds((options.ttl_seconds.unwrap_or(0) / 2) as i64);
+        let reusable_until = chrono::Utc::now() + self.stream_policy.min_reuse_remaining.max(requested_half);
+        let candidates: Vec<TempStreamSession> = sessions
+            .into_iter()
+            .filter(|s| s.camera_id == camera_id && !s.stream_url.is_empty() && !s.token.is_empty())
+            .filter(|s| s.quality == options.quality)
+            // the exemption is checked again on every read of the stream, so
+            // it has to match the user's current roles
+            .filter(|s| s.privacy_shutter_exempt == privacy_shutter_exempt)
+            .filter(|s| s.expires_at >= reusable_until)
+            .collect();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        // the temporary stream server may drop streams before they expire;
+        // when it cannot tell, a fresh stream is safer than a dead one
+        let live_streams: HashSet<String> = match self.temporary_stream_server.list_streams().await {
+            Ok(streams) => streams.into_iter().map(|stream| stream.id).collect(),
+            Err(err) => {
+                tracing::warn!("not reusing a session, temporary streams could not be listed: {:?}", err);
+                return Ok(None);
+            }
+        };
+        Ok(candidates
+            .into_iter()
+            .filter(|s| live_streams.contains(&s.stream_id))
+            .max_by_key(|s| s.expires_at))
+    }
+
+    async fn discard_session(&self, session: &TempStreamSession) {
+        if let Err(err) = self
+            .temp_stream_session_qc_collection
+            .delete_session(&session.user_id, &session.id)
+            .await
+        {
+            tracing::error!("failed to discard temporary stream session {}: {:?}", session.id, err);
+        }
+        if let Err(err) = self.temporary_stream_server.delete_stream(&session.stream_id).await {
+            tracing::error!("failed to discard temporary stream {}: {:?}", session.stream_id, err);
+        }
+    }
+
+    /// Fails when the active sessions, leaving out the `own` ones already
+    /// recorded for this request, leave no room for one more.
+    async fn ensure_within_concurrency_limits(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+        own: usize,
+    ) -> Result<(), UseCaseError> {
+        let counts = self
+            .temp_stream_session_qc_collection
+            .count_active_sessions(user_id, camera_id)
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError {
+                    message: "Failed to count temporary stream sessions".to_owned(),
+                    debug_details: format!("{:?}", e),
+                })
+            })?;
+        let usage = TempStreamUsage {
+            user: counts.user.saturating_sub(own),
+            camera: counts.camera.saturating_sub(own),
+            total: counts.total.saturating_sub(own),
+        };
+        let feedback = self.stream_policy.concurrency_limits.exceeded(usage);
+        if !feedback.is_empty() {
+            return Err(UseCaseError::LimitExceeded(BusinessError::new(
+                "too many active temporary streams, please try again later".to_string(),
+                feedback,
+            )));
+        }
+        Ok(())
+    }
+
     async fn apply_business_rules(
         &self,
         input: &GetCameraStreamUrlSanitizedInput,
@@ -80,51 +270,18 @@ where
 
         Ok(result)
     }
-
-    async fn camera_exists(&self, id: &str) -> Result<FieldValidationResult, UseCaseError> {
-        let camera_exists = self
-            .camera_qc_collection
-            .camera_exists_by_id(id)
-            .await
-            .map_err(|e| {
-                UseCaseError::InternalDependencyError({
-                    InternalDependencyError {
-                        message: "Failed to check if camera exists in the database".to_owned(),
-                        debug_details: e
-                            .0
-                            .debug_details
-                            .unwrap_or("No details provided".to_owned()),
-                    }
-                })
-            })?;
-
-        let result = match camera_exists {
-            true => FieldValidationResult::Valid,
-            false => {
-                let mut field_feedback: HashMap<String, Vec<String>> = HashMap::new();
-                field_feedback.insert(
-                    "id".to_string(),
-                    vec!["the camera should exists in the database".to_owned()],
-                );
-
-                FieldValidationResult::Invalid(
-                    "id".to_owned(),
-                    "camera not found in database".to_owned(),
-                )
-            }
-        };
-
-        Ok(result)
-    }
 }
-impl<IICameraCommandQueryCollection, IITemporaryStreamServer> IGetCameraStremaURLUseCase
-    for GetCameraStreamUrlUseCase<IICameraCommandQueryCollection, IITemporaryStreamServer>
+impl<IICameraCommandQueryCollection, IITemporaryStreamServer, IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection> IGetCameraStremaURLUseCase
+    for GetCameraStreamUrlUseCase<IICameraCommandQueryCollection, IITemporaryStreamServer, IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection>
 where
     IICameraCommandQueryCollection: ICameraQCCollection + Sync,
     IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
 {
-    async fn execute(&self, id: &str) -> Result<GetCameraStreamURLOutput, UseCaseError> {
-        let sanitized_input = GetCameraStreamUrlSanitizedInput::try_from(id).map_err(|e| {
+    async fn execute(&self, input: GetCameraStreamUrlInput) -> Result<GetCameraStreamURLOutput, UseCaseError> {
+        let user_id = input.user_id.as_str();
+        let sanitized_input = GetCameraStreamUrlSanitizedInput::try_from(input.camera_id.as_str()).map_err(|e| {
             UseCaseError::InternalDependencyError(InternalDependencyError {
                 message: "error while sanitizing input".to_string(),
                 debug_details: e,
@@ -133,14 +290,14 @@ where
 
         let business_rules_application_result = self
             .apply_business_rules(&sanitized_input)
-            .await
-            .map_err(|e| e)?;
+            .await?;
 
         if let UseCaseInputValidationResult::Invalid(r) = business_rules_application_result{
             return Err(
                 UseCaseError::BusinessError(BusinessError::new(r.message, r.feedback))
             )
         }
+        let (stream_options, protocol) = self.resolve_stream_options(&input)?;
 
         let camera = self
             .camera_qc_collection
@@ -162,9 +319,48 @@ where
                 InternalDependencyError { message: "Could not get tamporary stream for camera".to_owned(), debug_details: format!("{} no permanent stream found for camera", camera.id) }
             )),
         }?;
+
+        let privacy_shutter_exempt = is_exempt_from_privacy_shutter(&input.user_roles);
+        ensure_camera_not_blocked(
+            &self.camera_temp_blocking_qc_collection,
+            &camera.id,
+            user_id,
+            privacy_shutter_exempt,
+        )
+        .await?;
+
+        // concurrent requests for the same user and camera wait here, so only
+        // the first one creates a stream and the others reuse it
+        let _stream_guard = self
+            .stream_locks
+            .lock(&format!("{}/{}", user_id, camera.id))
+            .await;
+        if let Some(session) = self
+            .find_reusable_session(user_id, &camera.id, &stream_options, privacy_shutter_exempt)
+            .await?
+        {
+            return Ok(stream_output(session, protocol));
+        }
+
+        // requests that count against the limits wait for each other, so on
+        // one instance a request that sees the limit taken never backs out a
+        // stream another request already handed out
+        let limited = !self.stream_policy.concurrency_limits.is_unlimited();
+        let _limits_guard = match limited {
+            true => Some(self.stream_locks.lock(CONCURRENCY_LIMITS_LOCK_KEY).await),
+            false => None,
+        };
+        if limited {
+            self.ensure_within_concurrency_limits(user_id, &camera.id, 0).await?;
+        }
+
+        // cameras saved before the source url policy, or whose host now
+        // resolves elsewhere, must not reach the stream servers
+        recheck_source_url(&camera.source_url, &self.source_url_policy).await?;
+
         let temporary_stream = self
             .temporary_stream_server
-            .get_stream(&camera.id, &permanent_url)
+            .get_stream(&camera.id, &permanent_url, &stream_options)
             .await
             .map_err(|e| {
                 UseCaseError::InternalDependencyError(InternalDependencyError {
@@ -183,8 +379,240 @@ where
                 Err(err)
             }
         }?;
-        Ok(
-            GetCameraStreamURLOutput { camera_id: camera.id.clone(), temp_rtsp_url: temporary_stream.url, expiration_date }
-        )
+
+        let session = TempStreamSession {
+            id: ulid::Ulid::new().to_string(),
+            user_id: user_id.to_owned(),
+            camera_id: camera.id.clone(),
+            stream_id: temporary_stream.id,
+            stream_url: temporary_stream.url,
+            hls_url: temporary_stream.hls_url,
+            whep_url: temporary_stream.whep_url,
+            quality: stream_options.quality,
+            token: random_token(),
+            privacy_shutter_exempt,
+            issued_at: chrono::Utc::now(),
+            expires_at: expiration_date,
+        };
+        self.temp_stream_session_qc_collection
+            .create_session(session.clone())
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError {
+                    message: "Failed to record temporary stream session".to_owned(),
+                    debug_details: format!("{:?}", e),
+                })
+            })?;
+
+        // requests on other instances may have passed the first check at the
+        // same time; counting again with this session recorded catches them.
+        // Every request that sees the limit taken backs out, so two instances
+        // racing for the last slot may both reject their request
+        if limited {
+            if let Err(err) = self.ensure_within_concurrency_limits(user_id, &camera.id, 1).await {
+                self.discard_session(&session).await;
+                return Err(err);
+            }
+        }
+
+        Ok(stream_output(session, protocol))
+    }
+}
+
+/// Never formatted like the `{user_id}/{camera_id}` keys, which always hold a
+/// slash.
+const CONCURRENCY_LIMITS_LOCK_KEY: &str = "temp-stream-concurrency-limits";
+
+/// Every url carries the session access token so the media layer can
+/// authorize reads against the session.
+fn stream_output(session: TempStreamSession, preferred: Option<StreamProtocol>) -> GetCameraStreamURLOutput {
+    let access_token = session_access_token(&session);
+    let tokenized = |url: &str| with_access_token(url, &access_token);
+    let session = TempStreamSession {
+        stream_url: tokenized(&session.stream_url),
+        hls_url: session.hls_url.as_deref().map(tokenized),
+        whep_url: session.whep_url.as_deref().map(tokenized),
+        ..session
+    };
+    let (protocol, url) = match preferred {
+        Some(StreamProtocol::Hls) if session.hls_url.is_some() => {
+            (StreamProtocol::Hls, session.hls_url.clone())
+        }
+        Some(StreamProtocol::Whep) if session.whep_url.is_some() => {
+            (StreamProtocol::Whep, session.whep_url.clone())
+        }
+        _ => (StreamProtocol::Rtsp, None),
+    };
+    GetCameraStreamURLOutput {
+        session_id: session.id,
+        camera_id: session.camera_id,
+        url: url.unwrap_or(session.stream_url.clone()),
+        protocol,
+        temp_rtsp_url: session.stream_url,
+        hls_url: session.hls_url,
+        whep_url: session.whep_url,
+        quality: session.quality,
+        expiration_date: session.expires_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::layers::{
+        business::usecases::get_camera_stream_url::interface::TempStreamConcurrencyLimits,
+        ewm::{
+            main_database::qc_collection::{
+                camera_qc_collection::PutCameraCommandInput,
+                temp_stream_session_qc_collection::{
+                    ActiveTempStreamSessionCounts, CountTempStreamSessionsError, CreateTempStreamSessionError,
+                    DeleteTempStreamSessionError, FindTempStreamSessionError, ListTempStreamSessionsQueryError,
+                },
+                memory::{
+                    camera_qc_collection::InMemoryCameraQCCollection,
+                    camera_temp_blocking_qc_collection::InMemoryCameraTempBlockingQCCollection,
+                    temp_stream_session_qc_collection::InMemoryTempStreamSessionQCCollection,
+                },
+            },
+            temporary_stream_server::{StreamResource, TemporaryStreamOutput, TemporaryStreamServerError},
+        },
+    };
+
+    #[derive(Clone, Default)]
+    struct FakeStreamServer {
+        streams: Arc<Mutex<Vec<String>>>,
+    }
+
+    fn stream(id: &str) -> TemporaryStreamOutput {
+        TemporaryStreamOutput {
+            id: id.to_string(),
+            name: "garage".to_string(),
+            url: format!("rtsp://relay/{}", id),
+            hls_url: None,
+            whep_url: None,
+            expiration_date: Some(chrono::Utc::now() + chrono::Duration::minutes(10)),
+        }
+    }
+
+    impl ITemporaryStreamServer for FakeStreamServer {
+        async fn get_stream(
+            &self,
+            _camera_id: &str,
+            _source_camera_stream_url: &str,
+            _options: &TemporaryStreamOptions,
+        ) -> Result<TemporaryStreamOutput, TemporaryStreamServerError> {
+            let id = ulid::Ulid::new().to_string();
+            self.streams.lock().unwrap().push(id.clone());
+            Ok(stream(&id))
+        }
+
+        async fn delete_stream(&self, stream_id: &str) -> Result<(), TemporaryStreamServerError> {
+            self.streams.lock().unwrap().retain(|id| id != stream_id);
+            Ok(())
+        }
+
+        async fn list_streams(&self) -> Result<Vec<TemporaryStreamOutput>, TemporaryStreamServerError> {
+            Ok(self.streams.lock().unwrap().iter().map(|id| stream(id)).collect())
+        }
+
+        async fn fetch_resource(&self, url: &str) -> Result<StreamResource, TemporaryStreamServerError> {
+            Err(TemporaryStreamServerError {
+                message: "not served by the fake".to_string(),
+                debug_message: url.to_string(),
+            })
+        }
+    }
+
+    /// Counting takes long enough on both sides of the read for concurrent
+    /// requests to record their sessions and count them before either one
+    /// backs out.
+    #[derive(Default)]
+    struct SlowCountingSessions(InMemoryTempStreamSessionQCCollection);
+
+    impl ITempStreamSessionQCCollection for SlowCountingSessions {
+        async fn create_session(&self, session: TempStreamSession) -> Result<(), CreateTempStreamSessionError> {
+            self.0.create_session(session).await
+        }
+
+        async fn list_sessions_for_user(&self, user_id: &str) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+            self.0.list_sessions_for_user(user_id).await
+        }
+
+        async fn list_sessions(&self) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+            self.0.list_sessions().await
+        }
+
+        async fn find_session_by_id(&self, session_id: &str) -> Result<Option<TempStreamSession>, FindTempStreamSessionError> {
+            self.0.find_session_by_id(session_id).await
+        }
+
+        async fn delete_session(&self, user_id: &str, session_id: &str) -> Result<(), DeleteTempStreamSessionError> {
+            self.0.delete_session(user_id, session_id).await
+        }
+
+        async fn count_active_sessions(
+            &self,
+            user_id: &str,
+            camera_id: &str,
+        ) -> Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError> {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let counts = self.0.count_active_sessions(user_id, camera_id).await;
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            counts
+        }
+    }
+
+    fn input(user_id: &str) -> GetCameraStreamUrlInput {
+        GetCameraStreamUrlInput {
+            camera_id: "garage".to_string(),
+            user_id: user_id.to_string(),
+            user_roles: vec![],
+            quality: None,
+            ttl_seconds: None,
+            protocol: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_for_the_last_slot_admit_one() {
+        let cameras = InMemoryCameraQCCollection::default();
+        cameras
+            .put_camera(PutCameraCommandInput {
+                id: Some("garage".to_string()),
+                name: "Garage".to_string(),
+                source_url: "rtsp://203.0.113.10/stream".to_string(),
+                permanent_stream_url: Some("rtsp://relay/permanent/garage".to_string()),
+                stream_metadata: None,
+            })
+            .await
+            .unwrap();
+        let server = FakeStreamServer::default();
+        let use_case = GetCameraStreamUrlUseCase::new(
+            cameras,
+            server.clone(),
+            SlowCountingSessions::default(),
+            InMemoryCameraTempBlockingQCCollection::default(),
+            KeyedLocks::new(),
+            TempStreamPolicy {
+                min_reuse_remaining: chrono::Duration::seconds(60),
+                role_limits: vec![],
+                concurrency_limits: TempStreamConcurrencyLimits {
+                    per_user: None,
+                    per_camera: Some(1),
+                    total: None,
+                },
+            },
+            SourceUrlPolicy::default(),
+        );
+
+        let (first, second) = tokio::join!(use_case.execute(input("auth0|1")), use_case.execute(input("auth0|2")));
+        let granted = [&first, &second].iter().filter(|result| result.is_ok()).count();
+        assert_eq!(granted, 1);
+        assert!([first, second]
+            .into_iter()
+            .any(|result| matches!(result, Err(UseCaseError::LimitExceeded(_)))));
+        assert_eq!(server.streams.lock().unwrap().len(), 1);
     }
 }
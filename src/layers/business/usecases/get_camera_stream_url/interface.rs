@@ -1,11 +1,211 @@
-use crate::layers::business::shared::errors::UseCaseError;
+use std::{collections::HashMap, str::FromStr};
 
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::temporary_stream_server::{StreamProtocol, StreamQuality},
+};
+
+/// `quality` and `ttl_seconds` are what the client asked for; they are checked
+/// against the limits of `user_roles` before a stream is created. `protocol`
+/// is the preferred playback protocol.
+pub struct GetCameraStreamUrlInput {
+    pub camera_id: String,
+    pub user_id: String,
+    pub user_roles: Vec<String>,
+    pub quality: Option<String>,
+    pub ttl_seconds: Option<u64>,
+    pub protocol: Option<String>,
+}
+
+/// `url` is the url for `protocol`, which is the requested protocol when the
+/// relay offers it and RTSP otherwise.
 pub struct GetCameraStreamURLOutput {
+    pub session_id: String,
     pub camera_id: String,
     pub temp_rtsp_url: String,
+    pub hls_url: Option<String>,
+    pub whep_url: Option<String>,
+    pub protocol: StreamProtocol,
+    pub url: String,
+    pub quality: StreamQuality,
     pub expiration_date: chrono::DateTime<chrono::Utc>,
 }
 
+/// Highest quality and longest lifetime a role may request, written as
+/// `role=quality:ttl_seconds` in configuration. The role `*` applies to
+/// every user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempStreamRoleLimit {
+    pub role: String,
+    pub max_quality: StreamQuality,
+    pub max_ttl_seconds: u64,
+}
+
+impl FromStr for TempStreamRoleLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (role, limits) = s
+            .split_once('=')
+            .ok_or(format!("expected role=quality:ttl_seconds, got {:?}", s))?;
+        let (quality, ttl) = limits
+            .split_once(':')
+            .ok_or(format!("expected role=quality:ttl_seconds, got {:?}", s))?;
+        Ok(TempStreamRoleLimit {
+            role: role.trim().to_string(),
+            max_quality: quality.parse()?,
+            max_ttl_seconds: ttl
+                .trim()
+                .parse()
+                .map_err(|err| format!("invalid ttl {:?}: {}", ttl, err))?,
+        })
+    }
+}
+
+/// An existing session for the same user, camera and quality is handed out
+/// again instead of creating a new stream while it has at least
+/// `min_reuse_remaining` lifetime left, or half the requested ttl if that is
+/// longer.
+#[derive(Debug, Clone)]
+pub struct TempStreamPolicy {
+    pub min_reuse_remaining: chrono::Duration,
+    pub role_limits: Vec<TempStreamRoleLimit>,
+    pub concurrency_limits: TempStreamConcurrencyLimits,
+}
+
+/// Maximum number of unexpired temporary stream sessions at once; `None`
+/// means unlimited. Reusing an existing session never counts against them.
+#[derive(Debug, Clone, Default)]
+pub struct TempStreamConcurrencyLimits {
+    pub per_user: Option<usize>,
+    pub per_camera: Option<usize>,
+    pub total: Option<usize>,
+}
+
+/// Active sessions for the requesting user, the requested camera and overall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempStreamUsage {
+    pub user: usize,
+    pub camera: usize,
+    pub total: usize,
+}
+
+impl TempStreamConcurrencyLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_user.is_none() && self.per_camera.is_none() && self.total.is_none()
+    }
+
+    /// Feedback for every limit that one more session would exceed, keyed by
+    /// `user`, `camera` and `total`.
+    pub fn exceeded(&self, usage: TempStreamUsage) -> HashMap<String, Vec<String>> {
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, limit, used) in [
+            ("user", self.per_user, usage.user),
+            ("camera", self.per_camera, usage.camera),
+            ("total", self.total, usage.total),
+        ] {
+            if let Some(limit) = limit.filter(|limit| used >= *limit) {
+                feedback.entry(key.to_string()).or_default().push(format!(
+                    "{} of {} active temporary streams in use",
+                    used, limit
+                ));
+            }
+        }
+        feedback
+    }
+}
+
+impl TempStreamPolicy {
+    /// Most permissive limits across the user's roles. Users without any
+    /// matching limit keep the server defaults: original quality and no
+    /// custom ttl.
+    pub fn limits_for(&self, roles: &[String]) -> (StreamQuality, Option<u64>) {
+        let matching: Vec<&TempStreamRoleLimit> = self
+            .role_limits
+            .iter()
+            .filter(|limit| limit.role == "*" || roles.contains(&limit.role))
+            .collect();
+        if matching.is_empty() {
+            return (StreamQuality::Original, None);
+        }
+        let max_quality = matching.iter().map(|l| l.max_quality).max().unwrap_or(StreamQuality::Original);
+        let max_ttl_seconds = matching.iter().map(|l| l.max_ttl_seconds).max();
+        (max_quality, max_ttl_seconds)
+    }
+
+    /// The ttl a stream gets for the user's roles. An omitted ttl falls back
+    /// to the role maximum, so it cannot be used to get past the cap.
+    pub fn ttl_for(&self, roles: &[String], requested: Option<u64>) -> Result<Option<u64>, String> {
+        let (_, max_ttl_seconds) = self.limits_for(roles);
+        match (requested, max_ttl_seconds) {
+            (None, max) => Ok(max),
+            (Some(0), _) => Err("ttl must be greater than 0".to_string()),
+            (Some(_), None) => Err("ttl cannot be requested with your roles".to_string()),
+            (Some(ttl), Some(max)) if ttl > max => Err(format!("ttl cannot exceed {} seconds", max)),
+            (Some(ttl), Some(_)) => Ok(Some(ttl)),
+        }
+    }
+}
+
 pub trait IGetCameraStremaURLUseCase {
-    fn execute(&self, id: &str) -> impl std::future::Future<Output = Result<GetCameraStreamURLOutput, UseCaseError>> + Send;
-}
\ No newline at end of file
+    fn execute(&self, input: GetCameraStreamUrlInput) -> impl std::future::Future<Output = Result<GetCameraStreamURLOutput, UseCaseError>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_take_the_most_permissive_matching_role() {
+        let policy = TempStreamPolicy {
+            min_reuse_remaining: chrono::Duration::seconds(60),
+            role_limits: vec![
+                "*=downscaled:600".parse().unwrap(),
+                "Kiosk=original:86400".parse().unwrap(),
+            ],
+            concurrency_limits: TempStreamConcurrencyLimits::default(),
+        };
+        assert_eq!(
+            policy.limits_for(&["Viewer".to_string()]),
+            (StreamQuality::Downscaled, Some(600))
+        );
+        assert_eq!(
+            policy.limits_for(&["Kiosk".to_string()]),
+            (StreamQuality::Original, Some(86400))
+        );
+        assert!("Kiosk=best:10".parse::<TempStreamRoleLimit>().is_err());
+    }
+
+    #[test]
+    fn test_omitted_ttl_is_capped_at_the_role_maximum() {
+        let policy = TempStreamPolicy {
+            min_reuse_remaining: chrono::Duration::seconds(60),
+            role_limits: vec!["Viewer=downscaled:600".parse().unwrap()],
+            concurrency_limits: TempStreamConcurrencyLimits::default(),
+        };
+        let viewer = ["Viewer".to_string()];
+        assert_eq!(policy.ttl_for(&viewer, None), Ok(Some(600)));
+        assert_eq!(policy.ttl_for(&viewer, Some(300)), Ok(Some(300)));
+        assert!(policy.ttl_for(&viewer, Some(601)).is_err());
+        assert!(policy.ttl_for(&viewer, Some(0)).is_err());
+        // roles without a limit keep the server default
+        assert_eq!(policy.ttl_for(&["Admin".to_string()], None), Ok(None));
+        assert!(policy.ttl_for(&["Admin".to_string()], Some(300)).is_err());
+    }
+
+    #[test]
+    fn test_concurrency_limits_report_usage() {
+        let limits = TempStreamConcurrencyLimits {
+            per_user: Some(2),
+            per_camera: None,
+            total: Some(10),
+        };
+        let under = TempStreamUsage { user: 1, camera: 7, total: 9 };
+        assert!(limits.exceeded(under).is_empty());
+
+        let feedback = limits.exceeded(TempStreamUsage { user: 2, camera: 7, total: 9 });
+        assert_eq!(feedback.len(), 1);
+        assert_eq!(feedback["user"], vec!["2 of 2 active temporary streams in use".to_string()]);
+        assert!(TempStreamConcurrencyLimits::default().is_unlimited());
+    }
+}
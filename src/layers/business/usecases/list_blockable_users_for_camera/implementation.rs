@@ -1,14 +1,20 @@
 use std::collections::HashSet;
 
+use chrono::Utc;
+
 use crate::layers::{
-    business::shared::errors::{InternalDependencyError, UseCaseError},
+    business::shared::{
+        camera_access::is_privacy_shutter_closed,
+        errors::{InternalDependencyError, UseCaseError},
+        user_search::{build_search_users_query, map_search_users_error, UserSearchInput},
+    },
     ewm::main_database::qc_collection::{
         camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
-        user_qc_collection::IUserQCCollection,
+        user_qc_collection::{IUserQCCollection, SearchUsersQuery},
     },
 };
 
-use super::interface::{BlockableUserItem, IListBlockableUsersForCameraUseCase};
+use super::interface::{BlockableUserItem, BlockableUsersPage, IListBlockableUsersForCameraUseCase};
 
 pub struct ListBlockableUsersForCameraUseCaseImp<IICameraTempBlockingQCCollection, IIUserQCCollection>
 where
@@ -42,46 +48,67 @@ where
     IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
     IIUserQCCollection: IUserQCCollection + Sync,
 {
-    async fn execute(&self, camera_id: &str) -> Result<Vec<BlockableUserItem>, UseCaseError> {
-        let blockings = self
+    async fn execute(&self, camera_id: &str, input: UserSearchInput) -> Result<BlockableUsersPage, UseCaseError> {
+        // checked before anything is loaded so a bad limit is reported even
+        // while the shutter is closed
+        let query = build_search_users_query(input, true, HashSet::new())?;
+
+        // a closed privacy shutter already blocks everyone who could be blocked
+        let privacy_shutter = self
             .camera_temp_blocking_qc_collection
-            .list_temp_blocking_for_camera(camera_id)
+            .get_privacy_shutter(camera_id)
             .await
             .map_err(|err| {
                 tracing::error!("{:?}", err);
                 UseCaseError::InternalDependencyError(InternalDependencyError::new(
-                    "failed to list camera temp blockings".to_string(),
+                    "failed to load camera privacy shutter".to_string(),
                     format!("{:?}", err),
                 ))
             })?;
+        if privacy_shutter.is_some_and(|shutter| is_privacy_shutter_closed(&shutter, Utc::now())) {
+            return Ok(BlockableUsersPage {
+                users: vec![],
+                next_cursor: None,
+            });
+        }
 
-        let blocked_user_ids: HashSet<String> = blockings
-            .into_iter()
-            .map(|b| b.user_id)
-            .collect();
-
-        let all_users = self
-            .user_qc_collection
-            .list_users()
+        let blockings = self
+            .camera_temp_blocking_qc_collection
+            .list_temp_blocking_for_camera(camera_id)
             .await
             .map_err(|err| {
                 tracing::error!("{:?}", err);
                 UseCaseError::InternalDependencyError(InternalDependencyError::new(
-                    "failed to list users".to_string(),
+                    "failed to list camera temp blockings".to_string(),
                     format!("{:?}", err),
                 ))
             })?;
 
-        let blockable_users = all_users
+        let blocked_user_ids: HashSet<String> = blockings
             .into_iter()
-            .filter(|user| !blocked_user_ids.contains(&user.user_id))
-            .map(|user| BlockableUserItem {
-                user_id: user.user_id,
-                email: user.email,
-                name: user.name,
-            })
+            .map(|b| b.user_id)
             .collect();
 
-        Ok(blockable_users)
+        let page = self
+            .user_qc_collection
+            .search_users(SearchUsersQuery {
+                exclude_user_ids: blocked_user_ids,
+                ..query
+            })
+            .await
+            .map_err(map_search_users_error)?;
+
+        Ok(BlockableUsersPage {
+            users: page
+                .users
+                .into_iter()
+                .map(|user| BlockableUserItem {
+                    user_id: user.user_id,
+                    email: user.email,
+                    name: user.name,
+                })
+                .collect(),
+            next_cursor: page.next_cursor,
+        })
     }
 }
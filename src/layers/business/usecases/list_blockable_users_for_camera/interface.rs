@@ -1,4 +1,4 @@
-use crate::layers::business::shared::errors::UseCaseError;
+use crate::layers::business::shared::{errors::UseCaseError, user_search::UserSearchInput};
 
 pub struct BlockableUserItem {
     pub user_id: String,
@@ -6,9 +6,17 @@ pub struct BlockableUserItem {
     pub name: String,
 }
 
+/// A missing `next_cursor` marks the last page.
+pub struct BlockableUsersPage {
+    pub users: Vec<BlockableUserItem>,
+    pub next_cursor: Option<String>,
+}
+
+/// Active users without a blocking on the camera.
 pub trait IListBlockableUsersForCameraUseCase {
     fn execute(
         &self,
         camera_id: &str,
-    ) -> impl std::future::Future<Output = Result<Vec<BlockableUserItem>, UseCaseError>> + Send;
+        input: UserSearchInput,
+    ) -> impl std::future::Future<Output = Result<BlockableUsersPage, UseCaseError>> + Send;
 }
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::layers::{
+    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::blocking_history_qc_collection::{
+        BlockingHistoryPage, BlockingHistoryQuery, IBlockingHistoryQCCollection, QueryBlockingHistoryError,
+    },
+};
+
+use super::interface::{
+    IListBlockingHistoryUseCase, ListBlockingHistoryInput, DEFAULT_BLOCKING_HISTORY_PAGE_SIZE,
+    MAX_BLOCKING_HISTORY_PAGE_SIZE,
+};
+
+pub struct ListBlockingHistoryUseCaseImp<IIBlockingHistoryQCCollection>
+where
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection,
+{
+    blocking_history_qc_collection: IIBlockingHistoryQCCollection,
+}
+
+impl<IIBlockingHistoryQCCollection> ListBlockingHistoryUseCaseImp<IIBlockingHistoryQCCollection>
+where
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+{
+    pub fn new(blocking_history_qc_collection: IIBlockingHistoryQCCollection) -> Self {
+        Self {
+            blocking_history_qc_collection,
+        }
+    }
+}
+
+fn invalid_input(details: HashMap<String, Vec<String>>) -> UseCaseError {
+    UseCaseError::BusinessError(BusinessError::new(
+        "could not complete operation due to invalid date, please check feedback".to_string(),
+        details,
+    ))
+}
+
+/// Turns the raw input into a query, collecting every problem at once.
+fn build_query(input: ListBlockingHistoryInput) -> Result<BlockingHistoryQuery, HashMap<String, Vec<String>>> {
+    let mut details: HashMap<String, Vec<String>> = HashMap::new();
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let camera_id = non_empty(input.camera_id);
+    let user_id = non_empty(input.user_id);
+    if camera_id.is_none() && user_id.is_none() {
+        details
+            .entry("camera_id".to_string())
+            .or_default()
+            .push("camera_id or user_id must be provided".to_string());
+    }
+
+    let mut parse = |name: &str, value: Option<String>| -> Option<DateTime<Utc>> {
+        let value = value?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|date| date.with_timezone(&Utc))
+            .map_err(|_| {
+                details
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(format!("{} must be an RFC 3339 date", name))
+            })
+            .ok()
+    };
+    let from = parse("from", input.from);
+    let to = parse("to", input.to);
+    if let (Some(from), Some(to)) = (from, to) {
+        if from >= to {
+            details
+                .entry("to".to_string())
+                .or_default()
+                .push("to must be after from".to_string());
+        }
+    }
+
+    let limit = input.limit.unwrap_or(DEFAULT_BLOCKING_HISTORY_PAGE_SIZE);
+    if !(1..=MAX_BLOCKING_HISTORY_PAGE_SIZE).contains(&limit) {
+        details
+            .entry("limit".to_string())
+            .or_default()
+            .push(format!("limit must be between 1 and {}", MAX_BLOCKING_HISTORY_PAGE_SIZE));
+    }
+
+    if !details.is_empty() {
+        return Err(details);
+    }
+    Ok(BlockingHistoryQuery {
+        camera_id,
+        user_id,
+        from,
+        to,
+        limit,
+        cursor: non_empty(input.cursor),
+    })
+}
+
+impl<IIBlockingHistoryQCCollection> IListBlockingHistoryUseCase
+    for ListBlockingHistoryUseCaseImp<IIBlockingHistoryQCCollection>
+where
+    IIBlockingHistoryQCCollection: IBlockingHistoryQCCollection + Sync,
+{
+    async fn execute(&self, input: ListBlockingHistoryInput) -> Result<BlockingHistoryPage, UseCaseError> {
+        let query = build_query(input).map_err(invalid_input)?;
+
+        self.blocking_history_qc_collection
+            .query(query)
+            .await
+            .map_err(|err| match err {
+                QueryBlockingHistoryError::InvalidCursor => invalid_input(HashMap::from([(
+                    "cursor".to_string(),
+                    vec!["cursor is not a next_cursor returned by this endpoint".to_string()],
+                )])),
+                QueryBlockingHistoryError::Database(err) => {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to query blocking history".to_string(),
+                        format!("{:?}", err),
+                    ))
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> ListBlockingHistoryInput {
+        ListBlockingHistoryInput {
+            camera_id: None,
+            user_id: None,
+            from: None,
+            to: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    #[test]
+    fn test_build_query_collects_all_problems() {
+        let details = build_query(ListBlockingHistoryInput {
+            from: Some("2025-02-01T00:00:00Z".to_string()),
+            to: Some("2025-01-01T00:00:00Z".to_string()),
+            limit: Some(0),
+            ..input()
+        })
+        .err()
+        .unwrap();
+        let mut fields: Vec<&str> = details.keys().map(|k| k.as_str()).collect();
+        fields.sort();
+        assert_eq!(fields, vec!["camera_id", "limit", "to"]);
+    }
+
+    #[test]
+    fn test_build_query_defaults() {
+        let query = build_query(ListBlockingHistoryInput {
+            camera_id: Some(" garage ".to_string()),
+            from: Some("2025-01-01T01:00:00+01:00".to_string()),
+            cursor: Some("".to_string()),
+            ..input()
+        })
+        .ok()
+        .unwrap();
+        assert_eq!(query.camera_id.as_deref(), Some("garage"));
+        assert_eq!(query.from, Some("2025-01-01T00:00:00Z".parse().unwrap()));
+        assert_eq!(query.limit, DEFAULT_BLOCKING_HISTORY_PAGE_SIZE);
+        assert!(query.cursor.is_none());
+    }
+}
@@ -0,0 +1,25 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::blocking_history_qc_collection::BlockingHistoryPage,
+};
+
+pub const DEFAULT_BLOCKING_HISTORY_PAGE_SIZE: i32 = 50;
+pub const MAX_BLOCKING_HISTORY_PAGE_SIZE: i32 = 100;
+
+/// At least one of `camera_id` and `user_id` is required. `from` and `to`
+/// are RFC 3339 dates; a blocking matches when it overlapped `[from, to)`.
+pub struct ListBlockingHistoryInput {
+    pub camera_id: Option<String>,
+    pub user_id: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i32>,
+    pub cursor: Option<String>,
+}
+
+pub trait IListBlockingHistoryUseCase {
+    fn execute(
+        &self,
+        input: ListBlockingHistoryInput,
+    ) -> impl std::future::Future<Output = Result<BlockingHistoryPage, UseCaseError>> + Send;
+}
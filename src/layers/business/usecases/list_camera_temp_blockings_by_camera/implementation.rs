@@ -90,6 +90,11 @@ where
                         user_id: blocking.user_id,
                         user_name,
                     },
+                    reason: blocking.reason,
+                    share_reason: blocking.share_reason,
+                    created_by: blocking.created_by,
+                    created_at: blocking.created_at,
+                    updated_at: blocking.updated_at,
                 }
             })
             .collect();
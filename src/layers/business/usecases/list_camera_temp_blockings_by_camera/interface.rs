@@ -10,6 +10,11 @@ pub struct CameraTempBlockingItem {
     pub camera_id: String,
     pub end_date: String,
     pub blocked_user: BlockedUserInfo,
+    pub reason: Option<String>,
+    pub share_reason: bool,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
 }
 
 pub trait IListCameraTempBlockingsByCameraUseCase {
@@ -47,7 +47,7 @@ impl<IICameraCommandQueryCollection> IListCamerasUseCase for ListCamerasUseCaseI
 #[cfg(test)]
 mod tests {
 
-    use crate::layers::ewm::main_database::qc_collection::{camera_qc_collection::{CameraListQueryResultItem, ListCamerasQueryError}, error::QCError};
+    use crate::layers::ewm::main_database::qc_collection::{camera_qc_collection::{CameraListQueryResultItem, CameraStreamMetadata, CheckIfCameraExistsError, CreateCameraCommandError, CreateCameraCommandOutput, DeleteCameraCommandError, FindCameraByIdResult, FindCamerabyIdError, ListCamerasQueryError, PutCameraCommandInput, UpdateCameraCommandError, UpdateCameraCommandInput, UpdateCameraStreamMetadataError}, error::QCError};
 
     use super::*;
 
@@ -67,41 +67,44 @@ mod tests {
         
         async fn put_camera(
             &self,
-            command_input: crate::layers::ewm::main_database::qc_collection::camera_qc_collection::PutCameraCommandInput,
-        ) -> Result<crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CreateCameraCommandOutput, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CreateCameraCommandError> {
+            _command_input: PutCameraCommandInput,
+        ) -> Result<CreateCameraCommandOutput, CreateCameraCommandError> {
             todo!()
         }
-        
-        async fn delete_camera_by_id(
-            &self,
-            id: &str,
-        ) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_qc_collection::DeleteCameraCommandError> {
+
+        async fn delete_camera_by_id(&self, _id: &str) -> Result<(), DeleteCameraCommandError> {
             todo!()
         }
-        
-        fn find_camera_by_id(
+
+        async fn find_camera_by_id(&self, _id: &str) -> Result<FindCameraByIdResult, FindCamerabyIdError> {
+            Ok(FindCameraByIdResult {
+                id: "1".to_string(),
+                name: "Mock Camera".to_string(),
+                source_url: "mock://camera".to_string(),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                permanent_stream_url: None,
+                stream_metadata: None
+            })
+        }
+
+        async fn camera_exists_by_id(&self, _id: &str) -> Result<bool, CheckIfCameraExistsError> {
+            Ok(true)
+        }
+
+        async fn update_camera_stream_metadata(
             &self,
-            id: &str,
-        ) -> impl std::future::Future<Output = Result<crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCameraByIdResult, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCamerabyIdError>> + Send {
-            async move {
-                Ok(crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCameraByIdResult {
-                    id: "1".to_string(),
-                    name: "Mock Camera".to_string(),
-                    source_url: "mock://camera".to_string(),
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                    permanent_stream_url: None
-                })
-            }
+            _id: &str,
+            _stream_metadata: CameraStreamMetadata,
+        ) -> Result<(), UpdateCameraStreamMetadataError> {
+            Err(UpdateCameraStreamMetadataError(QCError { message: "not supported by the mock".to_string(), debug_details: None }))
         }
-        
-        fn camera_exists_by_id(
+
+        async fn update_camera(
             &self,
-            id: &str,
-        ) -> impl std::future::Future<Output = Result<bool, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CheckIfCameraExistsError>> + Send {
-            async move {
-                Ok(true)
-            }
+            _command_input: UpdateCameraCommandInput,
+        ) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+            Err(UpdateCameraCommandError(QCError { message: "not supported by the mock".to_string(), debug_details: None }))
         }
     }
 
@@ -0,0 +1,46 @@
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::{
+        ICameraTempBlockingQCCollection, RecurringCameraBlocking,
+    },
+};
+
+use super::interface::IListRecurringCameraBlockingsUseCase;
+
+pub struct ListRecurringCameraBlockingsUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    ListRecurringCameraBlockingsUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection> IListRecurringCameraBlockingsUseCase
+    for ListRecurringCameraBlockingsUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, camera_id: &str) -> Result<Vec<RecurringCameraBlocking>, UseCaseError> {
+        self.camera_temp_blocking_qc_collection
+            .list_recurring_blockings_for_camera(camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load recurring camera blockings from database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })
+    }
+}
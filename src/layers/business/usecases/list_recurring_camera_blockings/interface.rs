@@ -0,0 +1,11 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking,
+};
+
+pub trait IListRecurringCameraBlockingsUseCase {
+    fn execute(
+        &self,
+        camera_id: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<RecurringCameraBlocking>, UseCaseError>> + Send;
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::{
+        main_database::qc_collection::temp_stream_session_qc_collection::ITempStreamSessionQCCollection,
+        temporary_stream_server::ITemporaryStreamServer,
+    },
+};
+
+use super::interface::{
+    IListTempStreamSessionsUseCase, ListTempStreamSessionsInput, TempStreamSessionItem,
+};
+
+pub struct ListTempStreamSessionsUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection,
+    IITemporaryStreamServer: ITemporaryStreamServer,
+{
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    temporary_stream_server: IITemporaryStreamServer,
+}
+
+impl<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+    ListTempStreamSessionsUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    pub fn new(
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        temporary_stream_server: IITemporaryStreamServer,
+    ) -> Self {
+        Self {
+            temp_stream_session_qc_collection,
+            temporary_stream_server,
+        }
+    }
+}
+
+impl<IITempStreamSessionQCCollection, IITemporaryStreamServer> IListTempStreamSessionsUseCase
+    for ListTempStreamSessionsUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    async fn execute(
+        &self,
+        input: ListTempStreamSessionsInput,
+    ) -> Result<Vec<TempStreamSessionItem>, UseCaseError> {
+        let sessions = match input.user_id {
+            Some(user_id) => {
+                self.temp_stream_session_qc_collection
+                    .list_sessions_for_user(user_id.trim())
+                    .await
+            }
+            None => self.temp_stream_session_qc_collection.list_sessions().await,
+        }
+        .map_err(|err| {
+            tracing::error!("{:?}", err);
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "failed to list temporary stream sessions".to_string(),
+                format!("{:?}", err),
+            ))
+        })?;
+
+        // a session only counts as active while its stream still exists on the
+        // temporary stream server, which may drop streams before they expire
+        let live_streams: HashSet<String> = self
+            .temporary_stream_server
+            .list_streams()
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to list temporary streams".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?
+            .into_iter()
+            .map(|stream| stream.id)
+            .collect();
+
+        let now = Utc::now();
+        let mut active_sessions: Vec<TempStreamSessionItem> = sessions
+            .into_iter()
+            .filter(|session| session.expires_at > now && live_streams.contains(&session.stream_id))
+            .map(|session| TempStreamSessionItem {
+                id: session.id,
+                user_id: session.user_id,
+                camera_id: session.camera_id,
+                stream_id: session.stream_id,
+                issued_at: session.issued_at,
+                expires_at: session.expires_at,
+            })
+            .collect();
+        active_sessions.sort_by_key(|session| std::cmp::Reverse(session.issued_at));
+        Ok(active_sessions)
+    }
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+
+use crate::layers::business::shared::errors::UseCaseError;
+
+/// `user_id` restricts the listing to one user's sessions; `None` lists the
+/// sessions of every user.
+pub struct ListTempStreamSessionsInput {
+    pub user_id: Option<String>,
+}
+
+pub struct TempStreamSessionItem {
+    pub id: String,
+    pub user_id: String,
+    pub camera_id: String,
+    pub stream_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub trait IListTempStreamSessionsUseCase {
+    fn execute(
+        &self,
+        input: ListTempStreamSessionsInput,
+    ) -> impl std::future::Future<Output = Result<Vec<TempStreamSessionItem>, UseCaseError>> + Send;
+}
@@ -1,9 +1,14 @@
+use std::collections::HashSet;
+
 use crate::layers::{
-    business::shared::errors::{InternalDependencyError, UseCaseError},
+    business::shared::{
+        errors::UseCaseError,
+        user_search::{build_search_users_query, map_search_users_error, UserSearchInput},
+    },
     ewm::main_database::qc_collection::user_qc_collection::IUserQCCollection,
 };
 
-use super::interface::{IListUsersUseCase, UserListItem};
+use super::interface::{IListUsersUseCase, UserListItem, UserListPage};
 
 pub struct ListUsersUseCaseImp<IIUserQCCollection>
 where
@@ -25,28 +30,18 @@ impl<IIUserQCCollection> IListUsersUseCase for ListUsersUseCaseImp<IIUserQCColle
 where
     IIUserQCCollection: IUserQCCollection + Sync,
 {
-    async fn execute(&self) -> Result<Vec<UserListItem>, UseCaseError> {
-        let query_results = self
+    async fn execute(&self, input: UserSearchInput) -> Result<UserListPage, UseCaseError> {
+        let query = build_search_users_query(input, false, HashSet::new())?;
+
+        let page = self
             .user_qc_collection
-            .list_users()
+            .search_users(query)
             .await
-            .map_err(|err| {
-                tracing::error!("{:?}", err);
-                UseCaseError::InternalDependencyError(InternalDependencyError::new(
-                    "failed to load users from database".to_string(),
-                    format!("{:?}", err),
-                ))
-            })?;
-
-        let results = query_results
-            .into_iter()
-            .map(|s| UserListItem {
-                user_id: s.user_id,
-                email: s.email,
-                name: s.name,
-            })
-            .collect();
+            .map_err(map_search_users_error)?;
 
-        Ok(results)
+        Ok(UserListPage {
+            users: page.users.into_iter().map(UserListItem::from).collect(),
+            next_cursor: page.next_cursor,
+        })
     }
 }
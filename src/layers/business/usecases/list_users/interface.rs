@@ -1,13 +1,44 @@
-use crate::layers::business::shared::errors::UseCaseError;
+use crate::layers::{
+    business::shared::{errors::UseCaseError, user_search::UserSearchInput},
+    ewm::main_database::qc_collection::user_qc_collection::UserListQueryResultItem,
+};
 
 pub struct UserListItem {
     pub user_id: String,
     pub email: String,
     pub name: String,
+    pub deactivated_at: Option<String>,
+    pub roles: Vec<String>,
+    pub removed_at: Option<String>,
+    pub granted_roles: Vec<String>,
+    pub revoked_roles: Vec<String>,
 }
 
+impl From<UserListQueryResultItem> for UserListItem {
+    fn from(user: UserListQueryResultItem) -> Self {
+        UserListItem {
+            user_id: user.user_id,
+            email: user.email,
+            name: user.name,
+            deactivated_at: user.deactivated_at,
+            roles: user.roles,
+            removed_at: user.removed_at,
+            granted_roles: user.granted_roles,
+            revoked_roles: user.revoked_roles,
+        }
+    }
+}
+
+/// A missing `next_cursor` marks the last page.
+pub struct UserListPage {
+    pub users: Vec<UserListItem>,
+    pub next_cursor: Option<String>,
+}
+
+/// Lists users including deactivated and removed ones.
 pub trait IListUsersUseCase {
     fn execute(
         &self,
-    ) -> impl std::future::Future<Output = Result<Vec<UserListItem>, UseCaseError>> + Send;
+        input: UserSearchInput,
+    ) -> impl std::future::Future<Output = Result<UserListPage, UseCaseError>> + Send;
 }
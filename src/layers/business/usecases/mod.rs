@@ -1,6 +1,7 @@
 pub mod list_cameras;
 pub mod create_camera;
 pub mod put_camera;
+pub mod patch_camera;
 pub mod delete_camera;
 pub mod get_camera_stream_url;
 pub mod v2;
@@ -8,4 +9,26 @@ pub mod list_users;
 pub mod create_camera_temp_blocking;
 pub mod delete_camera_temp_blocking;
 pub mod list_camera_temp_blockings_by_camera;
-pub mod list_blockable_users_for_camera;
\ No newline at end of file
+pub mod list_blockable_users_for_camera;
+pub mod test_camera_connection;
+pub mod discover_cameras;
+pub mod list_temp_stream_sessions;
+pub mod revoke_temp_stream_session;
+pub mod proxy_hls_request;
+pub mod authorize_media_access;
+pub mod patch_camera_temp_blocking;
+pub mod create_recurring_camera_blocking;
+pub mod list_recurring_camera_blockings;
+pub mod delete_recurring_camera_blocking;
+
+pub mod archive_expired_temp_blockings;
+pub mod list_blocking_history;
+pub mod set_camera_privacy_shutter;
+pub mod get_camera_privacy_shutter;
+pub mod delete_camera_privacy_shutter;
+pub mod create_user;
+pub mod update_user;
+pub mod deactivate_user;
+pub mod resolve_user_roles;
+pub mod sync_users;
+pub mod set_user_roles;
\ No newline at end of file
@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::{
+        shared::{
+            business_rules::{
+                FieldValidationResult, UseCaseInputValidationResult, UseCaseInvalidInputResult,
+            },
+            camera_probe::{probe_on_save, CameraProbePolicy},
+            errors::{BusinessError, InternalDependencyError, UseCaseError},
+            validation_rules::{
+                rtsp_url::rtsp_url,
+                source_url::{recheck_source_url, safe_source_url, SourceUrlPolicy},
+                strings::non_empty,
+            },
+        },
+        usecases::patch_camera::{
+            interface::{IPatchCameraUseCase, PatchCameraInput, PatchCameraOutput, PatchValue},
+            sanitization_rules::PatchCameraSanitizedInput,
+        },
+    },
+    ewm::{
+        main_database::qc_collection::camera_qc_collection::{
+            ICameraQCCollection, UpdateCameraCommandInput,
+        },
+        permanent_stream_server::{AddStreamInput, IPermanentStreamServer},
+        rtsp_prober::IRtspProber,
+    },
+};
+
+pub struct PatchCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+where
+    IICamercaQCCollection: ICameraQCCollection,
+    IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
+{
+    camera_qc_collection: IICamercaQCCollection,
+    permanent_stream_server: IIPermanentStreamServer,
+    rtsp_prober: IIRtspProber,
+    probe_policy: CameraProbePolicy,
+    source_url_policy: SourceUrlPolicy,
+}
+
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+    PatchCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+where
+    IICamercaQCCollection: ICameraQCCollection,
+    IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
+{
+    pub fn new(
+        camera_qc_collection: IICamercaQCCollection,
+        permanent_stream_server: IIPermanentStreamServer,
+        rtsp_prober: IIRtspProber,
+        probe_policy: CameraProbePolicy,
+        source_url_policy: SourceUrlPolicy,
+    ) -> Self {
+        Self {
+            camera_qc_collection,
+            permanent_stream_server,
+            rtsp_prober,
+            probe_policy,
+            source_url_policy,
+        }
+    }
+
+    async fn apply_business_rules(
+        &self,
+        input: &PatchCameraSanitizedInput,
+    ) -> UseCaseInputValidationResult {
+        tracing::info!("{:?}", input.0);
+        let mut fields_validation_result: Vec<FieldValidationResult> =
+            vec![non_empty(&input.0.id, "id", format!("{} cannot be empty", "id"))];
+
+        match &input.0.name {
+            PatchValue::Absent => {}
+            PatchValue::Null => fields_validation_result.push(FieldValidationResult::Invalid(
+                "name".to_string(),
+                "name cannot be removed".to_string(),
+            )),
+            PatchValue::Value(name) => fields_validation_result.push(non_empty(
+                name,
+                "name",
+                format!("{} cannot be empty", "name"),
+            )),
+        }
+
+        match &input.0.source_url {
+            PatchValue::Absent => {}
+            PatchValue::Null => fields_validation_result.push(FieldValidationResult::Invalid(
+                "source_url".to_string(),
+                "source_url cannot be removed".to_string(),
+            )),
+            PatchValue::Value(source_url) => {
+                fields_validation_result.push(non_empty(
+                    source_url,
+                    "source_url",
+                    format!("{} cannot be empty", "source_url"),
+                ));
+                fields_validation_result.push(rtsp_url(
+                    source_url,
+                    "source_url",
+                    "must be a valid rtmp url",
+                ));
+                if !source_url.is_empty() {
+                    fields_validation_result.push(
+                        safe_source_url(source_url, "source_url", &self.source_url_policy).await,
+                    );
+                }
+            }
+        }
+
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        for vr in fields_validation_result {
+            if let FieldValidationResult::Invalid(field_name, message) = vr {
+                feedback.entry(field_name).or_insert(vec![]).push(message);
+            }
+        }
+
+        if feedback.is_empty() {
+            UseCaseInputValidationResult::Valid
+        } else {
+            UseCaseInputValidationResult::Invalid(UseCaseInvalidInputResult::new(
+                "could not complete operation due to invalid data, please check feedback"
+                    .to_string(),
+                feedback,
+            ))
+        }
+    }
+}
+
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber> IPatchCameraUseCase
+    for PatchCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+where
+    IICamercaQCCollection: ICameraQCCollection + Sync,
+    IIPermanentStreamServer: IPermanentStreamServer + Sync,
+    IIRtspProber: IRtspProber + Sync,
+{
+    async fn execute(&self, input: PatchCameraInput) -> Result<PatchCameraOutput, UseCaseError> {
+        let sanitized_input: PatchCameraSanitizedInput = input.try_into().map_err(|err| {
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "Failed to sanitize input".to_owned(),
+                err,
+            ))
+        })?;
+
+        let business_rules_result = self.apply_business_rules(&sanitized_input).await;
+        if let UseCaseInputValidationResult::Invalid(invalid_result) = business_rules_result {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                invalid_result.message,
+                invalid_result.feedback,
+            )));
+        }
+
+        let camera_exists = self
+            .camera_qc_collection
+            .camera_exists_by_id(&sanitized_input.0.id)
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "Failed to check if camera exists in database".to_owned(),
+                    format!("{:?}", e),
+                ))
+            })?;
+        if !camera_exists {
+            let mut feedback = HashMap::new();
+            feedback.insert("id".to_string(), vec!["camera not found in database".to_string()]);
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        let current_camera_state = self
+            .camera_qc_collection
+            .find_camera_by_id(&sanitized_input.0.id)
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "Failed to find camera in database".to_owned(),
+                    format!("{:?}", e),
+                ))
+            })?;
+
+        let name = match sanitized_input.0.name {
+            PatchValue::Value(name) if name != current_camera_state.name => Some(name),
+            _ => None,
+        };
+        let source_url = match sanitized_input.0.source_url {
+            PatchValue::Value(url) if url != current_camera_state.source_url => Some(url),
+            _ => None,
+        };
+
+        if name.is_none() && source_url.is_none() {
+            return Ok(PatchCameraOutput {
+                id: current_camera_state.id,
+                name: current_camera_state.name,
+                source_url: current_camera_state.source_url,
+                stream_metadata: current_camera_state.stream_metadata,
+                created_at: current_camera_state.created_at,
+                updated_at: current_camera_state.updated_at,
+            });
+        }
+
+        let mut permanent_stream_url = None;
+        let mut stream_metadata = None;
+        if let Some(source_url) = &source_url {
+            stream_metadata = Some(
+                probe_on_save(&self.rtsp_prober, &self.probe_policy, &self.source_url_policy, source_url)
+                    .await?,
+            );
+            recheck_source_url(source_url, &self.source_url_policy).await?;
+
+            let add_stream_request = AddStreamInput {
+                id: current_camera_state.id.clone(),
+                name: name.clone().unwrap_or(current_camera_state.name.clone()),
+                url: source_url.clone(),
+            };
+            let permanent_server_response = self
+                .permanent_stream_server
+                .put_stream(add_stream_request)
+                .await
+                .map_err(|err| {
+                    let debug_message = format!("{:?}", err);
+                    tracing::error!("Failed to re-register permanent stream: {}", debug_message);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "Failed to re-register permanent stream".to_owned(),
+                        debug_message,
+                    ))
+                })?;
+            permanent_stream_url = Some(permanent_server_response.url);
+        }
+
+        let update_camera_command = UpdateCameraCommandInput {
+            id: current_camera_state.id,
+            name,
+            source_url,
+            permanent_stream_url,
+            stream_metadata,
+        };
+
+        let updated_camera = self
+            .camera_qc_collection
+            .update_camera(update_camera_command)
+            .await
+            .map_err(|e| {
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "Failed to update camera in database".to_owned(),
+                    format!("{:?}", e),
+                ))
+            })?;
+
+        Ok(PatchCameraOutput {
+            id: updated_camera.id,
+            name: updated_camera.name,
+            source_url: updated_camera.source_url,
+            stream_metadata: updated_camera.stream_metadata,
+            created_at: updated_camera.created_at,
+            updated_at: updated_camera.updated_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::layers::ewm::{
+        main_database::qc_collection::{
+            camera_qc_collection::PutCameraCommandInput, memory::camera_qc_collection::InMemoryCameraQCCollection,
+        },
+        permanent_stream_server::{AddCreationOutput, PermanentStreamAPIError, Stream},
+        rtsp_prober::{RtspProbeError, StreamDescription},
+    };
+
+    #[derive(Clone, Default)]
+    struct RecordingStreamServer {
+        put_urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl IPermanentStreamServer for RecordingStreamServer {
+        async fn list_streams(&self) -> Result<Vec<Stream>, PermanentStreamAPIError> {
+            Ok(vec![])
+        }
+
+        async fn put_stream(&self, input: AddStreamInput) -> Result<AddCreationOutput, PermanentStreamAPIError> {
+            self.put_urls.lock().unwrap().push(input.url.clone());
+            Ok(AddCreationOutput {
+                url: format!("rtsp://relay/{}", input.id),
+                id: input.id,
+                name: input.name,
+            })
+        }
+
+        async fn remove_stream(&self, id: &str) -> Result<String, PermanentStreamAPIError> {
+            Ok(id.to_string())
+        }
+    }
+
+    struct UnusedProber;
+
+    impl IRtspProber for UnusedProber {
+        async fn describe(
+            &self,
+            _source_url: &str,
+            _addresses: &[std::net::SocketAddr],
+        ) -> Result<StreamDescription, RtspProbeError> {
+            unreachable!("probing is disabled")
+        }
+    }
+
+    async fn use_case() -> (
+        PatchCameraUseCase<InMemoryCameraQCCollection, RecordingStreamServer, UnusedProber>,
+        RecordingStreamServer,
+    ) {
+        let cameras = InMemoryCameraQCCollection::default();
+        cameras
+            .put_camera(PutCameraCommandInput {
+                id: Some("garage".to_string()),
+                name: "Garage".to_string(),
+                source_url: "rtsp://192.168.1.20/live".to_string(),
+                permanent_stream_url: Some("rtsp://relay/garage".to_string()),
+                stream_metadata: None,
+            })
+            .await
+            .unwrap();
+        let stream_server = RecordingStreamServer::default();
+        let use_case = PatchCameraUseCase::new(
+            cameras,
+            stream_server.clone(),
+            UnusedProber,
+            CameraProbePolicy {
+                probe_on_save: false,
+                reject_unreachable: false,
+            },
+            SourceUrlPolicy::default(),
+        );
+        (use_case, stream_server)
+    }
+
+    fn input(name: PatchValue<String>, source_url: PatchValue<String>) -> PatchCameraInput {
+        PatchCameraInput {
+            id: "garage".to_string(),
+            name,
+            source_url,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_absent_fields_are_left_unchanged() {
+        let (use_case, stream_server) = use_case().await;
+        let out = use_case
+            .execute(input(PatchValue::Absent, PatchValue::Absent))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(out.name, "Garage");
+        assert_eq!(out.source_url, "rtsp://192.168.1.20/live");
+
+        let out = use_case
+            .execute(input(PatchValue::Value(" Back  yard ".to_string()), PatchValue::Absent))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(out.name, "Back yard");
+        assert_eq!(out.source_url, "rtsp://192.168.1.20/live");
+        assert!(stream_server.put_urls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_null_fields_are_rejected() {
+        let (use_case, _) = use_case().await;
+        let Err(UseCaseError::BusinessError(err)) = use_case.execute(input(PatchValue::Null, PatchValue::Null)).await
+        else {
+            panic!("expected a business error");
+        };
+        assert_eq!(err.message, "could not complete operation due to invalid data, please check feedback");
+        assert_eq!(err.details["name"], vec!["name cannot be removed".to_string()]);
+        assert_eq!(err.details["source_url"], vec!["source_url cannot be removed".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_is_re_registered_only_when_source_url_changes() {
+        let (use_case, stream_server) = use_case().await;
+        use_case
+            .execute(input(PatchValue::Absent, PatchValue::Value("rtsp://192.168.1.20/live".to_string())))
+            .await
+            .ok()
+            .unwrap();
+        assert!(stream_server.put_urls.lock().unwrap().is_empty());
+
+        let out = use_case
+            .execute(input(PatchValue::Absent, PatchValue::Value("rtsp://192.168.1.21/live".to_string())))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(out.source_url, "rtsp://192.168.1.21/live");
+        assert_eq!(*stream_server.put_urls.lock().unwrap(), vec!["rtsp://192.168.1.21/live".to_string()]);
+    }
+}
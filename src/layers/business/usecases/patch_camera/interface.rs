@@ -0,0 +1,35 @@
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_qc_collection::CameraStreamMetadata,
+};
+
+/// A field of a JSON merge patch: left out, explicitly set to `null`, or set
+/// to a new value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchValue<T> {
+    Absent,
+    Null,
+    Value(T),
+}
+
+#[derive(Debug)]
+pub struct PatchCameraInput {
+    pub id: String,
+    pub name: PatchValue<String>,
+    pub source_url: PatchValue<String>,
+}
+
+pub struct PatchCameraOutput {
+    pub id: String,
+    pub name: String,
+    pub source_url: String,
+    pub stream_metadata: Option<CameraStreamMetadata>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+pub trait IPatchCameraUseCase {
+    fn execute(&self, input: PatchCameraInput) -> impl std::future::Future<Output = Result<PatchCameraOutput, UseCaseError>> + Send;
+}
@@ -0,0 +1,3 @@
+pub mod sanitization_rules;
+pub mod interface;
+pub mod implementation;
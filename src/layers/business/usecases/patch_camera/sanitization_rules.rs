@@ -0,0 +1,30 @@
+use crate::layers::business::{shared::sanitization_rules::{pipe_all, strings::{remove_double_spaces, trim_both_sides}}, usecases::patch_camera::interface::{PatchCameraInput, PatchValue}};
+
+#[derive(Debug)]
+pub struct PatchCameraSanitizedInput(pub PatchCameraInput);
+
+fn sanitize_patch_value(value: &PatchValue<String>) -> Result<PatchValue<String>, String> {
+    match value {
+        PatchValue::Value(v) => Ok(PatchValue::Value(pipe_all(vec![
+            trim_both_sides,
+            remove_double_spaces
+        ], v)?)),
+        other => Ok(other.clone()),
+    }
+}
+
+impl TryFrom<PatchCameraInput> for PatchCameraSanitizedInput {
+    type Error = String;
+
+    fn try_from(value: PatchCameraInput) -> Result<Self, Self::Error> {
+        let inner = PatchCameraInput {
+            id: pipe_all(vec![
+                trim_both_sides,
+            ], &value.id)?,
+            name: sanitize_patch_value(&value.name)?,
+            source_url: sanitize_patch_value(&value.source_url)?,
+        };
+
+        Ok(Self(inner))
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::shared::{
+        business_rules::FieldValidationResult,
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        validation_rules::dates::blocking_period,
+    },
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::{
+        CameraTempBlocking, ICameraTempBlockingQCCollection, UpdateCameraTempBlockingInput,
+    },
+};
+
+use super::interface::{IPatchCameraTempBlockingUseCase, PatchCameraTempBlockingInput};
+
+pub struct PatchCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection>
+    PatchCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    pub fn new(camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+        }
+    }
+
+    /// Tells apart a blocking removed meanwhile from one whose dates changed.
+    async fn update_conflict(&self, camera_id: &str, user_id: &str) -> UseCaseError {
+        match self
+            .camera_temp_blocking_qc_collection
+            .get_temp_blocking(camera_id, user_id)
+            .await
+        {
+            Ok(None) => not_found(),
+            Ok(Some(_)) => {
+                let mut details = HashMap::new();
+                details.insert(
+                    "blocking".to_string(),
+                    vec!["the temp blocking was changed meanwhile, please try again".to_string()],
+                );
+                UseCaseError::BusinessError(BusinessError::new(
+                    "temp blocking was changed by another request".to_string(),
+                    details,
+                ))
+            }
+            Err(err) => {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load camera temp blocking".to_string(),
+                    format!("{:?}", err),
+                ))
+            }
+        }
+    }
+}
+
+fn not_found() -> UseCaseError {
+    let mut details = HashMap::new();
+    details.insert(
+        "user_id".to_string(),
+        vec!["the user has no temp blocking for this camera".to_string()],
+    );
+    UseCaseError::BusinessError(BusinessError::new(
+        "temp blocking not found".to_string(),
+        details,
+    ))
+}
+
+impl<IICameraTempBlockingQCCollection> IPatchCameraTempBlockingUseCase
+    for PatchCameraTempBlockingUseCaseImp<IICameraTempBlockingQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+{
+    async fn execute(&self, input: PatchCameraTempBlockingInput) -> Result<CameraTempBlocking, UseCaseError> {
+        if input.start_time.is_none()
+            && input.end_time.is_none()
+            && input.reason.is_none()
+            && input.share_reason.is_none()
+        {
+            let mut details = HashMap::new();
+            details.insert(
+                "body".to_string(),
+                vec!["start_time, end_time, reason or share_reason must be provided".to_string()],
+            );
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                details,
+            )));
+        }
+
+        let existing = self
+            .camera_temp_blocking_qc_collection
+            .get_temp_blocking(&input.camera_id, &input.user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load camera temp blocking".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?
+            .ok_or_else(not_found)?;
+
+        // blockings created before start dates were stored are treated as
+        // having started right away, so only the end date constrains them
+        let start_time = input
+            .start_time
+            .clone()
+            .or(existing.start_date.clone())
+            .unwrap_or_else(|| chrono::DateTime::UNIX_EPOCH.to_rfc3339());
+        let end_time = input.end_time.clone().unwrap_or(existing.end_date.clone());
+
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        for vr in blocking_period(&start_time, &end_time, chrono::Utc::now()) {
+            if let FieldValidationResult::Invalid(field_name, message) = vr {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid date, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        // the period was checked against the dates just read, so the write
+        // only goes through while they are still stored
+        let updated = self
+            .camera_temp_blocking_qc_collection
+            .update_temp_blocking(
+                &input.camera_id,
+                &input.user_id,
+                UpdateCameraTempBlockingInput {
+                    start_time: input.start_time,
+                    end_time: input.end_time,
+                    reason: input.reason,
+                    share_reason: input.share_reason,
+                    expected_start_date: existing.start_date,
+                    expected_end_date: existing.end_date,
+                },
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to update camera temp blocking".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        match updated {
+            Some(blocking) => Ok(blocking),
+            None => Err(self.update_conflict(&input.camera_id, &input.user_id).await),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::CameraTempBlocking,
+};
+
+/// At least one field must be given; the others keep their stored value.
+pub struct PatchCameraTempBlockingInput {
+    pub camera_id: String,
+    pub user_id: String,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub reason: Option<String>,
+    pub share_reason: Option<bool>,
+}
+
+pub trait IPatchCameraTempBlockingUseCase {
+    fn execute(
+        &self,
+        input: PatchCameraTempBlockingInput,
+    ) -> impl std::future::Future<Output = Result<CameraTempBlocking, UseCaseError>> + Send;
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use url::Url;
+
+use crate::layers::{
+    business::shared::{
+        camera_access::{ensure_camera_not_blocked, session_access_token},
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+    },
+    ewm::{
+        main_database::qc_collection::{
+            camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection,
+            temp_stream_session_qc_collection::ITempStreamSessionQCCollection,
+        },
+        temporary_stream_server::ITemporaryStreamServer,
+    },
+};
+
+use super::interface::{IProxyHlsRequestUseCase, ProxyHlsRequestInput, ProxyHlsRequestOutput};
+
+pub struct ProxyHlsRequestUseCaseImp<
+    IITempStreamSessionQCCollection,
+    IICameraTempBlockingQCCollection,
+    IITemporaryStreamServer,
+> where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IITemporaryStreamServer: ITemporaryStreamServer,
+{
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    temporary_stream_server: IITemporaryStreamServer,
+}
+
+impl<IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection, IITemporaryStreamServer>
+    ProxyHlsRequestUseCaseImp<
+        IITempStreamSessionQCCollection,
+        IICameraTempBlockingQCCollection,
+        IITemporaryStreamServer,
+    >
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    pub fn new(
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        temporary_stream_server: IITemporaryStreamServer,
+    ) -> Self {
+        Self {
+            temp_stream_session_qc_collection,
+            camera_temp_blocking_qc_collection,
+            temporary_stream_server,
+        }
+    }
+}
+
+fn session_error(field: &str, message: &str) -> UseCaseError {
+    let mut details = HashMap::new();
+    details.insert(field.to_string(), vec![message.to_string()]);
+    UseCaseError::BusinessError(BusinessError::new(
+        "stream session does not allow this request".to_string(),
+        details,
+    ))
+}
+
+impl<IITempStreamSessionQCCollection, IICameraTempBlockingQCCollection, IITemporaryStreamServer>
+    IProxyHlsRequestUseCase
+    for ProxyHlsRequestUseCaseImp<
+        IITempStreamSessionQCCollection,
+        IICameraTempBlockingQCCollection,
+        IITemporaryStreamServer,
+    >
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    async fn execute(&self, input: ProxyHlsRequestInput) -> Result<ProxyHlsRequestOutput, UseCaseError> {
+        let session = self
+            .temp_stream_session_qc_collection
+            .find_session_by_id(&input.session_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to find temporary stream session".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?
+            .filter(|session| session.user_id == input.user_id)
+            .ok_or(session_error("session_id", "session not found"))?;
+        if session.expires_at <= Utc::now() {
+            return Err(session_error("session_id", "session has expired"));
+        }
+        ensure_camera_not_blocked(
+            &self.camera_temp_blocking_qc_collection,
+            &session.camera_id,
+            &session.user_id,
+            session.privacy_shutter_exempt,
+        )
+        .await?;
+
+        let hls_url = session
+            .hls_url
+            .as_deref()
+            .and_then(|url| Url::parse(url).ok())
+            .ok_or(session_error("session_id", "stream has no HLS variant"))?;
+        let mut upstream_url = upstream_url(&hls_url, &input.path, input.query.as_deref())
+            .ok_or(session_error("path", "path is not part of this stream"))?;
+        // the relay authorizes reads through the media auth hook, which expects
+        // the session token like any other client
+        upstream_url
+            .query_pairs_mut()
+            .append_pair("token", &session_access_token(&session));
+
+        let resource = self
+            .temporary_stream_server
+            .fetch_resource(upstream_url.as_str())
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to fetch stream resource".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        let is_playlist = upstream_url.path().ends_with(".m3u8")
+            || resource
+                .content_type
+                .as_deref()
+                .is_some_and(|t| t.to_lowercase().contains("mpegurl"));
+        let body = if is_playlist && resource.status == 200 {
+            let playlist = String::from_utf8_lossy(&resource.body);
+            rewrite_playlist(&playlist, &upstream_url, &base_of(&hls_url), &input.proxy_prefix)
+                .into_bytes()
+        } else {
+            resource.body
+        };
+
+        Ok(ProxyHlsRequestOutput {
+            status: resource.status,
+            content_type: resource.content_type,
+            body,
+        })
+    }
+}
+
+/// Directory of the session playlist; everything the proxy serves for the
+/// session must live below it.
+fn base_of(hls_url: &Url) -> String {
+    let mut base = hls_url.clone();
+    base.set_query(None);
+    base.set_fragment(None);
+    let text = base.to_string();
+    match text.rfind('/') {
+        Some(index) => text[..=index].to_string(),
+        None => text,
+    }
+}
+
+fn upstream_url(hls_url: &Url, path: &str, query: Option<&str>) -> Option<Url> {
+    if path.is_empty()
+        || path.starts_with('/')
+        || path.contains('\\')
+        || path.split('/').any(|segment| segment == ".." || segment == ".")
+    {
+        return None;
+    }
+    let base = base_of(hls_url);
+    let mut url = Url::parse(&base).ok()?.join(path).ok()?;
+    if !url.as_str().starts_with(&base) {
+        return None;
+    }
+    url.set_query(query);
+    Some(url)
+}
+
+/// Points every uri in the playlist that lives below `base` back through the
+/// proxy. Uris elsewhere are left untouched.
+pub fn rewrite_playlist(playlist: &str, playlist_url: &Url, base: &str, proxy_prefix: &str) -> String {
+    let rewrite = |uri: &str| -> String {
+        match playlist_url.join(uri) {
+            Ok(resolved) if resolved.as_str().starts_with(base) => {
+                format!("{}{}", proxy_prefix, &resolved.as_str()[base.len()..])
+            }
+            _ => uri.to_string(),
+        }
+    };
+
+    let mut rewritten: Vec<String> = Vec::new();
+    for line in playlist.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            rewritten.push(line.to_string());
+        } else if trimmed.starts_with('#') {
+            rewritten.push(rewrite_uri_attribute(line, &rewrite));
+        } else {
+            rewritten.push(rewrite(trimmed));
+        }
+    }
+    let mut output = rewritten.join("\n");
+    if playlist.ends_with('\n') {
+        output.push('\n');
+    }
+    output
+}
+
+fn rewrite_uri_attribute(line: &str, rewrite: &impl Fn(&str) -> String) -> String {
+    let Some(start) = line.find("URI=\"").map(|i| i + "URI=\"".len()) else {
+        return line.to_string();
+    };
+    let Some(end) = line[start..].find('"').map(|i| start + i) else {
+        return line.to_string();
+    };
+    format!("{}{}{}", &line[..start], rewrite(&line[start..end]), &line[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_playlist_routes_uris_through_proxy() {
+        let playlist_url = Url::parse("http://relay:8888/stream-1/index.m3u8").unwrap();
+        let playlist = "#EXTM3U\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:2.0,\nsegment_1.ts?part=1\n#EXTINF:2.0,\nhttp://relay:8888/stream-1/segment_2.ts\nhttp://cdn/other.ts\n";
+        let rewritten = rewrite_playlist(
+            playlist,
+            &playlist_url,
+            &base_of(&playlist_url),
+            "/hls/session-1/",
+        );
+        assert_eq!(
+            rewritten,
+            "#EXTM3U\n#EXT-X-MAP:URI=\"/hls/session-1/init.mp4\"\n#EXTINF:2.0,\n/hls/session-1/segment_1.ts?part=1\n#EXTINF:2.0,\n/hls/session-1/segment_2.ts\nhttp://cdn/other.ts\n"
+        );
+    }
+
+    #[test]
+    fn test_upstream_url_stays_below_the_stream() {
+        let hls_url = Url::parse("http://relay:8888/stream-1/index.m3u8?token=a").unwrap();
+        assert_eq!(
+            upstream_url(&hls_url, "video/segment_1.ts", None).unwrap().as_str(),
+            "http://relay:8888/stream-1/video/segment_1.ts"
+        );
+        assert!(upstream_url(&hls_url, "../stream-2/index.m3u8", None).is_none());
+        assert!(upstream_url(&hls_url, "/stream-2/index.m3u8", None).is_none());
+        assert!(upstream_url(&hls_url, "http://evil/index.m3u8", None).is_none());
+    }
+}
@@ -0,0 +1,25 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+/// `path` is relative to the session's HLS playlist directory, e.g.
+/// `index.m3u8` or `segment_12.ts`. `proxy_prefix` is where the proxy route
+/// for the session is mounted and is used to rewrite playlist entries.
+pub struct ProxyHlsRequestInput {
+    pub session_id: String,
+    pub user_id: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub proxy_prefix: String,
+}
+
+pub struct ProxyHlsRequestOutput {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+pub trait IProxyHlsRequestUseCase {
+    fn execute(
+        &self,
+        input: ProxyHlsRequestInput,
+    ) -> impl std::future::Future<Output = Result<ProxyHlsRequestOutput, UseCaseError>> + Send;
+}
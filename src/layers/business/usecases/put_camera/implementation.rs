@@ -6,8 +6,13 @@ use crate::layers::{
             business_rules::{
                 FieldValidationResult, UseCaseInputValidationResult, UseCaseInvalidInputResult,
             },
+            camera_probe::{probe_on_save, CameraProbePolicy},
             errors::{BusinessError, InternalDependencyError, UseCaseError},
-            validation_rules::{rtsp_url::rtsp_url, strings::non_empty},
+            validation_rules::{
+                rtsp_url::rtsp_url,
+                source_url::{recheck_source_url, safe_source_url, SourceUrlPolicy},
+                strings::non_empty,
+            },
         },
         usecases::put_camera::{
             interface::{IPutCameraUseCase, PutCameraInput, PutCameraOutput},
@@ -19,31 +24,43 @@ use crate::layers::{
             ICameraQCCollection, PutCameraCommandInput,
         },
         permanent_stream_server::IPermanentStreamServer,
+        rtsp_prober::IRtspProber,
     },
 };
 
-pub struct PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+pub struct PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection,
     IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
 {
     pub camera_qc_collection: IICamercaQCCollection,
     pub permanent_stream_server: IIPermanentStreamServer,
+    pub rtsp_prober: IIRtspProber,
+    pub probe_policy: CameraProbePolicy,
+    pub source_url_policy: SourceUrlPolicy,
 }
 
-impl<IICamercaQCCollection, IIPermanentStreamServer>
-    PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
+    PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection,
     IIPermanentStreamServer: IPermanentStreamServer,
+    IIRtspProber: IRtspProber + Sync,
 {
     pub fn new(
         camera_qc_collection: IICamercaQCCollection,
         permanent_stream_server: IIPermanentStreamServer,
+        rtsp_prober: IIRtspProber,
+        probe_policy: CameraProbePolicy,
+        source_url_policy: SourceUrlPolicy,
     ) -> Self {
         Self {
             camera_qc_collection,
             permanent_stream_server,
+            rtsp_prober,
+            probe_policy,
+            source_url_policy,
         }
     }
 
@@ -52,7 +69,7 @@ where
         input: &PutCameraSanitizedInput,
     ) -> UseCaseInputValidationResult {
         tracing::info!("{:?}", input.0);
-        let fields_validation_result: Vec<FieldValidationResult> = vec![
+        let mut fields_validation_result: Vec<FieldValidationResult> = vec![
             non_empty(&input.0.id, "id", format!("{} cannot be empty", "id")),
             non_empty(&input.0.name, "name", format!("{} cannot be empty", "name")),
             non_empty(
@@ -66,6 +83,11 @@ where
                 "must be a valid rtmp url",
             ),
         ];
+        if !input.0.source_url.is_empty() {
+            fields_validation_result.push(
+                safe_source_url(&input.0.source_url, "source_url", &self.source_url_policy).await,
+            );
+        }
         let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
         for vr in fields_validation_result {
             if let FieldValidationResult::Invalid(field_name, message) = vr {
@@ -85,11 +107,12 @@ where
     }
 }
 
-impl<IICamercaQCCollection, IIPermanentStreamServer> IPutCameraUseCase
-    for PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer>
+impl<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber> IPutCameraUseCase
+    for PutCameraUseCase<IICamercaQCCollection, IIPermanentStreamServer, IIRtspProber>
 where
     IICamercaQCCollection: ICameraQCCollection + Sync,
     IIPermanentStreamServer: IPermanentStreamServer + Sync,
+    IIRtspProber: IRtspProber + Sync,
 {
     async fn execute(&self, input: PutCameraInput) -> Result<PutCameraOutput, UseCaseError> {
         let sanitized_input: PutCameraSanitizedInput = input.try_into().map_err(|err| {
@@ -118,11 +141,28 @@ where
                 ))
             })?;
 
+        recheck_source_url(&sanitized_input.0.source_url, &self.source_url_policy).await?;
+        let probed_metadata = probe_on_save(
+            &self.rtsp_prober,
+            &self.probe_policy,
+            &self.source_url_policy,
+            &sanitized_input.0.source_url,
+        )
+        .await?;
+        let stream_metadata = match probed_metadata {
+            Some(metadata) => Some(metadata),
+            None if current_camera_state.source_url == sanitized_input.0.source_url => {
+                current_camera_state.stream_metadata
+            }
+            None => None,
+        };
+
         let update_camera_command = PutCameraCommandInput {
             id: Some(sanitized_input.0.id),
             name: sanitized_input.0.name,
             source_url: sanitized_input.0.source_url,
             permanent_stream_url: current_camera_state.permanent_stream_url,
+            stream_metadata,
         };
 
         let update_camera_command_result = self
@@ -140,6 +180,7 @@ where
             id: update_camera_command_result.id,
             name: update_camera_command_result.name,
             source_url: update_camera_command_result.source_url,
+            stream_metadata: update_camera_command_result.stream_metadata,
             created_at: update_camera_command_result.created_at,
             updated_at: update_camera_command_result.updated_at,
         };
@@ -1,6 +1,9 @@
 use chrono::Utc;
 
-use crate::layers::business::shared::errors::UseCaseError;
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_qc_collection::CameraStreamMetadata,
+};
 
 #[derive(Debug)]
 pub struct PutCameraInput {
@@ -13,6 +16,7 @@ pub struct PutCameraOutput {
     pub id: String,
     pub name: String,
     pub source_url: String,
+    pub stream_metadata: Option<CameraStreamMetadata>,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
 }
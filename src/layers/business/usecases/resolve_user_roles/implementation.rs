@@ -0,0 +1,47 @@
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::main_database::qc_collection::user_qc_collection::{merge_roles, IUserQCCollection},
+};
+
+use super::interface::IResolveUserRolesUseCase;
+
+pub struct ResolveUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection,
+{
+    user_qc_collection: IIUserQCCollection,
+}
+
+impl<IIUserQCCollection> ResolveUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    pub fn new(user_qc_collection: IIUserQCCollection) -> Self {
+        Self { user_qc_collection }
+    }
+}
+
+impl<IIUserQCCollection> IResolveUserRolesUseCase for ResolveUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self, user_id: &str, token_roles: Vec<String>) -> Result<Option<Vec<String>>, UseCaseError> {
+        let user = self
+            .user_qc_collection
+            .find_user_by_id(user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to load user from database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        match user {
+            None => Ok(Some(token_roles)),
+            Some(user) if !user.is_active() => Ok(None),
+            Some(user) => Ok(Some(merge_roles(token_roles, &user.granted_roles, &user.revoked_roles))),
+        }
+    }
+}
+
@@ -0,0 +1,13 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+/// The roles an authenticated user acts with: the token roles plus local
+/// grants, minus local revocations. `None` when the user was deactivated and
+/// may not use the API. Users without a record keep their token
+/// roles, since records are only created for managed users.
+pub trait IResolveUserRolesUseCase {
+    fn execute(
+        &self,
+        user_id: &str,
+        token_roles: Vec<String>,
+    ) -> impl std::future::Future<Output = Result<Option<Vec<String>>, UseCaseError>> + Send;
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::shared::errors::{BusinessError, InternalDependencyError, UseCaseError},
+    ewm::{
+        main_database::qc_collection::temp_stream_session_qc_collection::ITempStreamSessionQCCollection,
+        temporary_stream_server::ITemporaryStreamServer,
+    },
+};
+
+use super::interface::IRevokeTempStreamSessionUseCase;
+
+pub struct RevokeTempStreamSessionUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection,
+    IITemporaryStreamServer: ITemporaryStreamServer,
+{
+    temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+    temporary_stream_server: IITemporaryStreamServer,
+}
+
+impl<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+    RevokeTempStreamSessionUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    pub fn new(
+        temp_stream_session_qc_collection: IITempStreamSessionQCCollection,
+        temporary_stream_server: IITemporaryStreamServer,
+    ) -> Self {
+        Self {
+            temp_stream_session_qc_collection,
+            temporary_stream_server,
+        }
+    }
+}
+
+impl<IITempStreamSessionQCCollection, IITemporaryStreamServer> IRevokeTempStreamSessionUseCase
+    for RevokeTempStreamSessionUseCaseImp<IITempStreamSessionQCCollection, IITemporaryStreamServer>
+where
+    IITempStreamSessionQCCollection: ITempStreamSessionQCCollection + Sync,
+    IITemporaryStreamServer: ITemporaryStreamServer + Sync,
+{
+    async fn execute(&self, session_id: &str) -> Result<(), UseCaseError> {
+        let session = self
+            .temp_stream_session_qc_collection
+            .find_session_by_id(session_id.trim())
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to find temporary stream session".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        let Some(session) = session else {
+            let mut details = HashMap::new();
+            details.insert("id".to_string(), vec!["session not found".to_string()]);
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                details,
+            )));
+        };
+
+        self.temporary_stream_server
+            .delete_stream(&session.stream_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to delete temporary stream".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        self.temp_stream_session_qc_collection
+            .delete_session(&session.user_id, &session.id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to delete temporary stream session".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        Ok(())
+    }
+}
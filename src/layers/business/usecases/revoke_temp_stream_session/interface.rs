@@ -0,0 +1,8 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+pub trait IRevokeTempStreamSessionUseCase {
+    fn execute(
+        &self,
+        session_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), UseCaseError>> + Send;
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::{
+        business_rules::FieldValidationResult,
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        validation_rules::dates::blocking_period,
+    },
+    ewm::main_database::qc_collection::{
+        camera_qc_collection::ICameraQCCollection,
+        camera_temp_blocking_qc_collection::{CameraPrivacyShutter, ICameraTempBlockingQCCollection},
+    },
+};
+
+use super::interface::{ISetCameraPrivacyShutterUseCase, SetCameraPrivacyShutterInput};
+
+pub struct SetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection, IICameraQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection,
+    IICameraQCCollection: ICameraQCCollection,
+{
+    camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+    camera_qc_collection: IICameraQCCollection,
+}
+
+impl<IICameraTempBlockingQCCollection, IICameraQCCollection>
+    SetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection, IICameraQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+{
+    pub fn new(
+        camera_temp_blocking_qc_collection: IICameraTempBlockingQCCollection,
+        camera_qc_collection: IICameraQCCollection,
+    ) -> Self {
+        Self {
+            camera_temp_blocking_qc_collection,
+            camera_qc_collection,
+        }
+    }
+}
+
+impl<IICameraTempBlockingQCCollection, IICameraQCCollection> ISetCameraPrivacyShutterUseCase
+    for SetCameraPrivacyShutterUseCaseImp<IICameraTempBlockingQCCollection, IICameraQCCollection>
+where
+    IICameraTempBlockingQCCollection: ICameraTempBlockingQCCollection + Sync,
+    IICameraQCCollection: ICameraQCCollection + Sync,
+{
+    async fn execute(&self, input: SetCameraPrivacyShutterInput) -> Result<CameraPrivacyShutter, UseCaseError> {
+        let now = Utc::now();
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        let start_time = input.start_time.clone().unwrap_or_else(|| now.to_rfc3339());
+        for result in blocking_period(&start_time, &input.end_time, now) {
+            if let FieldValidationResult::Invalid(field_name, message) = result {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+
+        let camera_exists = match input.camera_id.is_empty() {
+            true => false,
+            false => self
+                .camera_qc_collection
+                .camera_exists_by_id(&input.camera_id)
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                        "failed to check if camera exists in the database".to_string(),
+                        format!("{:?}", err),
+                    ))
+                })?,
+        };
+        if !camera_exists {
+            feedback
+                .entry("camera_id".to_string())
+                .or_default()
+                .push("camera not found in database".to_string());
+        }
+
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid date, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        let shutter = CameraPrivacyShutter {
+            camera_id: input.camera_id,
+            start_date: input.start_time,
+            end_date: input.end_time,
+            reason: input.reason.filter(|r| !r.trim().is_empty()),
+            share_reason: input.share_reason,
+            created_by: Some(input.created_by),
+            created_at: Some(now.to_rfc3339()),
+        };
+        self.camera_temp_blocking_qc_collection
+            .put_privacy_shutter(shutter.clone())
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to set camera privacy shutter".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        Ok(shutter)
+    }
+}
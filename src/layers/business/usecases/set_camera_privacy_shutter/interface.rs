@@ -0,0 +1,22 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::CameraPrivacyShutter,
+};
+
+/// Closes the camera for every non-admin user from `start_time`, or right
+/// away without one, until `end_time`. Replaces the camera's current shutter.
+pub struct SetCameraPrivacyShutterInput {
+    pub camera_id: String,
+    pub start_time: Option<String>,
+    pub end_time: String,
+    pub reason: Option<String>,
+    pub share_reason: bool,
+    pub created_by: String,
+}
+
+pub trait ISetCameraPrivacyShutterUseCase {
+    fn execute(
+        &self,
+        input: SetCameraPrivacyShutterInput,
+    ) -> impl std::future::Future<Output = Result<CameraPrivacyShutter, UseCaseError>> + Send;
+}
@@ -0,0 +1,2 @@
+pub mod interface;
+pub mod implementation;
\ No newline at end of file
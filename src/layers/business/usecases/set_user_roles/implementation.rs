@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::{
+        shared::{
+            business_rules::FieldValidationResult,
+            errors::{BusinessError, InternalDependencyError, UseCaseError},
+            validation_rules::user,
+        },
+        usecases::list_users::interface::UserListItem,
+    },
+    ewm::main_database::qc_collection::user_qc_collection::{IUserQCCollection, SetRoleOverridesCommandInput},
+};
+
+use super::interface::{ISetUserRolesUseCase, SetUserRolesInput};
+
+const ADMIN_ROLE: &str = "Admin";
+
+pub struct SetUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection,
+{
+    user_qc_collection: IIUserQCCollection,
+}
+
+impl<IIUserQCCollection> SetUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    pub fn new(user_qc_collection: IIUserQCCollection) -> Self {
+        Self { user_qc_collection }
+    }
+}
+
+/// Trims and deduplicates both lists, collecting every problem at once.
+fn validate(input: &SetUserRolesInput) -> Result<SetRoleOverridesCommandInput, HashMap<String, Vec<String>>> {
+    let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+    let mut clean = |roles: &[String], field_name: &str| -> Vec<String> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for role in roles.iter().map(|role| role.trim().to_string()) {
+            if let FieldValidationResult::Invalid(field_name, message) = user::role_name(&role, field_name) {
+                feedback.entry(field_name).or_default().push(message);
+            } else if !cleaned.contains(&role) {
+                cleaned.push(role);
+            }
+        }
+        cleaned
+    };
+    let granted_roles = clean(&input.granted_roles, "granted_roles");
+    let revoked_roles = clean(&input.revoked_roles, "revoked_roles");
+
+    for role in granted_roles.iter().filter(|role| revoked_roles.contains(role)) {
+        feedback
+            .entry("revoked_roles".to_string())
+            .or_default()
+            .push(format!("{:?} cannot be granted and revoked at once", role));
+    }
+    if input.user_id == input.set_by && revoked_roles.iter().any(|role| role == ADMIN_ROLE) {
+        feedback
+            .entry("revoked_roles".to_string())
+            .or_default()
+            .push("admins cannot revoke their own admin role".to_string());
+    }
+
+    if !feedback.is_empty() {
+        return Err(feedback);
+    }
+    Ok(SetRoleOverridesCommandInput {
+        granted_roles,
+        revoked_roles,
+    })
+}
+
+impl<IIUserQCCollection> ISetUserRolesUseCase for SetUserRolesUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self, input: SetUserRolesInput) -> Result<UserListItem, UseCaseError> {
+        let overrides = validate(&input).map_err(|feedback| {
+            UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                feedback,
+            ))
+        })?;
+
+        let updated = self
+            .user_qc_collection
+            .set_role_overrides(&input.user_id, overrides)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to update user roles".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        match updated {
+            Some(user) => Ok(user.into()),
+            None => {
+                let mut details = HashMap::new();
+                details.insert("user_id".to_string(), vec!["user not found in database".to_string()]);
+                Err(UseCaseError::BusinessError(BusinessError::new(
+                    "user not found".to_string(),
+                    details,
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(granted: &[&str], revoked: &[&str]) -> SetUserRolesInput {
+        SetUserRolesInput {
+            user_id: "auth0|1".to_string(),
+            granted_roles: granted.iter().map(|r| r.to_string()).collect(),
+            revoked_roles: revoked.iter().map(|r| r.to_string()).collect(),
+            set_by: "auth0|admin".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_trims_and_deduplicates() {
+        let overrides = validate(&input(&[" Viewer", "Viewer", "Operator"], &["Admin"])).ok().unwrap();
+        assert_eq!(overrides.granted_roles, vec!["Viewer", "Operator"]);
+        assert_eq!(overrides.revoked_roles, vec!["Admin"]);
+    }
+
+    #[test]
+    fn test_validate_rejects_conflicts_and_self_lockout() {
+        let feedback = validate(&input(&["Viewer", ""], &["Viewer"])).err().unwrap();
+        assert_eq!(feedback["granted_roles"].len(), 1);
+        assert_eq!(feedback["revoked_roles"].len(), 1);
+
+        let own = SetUserRolesInput {
+            set_by: "auth0|1".to_string(),
+            ..input(&[], &["Admin"])
+        };
+        assert!(validate(&own).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use crate::layers::business::{shared::errors::UseCaseError, usecases::list_users::interface::UserListItem};
+
+/// Replaces the user's local role overrides; empty lists fall back to the
+/// token roles.
+pub struct SetUserRolesInput {
+    pub user_id: String,
+    pub granted_roles: Vec<String>,
+    pub revoked_roles: Vec<String>,
+    /// Admin making the change, who may not revoke their own admin role
+    pub set_by: String,
+}
+
+pub trait ISetUserRolesUseCase {
+    fn execute(
+        &self,
+        input: SetUserRolesInput,
+    ) -> impl std::future::Future<Output = Result<UserListItem, UseCaseError>> + Send;
+}
@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+
+use crate::layers::{
+    business::shared::errors::{InternalDependencyError, UseCaseError},
+    ewm::{
+        identity_provider::IIdentityProvider,
+        main_database::qc_collection::user_qc_collection::{
+            IUserQCCollection, UpsertUserCommandInput, UserListQueryResultItem,
+        },
+    },
+};
+
+use super::interface::{ISyncUsersUseCase, SyncUsersOutput};
+
+pub struct SyncUsersUseCaseImp<IIIdentityProvider, IIUserQCCollection>
+where
+    IIIdentityProvider: IIdentityProvider,
+    IIUserQCCollection: IUserQCCollection,
+{
+    identity_provider: IIIdentityProvider,
+    user_qc_collection: IIUserQCCollection,
+}
+
+impl<IIIdentityProvider, IIUserQCCollection> SyncUsersUseCaseImp<IIIdentityProvider, IIUserQCCollection>
+where
+    IIIdentityProvider: IIdentityProvider + Sync,
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    pub fn new(identity_provider: IIIdentityProvider, user_qc_collection: IIUserQCCollection) -> Self {
+        Self {
+            identity_provider,
+            user_qc_collection,
+        }
+    }
+}
+
+/// Stored users missing from the identity provider that are not flagged yet.
+fn newly_removed<'a>(
+    stored: &'a [UserListQueryResultItem],
+    provider_user_ids: &HashSet<&str>,
+) -> Vec<&'a UserListQueryResultItem> {
+    stored
+        .iter()
+        .filter(|user| user.removed_at.is_none() && !provider_user_ids.contains(user.user_id.as_str()))
+        .collect()
+}
+
+impl<IIIdentityProvider, IIUserQCCollection> ISyncUsersUseCase
+    for SyncUsersUseCaseImp<IIIdentityProvider, IIUserQCCollection>
+where
+    IIIdentityProvider: IIdentityProvider + Sync,
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self) -> Result<SyncUsersOutput, UseCaseError> {
+        let now = Utc::now();
+        let provider_users = self.identity_provider.list_users().await.map_err(|err| {
+            tracing::error!("{:?}", err);
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "failed to list users from the identity provider".to_string(),
+                format!("{:?}", err),
+            ))
+        })?;
+        // an empty tenant is far more likely a misconfigured connection than
+        // a real state, and would flag every user
+        if provider_users.is_empty() {
+            return Err(UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "identity provider returned no users".to_string(),
+                "refusing to flag all stored users as removed".to_string(),
+            )));
+        }
+
+        let stored_users = self.user_qc_collection.list_users().await.map_err(|err| {
+            tracing::error!("{:?}", err);
+            UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                "failed to list users from database".to_string(),
+                format!("{:?}", err),
+            ))
+        })?;
+
+        let provider_user_ids: HashSet<&str> = provider_users.iter().map(|u| u.user_id.as_str()).collect();
+        let mut removed = 0;
+        for user in newly_removed(&stored_users, &provider_user_ids) {
+            match self.user_qc_collection.mark_user_removed(&user.user_id, now).await {
+                Ok(_) => removed += 1,
+                Err(err) => tracing::error!("failed to flag user {} as removed: {:?}", user.user_id, err),
+            }
+        }
+
+        let mut upserted = 0;
+        for user in provider_users {
+            let user_id = user.user_id.clone();
+            let result = self
+                .user_qc_collection
+                .upsert_user(UpsertUserCommandInput {
+                    user_id: user.user_id,
+                    email: user.email,
+                    name: user.name,
+                    roles: user.roles,
+                    synced_at: now,
+                })
+                .await;
+            // one failing user should not stop the others; the next sync
+            // tries again
+            match result {
+                Ok(_) => upserted += 1,
+                Err(err) => tracing::error!("failed to sync user {}: {:?}", user_id, err),
+            }
+        }
+
+        Ok(SyncUsersOutput { upserted, removed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(user_id: &str, removed_at: Option<&str>) -> UserListQueryResultItem {
+        UserListQueryResultItem {
+            user_id: user_id.to_string(),
+            email: format!("{}@example.com", user_id),
+            name: user_id.to_string(),
+            deactivated_at: None,
+            roles: vec![],
+            removed_at: removed_at.map(str::to_string),
+            granted_roles: vec![],
+            revoked_roles: vec![],
+        }
+    }
+
+    #[test]
+    fn test_only_unflagged_missing_users_are_removed() {
+        let stored_users = [
+            stored("kept", None),
+            stored("gone", None),
+            stored("gone-earlier", Some("2025-01-01T00:00:00+00:00")),
+            // back at the provider; the upsert clears the flag
+            stored("returned", Some("2025-01-01T00:00:00+00:00")),
+        ];
+        let provider_user_ids = HashSet::from(["kept", "returned", "new"]);
+        let removed: Vec<&str> = newly_removed(&stored_users, &provider_user_ids)
+            .into_iter()
+            .map(|u| u.user_id.as_str())
+            .collect();
+        assert_eq!(removed, vec!["gone"]);
+    }
+}
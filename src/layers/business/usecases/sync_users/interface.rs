@@ -0,0 +1,15 @@
+use crate::layers::business::shared::errors::UseCaseError;
+
+#[derive(Debug, PartialEq)]
+pub struct SyncUsersOutput {
+    /// Users created or refreshed from the identity provider
+    pub upserted: usize,
+    /// Users newly flagged as removed at the identity provider
+    pub removed: usize,
+}
+
+/// Copies email, name and roles of every identity provider user into the
+/// user collection and flags stored users the provider no longer knows.
+pub trait ISyncUsersUseCase {
+    fn execute(&self) -> impl std::future::Future<Output = Result<SyncUsersOutput, UseCaseError>> + Send;
+}
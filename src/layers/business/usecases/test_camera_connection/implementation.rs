@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::shared::{
+        camera_probe::probe_stream_metadata,
+        errors::{BusinessError, InternalDependencyError, UseCaseError},
+        validation_rules::source_url::SourceUrlPolicy,
+    },
+    ewm::{
+        main_database::qc_collection::camera_qc_collection::ICameraQCCollection,
+        rtsp_prober::IRtspProber,
+    },
+};
+
+use super::interface::{ITestCameraConnectionUseCase, TestCameraConnectionOutput};
+
+pub struct TestCameraConnectionUseCaseImp<IICameraQCCollection, IIRtspProber>
+where
+    IICameraQCCollection: ICameraQCCollection,
+    IIRtspProber: IRtspProber,
+{
+    camera_qc_collection: IICameraQCCollection,
+    rtsp_prober: IIRtspProber,
+    source_url_policy: SourceUrlPolicy,
+}
+
+impl<IICameraQCCollection, IIRtspProber> TestCameraConnectionUseCaseImp<IICameraQCCollection, IIRtspProber>
+where
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIRtspProber: IRtspProber + Sync,
+{
+    pub fn new(
+        camera_qc_collection: IICameraQCCollection,
+        rtsp_prober: IIRtspProber,
+        source_url_policy: SourceUrlPolicy,
+    ) -> Self {
+        Self {
+            camera_qc_collection,
+            rtsp_prober,
+            source_url_policy,
+        }
+    }
+}
+
+impl<IICameraQCCollection, IIRtspProber> ITestCameraConnectionUseCase
+    for TestCameraConnectionUseCaseImp<IICameraQCCollection, IIRtspProber>
+where
+    IICameraQCCollection: ICameraQCCollection + Sync,
+    IIRtspProber: IRtspProber + Sync,
+{
+    async fn execute(&self, camera_id: &str) -> Result<TestCameraConnectionOutput, UseCaseError> {
+        let camera_id = camera_id.trim();
+        let camera_exists = self
+            .camera_qc_collection
+            .camera_exists_by_id(camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to check if camera exists in the database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        if !camera_exists {
+            let mut details = HashMap::new();
+            details.insert("id".to_string(), vec!["camera not found in database".to_string()]);
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                details,
+            )));
+        }
+
+        let camera = self
+            .camera_qc_collection
+            .find_camera_by_id(camera_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to find camera in database".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        let stream_metadata = match probe_stream_metadata(&self.rtsp_prober, &self.source_url_policy, &camera.source_url).await {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                tracing::info!("camera {} is unreachable: {:?}", camera.id, err);
+                return Ok(TestCameraConnectionOutput {
+                    camera_id: camera.id,
+                    reachable: false,
+                    error: Some(err.message),
+                    stream_metadata: None,
+                });
+            }
+        };
+
+        self.camera_qc_collection
+            .update_camera_stream_metadata(&camera.id, stream_metadata.clone())
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to store camera stream metadata".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+
+        Ok(TestCameraConnectionOutput {
+            camera_id: camera.id,
+            reachable: true,
+            error: None,
+            stream_metadata: Some(stream_metadata),
+        })
+    }
+}
@@ -0,0 +1,18 @@
+use crate::layers::{
+    business::shared::errors::UseCaseError,
+    ewm::main_database::qc_collection::camera_qc_collection::CameraStreamMetadata,
+};
+
+pub struct TestCameraConnectionOutput {
+    pub camera_id: String,
+    pub reachable: bool,
+    pub error: Option<String>,
+    pub stream_metadata: Option<CameraStreamMetadata>,
+}
+
+pub trait ITestCameraConnectionUseCase {
+    fn execute(
+        &self,
+        camera_id: &str,
+    ) -> impl std::future::Future<Output = Result<TestCameraConnectionOutput, UseCaseError>> + Send;
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::layers::{
+    business::{
+        shared::{
+            business_rules::FieldValidationResult,
+            errors::{BusinessError, InternalDependencyError, UseCaseError},
+            validation_rules::user,
+        },
+        usecases::list_users::interface::UserListItem,
+    },
+    ewm::main_database::qc_collection::user_qc_collection::{IUserQCCollection, UpdateUserCommandInput},
+};
+
+use super::interface::{IUpdateUserUseCase, UpdateUserInput};
+
+pub struct UpdateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection,
+{
+    user_qc_collection: IIUserQCCollection,
+}
+
+impl<IIUserQCCollection> UpdateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    pub fn new(user_qc_collection: IIUserQCCollection) -> Self {
+        Self { user_qc_collection }
+    }
+}
+
+impl<IIUserQCCollection> IUpdateUserUseCase for UpdateUserUseCaseImp<IIUserQCCollection>
+where
+    IIUserQCCollection: IUserQCCollection + Sync,
+{
+    async fn execute(&self, input: UpdateUserInput) -> Result<UserListItem, UseCaseError> {
+        let email = input.email.map(|email| email.trim().to_lowercase());
+        let name = input.name.map(|name| name.trim().to_string());
+
+        let mut feedback: HashMap<String, Vec<String>> = HashMap::new();
+        if email.is_none() && name.is_none() {
+            feedback
+                .entry("name".to_string())
+                .or_default()
+                .push("email or name must be provided".to_string());
+        }
+        let results = [email.as_deref().map(user::email), name.as_deref().map(user::user_name)];
+        for result in results.into_iter().flatten() {
+            if let FieldValidationResult::Invalid(field_name, message) = result {
+                feedback.entry(field_name).or_default().push(message);
+            }
+        }
+        if !feedback.is_empty() {
+            return Err(UseCaseError::BusinessError(BusinessError::new(
+                "could not complete operation due to invalid data, please check feedback".to_string(),
+                feedback,
+            )));
+        }
+
+        let updated = self
+            .user_qc_collection
+            .update_user(&input.user_id, UpdateUserCommandInput { email, name })
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(InternalDependencyError::new(
+                    "failed to update user".to_string(),
+                    format!("{:?}", err),
+                ))
+            })?;
+        match updated {
+            Some(user) => Ok(user.into()),
+            None => {
+                let mut details = HashMap::new();
+                details.insert("user_id".to_string(), vec!["user not found in database".to_string()]);
+                Err(UseCaseError::BusinessError(BusinessError::new(
+                    "user not found".to_string(),
+                    details,
+                )))
+            }
+        }
+    }
+}
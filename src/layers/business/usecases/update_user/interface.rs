@@ -0,0 +1,15 @@
+use crate::layers::business::{shared::errors::UseCaseError, usecases::list_users::interface::UserListItem};
+
+/// Fields left as `None` keep their value.
+pub struct UpdateUserInput {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+pub trait IUpdateUserUseCase {
+    fn execute(
+        &self,
+        input: UpdateUserInput,
+    ) -> impl std::future::Future<Output = Result<UserListItem, UseCaseError>> + Send;
+}
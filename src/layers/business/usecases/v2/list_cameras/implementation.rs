@@ -1,4 +1,4 @@
-use crate::layers::{business::{shared::errors::{InternalDependencyError, UseCaseError}, usecases::v2::list_cameras::interface::ListCamerasInput}, ewm::main_database::qc_collection::{camera_qc_collection::ICameraQCCollection, camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection}};
+use crate::layers::{business::{shared::{camera_access::{available_at, is_exempt_from_privacy_shutter, unavailable_reason}, errors::{InternalDependencyError, UseCaseError}}, usecases::v2::list_cameras::interface::ListCamerasInput}, ewm::main_database::qc_collection::{camera_qc_collection::ICameraQCCollection, camera_temp_blocking_qc_collection::ICameraTempBlockingQCCollection}};
 
 use super::interface::{CameraListItem, IListCamerasUseCase};
 
@@ -47,7 +47,8 @@ impl<IICameraCommandQueryCollection, IICameraTempBlockingQCCollection>
                     id: s.id,
                     name: s.name,
                     source_url: s.source_url,
-                    is_available: super::interface::CameraAvailability::Available
+                    is_available: super::interface::CameraAvailability::Available,
+                    unavailable_reason: None
                 }
             })
             .collect();
@@ -61,9 +62,35 @@ impl<IICameraCommandQueryCollection, IICameraTempBlockingQCCollection>
                 )
             })?;
 
-        for blocking in temp_blockings {
-            if let Some(camera) = results.iter_mut().find(|c| c.id == blocking.camera_id) {
-                camera.is_available = super::interface::CameraAvailability::NotAvailable(blocking.end_date);
+        let recurring_blockings = self.camera_temp_blocking_qc_collection.list_recurring_blockings_for_user(&input.user_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("{:?}", err);
+                UseCaseError::InternalDependencyError(
+                    InternalDependencyError::new("failed to load recurring camera blockings from database".to_string(), format!("{:?}", err))
+                )
+            })?;
+
+        let privacy_shutters = match is_exempt_from_privacy_shutter(&input.user_roles) {
+            true => vec![],
+            false => self.camera_temp_blocking_qc_collection.list_privacy_shutters()
+                .await
+                .map_err(|err| {
+                    tracing::error!("{:?}", err);
+                    UseCaseError::InternalDependencyError(
+                        InternalDependencyError::new("failed to load camera privacy shutters from database".to_string(), format!("{:?}", err))
+                    )
+                })?,
+        };
+
+        let now = chrono::Utc::now();
+        for camera in results.iter_mut() {
+            let blocking = temp_blockings.iter().find(|b| b.camera_id == camera.id);
+            let privacy_shutter = privacy_shutters.iter().find(|s| s.camera_id == camera.id);
+            let recurring: Vec<_> = recurring_blockings.iter().filter(|r| r.camera_id == camera.id).cloned().collect();
+            if let Some(available_at) = available_at(blocking, privacy_shutter, &recurring, now) {
+                camera.is_available = super::interface::CameraAvailability::NotAvailable(available_at);
+                camera.unavailable_reason = unavailable_reason(blocking, privacy_shutter, now);
             }
         }
         Ok(results)
@@ -72,7 +99,7 @@ impl<IICameraCommandQueryCollection, IICameraTempBlockingQCCollection>
 #[cfg(test)]
 mod tests {
 
-    use crate::layers::ewm::main_database::qc_collection::{camera_qc_collection::{CameraListQueryResultItem, ListCamerasQueryError}, camera_temp_blocking_qc_collection::{CameraTempBlocking, ListCameraTempBlockingsQueryError}, error::QCError};
+    use crate::layers::ewm::main_database::qc_collection::{camera_qc_collection::{CameraListQueryResultItem, CameraStreamMetadata, CheckIfCameraExistsError, CreateCameraCommandError, CreateCameraCommandOutput, DeleteCameraCommandError, FindCameraByIdResult, FindCamerabyIdError, ListCamerasQueryError, PutCameraCommandInput, UpdateCameraCommandError, UpdateCameraCommandInput, UpdateCameraStreamMetadataError}, camera_temp_blocking_qc_collection::{CameraPrivacyShutter, CameraTempBlocking, ListCameraTempBlockingsQueryError}, error::QCError};
 
     use super::*;
 
@@ -92,45 +119,52 @@ mod tests {
         
         async fn put_camera(
             &self,
-            command_input: crate::layers::ewm::main_database::qc_collection::camera_qc_collection::PutCameraCommandInput,
-        ) -> Result<crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CreateCameraCommandOutput, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CreateCameraCommandError> {
+            _command_input: PutCameraCommandInput,
+        ) -> Result<CreateCameraCommandOutput, CreateCameraCommandError> {
             todo!()
         }
-        
-        async fn delete_camera_by_id(
-            &self,
-            id: &str,
-        ) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_qc_collection::DeleteCameraCommandError> {
+
+        async fn delete_camera_by_id(&self, _id: &str) -> Result<(), DeleteCameraCommandError> {
             todo!()
         }
-        
-        fn find_camera_by_id(
+
+        async fn find_camera_by_id(&self, _id: &str) -> Result<FindCameraByIdResult, FindCamerabyIdError> {
+            Ok(FindCameraByIdResult {
+                id: "1".to_string(),
+                name: "Mock Camera".to_string(),
+                source_url: "mock://camera".to_string(),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                permanent_stream_url: None,
+                stream_metadata: None
+            })
+        }
+
+        async fn camera_exists_by_id(&self, _id: &str) -> Result<bool, CheckIfCameraExistsError> {
+            Ok(true)
+        }
+
+        async fn update_camera_stream_metadata(
             &self,
-            id: &str,
-        ) -> impl std::future::Future<Output = Result<crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCameraByIdResult, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCamerabyIdError>> + Send {
-            async move {
-                Ok(crate::layers::ewm::main_database::qc_collection::camera_qc_collection::FindCameraByIdResult {
-                    id: "1".to_string(),
-                    name: "Mock Camera".to_string(),
-                    source_url: "mock://camera".to_string(),
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                    permanent_stream_url: None
-                })
-            }
+            _id: &str,
+            _stream_metadata: CameraStreamMetadata,
+        ) -> Result<(), UpdateCameraStreamMetadataError> {
+            Err(UpdateCameraStreamMetadataError(QCError { message: "not supported by the mock".to_string(), debug_details: None }))
         }
-        
-        fn camera_exists_by_id(
+
+        async fn update_camera(
             &self,
-            id: &str,
-        ) -> impl std::future::Future<Output = Result<bool, crate::layers::ewm::main_database::qc_collection::camera_qc_collection::CheckIfCameraExistsError>> + Send {
-            async move {
-                Ok(true)
-            }
+            _command_input: UpdateCameraCommandInput,
+        ) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+            Err(UpdateCameraCommandError(QCError { message: "not supported by the mock".to_string(), debug_details: None }))
         }
     }
 
-    struct MockCameraTempBlockingQCCollection;
+    #[derive(Default)]
+    struct MockCameraTempBlockingQCCollection {
+        recurring: Vec<crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking>,
+        privacy_shutters: Vec<CameraPrivacyShutter>,
+    }
 
     impl ICameraTempBlockingQCCollection for MockCameraTempBlockingQCCollection {
         async fn list_temp_blocking_for_user(&self, _user_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
@@ -152,6 +186,50 @@ mod tests {
         async fn get_temp_blocking(&self, _camera_id: &str, _user_id: &str) -> Result<Option<CameraTempBlocking>, crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::GetCameraTempBlockingError> {
             Ok(None)
         }
+
+        async fn update_temp_blocking(&self, _camera_id: &str, _user_id: &str, _input: crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::UpdateCameraTempBlockingInput) -> Result<Option<CameraTempBlocking>, crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::UpdateCameraTempBlockingError> {
+            Ok(None)
+        }
+
+        async fn list_recurring_blockings_for_user(&self, _user_id: &str) -> Result<Vec<crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+            Ok(self.recurring.clone())
+        }
+
+        async fn list_recurring_blockings_for_camera(&self, _camera_id: &str) -> Result<Vec<crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+            Ok(self.recurring.clone())
+        }
+
+        async fn create_recurring_blocking(&self, _blocking: crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::CreateRecurringCameraBlockingError> {
+            Ok(())
+        }
+
+        async fn delete_recurring_blocking(&self, _user_id: &str, _id: &str) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::DeleteCameraTempBlockingError> {
+            Ok(())
+        }
+
+        async fn list_all_temp_blockings(&self) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+            Ok(vec![])
+        }
+
+        async fn delete_temp_blocking_ending_at(&self, _camera_id: &str, _user_id: &str, _end_date: &str) -> Result<bool, crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::DeleteCameraTempBlockingError> {
+            Ok(false)
+        }
+
+        async fn list_privacy_shutters(&self) -> Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError> {
+            Ok(self.privacy_shutters.clone())
+        }
+
+        async fn get_privacy_shutter(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::GetCameraTempBlockingError> {
+            Ok(self.privacy_shutters.iter().find(|s| s.camera_id == camera_id).cloned())
+        }
+
+        async fn put_privacy_shutter(&self, _shutter: CameraPrivacyShutter) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::PutCameraPrivacyShutterError> {
+            Ok(())
+        }
+
+        async fn delete_privacy_shutter(&self, _camera_id: &str) -> Result<(), crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::DeleteCameraTempBlockingError> {
+            Ok(())
+        }
     }
 
     #[tokio::test]
@@ -164,14 +242,14 @@ mod tests {
             should_fail: false,
         };
 
-        let mock_temp_blocking = MockCameraTempBlockingQCCollection;
+        let mock_temp_blocking = MockCameraTempBlockingQCCollection::default();
 
         let use_case = ListCamerasUseCaseImp {
             camera_qc_collection: mock_collection,
             camera_temp_blocking_qc_collection: mock_temp_blocking,
         };
 
-        let input = ListCamerasInput { user_id: "test_user".to_string() };
+        let input = ListCamerasInput { user_id: "test_user".to_string(), user_roles: vec![] };
         let result = use_case.execute(&input).await;
         assert!(result.is_ok());
         let cameras = result.unwrap();
@@ -187,14 +265,14 @@ mod tests {
             should_fail: true,
         };
 
-        let mock_temp_blocking = MockCameraTempBlockingQCCollection;
+        let mock_temp_blocking = MockCameraTempBlockingQCCollection::default();
 
         let use_case = ListCamerasUseCaseImp {
             camera_qc_collection: mock_collection,
             camera_temp_blocking_qc_collection: mock_temp_blocking,
         };
 
-        let input = ListCamerasInput { user_id: "test_user".to_string() };
+        let input = ListCamerasInput { user_id: "test_user".to_string(), user_roles: vec![] };
         let result = use_case.execute(&input).await;
         assert!(result.is_err());
         if let UseCaseError::InternalDependencyError(err) = result.unwrap_err() {
@@ -211,17 +289,87 @@ mod tests {
             should_fail: false,
         };
 
-        let mock_temp_blocking = MockCameraTempBlockingQCCollection;
+        let mock_temp_blocking = MockCameraTempBlockingQCCollection::default();
 
         let use_case = ListCamerasUseCaseImp {
             camera_qc_collection: mock_collection,
             camera_temp_blocking_qc_collection: mock_temp_blocking,
         };
 
-        let input = ListCamerasInput { user_id: "test_user".to_string() };
+        let input = ListCamerasInput { user_id: "test_user".to_string(), user_roles: vec![] };
         let result = use_case.execute(&input).await;
         assert!(result.is_ok());
         let cameras = result.unwrap();
         assert!(cameras.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_list_cameras_applies_recurring_blockings() {
+        let mock_collection = MockCameraQCCollection {
+            cameras: vec![
+                CameraListQueryResultItem { id: 1.to_string(), name: "Camera 1".to_string(), source_url: "something".to_string() },
+                CameraListQueryResultItem { id: 2.to_string(), name: "Camera 2".to_string(), source_url: "something".to_string() },
+            ],
+            should_fail: false,
+        };
+        let mock_temp_blocking = MockCameraTempBlockingQCCollection {
+            recurring: vec![crate::layers::ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::RecurringCameraBlocking {
+                id: "r1".to_string(),
+                camera_id: "2".to_string(),
+                user_id: "test_user".to_string(),
+                rrule: "FREQ=DAILY".to_string(),
+                start_time: "00:00".to_string(),
+                end_time: "00:00".to_string(),
+                timezone: "UTC".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let use_case = ListCamerasUseCaseImp::new(mock_collection, mock_temp_blocking);
+
+        let input = ListCamerasInput { user_id: "test_user".to_string(), user_roles: vec![] };
+        let cameras = use_case.execute(&input).await.unwrap();
+        assert!(matches!(cameras[0].is_available, super::super::interface::CameraAvailability::Available));
+        // a whole-day rule repeating every day never frees the camera, so the
+        // lookahead stops at its bound somewhere in the future
+        match &cameras[1].is_available {
+            super::super::interface::CameraAvailability::NotAvailable(at) => {
+                assert!(chrono::DateTime::parse_from_rfc3339(at).unwrap() > chrono::Utc::now());
+            }
+            other => panic!("expected camera 2 to be blocked, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_cameras_applies_privacy_shutter_to_non_admins() {
+        let cameras = || MockCameraQCCollection {
+            cameras: vec![
+                CameraListQueryResultItem { id: 1.to_string(), name: "Camera 1".to_string(), source_url: "something".to_string() },
+            ],
+            should_fail: false,
+        };
+        let shutters = || MockCameraTempBlockingQCCollection {
+            privacy_shutters: vec![CameraPrivacyShutter {
+                camera_id: "1".to_string(),
+                start_date: None,
+                end_date: (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+                reason: Some("party".to_string()),
+                share_reason: true,
+                created_by: Some("admin".to_string()),
+                created_at: None,
+            }],
+            ..Default::default()
+        };
+
+        let use_case = ListCamerasUseCaseImp::new(cameras(), shutters());
+        let input = ListCamerasInput { user_id: "test_user".to_string(), user_roles: vec!["User".to_string()] };
+        let listed = use_case.execute(&input).await.unwrap();
+        assert!(matches!(listed[0].is_available, super::super::interface::CameraAvailability::NotAvailable(_)));
+        assert_eq!(listed[0].unavailable_reason.as_deref(), Some("party"));
+
+        let use_case = ListCamerasUseCaseImp::new(cameras(), shutters());
+        let input = ListCamerasInput { user_id: "admin".to_string(), user_roles: vec!["Admin".to_string()] };
+        let listed = use_case.execute(&input).await.unwrap();
+        assert!(matches!(listed[0].is_available, super::super::interface::CameraAvailability::Available));
+    }
 }
@@ -12,11 +12,15 @@ pub struct CameraListItem {
     pub id: String,
     pub name: String,
     pub source_url: String,
-    pub is_available: CameraAvailability
+    pub is_available: CameraAvailability,
+    /// Reason of the user's blocking or of the camera's privacy shutter,
+    /// when the admin shared it
+    pub unavailable_reason: Option<String>
 }
 
 pub struct ListCamerasInput {
-    pub user_id: String
+    pub user_id: String,
+    pub user_roles: Vec<String>
 }
 
 pub trait IListCamerasUseCase {
@@ -9,8 +9,6 @@ use reqwest::Client;
 use serde::Deserialize;
 use tokio::sync::RwLock;
 
-use crate::layers::ewi::middleware::auth0::Claims;
-
 #[derive(Clone)]
 pub struct Auth0Config {
     pub domain: String,
@@ -34,6 +32,8 @@ pub struct Jwk {
     pub alg: Option<String>,
 }
 
+/// The authenticated user with their effective roles, put into the request
+/// by the auth middleware.
 #[derive(Debug, Clone)]
 pub struct User {
     pub id: String,
@@ -47,15 +47,11 @@ where
     type Rejection = StatusCode;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let claims = parts
+        parts
             .extensions
-            .get::<Claims>()
-            .ok_or(StatusCode::UNAUTHORIZED)?;
-
-        Ok(User {
-            id: claims.sub.clone(),
-            roles: claims.roles.clone().unwrap_or_default(),
-        })
+            .get::<User>()
+            .cloned()
+            .ok_or(StatusCode::UNAUTHORIZED)
     }
 }
 
@@ -2,9 +2,24 @@
 pub mod auth0;
 
 
+use std::net::SocketAddr;
+
+use ipnet::IpNet;
 use serde::Serialize;
+use sqlx::AnyPool;
 
-use crate::layers::ewi::appstate::auth0::Auth0State;
+use crate::layers::{
+    business::{
+        shared::{keyed_locks::KeyedLocks, validation_rules::source_url::PortRange},
+        usecases::get_camera_stream_url::interface::{TempStreamConcurrencyLimits, TempStreamRoleLimit},
+    },
+    ewi::appstate::auth0::Auth0State,
+    ewm::{
+        identity_provider::Auth0ManagementConfig,
+        main_database::{qc_collection::memory::MemoryStore, StorageBackend},
+        temporary_stream_server,
+    },
+};
 
 //TODO: Find out how to shape AppState
 #[derive(Clone, Serialize)]
@@ -18,23 +33,65 @@ pub struct StreamInfo {
 pub struct AppState {
     pub aws_config: aws_config::SdkConfig,
     pub app_config: AppConfig,
-    pub auth0: Auth0State
+    pub auth0: Auth0State,
+    pub temp_stream_locks: KeyedLocks,
+    pub storage: Storage,
+    /// Shared by every request to the temporary stream server
+    pub temporary_stream_server_client: reqwest::Client,
+}
+
+/// The store the collections are built on, set up once at startup from
+/// `AppConfig::storage_backend`.
+#[derive(Clone)]
+pub enum Storage {
+    DynamoDb,
+    /// Shared by every collection; clones see the same data
+    Memory(MemoryStore),
+    Sql(AnyPool),
 }
 
 #[derive(Clone)]
 pub struct AppConfig {
+    pub storage_backend: StorageBackend,
+    /// e.g. `sqlite:///var/lib/cameras/db.sqlite` or `postgres://...`;
+    /// required by the `Sql` backend
+    pub database_url: Option<String>,
+    /// Empty with the `Sql` backend, which does not use DynamoDB
     pub dynamo_db_table: String,
     pub permanent_relay_server_base_url: String,
     pub temporary_stream_server_base_url: String,
+    pub camera_probe_on_save: bool,
+    pub camera_probe_reject_unreachable: bool,
+    pub camera_probe_timeout_ms: u64,
+    pub onvif_discovery_address: SocketAddr,
+    pub onvif_discovery_timeout_ms: u64,
+    pub source_url_allowed_cidrs: Vec<IpNet>,
+    pub source_url_denied_cidrs: Vec<IpNet>,
+    pub source_url_allowed_ports: Vec<PortRange>,
+    pub temp_stream_reuse_min_remaining_secs: i64,
+    pub temp_stream_role_limits: Vec<TempStreamRoleLimit>,
+    pub temp_stream_concurrency_limits: TempStreamConcurrencyLimits,
+    pub media_auth_secret: Option<String>,
+    pub backfill_indexes_on_startup: bool,
+    /// How often expired blockings are moved to the history; 0 disables it
+    pub blocking_history_sweep_interval_secs: u64,
+    /// `None` when no management API credentials are configured, which
+    /// disables the user sync
+    pub auth0_management: Option<Auth0ManagementConfig>,
+    /// How often users are synced from Auth0; 0 disables it
+    pub user_sync_interval_secs: u64,
 }
 
 
 impl AppState {
-    pub fn new(aws_config: aws_config::SdkConfig, app_config: AppConfig, auth0: Auth0State) -> Self {
+    pub fn new(aws_config: aws_config::SdkConfig, app_config: AppConfig, auth0: Auth0State, storage: Storage) -> Self {
         AppState {
             aws_config,
             app_config,
-            auth0
+            auth0,
+            temp_stream_locks: KeyedLocks::new(),
+            storage,
+            temporary_stream_server_client: temporary_stream_server::http_client(),
         }
     }
 }
@@ -0,0 +1,116 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::layers::{
+    business::usecases::list_blocking_history::{
+        implementation::ListBlockingHistoryUseCaseImp,
+        interface::{IListBlockingHistoryUseCase, ListBlockingHistoryInput},
+    },
+    ewi::{appstate::{auth0::User, AppState}, error::AppError},
+    ewm::main_database::qc_collection::blocking_history_qc_collection::{
+        BlockingHistoryEntry, BlockingHistoryQCCollection,
+    },
+};
+
+#[derive(Deserialize, IntoParams)]
+pub struct BlockingHistoryQuery {
+    pub camera_id: Option<String>,
+    pub user_id: Option<String>,
+    /// Only blockings still running at or after this RFC 3339 date
+    pub from: Option<String>,
+    /// Only blockings started before this RFC 3339 date
+    pub to: Option<String>,
+    /// Page size, 50 by default and at most 100
+    pub limit: Option<i32>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BlockingHistoryHttpResponseItem {
+    pub id: String,
+    pub camera_id: String,
+    pub user_id: String,
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub archived_at: String,
+    /// `deleted` or `expired`
+    pub archive_reason: String,
+    /// Admin who deleted the blocking; empty for expired blockings
+    pub archived_by: Option<String>,
+}
+
+impl From<BlockingHistoryEntry> for BlockingHistoryHttpResponseItem {
+    fn from(entry: BlockingHistoryEntry) -> Self {
+        BlockingHistoryHttpResponseItem {
+            id: entry.id(),
+            camera_id: entry.camera_id,
+            user_id: entry.user_id,
+            start_date: entry.start_date,
+            end_date: entry.end_date,
+            reason: entry.reason,
+            created_by: entry.created_by,
+            created_at: entry.created_at,
+            archived_at: entry.archived_at.to_rfc3339(),
+            archive_reason: entry.archive_reason,
+            archived_by: entry.archived_by,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BlockingHistoryHttpResponse {
+    /// Newest first
+    pub items: Vec<BlockingHistoryHttpResponseItem>,
+    /// Pass as `cursor` to get the next page; missing on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/blocking-history",
+    tag = "blocking-history",
+    params(BlockingHistoryQuery),
+    responses(
+        (status = 200, description = "Deleted and expired blockings of a camera and/or user (Admin only)", body = BlockingHistoryHttpResponse),
+        (status = 400, description = "Neither camera_id nor user_id given, or invalid dates, limit or cursor"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn list_blocking_history(
+    State(blocking_history_qc_collection): State<BlockingHistoryQCCollection>,
+    Query(query): Query<BlockingHistoryQuery>,
+    user: User,
+) -> Result<Json<BlockingHistoryHttpResponse>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+    let use_case = ListBlockingHistoryUseCaseImp::new(blocking_history_qc_collection);
+    let page = use_case
+        .execute(ListBlockingHistoryInput {
+            camera_id: query.camera_id,
+            user_id: query.user_id,
+            from: query.from,
+            to: query.to,
+            limit: query.limit,
+            cursor: query.cursor,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+    Ok(Json(BlockingHistoryHttpResponse {
+        items: page.entries.into_iter().map(|entry| entry.into()).collect(),
+        next_cursor: page.next_cursor,
+    }))
+}
+
+pub fn setup_endpoints(router: Router<AppState>) -> Router<AppState> {
+    router.route("/blocking-history", get(list_blocking_history))
+}
@@ -1,26 +1,59 @@
 use axum::{
-    extract::{Path, State},
-    routing::{delete, get, post, put},
+    extract::{Path, Query, State},
+    routing::{delete, get, patch, post, put},
     Json, Router,
 };
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::layers::{
-    business::usecases::{
+    business::{shared::{camera_probe::CameraProbePolicy, keyed_locks::KeyedLocks, validation_rules::source_url::SourceUrlPolicy}, usecases::{
         create_camera::{CreateCameraInput, CreateCameraOutput, CreateCameraUseCase},
         create_camera_temp_blocking::{
             implementation::CreateCameraTempBlockingUseCaseImp,
             interface::{CreateCameraTempBlockingInput, ICreateCameraTempBlockingUseCase},
         },
         delete_camera::{implementation::DeleteCameraUseCase, interface::IDeleteCameraUseCase},
+        discover_cameras::{
+            implementation::DiscoverCamerasUseCaseImp,
+            interface::{DiscoverCamerasInput, DiscoverCamerasOutput, DiscoveredCameraCandidate, IDiscoverCamerasUseCase},
+        },
         get_camera_stream_url::{
-            implementation::GetCameraStreamUrlUseCase, interface::IGetCameraStremaURLUseCase,
+            implementation::GetCameraStreamUrlUseCase,
+            interface::{GetCameraStreamUrlInput, IGetCameraStremaURLUseCase, TempStreamPolicy},
         },
         delete_camera_temp_blocking::{
             implementation::DeleteCameraTempBlockingUseCaseImp,
             interface::{DeleteCameraTempBlockingInput, IDeleteCameraTempBlockingUseCase},
         },
+        create_recurring_camera_blocking::{
+            implementation::CreateRecurringCameraBlockingUseCaseImp,
+            interface::{CreateRecurringCameraBlockingInput, ICreateRecurringCameraBlockingUseCase},
+        },
+        delete_recurring_camera_blocking::{
+            implementation::DeleteRecurringCameraBlockingUseCaseImp,
+            interface::{DeleteRecurringCameraBlockingInput, IDeleteRecurringCameraBlockingUseCase},
+        },
+        set_camera_privacy_shutter::{
+            implementation::SetCameraPrivacyShutterUseCaseImp,
+            interface::{ISetCameraPrivacyShutterUseCase, SetCameraPrivacyShutterInput},
+        },
+        get_camera_privacy_shutter::{
+            implementation::GetCameraPrivacyShutterUseCaseImp,
+            interface::IGetCameraPrivacyShutterUseCase,
+        },
+        delete_camera_privacy_shutter::{
+            implementation::DeleteCameraPrivacyShutterUseCaseImp,
+            interface::IDeleteCameraPrivacyShutterUseCase,
+        },
+        list_recurring_camera_blockings::{
+            implementation::ListRecurringCameraBlockingsUseCaseImp,
+            interface::IListRecurringCameraBlockingsUseCase,
+        },
+        patch_camera_temp_blocking::{
+            implementation::PatchCameraTempBlockingUseCaseImp,
+            interface::{IPatchCameraTempBlockingUseCase, PatchCameraTempBlockingInput},
+        },
         list_blockable_users_for_camera::{
             implementation::ListBlockableUsersForCameraUseCaseImp,
             interface::IListBlockableUsersForCameraUseCase,
@@ -33,19 +66,31 @@ use crate::layers::{
             implementation::ListCamerasUseCaseImp,
             interface::{CameraListItem, IListCamerasUseCase},
         },
+        patch_camera::{
+            implementation::PatchCameraUseCase,
+            interface::{IPatchCameraUseCase, PatchCameraInput, PatchCameraOutput, PatchValue},
+        },
         put_camera::{
             implementation::PutCameraUseCase,
             interface::{IPutCameraUseCase, PutCameraInput, PutCameraOutput},
         },
-    },
-    ewi::{appstate::{auth0::User, AppState}, error::AppError},
+        test_camera_connection::{
+            implementation::TestCameraConnectionUseCaseImp,
+            interface::{ITestCameraConnectionUseCase, TestCameraConnectionOutput},
+        },
+    }},
+    ewi::{appstate::{auth0::User, AppState}, endpoints::users::UserSearchParams, error::AppError},
     ewm::{
         main_database::qc_collection::{
-            camera_qc_collection::CameraQCCollection,
-            camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection,
+            blocking_history_qc_collection::BlockingHistoryQCCollection,
+            camera_qc_collection::{CameraQCCollection, CameraStreamMetadata},
+            camera_temp_blocking_qc_collection::{CameraPrivacyShutter, CameraTempBlockingQCCollection, RecurringCameraBlocking},
+            temp_stream_session_qc_collection::TempStreamSessionQCCollection,
             user_qc_collection::UserQCCollection,
         },
+        onvif::OnvifClient,
         permanent_stream_server::PermanentStreamServer,
+        rtsp_prober::RtspProber,
         temporary_stream_server::TemporaryStreamServer,
     },
 };
@@ -93,11 +138,37 @@ pub async fn list_cameras(
     Ok(Json(result))
 }
 
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CameraStreamMetadataHttpResponse {
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub framerate: Option<f64>,
+    pub has_audio: bool,
+    pub audio_codec: Option<String>,
+    pub probed_at: String,
+}
+
+impl From<CameraStreamMetadata> for CameraStreamMetadataHttpResponse {
+    fn from(value: CameraStreamMetadata) -> Self {
+        CameraStreamMetadataHttpResponse {
+            video_codec: value.video_codec,
+            width: value.width,
+            height: value.height,
+            framerate: value.framerate,
+            has_audio: value.has_audio,
+            audio_codec: value.audio_codec,
+            probed_at: value.probed_at.to_rfc3339(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct CameraCreationHTTPResponseBody {
     pub id: String,
     pub name: String,
     pub source_url: String,
+    pub stream_metadata: Option<CameraStreamMetadataHttpResponse>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -107,11 +178,11 @@ pub struct CreateCameraHttpInput {
     pub source_url: String,
 }
 
-impl Into<CreateCameraInput> for CreateCameraHttpInput {
-    fn into(self) -> CreateCameraInput {
+impl From<CreateCameraHttpInput> for CreateCameraInput {
+    fn from(value: CreateCameraHttpInput) -> Self {
         CreateCameraInput {
-            name: self.name,
-            source_url: self.source_url,
+            name: value.name,
+            source_url: value.source_url,
         }
     }
 }
@@ -122,6 +193,7 @@ impl From<CreateCameraOutput> for CameraCreationHTTPResponseBody {
             id: output.id,
             name: output.name,
             source_url: output.source_url,
+            stream_metadata: output.stream_metadata.map(|m| m.into()),
             updated_at: output.updated_at.to_rfc3339(),
             created_at: output.created_at.to_rfc3339(),
         }
@@ -139,10 +211,18 @@ impl From<CreateCameraOutput> for CameraCreationHTTPResponseBody {
 pub async fn create_camera(
     State(camera_qc_collection): State<CameraQCCollection>,
     State(permanent_stream_server): State<PermanentStreamServer>,
+    State(rtsp_prober): State<RtspProber>,
+    State(probe_policy): State<CameraProbePolicy>,
+    State(source_url_policy): State<SourceUrlPolicy>,
     Json(input): Json<CreateCameraHttpInput>,
 ) -> Result<Json<CameraCreationHTTPResponseBody>, AppError> {
-    let create_camera_use_case =
-        CreateCameraUseCase::new(camera_qc_collection, permanent_stream_server);
+    let create_camera_use_case = CreateCameraUseCase::new(
+        camera_qc_collection,
+        permanent_stream_server,
+        rtsp_prober,
+        probe_policy,
+        source_url_policy,
+    );
     let result = create_camera_use_case
         .execute(input.into())
         .await
@@ -162,6 +242,7 @@ pub struct CameraUpdateHTTPResponseBody {
     pub id: String,
     pub name: String,
     pub source_url: String,
+    pub stream_metadata: Option<CameraStreamMetadataHttpResponse>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -172,6 +253,7 @@ impl From<PutCameraOutput> for CameraUpdateHTTPResponseBody {
             id: value.id,
             name: value.name,
             source_url: value.source_url,
+            stream_metadata: value.stream_metadata.map(|m| m.into()),
             created_at: value.created_at.to_rfc3339(),
             updated_at: value.updated_at.to_rfc3339(),
         }
@@ -194,10 +276,18 @@ pub async fn put_camera(
     Path(id): Path<String>,
     State(camera_qc_collection): State<CameraQCCollection>,
     State(permanent_stream_server): State<PermanentStreamServer>,
+    State(rtsp_prober): State<RtspProber>,
+    State(probe_policy): State<CameraProbePolicy>,
+    State(source_url_policy): State<SourceUrlPolicy>,
     Json(input): Json<UpdateCameraHttpInput>,
 ) -> Result<Json<CameraUpdateHTTPResponseBody>, AppError> {
-    let update_camera_use_case =
-        PutCameraUseCase::new(camera_qc_collection, permanent_stream_server);
+    let update_camera_use_case = PutCameraUseCase::new(
+        camera_qc_collection,
+        permanent_stream_server,
+        rtsp_prober,
+        probe_policy,
+        source_url_policy,
+    );
     tracing::info!("id received from path {}", id);
 
     let use_case_in = PutCameraInput {
@@ -213,6 +303,89 @@ pub async fn put_camera(
     Ok(Json(use_case_out.into()))
 }
 
+/// Distinguishes an explicit `null` from a missing field, as JSON merge
+/// patch requires: missing is `None`, `null` is `Some(None)`.
+fn deserialize_merge_patch_field<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn into_patch_value<T>(value: Option<Option<T>>) -> PatchValue<T> {
+    match value {
+        None => PatchValue::Absent,
+        Some(None) => PatchValue::Null,
+        Some(Some(v)) => PatchValue::Value(v),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PatchCameraHttpInput {
+    #[serde(default, deserialize_with = "deserialize_merge_patch_field")]
+    #[schema(value_type = Option<String>)]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_merge_patch_field")]
+    #[schema(value_type = Option<String>)]
+    pub source_url: Option<Option<String>>,
+}
+
+impl From<PatchCameraOutput> for CameraUpdateHTTPResponseBody {
+    fn from(value: PatchCameraOutput) -> Self {
+        CameraUpdateHTTPResponseBody {
+            id: value.id,
+            name: value.name,
+            source_url: value.source_url,
+            stream_metadata: value.stream_metadata.map(|m| m.into()),
+            created_at: value.created_at.to_rfc3339(),
+            updated_at: value.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/cameras/{id}",
+    tag = "cameras",
+    params(
+        ("id" = String, Path, description = "Camera ID")
+    ),
+    request_body(content = PatchCameraHttpInput, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Camera partially updated successfully", body = CameraUpdateHTTPResponseBody)
+    )
+)]
+pub async fn patch_camera(
+    Path(id): Path<String>,
+    State(camera_qc_collection): State<CameraQCCollection>,
+    State(permanent_stream_server): State<PermanentStreamServer>,
+    State(rtsp_prober): State<RtspProber>,
+    State(probe_policy): State<CameraProbePolicy>,
+    State(source_url_policy): State<SourceUrlPolicy>,
+    Json(input): Json<PatchCameraHttpInput>,
+) -> Result<Json<CameraUpdateHTTPResponseBody>, AppError> {
+    let patch_camera_use_case = PatchCameraUseCase::new(
+        camera_qc_collection,
+        permanent_stream_server,
+        rtsp_prober,
+        probe_policy,
+        source_url_policy,
+    );
+
+    let use_case_in = PatchCameraInput {
+        id,
+        name: into_patch_value(input.name),
+        source_url: into_patch_value(input.source_url),
+    };
+    let use_case_out = patch_camera_use_case
+        .execute(use_case_in)
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(use_case_out.into()))
+}
+
 #[utoipa::path(
     delete,
     path = "/cameras/{id}",
@@ -238,37 +411,219 @@ pub async fn delete_camera(
     Ok(())
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct CameraConnectionTestHttpResponseBody {
+    pub camera_id: String,
+    pub reachable: bool,
+    pub error: Option<String>,
+    pub stream_metadata: Option<CameraStreamMetadataHttpResponse>,
+}
+
+impl From<TestCameraConnectionOutput> for CameraConnectionTestHttpResponseBody {
+    fn from(value: TestCameraConnectionOutput) -> Self {
+        CameraConnectionTestHttpResponseBody {
+            camera_id: value.camera_id,
+            reachable: value.reachable,
+            error: value.error,
+            stream_metadata: value.stream_metadata.map(|m| m.into()),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/cameras/{id}/test-connection",
+    tag = "cameras",
+    params(
+        ("id" = String, Path, description = "Camera ID")
+    ),
+    responses(
+        (status = 200, description = "Result of an RTSP DESCRIBE against the camera source (Admin only)", body = CameraConnectionTestHttpResponseBody),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn test_camera_connection(
+    Path(id): Path<String>,
+    State(camera_qc_collection): State<CameraQCCollection>,
+    State(rtsp_prober): State<RtspProber>,
+    State(source_url_policy): State<SourceUrlPolicy>,
+    user: User,
+) -> Result<Json<CameraConnectionTestHttpResponseBody>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = TestCameraConnectionUseCaseImp::new(camera_qc_collection, rtsp_prober, source_url_policy);
+    let out = use_case
+        .execute(&id)
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+    Ok(Json(out.into()))
+}
+
+#[derive(Deserialize, ToSchema, Default)]
+pub struct DiscoverCamerasHttpInput {
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DiscoveredCameraHttpResponseItem {
+    pub name: String,
+    pub source_url: String,
+    pub device_address: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub profile_name: String,
+}
+
+impl From<DiscoveredCameraCandidate> for DiscoveredCameraHttpResponseItem {
+    fn from(value: DiscoveredCameraCandidate) -> Self {
+        DiscoveredCameraHttpResponseItem {
+            name: value.name,
+            source_url: value.source_url,
+            device_address: value.device_address,
+            manufacturer: value.manufacturer,
+            model: value.model,
+            profile_name: value.profile_name,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DiscoverCamerasHttpResponseBody {
+    pub candidates: Vec<DiscoveredCameraHttpResponseItem>,
+    pub unreachable_devices: Vec<String>,
+}
+
+impl From<DiscoverCamerasOutput> for DiscoverCamerasHttpResponseBody {
+    fn from(value: DiscoverCamerasOutput) -> Self {
+        DiscoverCamerasHttpResponseBody {
+            candidates: value.candidates.into_iter().map(|c| c.into()).collect(),
+            unreachable_devices: value.unreachable_devices,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/cameras/discover",
+    tag = "cameras",
+    request_body = DiscoverCamerasHttpInput,
+    responses(
+        (status = 200, description = "ONVIF cameras found on the network that are not registered yet (Admin only)", body = DiscoverCamerasHttpResponseBody),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn discover_cameras(
+    State(camera_qc_collection): State<CameraQCCollection>,
+    State(onvif_client): State<OnvifClient>,
+    user: User,
+    input: Option<Json<DiscoverCamerasHttpInput>>,
+) -> Result<Json<DiscoverCamerasHttpResponseBody>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let input = input.map(|Json(input)| input).unwrap_or_default();
+    let use_case = DiscoverCamerasUseCaseImp::new(camera_qc_collection, onvif_client);
+    let out = use_case
+        .execute(DiscoverCamerasInput {
+            username: input.username,
+            password: input.password,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+    Ok(Json(out.into()))
+}
+
 #[derive(Serialize, ToSchema)]
 pub struct CameraStreamHttpResponseBody {
+    session_id: String,
     camera_id: String,
     temp_rtsp_url: String,
+    hls_url: Option<String>,
+    /// HLS playlist served through this API's authenticated proxy
+    proxied_hls_url: Option<String>,
+    whep_url: Option<String>,
+    /// Protocol of `url`: the preferred one when the relay offers it, `rtsp` otherwise
+    protocol: String,
+    url: String,
+    quality: String,
     expiration_date: String,
 }
+
+#[derive(Deserialize, IntoParams)]
+pub struct CameraStreamQuery {
+    /// `original` or `downscaled`; defaults to the best quality allowed for the user
+    pub quality: Option<String>,
+    /// Requested lifetime in seconds, capped per role
+    pub ttl: Option<u64>,
+    /// Preferred playback protocol: `rtsp`, `hls` or `whep`
+    pub protocol: Option<String>,
+}
 #[utoipa::path(
     get,
     path = "/cameras/{id}/temp-stream",
     tag = "cameras",
     params(
-        ("id" = String, Path, description = "Camera ID")
+        ("id" = String, Path, description = "Camera ID"),
+        CameraStreamQuery
     ),
     responses(
-        (status = 200, description = "Temporary stream URL", body = CameraStreamHttpResponseBody)
+        (status = 200, description = "Temporary stream URL", body = CameraStreamHttpResponseBody),
+        (status = 429, description = "Concurrent temporary stream limit reached, details report current usage")
     )
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_camera_stream_url(
     Path(id): Path<String>,
     State(camera_qc_collection): State<CameraQCCollection>,
     State(temporary_stream_server): State<TemporaryStreamServer>,
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(stream_locks): State<KeyedLocks>,
+    State(stream_policy): State<TempStreamPolicy>,
+    State(source_url_policy): State<SourceUrlPolicy>,
+    Query(query): Query<CameraStreamQuery>,
+    user: User,
 ) -> Result<Json<CameraStreamHttpResponseBody>, AppError> {
-    let get_stream_url_use_case =
-        GetCameraStreamUrlUseCase::new(camera_qc_collection, temporary_stream_server);
+    let get_stream_url_use_case = GetCameraStreamUrlUseCase::new(
+        camera_qc_collection,
+        temporary_stream_server,
+        temp_stream_session_qc_collection,
+        camera_temp_blocking_qc_collection,
+        stream_locks,
+        stream_policy,
+        source_url_policy,
+    );
     let out = get_stream_url_use_case
-        .execute(&id)
+        .execute(GetCameraStreamUrlInput {
+            camera_id: id,
+            user_id: user.id,
+            user_roles: user.roles,
+            quality: query.quality,
+            ttl_seconds: query.ttl,
+            protocol: query.protocol,
+        })
         .await
         .map_err(|err| AppError::from_use_case_error(err, None))?;
+    let proxied_hls_url = out
+        .hls_url
+        .as_deref()
+        .and_then(|url| url::Url::parse(url).ok())
+        .and_then(|url| url.path_segments()?.next_back().map(str::to_owned))
+        .map(|playlist| format!("/hls/{}/{}", out.session_id, playlist));
     Ok(Json(CameraStreamHttpResponseBody {
+        session_id: out.session_id,
         camera_id: out.camera_id,
         temp_rtsp_url: out.temp_rtsp_url,
+        proxied_hls_url,
+        hls_url: out.hls_url,
+        whep_url: out.whep_url,
+        protocol: out.protocol.as_str().to_string(),
+        url: out.url,
+        quality: out.quality.as_str().to_string(),
         expiration_date: out.expiration_date.to_rfc3339(),
     }))
 }
@@ -279,6 +634,10 @@ pub struct CreateCameraTempBlockingHttpInput {
     pub start_time: String,
     pub end_time: String,
     pub user_ids: Vec<String>,
+    pub reason: Option<String>,
+    /// Show the reason to the blocked users
+    #[serde(default)]
+    pub share_reason: bool,
 }
 
 #[utoipa::path(
@@ -293,6 +652,8 @@ pub struct CreateCameraTempBlockingHttpInput {
 )]
 pub async fn create_camera_temp_blocking(
     State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(camera_qc_collection): State<CameraQCCollection>,
+    State(user_qc_collection): State<UserQCCollection>,
     user: User,
     Json(input): Json<CreateCameraTempBlockingHttpInput>,
 ) -> Result<(), AppError> {
@@ -300,13 +661,20 @@ pub async fn create_camera_temp_blocking(
         return Err(AppError::Forbidden("Admin role required".to_string()));
     }
 
-    let use_case = CreateCameraTempBlockingUseCaseImp::new(camera_temp_blocking_qc_collection);
+    let use_case = CreateCameraTempBlockingUseCaseImp::new(
+        camera_temp_blocking_qc_collection,
+        camera_qc_collection,
+        user_qc_collection,
+    );
 
     let use_case_input = CreateCameraTempBlockingInput {
         camera_id: input.camera_id,
         start_time: input.start_time,
         end_time: input.end_time,
         user_ids: input.user_ids,
+        reason: input.reason,
+        share_reason: input.share_reason,
+        created_by: user.id,
     };
 
     use_case
@@ -323,12 +691,20 @@ pub struct BlockedUserHttpResponse {
     pub user_name: String,
 }
 
+/// Admins see every field. Other users only see the reason of their own
+/// blocking when it is shared with them, and none of the audit fields.
 #[derive(Serialize, ToSchema)]
 pub struct CameraTempBlockingHttpResponseItem {
     pub id: String,
     pub camera_id: String,
     pub end_date: String,
     pub blocked_user: BlockedUserHttpResponse,
+    pub reason: Option<String>,
+    pub share_reason: Option<bool>,
+    /// Id of the admin who created the blocking
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
 }
 
 #[utoipa::path(
@@ -339,14 +715,16 @@ pub struct CameraTempBlockingHttpResponseItem {
         ("id" = String, Path, description = "Camera ID")
     ),
     responses(
-        (status = 200, description = "List of temp blockings for the camera", body = Vec<CameraTempBlockingHttpResponseItem>)
+        (status = 200, description = "List of temp blockings for the camera; audit fields are only returned to admins", body = Vec<CameraTempBlockingHttpResponseItem>)
     )
 )]
 pub async fn list_camera_temp_blockings_by_camera(
     Path(id): Path<String>,
     State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
     State(user_qc_collection): State<UserQCCollection>,
+    user: User,
 ) -> Result<Json<Vec<CameraTempBlockingHttpResponseItem>>, AppError> {
+    let is_admin = user.roles.contains(&"Admin".to_string());
     let use_case = ListCameraTempBlockingsByCameraUseCaseImp::new(camera_temp_blocking_qc_collection, user_qc_collection);
 
     let blockings = use_case
@@ -356,14 +734,35 @@ pub async fn list_camera_temp_blockings_by_camera(
 
     let result = blockings
         .into_iter()
-        .map(|b| CameraTempBlockingHttpResponseItem {
-            id: b.id,
-            camera_id: b.camera_id,
-            end_date: b.end_date,
-            blocked_user: BlockedUserHttpResponse {
-                user_id: b.blocked_user.user_id,
-                user_name: b.blocked_user.user_name,
-            },
+        .map(|b| {
+            let reason = match is_admin || (b.share_reason && b.blocked_user.user_id == user.id) {
+                true => b.reason,
+                false => None,
+            };
+            let item = CameraTempBlockingHttpResponseItem {
+                id: b.id,
+                camera_id: b.camera_id,
+                end_date: b.end_date,
+                blocked_user: BlockedUserHttpResponse {
+                    user_id: b.blocked_user.user_id,
+                    user_name: b.blocked_user.user_name,
+                },
+                reason,
+                share_reason: None,
+                created_by: None,
+                created_at: None,
+                updated_at: None,
+            };
+            match is_admin {
+                true => CameraTempBlockingHttpResponseItem {
+                    share_reason: Some(b.share_reason),
+                    created_by: b.created_by,
+                    created_at: b.created_at,
+                    updated_at: b.updated_at,
+                    ..item
+                },
+                false => item,
+            }
         })
         .collect();
 
@@ -377,15 +776,24 @@ pub struct BlockableUserHttpResponseItem {
     pub name: String,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct BlockableUsersHttpResponse {
+    pub users: Vec<BlockableUserHttpResponseItem>,
+    /// Pass as `cursor` to get the next page; missing on the last page
+    pub next_cursor: Option<String>,
+}
+
 #[utoipa::path(
     get,
     path = "/cameras/{id}/blockable-users",
     tag = "cameras",
     params(
-        ("id" = String, Path, description = "Camera ID")
+        ("id" = String, Path, description = "Camera ID"),
+        UserSearchParams
     ),
     responses(
-        (status = 200, description = "List of users that can be blocked from the camera", body = Vec<BlockableUserHttpResponseItem>),
+        (status = 200, description = "Page of active users that can be blocked from the camera, empty while its privacy shutter is closed", body = BlockableUsersHttpResponse),
+        (status = 400, description = "Invalid limit or cursor"),
         (status = 403, description = "Forbidden - Admin role required")
     )
 )]
@@ -393,20 +801,22 @@ pub async fn list_blockable_users_for_camera(
     Path(id): Path<String>,
     State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
     State(user_qc_collection): State<UserQCCollection>,
+    Query(params): Query<UserSearchParams>,
     user: User,
-) -> Result<Json<Vec<BlockableUserHttpResponseItem>>, AppError> {
+) -> Result<Json<BlockableUsersHttpResponse>, AppError> {
     if !user.roles.contains(&"Admin".to_string()) {
         return Err(AppError::Forbidden("Admin role required".to_string()));
     }
 
     let use_case = ListBlockableUsersForCameraUseCaseImp::new(camera_temp_blocking_qc_collection, user_qc_collection);
 
-    let users = use_case
-        .execute(&id)
+    let page = use_case
+        .execute(&id, params.into())
         .await
         .map_err(|err| AppError::from_use_case_error(err, None))?;
 
-    let result = users
+    let users = page
+        .users
         .into_iter()
         .map(|u| BlockableUserHttpResponseItem {
             user_id: u.user_id,
@@ -415,7 +825,10 @@ pub async fn list_blockable_users_for_camera(
         })
         .collect();
 
-    Ok(Json(result))
+    Ok(Json(BlockableUsersHttpResponse {
+        users,
+        next_cursor: page.next_cursor,
+    }))
 }
 
 #[utoipa::path(
@@ -427,22 +840,384 @@ pub async fn list_blockable_users_for_camera(
         ("user_id" = String, Path, description = "User ID")
     ),
     responses(
-        (status = 200, description = "Temp blocking deleted successfully")
+        (status = 200, description = "Temp blocking archived to the blocking history and deleted")
     )
 )]
 pub async fn delete_camera_temp_blocking(
     Path((camera_id, user_id)): Path<(String, String)>,
     State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(blocking_history_qc_collection): State<BlockingHistoryQCCollection>,
+    user: User,
+) -> Result<(), AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = DeleteCameraTempBlockingUseCaseImp::new(
+        camera_temp_blocking_qc_collection,
+        blocking_history_qc_collection,
+    );
+
+    use_case
+        .execute(DeleteCameraTempBlockingInput {
+            camera_id,
+            user_id,
+            deleted_by: user.id,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(())
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PatchCameraTempBlockingHttpInput {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub reason: Option<String>,
+    pub share_reason: Option<bool>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CameraTempBlockingHttpResponse {
+    pub camera_id: String,
+    pub user_id: String,
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    pub share_reason: bool,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/cameras/{camera_id}/temp-blockings/{user_id}",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID"),
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    request_body = PatchCameraTempBlockingHttpInput,
+    responses(
+        (status = 200, description = "Temp blocking updated successfully", body = CameraTempBlockingHttpResponse),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn patch_camera_temp_blocking(
+    Path((camera_id, user_id)): Path<(String, String)>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    user: User,
+    Json(input): Json<PatchCameraTempBlockingHttpInput>,
+) -> Result<Json<CameraTempBlockingHttpResponse>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = PatchCameraTempBlockingUseCaseImp::new(camera_temp_blocking_qc_collection);
+
+    let blocking = use_case
+        .execute(PatchCameraTempBlockingInput {
+            camera_id,
+            user_id,
+            start_time: input.start_time,
+            end_time: input.end_time,
+            reason: input.reason,
+            share_reason: input.share_reason,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(CameraTempBlockingHttpResponse {
+        camera_id: blocking.camera_id,
+        user_id: blocking.user_id,
+        start_date: blocking.start_date,
+        end_date: blocking.end_date,
+        reason: blocking.reason,
+        share_reason: blocking.share_reason,
+        created_by: blocking.created_by,
+        created_at: blocking.created_at,
+        updated_at: blocking.updated_at,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateRecurringCameraBlockingHttpInput {
+    pub user_ids: Vec<String>,
+    /// e.g. `FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR`; DAILY and WEEKLY with BYDAY and UNTIL are supported
+    pub rrule: String,
+    /// Local start time, e.g. `22:00`
+    pub start_time: String,
+    /// Local end time, e.g. `07:00`; earlier than the start means the next day
+    pub end_time: String,
+    /// IANA timezone, e.g. `Europe/Berlin`
+    pub timezone: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RecurringCameraBlockingHttpResponseItem {
+    pub id: String,
+    pub camera_id: String,
+    pub user_id: String,
+    pub rrule: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub timezone: String,
+}
+
+impl From<RecurringCameraBlocking> for RecurringCameraBlockingHttpResponseItem {
+    fn from(blocking: RecurringCameraBlocking) -> Self {
+        RecurringCameraBlockingHttpResponseItem {
+            id: blocking.id,
+            camera_id: blocking.camera_id,
+            user_id: blocking.user_id,
+            rrule: blocking.rrule,
+            start_time: blocking.start_time,
+            end_time: blocking.end_time,
+            timezone: blocking.timezone,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/cameras/{camera_id}/recurring-blockings",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID")
+    ),
+    request_body = CreateRecurringCameraBlockingHttpInput,
+    responses(
+        (status = 200, description = "One recurring blocking per user", body = Vec<RecurringCameraBlockingHttpResponseItem>),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn create_recurring_camera_blocking(
+    Path(camera_id): Path<String>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    user: User,
+    Json(input): Json<CreateRecurringCameraBlockingHttpInput>,
+) -> Result<Json<Vec<RecurringCameraBlockingHttpResponseItem>>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = CreateRecurringCameraBlockingUseCaseImp::new(camera_temp_blocking_qc_collection);
+
+    let blockings = use_case
+        .execute(CreateRecurringCameraBlockingInput {
+            camera_id,
+            user_ids: input.user_ids,
+            rrule: input.rrule,
+            start_time: input.start_time,
+            end_time: input.end_time,
+            timezone: input.timezone,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(blockings.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cameras/{camera_id}/recurring-blockings",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID")
+    ),
+    responses(
+        (status = 200, description = "Recurring blockings for the camera", body = Vec<RecurringCameraBlockingHttpResponseItem>),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn list_recurring_camera_blockings(
+    Path(camera_id): Path<String>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    user: User,
+) -> Result<Json<Vec<RecurringCameraBlockingHttpResponseItem>>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = ListRecurringCameraBlockingsUseCaseImp::new(camera_temp_blocking_qc_collection);
+
+    let blockings = use_case
+        .execute(&camera_id)
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(blockings.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cameras/{camera_id}/recurring-blockings/{user_id}/{id}",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID"),
+        ("user_id" = String, Path, description = "User ID"),
+        ("id" = String, Path, description = "Recurring blocking ID")
+    ),
+    responses(
+        (status = 200, description = "Recurring blocking deleted successfully"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn delete_recurring_camera_blocking(
+    Path((camera_id, user_id, id)): Path<(String, String, String)>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    user: User,
+) -> Result<(), AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = DeleteRecurringCameraBlockingUseCaseImp::new(camera_temp_blocking_qc_collection);
+
+    use_case
+        .execute(DeleteRecurringCameraBlockingInput { camera_id, user_id, id })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(())
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetCameraPrivacyShutterHttpInput {
+    /// RFC 3339; defaults to now
+    pub start_time: Option<String>,
+    /// RFC 3339
+    pub end_time: String,
+    pub reason: Option<String>,
+    /// Whether blocked users may see `reason`
+    #[serde(default)]
+    pub share_reason: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CameraPrivacyShutterHttpResponse {
+    pub camera_id: String,
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    pub share_reason: bool,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<CameraPrivacyShutter> for CameraPrivacyShutterHttpResponse {
+    fn from(shutter: CameraPrivacyShutter) -> Self {
+        CameraPrivacyShutterHttpResponse {
+            camera_id: shutter.camera_id,
+            start_date: shutter.start_date,
+            end_date: shutter.end_date,
+            reason: shutter.reason,
+            share_reason: shutter.share_reason,
+            created_by: shutter.created_by,
+            created_at: shutter.created_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/cameras/{camera_id}/privacy-shutter",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID")
+    ),
+    request_body = SetCameraPrivacyShutterHttpInput,
+    responses(
+        (status = 200, description = "Camera blocked for every non-admin user during the window, replacing any previous shutter", body = CameraPrivacyShutterHttpResponse),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn set_camera_privacy_shutter(
+    Path(camera_id): Path<String>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(camera_qc_collection): State<CameraQCCollection>,
+    user: User,
+    Json(input): Json<SetCameraPrivacyShutterHttpInput>,
+) -> Result<Json<CameraPrivacyShutterHttpResponse>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = SetCameraPrivacyShutterUseCaseImp::new(camera_temp_blocking_qc_collection, camera_qc_collection);
+
+    let shutter = use_case
+        .execute(SetCameraPrivacyShutterInput {
+            camera_id,
+            start_time: input.start_time,
+            end_time: input.end_time,
+            reason: input.reason,
+            share_reason: input.share_reason,
+            created_by: user.id,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(shutter.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cameras/{camera_id}/privacy-shutter",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID")
+    ),
+    responses(
+        (status = 200, description = "The camera's privacy shutter, null when it has none", body = Option<CameraPrivacyShutterHttpResponse>),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn get_camera_privacy_shutter(
+    Path(camera_id): Path<String>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    user: User,
+) -> Result<Json<Option<CameraPrivacyShutterHttpResponse>>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = GetCameraPrivacyShutterUseCaseImp::new(camera_temp_blocking_qc_collection);
+
+    let shutter = use_case
+        .execute(&camera_id)
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(shutter.map(Into::into)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cameras/{camera_id}/privacy-shutter",
+    tag = "cameras",
+    params(
+        ("camera_id" = String, Path, description = "Camera ID")
+    ),
+    responses(
+        (status = 200, description = "Privacy shutter removed"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn delete_camera_privacy_shutter(
+    Path(camera_id): Path<String>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
     user: User,
 ) -> Result<(), AppError> {
     if !user.roles.contains(&"Admin".to_string()) {
         return Err(AppError::Forbidden("Admin role required".to_string()));
     }
 
-    let use_case = DeleteCameraTempBlockingUseCaseImp::new(camera_temp_blocking_qc_collection);
+    let use_case = DeleteCameraPrivacyShutterUseCaseImp::new(camera_temp_blocking_qc_collection);
 
     use_case
-        .execute(DeleteCameraTempBlockingInput { camera_id, user_id })
+        .execute(&camera_id)
         .await
         .map_err(|err| AppError::from_use_case_error(err, None))?;
 
@@ -453,11 +1228,21 @@ pub fn setup_endpoints(router: Router<AppState>) -> Router<AppState> {
     router
         .route("/cameras", get(list_cameras))
         .route("/cameras", post(create_camera))
+        .route("/cameras/discover", post(discover_cameras))
         .route("/cameras/{id}", put(put_camera))
+        .route("/cameras/{id}", patch(patch_camera))
         .route("/cameras/{id}", delete(delete_camera))
+        .route("/cameras/{id}/test-connection", post(test_camera_connection))
         .route("/cameras/{id}/temp-stream", get(get_camera_stream_url))
         .route("/cameras/{id}/temp-blockings", get(list_camera_temp_blockings_by_camera))
         .route("/cameras/{id}/blockable-users", get(list_blockable_users_for_camera))
         .route("/cameras/{camera_id}/temp-blockings/{user_id}", delete(delete_camera_temp_blocking))
+        .route("/cameras/{camera_id}/temp-blockings/{user_id}", patch(patch_camera_temp_blocking))
         .route("/cameras/temp-blocking", post(create_camera_temp_blocking))
+        .route("/cameras/{camera_id}/recurring-blockings", post(create_recurring_camera_blocking))
+        .route("/cameras/{camera_id}/recurring-blockings", get(list_recurring_camera_blockings))
+        .route("/cameras/{camera_id}/recurring-blockings/{user_id}/{id}", delete(delete_recurring_camera_blocking))
+        .route("/cameras/{camera_id}/privacy-shutter", put(set_camera_privacy_shutter))
+        .route("/cameras/{camera_id}/privacy-shutter", get(get_camera_privacy_shutter))
+        .route("/cameras/{camera_id}/privacy-shutter", delete(delete_camera_privacy_shutter))
 }
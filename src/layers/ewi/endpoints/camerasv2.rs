@@ -21,6 +21,8 @@ pub struct CameraResultItemV2 {
     source_url: String,
     is_available: bool,
     available_at: Option<String>,
+    /// Why the camera is blocked for the user, if the admin shared it
+    unavailable_reason: Option<String>,
 }
 
 impl From<CameraListItem> for CameraResultItemV2 {
@@ -35,6 +37,7 @@ impl From<CameraListItem> for CameraResultItemV2 {
             source_url: item.source_url,
             is_available,
             available_at,
+            unavailable_reason: item.unavailable_reason,
         }
     }
 }
@@ -58,6 +61,7 @@ pub async fn list_cameras_v2(
 
     let input = ListCamerasInput {
         user_id: user.id,
+        user_roles: user.roles,
     };
 
     let cameras = list_cameras_use_case
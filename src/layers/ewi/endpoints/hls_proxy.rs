@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, RawQuery, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+
+use crate::layers::{
+    business::usecases::proxy_hls_request::{
+        implementation::ProxyHlsRequestUseCaseImp,
+        interface::{IProxyHlsRequestUseCase, ProxyHlsRequestInput},
+    },
+    ewi::{appstate::{auth0::User, AppState}, error::AppError},
+    ewm::{
+        main_database::qc_collection::{
+            camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection,
+            temp_stream_session_qc_collection::TempStreamSessionQCCollection,
+        },
+        temporary_stream_server::TemporaryStreamServer,
+    },
+};
+
+#[utoipa::path(
+    get,
+    path = "/hls/{session_id}/{path}",
+    tag = "temp-stream-sessions",
+    params(
+        ("session_id" = String, Path, description = "Temporary stream session ID"),
+        ("path" = String, Path, description = "Playlist or segment path below the session stream")
+    ),
+    responses(
+        (status = 200, description = "HLS playlist, with uris rewritten to this proxy, or media segment"),
+        (status = 403, description = "Session not owned by the user, expired, or camera blocked")
+    )
+)]
+pub async fn proxy_hls(
+    Path((session_id, path)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(temporary_stream_server): State<TemporaryStreamServer>,
+    user: User,
+) -> Result<Response, AppError> {
+    let use_case = ProxyHlsRequestUseCaseImp::new(
+        temp_stream_session_qc_collection,
+        camera_temp_blocking_qc_collection,
+        temporary_stream_server,
+    );
+    let out = use_case
+        .execute(ProxyHlsRequestInput {
+            proxy_prefix: format!("/hls/{}/", session_id),
+            session_id,
+            user_id: user.id,
+            path,
+            query,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, Some(StatusCode::FORBIDDEN)))?;
+
+    let status = StatusCode::from_u16(out.status).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut response = (status, out.body).into_response();
+    if let Some(content_type) = out.content_type.and_then(|t| t.parse().ok()) {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, header::HeaderValue::from_static("no-store"));
+    Ok(response)
+}
+
+pub fn setup_endpoints(router: Router<AppState>) -> Router<AppState> {
+    router.route("/hls/{session_id}/{*path}", get(proxy_hls))
+}
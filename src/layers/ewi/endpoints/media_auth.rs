@@ -0,0 +1,113 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::layers::{
+    business::usecases::authorize_media_access::{
+        implementation::AuthorizeMediaAccessUseCaseImp,
+        interface::{
+            AuthorizeMediaAccessInput, IAuthorizeMediaAccessUseCase, MediaAccessDecision,
+            MediaAuthPolicy,
+        },
+    },
+    ewi::{appstate::AppState, error::AppError},
+    ewm::main_database::qc_collection::{
+        camera_qc_collection::CameraQCCollection,
+        camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection,
+        temp_stream_session_qc_collection::TempStreamSessionQCCollection,
+    },
+};
+
+const SECRET_HEADER: &str = "X-Media-Auth-Secret";
+
+/// Body of MediaMTX's external HTTP authentication request.
+#[derive(Deserialize, ToSchema)]
+pub struct MediaAuthHttpInput {
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub token: Option<String>,
+    pub ip: Option<String>,
+    pub action: String,
+    pub path: String,
+    pub protocol: Option<String>,
+    pub id: Option<String>,
+    pub query: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct MediaAuthQuery {
+    /// Shared secret, for media servers that cannot send the `X-Media-Auth-Secret` header
+    pub secret: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/media-auth",
+    tag = "media",
+    params(MediaAuthQuery),
+    request_body = MediaAuthHttpInput,
+    responses(
+        (status = 200, description = "Access allowed"),
+        (status = 401, description = "Access denied")
+    ),
+    security(())
+)]
+pub async fn media_auth(
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(camera_qc_collection): State<CameraQCCollection>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(policy): State<MediaAuthPolicy>,
+    Query(query): Query<MediaAuthQuery>,
+    headers: HeaderMap,
+    Json(input): Json<MediaAuthHttpInput>,
+) -> Result<StatusCode, AppError> {
+    let hook_secret = headers
+        .get(SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+        .or(query.secret);
+    let use_case = AuthorizeMediaAccessUseCaseImp::new(
+        temp_stream_session_qc_collection,
+        camera_qc_collection,
+        camera_temp_blocking_qc_collection,
+        policy,
+    );
+    let decision = use_case
+        .execute(AuthorizeMediaAccessInput {
+            hook_secret,
+            action: input.action.clone(),
+            path: input.path.clone(),
+            token: input.token,
+            query: input.query,
+            password: input.password,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    match decision {
+        MediaAccessDecision::Allow => Ok(StatusCode::OK),
+        MediaAccessDecision::Deny(reason) => {
+            tracing::info!(
+                "denied {} on {} from {:?} via {:?}: {}",
+                input.action,
+                input.path,
+                input.ip,
+                input.protocol,
+                reason
+            );
+            Ok(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+/// Called by the stream servers rather than by users, so these routes are
+/// mounted outside the Auth0 middleware and authenticate with the shared
+/// secret instead.
+pub fn setup_public_endpoints(router: Router<AppState>) -> Router<AppState> {
+    router.route("/media-auth", post(media_auth))
+}
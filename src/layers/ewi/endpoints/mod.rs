@@ -2,12 +2,22 @@ use axum::Router;
 
 use crate::layers::ewi::appstate::AppState;
 
+pub mod blocking_history;
 pub mod cameras;
 pub mod camerasv2;
+pub mod hls_proxy;
+pub mod media_auth;
+pub mod temp_stream_sessions;
 pub mod users;
 
 pub fn setup_routes(router: Router<AppState>) -> Router<AppState> {
+    let router = blocking_history::setup_endpoints(router);
     let router = camerasv2::setup_endpoints(router);
     let router = cameras::setup_endpoints(router);
+    let router = hls_proxy::setup_endpoints(router);
+    let router = temp_stream_sessions::setup_endpoints(router);
     users::setup_endpoints(router)
-}
\ No newline at end of file
+}
+pub fn setup_public_routes(router: Router<AppState>) -> Router<AppState> {
+    media_auth::setup_public_endpoints(router)
+}
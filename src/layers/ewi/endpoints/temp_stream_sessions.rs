@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get},
+    Json, Router,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::layers::{
+    business::usecases::{
+        list_temp_stream_sessions::{
+            implementation::ListTempStreamSessionsUseCaseImp,
+            interface::{IListTempStreamSessionsUseCase, ListTempStreamSessionsInput, TempStreamSessionItem},
+        },
+        revoke_temp_stream_session::{
+            implementation::RevokeTempStreamSessionUseCaseImp,
+            interface::IRevokeTempStreamSessionUseCase,
+        },
+    },
+    ewi::{appstate::{auth0::User, AppState}, error::AppError},
+    ewm::{
+        main_database::qc_collection::temp_stream_session_qc_collection::TempStreamSessionQCCollection,
+        temporary_stream_server::TemporaryStreamServer,
+    },
+};
+
+#[derive(Serialize, ToSchema)]
+pub struct TempStreamSessionHttpResponseItem {
+    pub id: String,
+    pub user_id: String,
+    pub camera_id: String,
+    pub stream_id: String,
+    pub issued_at: String,
+    pub expires_at: String,
+}
+
+impl From<TempStreamSessionItem> for TempStreamSessionHttpResponseItem {
+    fn from(item: TempStreamSessionItem) -> Self {
+        TempStreamSessionHttpResponseItem {
+            id: item.id,
+            user_id: item.user_id,
+            camera_id: item.camera_id,
+            stream_id: item.stream_id,
+            issued_at: item.issued_at.to_rfc3339(),
+            expires_at: item.expires_at.to_rfc3339(),
+        }
+    }
+}
+
+async fn list_sessions(
+    temp_stream_session_qc_collection: TempStreamSessionQCCollection,
+    temporary_stream_server: TemporaryStreamServer,
+    user_id: Option<String>,
+) -> Result<Json<Vec<TempStreamSessionHttpResponseItem>>, AppError> {
+    let use_case = ListTempStreamSessionsUseCaseImp::new(
+        temp_stream_session_qc_collection,
+        temporary_stream_server,
+    );
+    let sessions = use_case
+        .execute(ListTempStreamSessionsInput { user_id })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+    Ok(Json(sessions.into_iter().map(|s| s.into()).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/temp-stream-sessions",
+    tag = "temp-stream-sessions",
+    responses(
+        (status = 200, description = "Active temporary stream sessions of every user (Admin only)", body = Vec<TempStreamSessionHttpResponseItem>),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn list_temp_stream_sessions(
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(temporary_stream_server): State<TemporaryStreamServer>,
+    user: User,
+) -> Result<Json<Vec<TempStreamSessionHttpResponseItem>>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+    list_sessions(temp_stream_session_qc_collection, temporary_stream_server, None).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/temp-stream-sessions/mine",
+    tag = "temp-stream-sessions",
+    responses(
+        (status = 200, description = "Active temporary stream sessions of the current user", body = Vec<TempStreamSessionHttpResponseItem>)
+    )
+)]
+pub async fn list_my_temp_stream_sessions(
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(temporary_stream_server): State<TemporaryStreamServer>,
+    user: User,
+) -> Result<Json<Vec<TempStreamSessionHttpResponseItem>>, AppError> {
+    list_sessions(temp_stream_session_qc_collection, temporary_stream_server, Some(user.id)).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/temp-stream-sessions/{id}",
+    tag = "temp-stream-sessions",
+    params(
+        ("id" = String, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 200, description = "Session revoked and its stream deleted (Admin only)"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn revoke_temp_stream_session(
+    Path(id): Path<String>,
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(temporary_stream_server): State<TemporaryStreamServer>,
+    user: User,
+) -> Result<(), AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+    let use_case = RevokeTempStreamSessionUseCaseImp::new(
+        temp_stream_session_qc_collection,
+        temporary_stream_server,
+    );
+    use_case
+        .execute(&id)
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+    Ok(())
+}
+
+pub fn setup_endpoints(router: Router<AppState>) -> Router<AppState> {
+    router
+        .route("/temp-stream-sessions", get(list_temp_stream_sessions))
+        .route("/temp-stream-sessions/mine", get(list_my_temp_stream_sessions))
+        .route("/temp-stream-sessions/{id}", delete(revoke_temp_stream_session))
+}
@@ -1,14 +1,50 @@
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{
+    extract::{Path, Query, State},
+    http,
+    routing::{get, patch, post, put},
+    Json, Router,
+};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::layers::{
-    business::usecases::list_users::{
-        implementation::ListUsersUseCaseImp,
-        interface::{IListUsersUseCase, UserListItem},
+    business::{shared::user_search::UserSearchInput, usecases::{
+        create_user::{
+            implementation::CreateUserUseCaseImp,
+            interface::{CreateUserInput, ICreateUserUseCase},
+        },
+        deactivate_user::{
+            implementation::DeactivateUserUseCaseImp,
+            interface::{DeactivateUserInput, IDeactivateUserUseCase},
+        },
+        list_users::{
+            implementation::ListUsersUseCaseImp,
+            interface::{IListUsersUseCase, UserListItem},
+        },
+        set_user_roles::{
+            implementation::SetUserRolesUseCaseImp,
+            interface::{ISetUserRolesUseCase, SetUserRolesInput},
+        },
+        sync_users::{
+            implementation::SyncUsersUseCaseImp,
+            interface::ISyncUsersUseCase,
+        },
+        update_user::{
+            implementation::UpdateUserUseCaseImp,
+            interface::{IUpdateUserUseCase, UpdateUserInput},
+        },
+    }},
+    ewi::{appstate::{auth0::User, AppState}, error::{AppError, UserInputError}},
+    ewm::{
+        identity_provider::Auth0ManagementClient,
+        main_database::qc_collection::{
+            blocking_history_qc_collection::BlockingHistoryQCCollection,
+            camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection,
+            temp_stream_session_qc_collection::TempStreamSessionQCCollection,
+            user_qc_collection::UserQCCollection,
+        },
+        temporary_stream_server::TemporaryStreamServer,
     },
-    ewi::{appstate::{auth0::User, AppState}, error::AppError},
-    ewm::main_database::qc_collection::user_qc_collection::UserQCCollection,
 };
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -16,6 +52,17 @@ pub struct UserResultItem {
     user_id: String,
     email: String,
     name: String,
+    /// Whether the user may still sign in
+    active: bool,
+    deactivated_at: Option<String>,
+    /// Roles as last synced from the identity provider
+    roles: Vec<String>,
+    /// Set when the identity provider no longer knows the user
+    removed_at: Option<String>,
+    /// Roles added on top of the token roles
+    granted_roles: Vec<String>,
+    /// Roles removed even when the token carries them
+    revoked_roles: Vec<String>,
 }
 
 impl From<UserListItem> for UserResultItem {
@@ -24,23 +71,66 @@ impl From<UserListItem> for UserResultItem {
             user_id: item.user_id,
             email: item.email,
             name: item.name,
+            active: item.deactivated_at.is_none(),
+            deactivated_at: item.deactivated_at,
+            roles: item.roles,
+            removed_at: item.removed_at,
+            granted_roles: item.granted_roles,
+            revoked_roles: item.revoked_roles,
+        }
+    }
+}
+
+/// Search and paging parameters shared by the user listings.
+#[derive(Deserialize, IntoParams)]
+pub struct UserSearchParams {
+    /// Case-insensitive prefix of the email, the name or any word of the name
+    pub search: Option<String>,
+    /// Comma separated roles; users holding any of them match
+    pub roles: Option<String>,
+    /// Page size, 50 by default and at most 100
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl From<UserSearchParams> for UserSearchInput {
+    fn from(params: UserSearchParams) -> Self {
+        UserSearchInput {
+            search: params.search,
+            roles: params
+                .roles
+                .map(|roles| roles.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            limit: params.limit,
+            cursor: params.cursor,
         }
     }
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct UserListHttpResponse {
+    pub users: Vec<UserResultItem>,
+    /// Pass as `cursor` to get the next page; missing on the last page
+    pub next_cursor: Option<String>,
+}
+
 #[utoipa::path(
     get,
     path = "/users",
     tag = "users",
+    params(UserSearchParams),
     responses(
-        (status = 200, description = "List all users (Admin only)", body = Vec<UserResultItem>),
+        (status = 200, description = "Page of users ordered by id (Admin only)", body = UserListHttpResponse),
+        (status = 400, description = "Invalid limit or cursor"),
         (status = 403, description = "Forbidden - Admin role required")
     )
 )]
 pub async fn list_users(
     State(user_qc_collection): State<UserQCCollection>,
+    Query(params): Query<UserSearchParams>,
     user: User,
-) -> Result<Json<Vec<UserResultItem>>, AppError> {
+) -> Result<Json<UserListHttpResponse>, AppError> {
     tracing::info!("User: {:?}", user);
     if !user.roles.contains(&"Admin".to_string()) {
         return Err(AppError::Forbidden("Admin role required".to_string()));
@@ -48,19 +138,302 @@ pub async fn list_users(
 
     let list_users_use_case = ListUsersUseCaseImp::new(user_qc_collection);
 
-    let users = list_users_use_case
+    let page = list_users_use_case
+        .execute(params.into())
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(UserListHttpResponse {
+        users: page.users.into_iter().map(|u| u.into()).collect(),
+        next_cursor: page.next_cursor,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateUserHttpInput {
+    /// Identity provider subject, e.g. `auth0|123`
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    request_body = CreateUserHttpInput,
+    responses(
+        (status = 200, description = "User created (Admin only)", body = UserResultItem),
+        (status = 400, description = "Invalid email or name, or the user already exists"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn create_user(
+    State(user_qc_collection): State<UserQCCollection>,
+    user: User,
+    Json(input): Json<CreateUserHttpInput>,
+) -> Result<Json<UserResultItem>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = CreateUserUseCaseImp::new(user_qc_collection);
+
+    let created = use_case
+        .execute(CreateUserInput {
+            user_id: input.user_id,
+            email: input.email,
+            name: input.name,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(created.into()))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateUserHttpInput {
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/users/{user_id}",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    request_body = UpdateUserHttpInput,
+    responses(
+        (status = 200, description = "User updated (Admin only)", body = UserResultItem),
+        (status = 400, description = "Invalid email or name, or the user does not exist"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn update_user(
+    Path(user_id): Path<String>,
+    State(user_qc_collection): State<UserQCCollection>,
+    user: User,
+    Json(input): Json<UpdateUserHttpInput>,
+) -> Result<Json<UserResultItem>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = UpdateUserUseCaseImp::new(user_qc_collection);
+
+    let updated = use_case
+        .execute(UpdateUserInput {
+            user_id,
+            email: input.email,
+            name: input.name,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(updated.into()))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeactivateUserHttpResponse {
+    pub user: UserResultItem,
+    /// One-off blockings moved to the blocking history
+    pub removed_blockings: usize,
+    pub removed_recurring_blockings: usize,
+    pub revoked_sessions: usize,
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{user_id}/deactivate",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User deactivated, their blockings and temporary stream sessions removed (Admin only)", body = DeactivateUserHttpResponse),
+        (status = 400, description = "The user does not exist or is the current user"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn deactivate_user(
+    Path(user_id): Path<String>,
+    State(user_qc_collection): State<UserQCCollection>,
+    State(camera_temp_blocking_qc_collection): State<CameraTempBlockingQCCollection>,
+    State(blocking_history_qc_collection): State<BlockingHistoryQCCollection>,
+    State(temp_stream_session_qc_collection): State<TempStreamSessionQCCollection>,
+    State(temporary_stream_server): State<TemporaryStreamServer>,
+    user: User,
+) -> Result<Json<DeactivateUserHttpResponse>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = DeactivateUserUseCaseImp::new(
+        user_qc_collection,
+        camera_temp_blocking_qc_collection,
+        blocking_history_qc_collection,
+        temp_stream_session_qc_collection,
+        temporary_stream_server,
+    );
+
+    let out = use_case
+        .execute(DeactivateUserInput {
+            user_id,
+            deactivated_by: user.id,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(DeactivateUserHttpResponse {
+        user: out.user.into(),
+        removed_blockings: out.removed_blockings,
+        removed_recurring_blockings: out.removed_recurring_blockings,
+        revoked_sessions: out.revoked_sessions,
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SyncUsersHttpResponse {
+    /// Users created or refreshed from Auth0
+    pub upserted: usize,
+    /// Users newly flagged as removed from Auth0
+    pub removed: usize,
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/sync",
+    tag = "users",
+    responses(
+        (status = 200, description = "Users synced from Auth0 (Admin only)", body = SyncUsersHttpResponse),
+        (status = 403, description = "Forbidden - Admin role required"),
+        (status = 503, description = "No Auth0 management API credentials configured")
+    )
+)]
+pub async fn sync_users(
+    State(identity_provider): State<Option<Auth0ManagementClient>>,
+    State(user_qc_collection): State<UserQCCollection>,
+    user: User,
+) -> Result<Json<SyncUsersHttpResponse>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+    let Some(identity_provider) = identity_provider else {
+        return Err(AppError::UserInputError(UserInputError {
+            status_code: http::StatusCode::SERVICE_UNAVAILABLE,
+            message: "user sync is not configured".to_string(),
+            details: serde_json::Value::Null,
+        }));
+    };
+
+    let use_case = SyncUsersUseCaseImp::new(identity_provider, user_qc_collection);
+
+    let out = use_case
         .execute()
         .await
         .map_err(|err| AppError::from_use_case_error(err, None))?;
 
-    let result = users
-        .into_iter()
-        .map(|u| u.into())
-        .collect::<Vec<UserResultItem>>();
+    Ok(Json(SyncUsersHttpResponse {
+        upserted: out.upserted,
+        removed: out.removed,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetUserRolesHttpInput {
+    /// Roles added on top of the token roles
+    #[serde(default)]
+    pub granted_roles: Vec<String>,
+    /// Roles removed even when the token carries them
+    #[serde(default)]
+    pub revoked_roles: Vec<String>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/{user_id}/roles",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    request_body = SetUserRolesHttpInput,
+    responses(
+        (status = 200, description = "Local role overrides replaced, effective from the user's next request (Admin only)", body = UserResultItem),
+        (status = 400, description = "Invalid role names, a role both granted and revoked, the current user's admin role revoked, or the user does not exist"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn set_user_roles(
+    Path(user_id): Path<String>,
+    State(user_qc_collection): State<UserQCCollection>,
+    user: User,
+    Json(input): Json<SetUserRolesHttpInput>,
+) -> Result<Json<UserResultItem>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = SetUserRolesUseCaseImp::new(user_qc_collection);
+
+    let updated = use_case
+        .execute(SetUserRolesInput {
+            user_id,
+            granted_roles: input.granted_roles,
+            revoked_roles: input.revoked_roles,
+            set_by: user.id,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
+
+    Ok(Json(updated.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{user_id}/roles",
+    tag = "users",
+    params(
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Local role overrides removed; the user is back to their token roles (Admin only)", body = UserResultItem),
+        (status = 400, description = "The user does not exist"),
+        (status = 403, description = "Forbidden - Admin role required")
+    )
+)]
+pub async fn reset_user_roles(
+    Path(user_id): Path<String>,
+    State(user_qc_collection): State<UserQCCollection>,
+    user: User,
+) -> Result<Json<UserResultItem>, AppError> {
+    if !user.roles.contains(&"Admin".to_string()) {
+        return Err(AppError::Forbidden("Admin role required".to_string()));
+    }
+
+    let use_case = SetUserRolesUseCaseImp::new(user_qc_collection);
+
+    let updated = use_case
+        .execute(SetUserRolesInput {
+            user_id,
+            granted_roles: vec![],
+            revoked_roles: vec![],
+            set_by: user.id,
+        })
+        .await
+        .map_err(|err| AppError::from_use_case_error(err, None))?;
 
-    Ok(Json(result))
+    Ok(Json(updated.into()))
 }
 
 pub fn setup_endpoints(router: Router<AppState>) -> Router<AppState> {
-    router.route("/users", get(list_users))
+    router
+        .route("/users", get(list_users))
+        .route("/users", post(create_user))
+        .route("/users/sync", post(sync_users))
+        .route("/users/{user_id}", patch(update_user))
+        .route("/users/{user_id}/deactivate", post(deactivate_user))
+        .route("/users/{user_id}/roles", put(set_user_roles).delete(reset_user_roles))
 }
@@ -1,5 +1,5 @@
 use axum::{http, response::{IntoResponse, Response}, Json};
-use serde::{de, Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
 
 use crate::layers::business::shared::errors::UseCaseError;
 
@@ -38,6 +38,14 @@ impl AppError {
                     details
                 })
             },
+            UseCaseError::LimitExceeded(be) => {
+                let details = serde_json::to_value(be.details).unwrap_or(serde_json::Value::String("could not get any details".to_string()));
+                AppError::UserInputError(UserInputError {
+                    status_code: http::StatusCode::TOO_MANY_REQUESTS,
+                    message: be.message,
+                    details
+                })
+            },
             UseCaseError::InternalDependencyError(internal_error) => {
                 AppError::InternalError(InternalError {
                     debug_message: internal_error.message
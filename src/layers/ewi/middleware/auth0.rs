@@ -1,9 +1,15 @@
-use axum::{extract::{Request, State}, http::HeaderMap, middleware::Next, response::Response};
+use axum::{extract::{FromRef, Request, State}, http::HeaderMap, middleware::Next, response::Response};
 use jsonwebtoken::{decode, decode_header, Algorithm, Validation};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::layers::ewi::appstate::{auth0::Auth0State, AppState};
+use crate::layers::{
+    business::usecases::resolve_user_roles::{
+        implementation::ResolveUserRolesUseCaseImp, interface::IResolveUserRolesUseCase,
+    },
+    ewi::appstate::{auth0::{Auth0State, User}, AppState},
+    ewm::main_database::qc_collection::user_qc_collection::UserQCCollection,
+};
 
 
 
@@ -66,9 +72,8 @@ pub async fn auth0_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let token = extract_bearer_token(&headers).map_err( |e| {
+    let token = extract_bearer_token(&headers).inspect_err(|_| {
         tracing::error!("Eror extracting token");
-        e
     })?;
     let claims = validate_jwt(&state.auth0, &token).await
         .map_err(|e| {
@@ -76,6 +81,26 @@ pub async fn auth0_middleware(
             StatusCode::UNAUTHORIZED
         })?;
 
+    // a valid token is not enough once an admin deactivated the user, and
+    // local role changes apply without waiting for a new token
+    let resolve_user_roles = ResolveUserRolesUseCaseImp::new(UserQCCollection::from_ref(&state));
+    let token_roles = claims.roles.clone().unwrap_or_default();
+    let roles = match resolve_user_roles.execute(&claims.sub, token_roles).await {
+        Ok(Some(roles)) => roles,
+        Ok(None) => {
+            tracing::info!("rejecting deactivated user {}", claims.sub);
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Err(err) => {
+            tracing::error!("error resolving user roles {:?}", err);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    req.extensions_mut().insert(User {
+        id: claims.sub.clone(),
+        roles,
+    });
     req.extensions_mut().insert(claims);
 
     Ok(next.run(req).await)
@@ -1,13 +1,25 @@
 use utoipa::OpenApi;
 
+use super::endpoints::blocking_history::{BlockingHistoryHttpResponse, BlockingHistoryHttpResponseItem};
 use super::endpoints::cameras::{
-    BlockableUserHttpResponseItem, BlockedUserHttpResponse, CameraCreationHTTPResponseBody,
-    CameraResultItem, CameraStreamHttpResponseBody, CameraTempBlockingHttpResponseItem,
-    CameraUpdateHTTPResponseBody, CreateCameraHttpInput, CreateCameraTempBlockingHttpInput,
-    UpdateCameraHttpInput,
+    BlockableUserHttpResponseItem, BlockableUsersHttpResponse, BlockedUserHttpResponse, CameraConnectionTestHttpResponseBody,
+    CameraCreationHTTPResponseBody, CameraResultItem, CameraStreamHttpResponseBody,
+    CameraStreamMetadataHttpResponse, CameraTempBlockingHttpResponseItem,
+    CameraUpdateHTTPResponseBody, CreateCameraHttpInput, DiscoverCamerasHttpInput,
+    DiscoverCamerasHttpResponseBody, DiscoveredCameraHttpResponseItem, CreateCameraTempBlockingHttpInput,
+    PatchCameraTempBlockingHttpInput, CameraTempBlockingHttpResponse,
+    CreateRecurringCameraBlockingHttpInput, RecurringCameraBlockingHttpResponseItem,
+    SetCameraPrivacyShutterHttpInput, CameraPrivacyShutterHttpResponse,
+    PatchCameraHttpInput, UpdateCameraHttpInput,
 };
 use super::endpoints::camerasv2::CameraResultItemV2;
-use super::endpoints::users::UserResultItem;
+use super::endpoints::media_auth::MediaAuthHttpInput;
+use super::endpoints::temp_stream_sessions::TempStreamSessionHttpResponseItem;
+use super::endpoints::users::{
+    CreateUserHttpInput, DeactivateUserHttpResponse, SetUserRolesHttpInput, SyncUsersHttpResponse,
+    UpdateUserHttpInput,
+    UserListHttpResponse, UserResultItem,
+};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -20,14 +32,36 @@ use super::endpoints::users::UserResultItem;
         super::endpoints::cameras::list_cameras,
         super::endpoints::cameras::create_camera,
         super::endpoints::cameras::put_camera,
+        super::endpoints::cameras::patch_camera,
         super::endpoints::cameras::delete_camera,
+        super::endpoints::cameras::test_camera_connection,
+        super::endpoints::cameras::discover_cameras,
         super::endpoints::cameras::get_camera_stream_url,
         super::endpoints::cameras::create_camera_temp_blocking,
         super::endpoints::cameras::list_camera_temp_blockings_by_camera,
         super::endpoints::cameras::list_blockable_users_for_camera,
         super::endpoints::cameras::delete_camera_temp_blocking,
+        super::endpoints::cameras::patch_camera_temp_blocking,
+        super::endpoints::cameras::create_recurring_camera_blocking,
+        super::endpoints::cameras::list_recurring_camera_blockings,
+        super::endpoints::cameras::delete_recurring_camera_blocking,
+        super::endpoints::cameras::set_camera_privacy_shutter,
+        super::endpoints::cameras::get_camera_privacy_shutter,
+        super::endpoints::cameras::delete_camera_privacy_shutter,
         super::endpoints::camerasv2::list_cameras_v2,
+        super::endpoints::blocking_history::list_blocking_history,
+        super::endpoints::temp_stream_sessions::list_temp_stream_sessions,
+        super::endpoints::temp_stream_sessions::list_my_temp_stream_sessions,
+        super::endpoints::temp_stream_sessions::revoke_temp_stream_session,
+        super::endpoints::hls_proxy::proxy_hls,
+        super::endpoints::media_auth::media_auth,
         super::endpoints::users::list_users,
+        super::endpoints::users::create_user,
+        super::endpoints::users::update_user,
+        super::endpoints::users::deactivate_user,
+        super::endpoints::users::sync_users,
+        super::endpoints::users::set_user_roles,
+        super::endpoints::users::reset_user_roles,
     ),
     components(
         schemas(
@@ -35,20 +69,46 @@ use super::endpoints::users::UserResultItem;
             CameraCreationHTTPResponseBody,
             CreateCameraHttpInput,
             UpdateCameraHttpInput,
+            PatchCameraHttpInput,
             CameraUpdateHTTPResponseBody,
             CameraStreamHttpResponseBody,
+            CameraStreamMetadataHttpResponse,
+            CameraConnectionTestHttpResponseBody,
+            DiscoverCamerasHttpInput,
+            DiscoveredCameraHttpResponseItem,
+            DiscoverCamerasHttpResponseBody,
             CameraResultItemV2,
             CreateCameraTempBlockingHttpInput,
+            PatchCameraTempBlockingHttpInput,
+            CameraTempBlockingHttpResponse,
+            CreateRecurringCameraBlockingHttpInput,
+            RecurringCameraBlockingHttpResponseItem,
+            SetCameraPrivacyShutterHttpInput,
+            CameraPrivacyShutterHttpResponse,
             CameraTempBlockingHttpResponseItem,
             BlockedUserHttpResponse,
             BlockableUserHttpResponseItem,
+            BlockableUsersHttpResponse,
+            TempStreamSessionHttpResponseItem,
+            BlockingHistoryHttpResponse,
+            BlockingHistoryHttpResponseItem,
+            MediaAuthHttpInput,
             UserResultItem,
+            UserListHttpResponse,
+            CreateUserHttpInput,
+            UpdateUserHttpInput,
+            DeactivateUserHttpResponse,
+            SyncUsersHttpResponse,
+            SetUserRolesHttpInput,
         )
     ),
     modifiers(&SecurityAddon),
     tags(
+        (name = "blocking-history", description = "Archive of deleted and expired camera blockings"),
         (name = "cameras", description = "Camera management endpoints"),
         (name = "cameras-v2", description = "Camera management endpoints v2"),
+        (name = "media", description = "Callbacks for the stream servers"),
+        (name = "temp-stream-sessions", description = "Temporary stream session endpoints"),
         (name = "users", description = "User management endpoints")
     )
 )]
@@ -0,0 +1,23 @@
+use aws_sdk_dynamodb::Client;
+use axum::extract::FromRef;
+
+use crate::layers::{
+    ewi::appstate::{AppState, Storage},
+    ewm::main_database::qc_collection::{
+        blocking_history_qc_collection::{BlockingHistoryQCCollection, DynamoBlockingHistoryQCCollection},
+        sql::blocking_history_qc_collection::SqlBlockingHistoryQCCollection,
+    },
+};
+
+impl FromRef<AppState> for BlockingHistoryQCCollection {
+    fn from_ref(app_state: &AppState) -> Self {
+        match &app_state.storage {
+            Storage::DynamoDb => {
+                let client = Client::new(&app_state.aws_config);
+                BlockingHistoryQCCollection::Dynamo(DynamoBlockingHistoryQCCollection::new(client, app_state.app_config.dynamo_db_table.clone()))
+            }
+            Storage::Memory(store) => BlockingHistoryQCCollection::Memory(store.blocking_history.clone()),
+            Storage::Sql(pool) => BlockingHistoryQCCollection::Sql(SqlBlockingHistoryQCCollection::new(pool.clone())),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use axum::extract::FromRef;
+
+use crate::layers::{business::shared::camera_probe::CameraProbePolicy, ewi::appstate::AppState};
+
+impl FromRef<AppState> for CameraProbePolicy {
+    fn from_ref(app_state: &AppState) -> Self {
+        CameraProbePolicy {
+            probe_on_save: app_state.app_config.camera_probe_on_save,
+            reject_unreachable: app_state.app_config.camera_probe_reject_unreachable,
+        }
+    }
+}
@@ -1,11 +1,23 @@
 use aws_sdk_dynamodb::Client;
 use axum::extract::FromRef;
 
-use crate::layers::{ewi::appstate::AppState, ewm::main_database::qc_collection::camera_qc_collection::CameraQCCollection};
+use crate::layers::{
+    ewi::appstate::{AppState, Storage},
+    ewm::main_database::qc_collection::{
+        camera_qc_collection::{CameraQCCollection, DynamoCameraQCCollection},
+        sql::camera_qc_collection::SqlCameraQCCollection,
+    },
+};
 
 impl FromRef<AppState> for CameraQCCollection {
     fn from_ref(app_state: &AppState) -> Self {
-        let client = Client::new(&app_state.aws_config);
-        CameraQCCollection::new(client, app_state.app_config.dynamo_db_table.clone())
+        match &app_state.storage {
+            Storage::DynamoDb => {
+                let client = Client::new(&app_state.aws_config);
+                CameraQCCollection::Dynamo(DynamoCameraQCCollection::new(client, app_state.app_config.dynamo_db_table.clone()))
+            }
+            Storage::Memory(store) => CameraQCCollection::Memory(store.cameras.clone()),
+            Storage::Sql(pool) => CameraQCCollection::Sql(SqlCameraQCCollection::new(pool.clone())),
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,23 @@
 use aws_sdk_dynamodb::Client;
 use axum::extract::FromRef;
 
-use crate::layers::{ewi::appstate::AppState, ewm::main_database::qc_collection::camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection};
+use crate::layers::{
+    ewi::appstate::{AppState, Storage},
+    ewm::main_database::qc_collection::{
+        camera_temp_blocking_qc_collection::{CameraTempBlockingQCCollection, DynamoCameraTempBlockingQCCollection},
+        sql::camera_temp_blocking_qc_collection::SqlCameraTempBlockingQCCollection,
+    },
+};
 
 impl FromRef<AppState> for CameraTempBlockingQCCollection {
     fn from_ref(app_state: &AppState) -> Self {
-        let client = Client::new(&app_state.aws_config);
-        CameraTempBlockingQCCollection::new(client, app_state.app_config.dynamo_db_table.clone())
+        match &app_state.storage {
+            Storage::DynamoDb => {
+                let client = Client::new(&app_state.aws_config);
+                CameraTempBlockingQCCollection::Dynamo(DynamoCameraTempBlockingQCCollection::new(client, app_state.app_config.dynamo_db_table.clone()))
+            }
+            Storage::Memory(store) => CameraTempBlockingQCCollection::Memory(store.camera_temp_blockings.clone()),
+            Storage::Sql(pool) => CameraTempBlockingQCCollection::Sql(SqlCameraTempBlockingQCCollection::new(pool.clone())),
+        }
     }
 }
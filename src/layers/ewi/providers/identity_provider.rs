@@ -0,0 +1,14 @@
+use axum::extract::FromRef;
+
+use crate::layers::{ewi::appstate::AppState, ewm::identity_provider::Auth0ManagementClient};
+
+/// `None` when no management API credentials are configured.
+impl FromRef<AppState> for Option<Auth0ManagementClient> {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state
+            .app_config
+            .auth0_management
+            .clone()
+            .map(Auth0ManagementClient::new)
+    }
+}
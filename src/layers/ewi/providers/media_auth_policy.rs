@@ -0,0 +1,14 @@
+use axum::extract::FromRef;
+
+use crate::layers::{
+    business::usecases::authorize_media_access::interface::MediaAuthPolicy,
+    ewi::appstate::AppState,
+};
+
+impl FromRef<AppState> for MediaAuthPolicy {
+    fn from_ref(app_state: &AppState) -> Self {
+        MediaAuthPolicy {
+            shared_secret: app_state.app_config.media_auth_secret.clone(),
+        }
+    }
+}
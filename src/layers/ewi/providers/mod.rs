@@ -1,6 +1,15 @@
 pub mod camera_qc_collection;
 pub mod camera_temp_blocking_qc_collection;
 pub mod aws_config;
+pub mod blocking_history_qc_collection;
+pub mod camera_probe_policy;
+pub mod identity_provider;
+pub mod media_auth_policy;
+pub mod onvif_client;
 pub mod permanent_stream_server;
+pub mod rtsp_prober;
+pub mod source_url_policy;
+pub mod temp_stream_policy;
+pub mod temp_stream_session_qc_collection;
 pub mod temporary_stream_server;
 pub mod user_qc_collection;
@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+use axum::extract::FromRef;
+
+use crate::layers::{ewi::appstate::AppState, ewm::onvif::OnvifClient};
+
+impl FromRef<AppState> for OnvifClient {
+    fn from_ref(app_state: &AppState) -> Self {
+        OnvifClient::new(
+            app_state.app_config.onvif_discovery_address,
+            Duration::from_millis(app_state.app_config.onvif_discovery_timeout_ms),
+        )
+    }
+}
@@ -0,0 +1,11 @@
+use std::time::Duration;
+
+use axum::extract::FromRef;
+
+use crate::layers::{ewi::appstate::AppState, ewm::rtsp_prober::RtspProber};
+
+impl FromRef<AppState> for RtspProber {
+    fn from_ref(app_state: &AppState) -> Self {
+        RtspProber::new(Duration::from_millis(app_state.app_config.camera_probe_timeout_ms))
+    }
+}
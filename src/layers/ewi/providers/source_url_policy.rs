@@ -0,0 +1,15 @@
+use axum::extract::FromRef;
+
+use crate::layers::{
+    business::shared::validation_rules::source_url::SourceUrlPolicy, ewi::appstate::AppState,
+};
+
+impl FromRef<AppState> for SourceUrlPolicy {
+    fn from_ref(app_state: &AppState) -> Self {
+        SourceUrlPolicy {
+            allowed_cidrs: app_state.app_config.source_url_allowed_cidrs.clone(),
+            denied_cidrs: app_state.app_config.source_url_denied_cidrs.clone(),
+            allowed_ports: app_state.app_config.source_url_allowed_ports.clone(),
+        }
+    }
+}
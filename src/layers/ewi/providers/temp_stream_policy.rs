@@ -0,0 +1,27 @@
+use axum::extract::FromRef;
+
+use crate::layers::{
+    business::{
+        shared::keyed_locks::KeyedLocks,
+        usecases::get_camera_stream_url::interface::TempStreamPolicy,
+    },
+    ewi::appstate::AppState,
+};
+
+impl FromRef<AppState> for KeyedLocks {
+    fn from_ref(app_state: &AppState) -> Self {
+        app_state.temp_stream_locks.clone()
+    }
+}
+
+impl FromRef<AppState> for TempStreamPolicy {
+    fn from_ref(app_state: &AppState) -> Self {
+        TempStreamPolicy {
+            min_reuse_remaining: chrono::Duration::seconds(
+                app_state.app_config.temp_stream_reuse_min_remaining_secs,
+            ),
+            role_limits: app_state.app_config.temp_stream_role_limits.clone(),
+            concurrency_limits: app_state.app_config.temp_stream_concurrency_limits.clone(),
+        }
+    }
+}
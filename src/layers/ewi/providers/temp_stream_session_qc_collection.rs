@@ -0,0 +1,23 @@
+use aws_sdk_dynamodb::Client;
+use axum::extract::FromRef;
+
+use crate::layers::{
+    ewi::appstate::{AppState, Storage},
+    ewm::main_database::qc_collection::{
+        temp_stream_session_qc_collection::{TempStreamSessionQCCollection, DynamoTempStreamSessionQCCollection},
+        sql::temp_stream_session_qc_collection::SqlTempStreamSessionQCCollection,
+    },
+};
+
+impl FromRef<AppState> for TempStreamSessionQCCollection {
+    fn from_ref(app_state: &AppState) -> Self {
+        match &app_state.storage {
+            Storage::DynamoDb => {
+                let client = Client::new(&app_state.aws_config);
+                TempStreamSessionQCCollection::Dynamo(DynamoTempStreamSessionQCCollection::new(client, app_state.app_config.dynamo_db_table.clone()))
+            }
+            Storage::Memory(store) => TempStreamSessionQCCollection::Memory(store.temp_stream_sessions.clone()),
+            Storage::Sql(pool) => TempStreamSessionQCCollection::Sql(SqlTempStreamSessionQCCollection::new(pool.clone())),
+        }
+    }
+}
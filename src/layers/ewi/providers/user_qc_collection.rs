@@ -1,11 +1,23 @@
 use aws_sdk_dynamodb::Client;
 use axum::extract::FromRef;
 
-use crate::layers::{ewi::appstate::AppState, ewm::main_database::qc_collection::user_qc_collection::UserQCCollection};
+use crate::layers::{
+    ewi::appstate::{AppState, Storage},
+    ewm::main_database::qc_collection::{
+        user_qc_collection::{UserQCCollection, DynamoUserQCCollection},
+        sql::user_qc_collection::SqlUserQCCollection,
+    },
+};
 
 impl FromRef<AppState> for UserQCCollection {
     fn from_ref(app_state: &AppState) -> Self {
-        let client = Client::new(&app_state.aws_config);
-        UserQCCollection::new(client, app_state.app_config.dynamo_db_table.clone())
+        match &app_state.storage {
+            Storage::DynamoDb => {
+                let client = Client::new(&app_state.aws_config);
+                UserQCCollection::Dynamo(DynamoUserQCCollection::new(client, app_state.app_config.dynamo_db_table.clone()))
+            }
+            Storage::Memory(store) => UserQCCollection::Memory(store.users.clone()),
+            Storage::Sql(pool) => UserQCCollection::Sql(SqlUserQCCollection::new(pool.clone())),
+        }
     }
 }
@@ -1,19 +1,44 @@
 
 use aws_config::BehaviorVersion;
 
-use axum::{middleware, Router};
+use axum::{extract::FromRef, middleware, Router};
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::layers::ewi::{
-    appstate::{auth0::{Auth0Config, Auth0State}, AppConfig, AppState}, endpoints, middleware::auth0::auth0_middleware, openapi::ApiDoc
+use crate::layers::{
+    business::usecases::{
+        archive_expired_temp_blockings::{
+            implementation::ArchiveExpiredTempBlockingsUseCaseImp,
+            interface::IArchiveExpiredTempBlockingsUseCase,
+        },
+        get_camera_stream_url::interface::{TempStreamConcurrencyLimits, TempStreamRoleLimit},
+        sync_users::{implementation::SyncUsersUseCaseImp, interface::ISyncUsersUseCase},
+    },
+    ewi::{
+        appstate::{auth0::{Auth0Config, Auth0State}, AppConfig, AppState, Storage}, endpoints, middleware::auth0::auth0_middleware, openapi::ApiDoc
+    },
+    ewm::{
+        identity_provider::{Auth0ManagementClient, Auth0ManagementConfig},
+        main_database::{
+            qc_collection::{
+                blocking_history_qc_collection::BlockingHistoryQCCollection,
+                camera_temp_blocking_qc_collection::CameraTempBlockingQCCollection,
+                memory::MemoryStore,
+                sql,
+                user_qc_collection::UserQCCollection,
+            },
+            StorageBackend,
+        },
+        temporary_stream_server::StreamQuality,
+    },
 };
-use std::env;
+use std::{env, net::SocketAddr, str::FromStr, time::Duration};
+use tokio::time::MissedTickBehavior;
 
 #[derive(Debug, Clone)]
-struct ReadConfigErr {
+pub struct ReadConfigErr {
     pub reason: String,
 }
 
@@ -47,9 +72,22 @@ fn read_server_config_from_env() -> Result<ServerConfig, ReadConfigErr> {
     })
 }
 fn read_app_config_from_env() -> Result<AppConfig, ReadConfigErr> {
-    let dynamo_db_table = env::var("DYNAMO_DB_TABLE").map_err(|err| ReadConfigErr {
-        reason: format!("Failed to read DYNAMO_DB_TABLE from env: {:?}", err),
-    })?;
+    let storage_backend = read_optional_env("STORAGE_BACKEND", StorageBackend::DynamoDb)?;
+    let database_url = env::var("DATABASE_URL").ok().filter(|s| !s.is_empty());
+    let dynamo_db_table = match storage_backend {
+        StorageBackend::Sql => {
+            if database_url.is_none() {
+                return Err(ReadConfigErr {
+                    reason: "DATABASE_URL is required with STORAGE_BACKEND=sql".to_string(),
+                });
+            }
+            env::var("DYNAMO_DB_TABLE").unwrap_or_default()
+        }
+        StorageBackend::Memory => env::var("DYNAMO_DB_TABLE").unwrap_or_default(),
+        StorageBackend::DynamoDb => env::var("DYNAMO_DB_TABLE").map_err(|err| ReadConfigErr {
+            reason: format!("Failed to read DYNAMO_DB_TABLE from env: {:?}", err),
+        })?,
+    };
 
     let permanent_relay_server_base_url = env::var("PERMANENT_STREAM_SERVER_URL").map_err(|err| ReadConfigErr {
         reason: format!("Failed to read PERMANENT_STREAM_SERVER_URL from env: {:?}", err),
@@ -59,7 +97,128 @@ fn read_app_config_from_env() -> Result<AppConfig, ReadConfigErr> {
         reason: format!("Failed to read TEMPORARY_STREAM_SERVER_URL from env: {:?}", err),
     })?;
 
-    Ok(AppConfig { dynamo_db_table, permanent_relay_server_base_url, temporary_stream_server_base_url })
+    let camera_probe_on_save = read_optional_env("CAMERA_PROBE_ON_SAVE", false)?;
+    let camera_probe_reject_unreachable = read_optional_env("CAMERA_PROBE_REJECT_UNREACHABLE", false)?;
+    let camera_probe_timeout_ms = read_optional_env("CAMERA_PROBE_TIMEOUT_MS", 5000)?;
+    let onvif_discovery_address = read_optional_env(
+        "ONVIF_DISCOVERY_ADDRESS",
+        SocketAddr::from(([239, 255, 255, 250], 3702)),
+    )?;
+    let onvif_discovery_timeout_ms = read_optional_env("ONVIF_DISCOVERY_TIMEOUT_MS", 3000)?;
+    let source_url_allowed_cidrs = read_list_env("SOURCE_URL_ALLOWED_CIDRS")?;
+    let source_url_denied_cidrs = read_list_env("SOURCE_URL_DENIED_CIDRS")?;
+    let source_url_allowed_ports = read_list_env("SOURCE_URL_ALLOWED_PORTS")?;
+    let backfill_indexes_on_startup = read_optional_env("BACKFILL_INDEXES_ON_STARTUP", true)?;
+    let blocking_history_sweep_interval_secs =
+        read_optional_env("BLOCKING_HISTORY_SWEEP_INTERVAL_SECS", 300)?;
+    let auth0_management = read_auth0_management_config_from_env()?;
+    let user_sync_interval_secs = read_optional_env("USER_SYNC_INTERVAL_SECS", 0)?;
+    let media_auth_secret = env::var("MEDIA_AUTH_SECRET").ok().filter(|s| !s.is_empty());
+    let temp_stream_reuse_min_remaining_secs =
+        read_optional_env("TEMP_STREAM_REUSE_MIN_REMAINING_SECS", 60)?;
+    let mut temp_stream_role_limits = read_list_env("TEMP_STREAM_ROLE_LIMITS")?;
+    if temp_stream_role_limits.is_empty() {
+        temp_stream_role_limits = vec![TempStreamRoleLimit {
+            role: "*".to_string(),
+            max_quality: StreamQuality::Original,
+            max_ttl_seconds: 3600,
+        }];
+    }
+    // 0 keeps a limit disabled
+    let limit = |name: &str| -> Result<Option<usize>, ReadConfigErr> {
+        Ok(Some(read_optional_env(name, 0usize)?).filter(|limit| *limit > 0))
+    };
+    let temp_stream_concurrency_limits = TempStreamConcurrencyLimits {
+        per_user: limit("TEMP_STREAM_MAX_PER_USER")?,
+        per_camera: limit("TEMP_STREAM_MAX_PER_CAMERA")?,
+        total: limit("TEMP_STREAM_MAX_TOTAL")?,
+    };
+
+    Ok(AppConfig {
+        storage_backend,
+        database_url,
+        dynamo_db_table,
+        permanent_relay_server_base_url,
+        temporary_stream_server_base_url,
+        camera_probe_on_save,
+        camera_probe_reject_unreachable,
+        camera_probe_timeout_ms,
+        onvif_discovery_address,
+        onvif_discovery_timeout_ms,
+        source_url_allowed_cidrs,
+        source_url_denied_cidrs,
+        source_url_allowed_ports,
+        temp_stream_reuse_min_remaining_secs,
+        temp_stream_role_limits,
+        temp_stream_concurrency_limits,
+        media_auth_secret,
+        backfill_indexes_on_startup,
+        blocking_history_sweep_interval_secs,
+        auth0_management,
+        user_sync_interval_secs,
+    })
+}
+
+/// The user sync is optional; it is only set up when both management API
+/// credentials are present.
+fn read_auth0_management_config_from_env() -> Result<Option<Auth0ManagementConfig>, ReadConfigErr> {
+    let client_id = env::var("AUTH0_MANAGEMENT_CLIENT_ID").ok().filter(|s| !s.is_empty());
+    let client_secret = env::var("AUTH0_MANAGEMENT_CLIENT_SECRET").ok().filter(|s| !s.is_empty());
+    let (Some(client_id), Some(client_secret)) = (client_id, client_secret) else {
+        return Ok(None);
+    };
+    let base_url = match env::var("AUTH0_MANAGEMENT_BASE_URL").ok().filter(|s| !s.is_empty()) {
+        Some(base_url) => base_url.trim_end_matches('/').to_string(),
+        None => {
+            let domain = env::var("AUTH0_DOMAIN").map_err(|err| ReadConfigErr {
+                reason: format!("Failed to read AUTH0_DOMAIN from env: {:?}", err),
+            })?;
+            format!("https://{}", domain)
+        }
+    };
+    let audience = env::var("AUTH0_MANAGEMENT_AUDIENCE")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| format!("{}/api/v2/", base_url));
+    Ok(Some(Auth0ManagementConfig {
+        base_url,
+        client_id,
+        client_secret,
+        audience,
+    }))
+}
+
+fn read_optional_env<T>(name: &str, default: T) -> Result<T, ReadConfigErr>
+where
+    T: FromStr,
+    T::Err: std::fmt::Debug,
+{
+    match env::var(name) {
+        Ok(value) => value.parse::<T>().map_err(|err| ReadConfigErr {
+            reason: format!("Failed to parse {}: {:?}", name, err),
+        }),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Reads a comma separated list, e.g. `10.0.0.0/8,192.168.0.0/16`. Missing or
+/// empty variables yield an empty list.
+fn read_list_env<T>(name: &str) -> Result<Vec<T>, ReadConfigErr>
+where
+    T: FromStr,
+    T::Err: std::fmt::Debug,
+{
+    let value = env::var(name).unwrap_or_default();
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse::<T>().map_err(|err| ReadConfigErr {
+                reason: format!("Failed to parse {} entry {:?}: {:?}", name, item, err),
+            })
+        })
+        .collect()
 }
 
 fn read_auth0_config_from_env() -> Result<Auth0Config, ReadConfigErr> {
@@ -101,12 +260,69 @@ pub async fn setup_and_run() -> Result<(), StartupServerError> {
 
     let auth0_state = Auth0State::new(auth0_config);
 
-    let app_state = AppState {
-        app_config,
-        aws_config: aws_sdk_config,
-        auth0: auth0_state
+    let storage = match app_config.storage_backend {
+        StorageBackend::DynamoDb => Storage::DynamoDb,
+        StorageBackend::Memory => Storage::Memory(MemoryStore::default()),
+        StorageBackend::Sql => {
+            let database_url = app_config.database_url.as_deref().unwrap_or_default();
+            Storage::Sql(sql::connect(database_url).await.map_err(|err| StartupServerError {
+                reason: format!("Failed to set up sql database: {:?}", err),
+            })?)
+        }
     };
 
+    let app_state = AppState::new(aws_sdk_config, app_config, auth0_state, storage);
+
+    if app_state.app_config.backfill_indexes_on_startup {
+        // runs next to the server; queries simply miss not yet backfilled items
+        let camera_temp_blocking_qc_collection = CameraTempBlockingQCCollection::from_ref(&app_state);
+        tokio::spawn(async move {
+            match camera_temp_blocking_qc_collection.backfill_camera_index().await {
+                Ok(updated) => tracing::info!("backfilled camera index on {} blockings", updated),
+                Err(err) => tracing::error!("failed to backfill camera index: {:?}", err),
+            }
+        });
+    }
+
+    if app_state.app_config.blocking_history_sweep_interval_secs > 0 {
+        let use_case = ArchiveExpiredTempBlockingsUseCaseImp::new(
+            CameraTempBlockingQCCollection::from_ref(&app_state),
+            BlockingHistoryQCCollection::from_ref(&app_state),
+        );
+        let period = Duration::from_secs(app_state.app_config.blocking_history_sweep_interval_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match use_case.execute().await {
+                    Ok(0) => {}
+                    Ok(archived) => tracing::info!("archived {} expired blockings", archived),
+                    Err(err) => tracing::error!("failed to archive expired blockings: {:?}", err),
+                }
+            }
+        });
+    }
+
+    if let (Some(identity_provider), true) = (
+        Option::<Auth0ManagementClient>::from_ref(&app_state),
+        app_state.app_config.user_sync_interval_secs > 0,
+    ) {
+        let use_case = SyncUsersUseCaseImp::new(identity_provider, UserQCCollection::from_ref(&app_state));
+        let period = Duration::from_secs(app_state.app_config.user_sync_interval_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match use_case.execute().await {
+                    Ok(out) => tracing::info!("synced {} users, flagged {} as removed", out.upserted, out.removed),
+                    Err(err) => tracing::error!("failed to sync users: {:?}", err),
+                }
+            }
+        });
+    }
+
     let app = endpoints::setup_routes(Router::new())
         .with_state(app_state.clone())
         .layer(
@@ -114,6 +330,7 @@ pub async fn setup_and_run() -> Result<(), StartupServerError> {
                 .layer(CorsLayer::permissive())
                 .layer(middleware::from_fn_with_state(app_state.clone(), auth0_middleware))
         )
+        .merge(endpoints::setup_public_routes(Router::new()).with_state(app_state.clone()))
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
 
     let bind_str = format!("{}:{}", server_config.http_host, server_config.http_port);
@@ -0,0 +1,475 @@
+use std::collections::{HashMap, HashSet};
+
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+const PAGE_SIZE: usize = 100;
+/// Auth0 refuses `page`/`per_page` requests beyond the first 1000 results
+const MAX_PAGED_RESULTS: usize = 1000;
+
+/// A user as the identity provider knows it, with the names of the roles
+/// assigned to them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityProviderUser {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct IdentityProviderError {
+    pub message: String,
+    pub debug_message: String,
+}
+
+pub trait IIdentityProvider {
+    /// Every user of the tenant. Fails as a whole rather than returning a
+    /// partial list, so callers can treat missing users as gone.
+    fn list_users(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<IdentityProviderUser>, IdentityProviderError>> + Send;
+}
+
+/// Machine to machine credentials for the Auth0 Management API. The
+/// application needs the `read:users` and `read:roles` scopes.
+#[derive(Debug, Clone)]
+pub struct Auth0ManagementConfig {
+    /// e.g. `https://tenant.eu.auth0.com`
+    pub base_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Usually `{base_url}/api/v2/`
+    pub audience: String,
+}
+
+pub struct Auth0ManagementClient {
+    config: Auth0ManagementConfig,
+}
+
+impl Auth0ManagementClient {
+    pub fn new(config: Auth0ManagementConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize)]
+struct TokenRequestBody<'a> {
+    grant_type: &'a str,
+    client_id: &'a str,
+    client_secret: &'a str,
+    audience: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct Auth0User {
+    user_id: String,
+    email: Option<String>,
+    name: Option<String>,
+    created_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Auth0Role {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Auth0RoleMember {
+    user_id: String,
+}
+
+fn request_failed(status: StatusCode, url: &str) -> IdentityProviderError {
+    IdentityProviderError {
+        message: format!("request failed with: {}", status),
+        debug_message: format!("request to {} failed with: {}", url, status),
+    }
+}
+
+impl Auth0ManagementClient {
+    async fn access_token(&self, client: &reqwest::Client) -> Result<String, IdentityProviderError> {
+        let url = format!("{}/oauth/token", self.config.base_url);
+        let response = client
+            .post(&url)
+            .json(&TokenRequestBody {
+                grant_type: "client_credentials",
+                client_id: &self.config.client_id,
+                client_secret: &self.config.client_secret,
+                audience: &self.config.audience,
+            })
+            .send()
+            .await
+            .map_err(|err| IdentityProviderError {
+                message: "Failed to request a management API token".to_string(),
+                debug_message: err.to_string(),
+            })?;
+        let status = response.status();
+        if status != StatusCode::OK {
+            return Err(request_failed(status, &url));
+        }
+        let token: TokenResponse = response.json().await.map_err(|err| IdentityProviderError {
+            message: "Failed to parse management API token".to_string(),
+            debug_message: format!("{:?}", err),
+        })?;
+        Ok(token.access_token)
+    }
+
+    async fn get_json(
+        &self,
+        client: &reqwest::Client,
+        token: &str,
+        path: &str,
+        query: &[(&str, String)],
+        key: &str,
+    ) -> Result<HashMap<String, serde_json::Value>, IdentityProviderError> {
+        let url = format!("{}{}", self.config.base_url, path);
+        let response = client
+            .get(&url)
+            .query(query)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|err| IdentityProviderError {
+                message: format!("Failed to fetch {}", key),
+                debug_message: err.to_string(),
+            })?;
+        let status = response.status();
+        if status != StatusCode::OK {
+            return Err(request_failed(status, &url));
+        }
+        response.json().await.map_err(|err| IdentityProviderError {
+            message: format!("Failed to parse {}", key),
+            debug_message: format!("{:?}", err),
+        })
+    }
+
+    /// Follows `page`/`per_page` until the `total` reported with
+    /// `include_totals=true` is reached, or Auth0's limit of 1000 results per
+    /// query. `key` names the list in the body, e.g. `users` or `roles`.
+    /// Returns the items with the reported total.
+    async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        token: &str,
+        path: &str,
+        key: &str,
+        query: &[(&str, String)],
+    ) -> Result<(Vec<T>, usize), IdentityProviderError> {
+        let mut items = Vec::new();
+        let mut total = 0;
+        for page in 0..MAX_PAGED_RESULTS / PAGE_SIZE {
+            let mut page_query = vec![
+                ("page", page.to_string()),
+                ("per_page", PAGE_SIZE.to_string()),
+                ("include_totals", "true".to_string()),
+            ];
+            page_query.extend(query.iter().cloned());
+            let mut body = self.get_json(client, token, path, &page_query, key).await?;
+            total = body.get("total").and_then(|t| t.as_u64()).unwrap_or(0) as usize;
+            let page_items: Vec<T> = take_list(&mut body, key)?;
+            let received = page_items.len();
+            items.extend(page_items);
+            if received == 0 || items.len() >= total {
+                break;
+            }
+        }
+        Ok((items, total))
+    }
+
+    /// Members of a role through checkpoint pagination, which has no result
+    /// limit: each request passes the `next` checkpoint of the previous one.
+    async fn get_role_members(
+        &self,
+        client: &reqwest::Client,
+        token: &str,
+        role_id: &str,
+    ) -> Result<Vec<Auth0RoleMember>, IdentityProviderError> {
+        let path = format!("/api/v2/roles/{}/users", role_id);
+        let mut members = Vec::new();
+        let mut from: Option<String> = None;
+        loop {
+            let mut query = vec![("take", PAGE_SIZE.to_string())];
+            if let Some(from) = from.take() {
+                query.push(("from", from));
+            }
+            let mut body = self.get_json(client, token, &path, &query, "users").await?;
+            let page: Vec<Auth0RoleMember> = take_list(&mut body, "users")?;
+            let received = page.len();
+            members.extend(page);
+            from = body.get("next").and_then(|next| next.as_str()).map(str::to_owned);
+            if received == 0 || from.is_none() {
+                return Ok(members);
+            }
+        }
+    }
+
+    /// Every user, sorted by creation date and read in windows that each stay
+    /// within the paging limit: a window starts at the creation date of the
+    /// last user the previous one returned. Users sharing that date come
+    /// back in the next window and are skipped.
+    async fn get_users(&self, client: &reqwest::Client, token: &str) -> Result<Vec<Auth0User>, IdentityProviderError> {
+        let mut users = Vec::new();
+        let mut seen = HashSet::new();
+        let mut from: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("search_engine", "v3".to_string()),
+                ("sort", "created_at:1".to_string()),
+            ];
+            if let Some(from) = &from {
+                query.push(("q", format!("created_at:[\"{}\" TO *]", from)));
+            }
+            let (window, total): (Vec<Auth0User>, usize) =
+                self.get_all_pages(client, token, "/api/v2/users", "users", &query).await?;
+            let last_created_at = window.last().and_then(|user| user.created_at.clone());
+            let known = users.len();
+            for user in window {
+                if seen.insert(user.user_id.clone()) {
+                    users.push(user);
+                }
+            }
+            if total <= MAX_PAGED_RESULTS {
+                return Ok(users);
+            }
+            if users.len() == known || last_created_at.is_none() {
+                return Err(IdentityProviderError {
+                    message: "Failed to fetch users".to_string(),
+                    debug_message: format!(
+                        "more than {} users share the creation date {:?}",
+                        MAX_PAGED_RESULTS, last_created_at
+                    ),
+                });
+            }
+            from = last_created_at;
+        }
+    }
+}
+
+fn take_list<T: DeserializeOwned>(
+    body: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Vec<T>, IdentityProviderError> {
+    Ok(body
+        .remove(key)
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|err| IdentityProviderError {
+            message: format!("Failed to parse {}", key),
+            debug_message: format!("{:?}", err),
+        })?
+        .unwrap_or_default())
+}
+
+impl IIdentityProvider for Auth0ManagementClient {
+    async fn list_users(&self) -> Result<Vec<IdentityProviderUser>, IdentityProviderError> {
+        let client = reqwest::Client::new();
+        let token = self.access_token(&client).await?;
+
+        let users = self.get_users(&client, &token).await?;
+        // tenants stay far below the paging limit in roles
+        let (roles, _): (Vec<Auth0Role>, usize) =
+            self.get_all_pages(&client, &token, "/api/v2/roles", "roles", &[]).await?;
+
+        // one request per role instead of one per user
+        let mut roles_by_user: HashMap<String, Vec<String>> = HashMap::new();
+        for role in roles {
+            let members = self.get_role_members(&client, &token, &role.id).await?;
+            for member in members {
+                roles_by_user.entry(member.user_id).or_default().push(role.name.clone());
+            }
+        }
+
+        Ok(users
+            .into_iter()
+            .map(|user| {
+                let email = user.email.unwrap_or_default();
+                let mut roles = roles_by_user.remove(&user.user_id).unwrap_or_default();
+                roles.sort();
+                IdentityProviderUser {
+                    name: user.name.unwrap_or_else(|| email.clone()),
+                    email,
+                    roles,
+                    user_id: user.user_id,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::{Path, Query, State},
+        http::{HeaderMap, StatusCode as HttpStatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    #[derive(Deserialize)]
+    struct UsersQuery {
+        page: usize,
+        per_page: usize,
+        q: Option<String>,
+        sort: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct CheckpointQuery {
+        take: usize,
+        from: Option<String>,
+    }
+
+    fn created_at(i: usize) -> String {
+        (chrono::DateTime::UNIX_EPOCH + chrono::Duration::seconds(i as i64)).to_rfc3339()
+    }
+
+    fn authorized(headers: &HeaderMap) -> Result<(), HttpStatusCode> {
+        match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+            Some("Bearer mgmt-token") => Ok(()),
+            _ => Err(HttpStatusCode::UNAUTHORIZED),
+        }
+    }
+
+    async fn token(Json(body): Json<Value>) -> Result<Json<Value>, HttpStatusCode> {
+        if body["grant_type"] != "client_credentials" || body["client_secret"] != "secret" {
+            return Err(HttpStatusCode::FORBIDDEN);
+        }
+        Ok(Json(json!({ "access_token": "mgmt-token", "token_type": "Bearer" })))
+    }
+
+    async fn users(
+        State(requests): State<Requests>,
+        headers: HeaderMap,
+        Query(query): Query<UsersQuery>,
+    ) -> Result<Json<Value>, HttpStatusCode> {
+        authorized(&headers)?;
+        if (query.page + 1) * query.per_page > 1000 || query.sort.as_deref() != Some("created_at:1") {
+            return Err(HttpStatusCode::BAD_REQUEST);
+        }
+        // windows after the first start at a creation date, inclusive
+        let from = query
+            .q
+            .as_deref()
+            .and_then(|q| q.strip_prefix("created_at:[\""))
+            .and_then(|q| q.strip_suffix("\" TO *]"))
+            .map(str::to_owned);
+        requests
+            .lock()
+            .unwrap()
+            .push(format!("users page {} from {}", query.page, from.as_deref().unwrap_or("start")));
+        // 1150 users, two of them created at the same time as a window edge
+        let all: Vec<Value> = (0..1150)
+            .map(|i| {
+                json!({
+                    "user_id": format!("auth0|{}", i),
+                    "email": format!("user{}@example.com", i),
+                    "name": format!("User {}", i),
+                    "created_at": created_at(if i == 1000 { 999 } else { i }),
+                })
+            })
+            .chain([json!({ "user_id": "google-oauth2|x", "email": "x@example.com", "created_at": created_at(2000) })])
+            .filter(|user| from.as_deref().is_none_or(|from| user["created_at"].as_str().unwrap() >= from))
+            .collect();
+        let page: Vec<Value> = all.iter().skip(query.page * query.per_page).take(query.per_page).cloned().collect();
+        Ok(Json(json!({ "start": query.page * query.per_page, "limit": query.per_page, "total": all.len(), "users": page })))
+    }
+
+    async fn roles(headers: HeaderMap) -> Result<Json<Value>, HttpStatusCode> {
+        authorized(&headers)?;
+        Ok(Json(json!({
+            "total": 3,
+            "roles": [
+                { "id": "rol_admin", "name": "Admin" },
+                { "id": "rol_viewer", "name": "Viewer" },
+                { "id": "rol_operator", "name": "Operator" }
+            ]
+        })))
+    }
+
+    async fn role_users(
+        headers: HeaderMap,
+        Path(role_id): Path<String>,
+        Query(query): Query<CheckpointQuery>,
+    ) -> Result<Json<Value>, HttpStatusCode> {
+        authorized(&headers)?;
+        let members: Vec<Value> = match role_id.as_str() {
+            "rol_admin" => vec![json!({ "user_id": "auth0|1" })],
+            "rol_viewer" => vec![json!({ "user_id": "auth0|1" }), json!({ "user_id": "auth0|2" })],
+            _ => (200..350).map(|i| json!({ "user_id": format!("auth0|{}", i) })).collect(),
+        };
+        let start = query.from.map(|from| from.parse::<usize>().unwrap()).unwrap_or(0);
+        let end = (start + query.take).min(members.len());
+        let mut body = json!({ "users": members[start..end] });
+        if end < members.len() {
+            body["next"] = json!(end.to_string());
+        }
+        Ok(Json(body))
+    }
+
+    async fn start_mock_server() -> (String, Requests) {
+        let requests: Requests = Arc::default();
+        let app = Router::new()
+            .route("/oauth/token", post(token))
+            .route("/api/v2/users", get(users))
+            .route("/api/v2/roles", get(roles))
+            .route("/api/v2/roles/{id}/users", get(role_users))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", address), requests)
+    }
+
+    fn client(base_url: &str, client_secret: &str) -> Auth0ManagementClient {
+        Auth0ManagementClient::new(Auth0ManagementConfig {
+            base_url: base_url.to_string(),
+            client_id: "client".to_string(),
+            client_secret: client_secret.to_string(),
+            audience: format!("{}/api/v2/", base_url),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_users_pages_and_merges_roles() {
+        let (base_url, requests) = start_mock_server().await;
+
+        let users = client(&base_url, "secret").list_users().await.unwrap();
+
+        assert_eq!(users.len(), 1151);
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 12);
+        assert_eq!(requests[10], format!("users page 0 from {}", created_at(999)));
+        let by_id = |id: &str| users.iter().find(|u| u.user_id == id).unwrap().clone();
+        assert_eq!(by_id("auth0|1").roles, vec!["Admin", "Viewer"]);
+        assert_eq!(by_id("auth0|2").roles, vec!["Viewer"]);
+        assert!(by_id("auth0|3").roles.is_empty());
+        // role members beyond the first checkpoint page
+        assert_eq!(by_id("auth0|349").roles, vec!["Operator"]);
+        assert_eq!(by_id("auth0|1000").email, "user1000@example.com");
+        // users without a name fall back to their email
+        assert_eq!(by_id("google-oauth2|x").name, "x@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_list_users_fails_without_token() {
+        let (base_url, requests) = start_mock_server().await;
+
+        let result = client(&base_url, "wrong").list_users().await;
+
+        assert!(result.is_err());
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -1 +1,40 @@
-pub mod qc_collection;
\ No newline at end of file
+pub mod qc_collection;
+
+use std::str::FromStr;
+
+/// Which store backs the collections. `Memory` keeps every collection in
+/// the process and loses them on restart; it is meant for local development
+/// and tests and needs no DynamoDB table. `Sql` keeps everything in the
+/// SQLite or PostgreSQL database at `DATABASE_URL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    DynamoDb,
+    Memory,
+    Sql,
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dynamodb" => Ok(StorageBackend::DynamoDb),
+            "memory" => Ok(StorageBackend::Memory),
+            "sql" => Ok(StorageBackend::Sql),
+            other => Err(format!("unknown storage backend {:?}, expected dynamodb, memory or sql", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_storage_backend() {
+        assert_eq!("dynamodb".parse(), Ok(StorageBackend::DynamoDb));
+        assert_eq!(" Memory ".parse(), Ok(StorageBackend::Memory));
+        assert_eq!("sql".parse(), Ok(StorageBackend::Sql));
+        assert!("postgres".parse::<StorageBackend>().is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::layers::ewm::main_database::qc_collection::memory::blocking_history_qc_collection::InMemoryBlockingHistoryQCCollection;
+use crate::layers::ewm::main_database::qc_collection::sql::blocking_history_qc_collection::SqlBlockingHistoryQCCollection;
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::{AttributeValue, Put, TransactWriteItem};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    camera_temp_blocking_qc_collection::CameraTempBlocking, error::QCError,
+};
+
+const CAMERA_PARTITION_KEY_PREFIX: &str = "blockingHistory/camera/";
+const USER_PARTITION_KEY_PREFIX: &str = "blockingHistory/user/";
+const SORTABLE_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// A blocking that was deleted or ran out. Each entry is stored twice, under
+/// the camera and under the user, so both lookups are queries. The sort key
+/// starts with the blocking's start in UTC, which makes date ranges a key
+/// condition.
+#[derive(Debug, Clone)]
+pub struct BlockingHistoryEntry {
+    pub camera_id: String,
+    pub user_id: String,
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub archived_at: DateTime<Utc>,
+    /// `deleted` or `expired`
+    pub archive_reason: String,
+    pub archived_by: Option<String>,
+}
+
+impl BlockingHistoryEntry {
+    pub fn archive(
+        blocking: CameraTempBlocking,
+        archived_at: DateTime<Utc>,
+        archive_reason: &str,
+        archived_by: Option<String>,
+    ) -> Self {
+        BlockingHistoryEntry {
+            camera_id: blocking.camera_id,
+            user_id: blocking.user_id,
+            start_date: blocking.start_date,
+            end_date: blocking.end_date,
+            reason: blocking.reason,
+            created_by: blocking.created_by,
+            created_at: blocking.created_at,
+            archived_at,
+            archive_reason: archive_reason.to_string(),
+            archived_by,
+        }
+    }
+
+    /// Identifies the blocking across both copies; archiving the same
+    /// blocking twice overwrites the entry instead of duplicating it.
+    pub fn id(&self) -> String {
+        format!("{}#{}#{}", sortable(self.started_at()), self.user_id, self.camera_id)
+    }
+
+    /// Blockings without a start date count from when they were created.
+    fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.start_date
+            .as_deref()
+            .or(self.created_at.as_deref())
+            .and_then(parse_date)
+    }
+}
+
+pub(super) fn parse_date(input: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(input).ok().map(|d| d.with_timezone(&Utc))
+}
+
+pub(super) fn sortable(date: Option<DateTime<Utc>>) -> String {
+    date.unwrap_or(DateTime::UNIX_EPOCH).format(SORTABLE_FORMAT).to_string()
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for BlockingHistoryEntry {
+    type Error = String;
+
+    fn try_from(value: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let optional = |name: &str| value.get(name).and_then(|v| v.as_s().ok()).cloned();
+        let string = |name: &str| optional(name).ok_or(format!("missing {}", name));
+        Ok(BlockingHistoryEntry {
+            camera_id: string("camera_id")?,
+            user_id: string("user_id")?,
+            start_date: optional("start_date"),
+            end_date: string("end_date")?,
+            reason: optional("reason"),
+            created_by: optional("created_by"),
+            created_at: optional("created_at"),
+            archived_at: parse_date(&string("archived_at")?).ok_or("invalid archived_at".to_string())?,
+            archive_reason: string("archive_reason")?,
+            archived_by: optional("archived_by"),
+        })
+    }
+}
+
+/// Entries overlapping `[from, to)` for a camera, a user or both. At least
+/// one of `camera_id` and `user_id` is required.
+pub struct BlockingHistoryQuery {
+    pub camera_id: Option<String>,
+    pub user_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: i32,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Newest first. A page may hold fewer than `limit` entries even when more
+/// follow; only a missing `next_cursor` marks the end.
+pub struct BlockingHistoryPage {
+    pub entries: Vec<BlockingHistoryEntry>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveBlockingError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub enum QueryBlockingHistoryError {
+    InvalidCursor,
+    Database(QCError),
+}
+
+pub trait IBlockingHistoryQCCollection {
+    fn archive(
+        &self,
+        entry: BlockingHistoryEntry,
+    ) -> impl std::future::Future<Output = Result<(), ArchiveBlockingError>> + Send;
+
+    fn query(
+        &self,
+        query: BlockingHistoryQuery,
+    ) -> impl std::future::Future<Output = Result<BlockingHistoryPage, QueryBlockingHistoryError>> + Send;
+}
+
+pub struct DynamoBlockingHistoryQCCollection {
+    client: aws_sdk_dynamodb::Client,
+    table: String,
+}
+
+impl DynamoBlockingHistoryQCCollection {
+    pub fn new(client: aws_sdk_dynamodb::Client, table: String) -> Self {
+        Self { client, table }
+    }
+}
+
+fn encode_cursor(key: &HashMap<String, AttributeValue>) -> Option<String> {
+    let plain: HashMap<&str, &str> = key
+        .iter()
+        .filter_map(|(name, value)| value.as_s().ok().map(|v| (name.as_str(), v.as_str())))
+        .collect();
+    serde_json::to_vec(&plain).ok().map(|json| URL_SAFE_NO_PAD.encode(json))
+}
+
+fn decode_cursor(cursor: &str) -> Option<HashMap<String, AttributeValue>> {
+    let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let plain: HashMap<String, String> = serde_json::from_slice(&json).ok()?;
+    Some(plain.into_iter().map(|(name, value)| (name, AttributeValue::S(value))).collect())
+}
+
+impl IBlockingHistoryQCCollection for DynamoBlockingHistoryQCCollection {
+    async fn archive(&self, entry: BlockingHistoryEntry) -> Result<(), ArchiveBlockingError> {
+        let sort_key = entry.id();
+        let mut item = HashMap::from([
+            ("sortKey".to_string(), AttributeValue::S(sort_key)),
+            ("camera_id".to_string(), AttributeValue::S(entry.camera_id.clone())),
+            ("user_id".to_string(), AttributeValue::S(entry.user_id.clone())),
+            ("end_date".to_string(), AttributeValue::S(entry.end_date.clone())),
+            ("end_date_utc".to_string(), AttributeValue::S(sortable(parse_date(&entry.end_date)))),
+            ("archived_at".to_string(), AttributeValue::S(entry.archived_at.to_rfc3339())),
+            ("archive_reason".to_string(), AttributeValue::S(entry.archive_reason.clone())),
+        ]);
+        let optional = [
+            ("start_date", entry.start_date),
+            ("reason", entry.reason),
+            ("created_by", entry.created_by),
+            ("created_at", entry.created_at),
+            ("archived_by", entry.archived_by),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                item.insert(name.to_string(), AttributeValue::S(value));
+            }
+        }
+
+        let mut transact_items = Vec::new();
+        for partition_key in [
+            format!("{}{}", CAMERA_PARTITION_KEY_PREFIX, entry.camera_id),
+            format!("{}{}", USER_PARTITION_KEY_PREFIX, entry.user_id),
+        ] {
+            let mut item = item.clone();
+            item.insert("partitionKey".to_string(), AttributeValue::S(partition_key));
+            let put = Put::builder()
+                .table_name(&self.table)
+                .set_item(Some(item))
+                .build()
+                .map_err(|e| ArchiveBlockingError(QCError::new(
+                    "failed to build blocking history item".to_string(),
+                    Some(format!("{:?}", e)),
+                )))?;
+            transact_items.push(TransactWriteItem::builder().put(put).build());
+        }
+
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await
+            .map_err(|e| ArchiveBlockingError(QCError::new(
+                "failed to archive blocking".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+        Ok(())
+    }
+
+    async fn query(&self, query: BlockingHistoryQuery) -> Result<BlockingHistoryPage, QueryBlockingHistoryError> {
+        let (partition_key, user_filter) = match (&query.camera_id, &query.user_id) {
+            (Some(camera_id), user_id) => (format!("{}{}", CAMERA_PARTITION_KEY_PREFIX, camera_id), user_id.clone()),
+            (None, Some(user_id)) => (format!("{}{}", USER_PARTITION_KEY_PREFIX, user_id), None),
+            (None, None) => {
+                return Err(QueryBlockingHistoryError::Database(QCError::new(
+                    "camera_id or user_id is required".to_string(),
+                    None,
+                )))
+            }
+        };
+
+        let mut request = self.client
+            .query()
+            .table_name(&self.table)
+            .scan_index_forward(false)
+            .limit(query.limit)
+            .expression_attribute_values(":pk", AttributeValue::S(partition_key));
+        // the sort key starts with the start date, so "started before `to`"
+        // is a key condition while "ended after `from`" has to be a filter
+        request = match query.to {
+            Some(to) => request
+                .key_condition_expression("partitionKey = :pk AND sortKey < :to")
+                .expression_attribute_values(":to", AttributeValue::S(sortable(Some(to)))),
+            None => request.key_condition_expression("partitionKey = :pk"),
+        };
+        let mut filters = Vec::new();
+        if let Some(from) = query.from {
+            filters.push("end_date_utc > :from");
+            request = request.expression_attribute_values(":from", AttributeValue::S(sortable(Some(from))));
+        }
+        if let Some(user_id) = user_filter {
+            filters.push("user_id = :user_id");
+            request = request.expression_attribute_values(":user_id", AttributeValue::S(user_id));
+        }
+        if !filters.is_empty() {
+            request = request.filter_expression(filters.join(" AND "));
+        }
+        if let Some(cursor) = &query.cursor {
+            let start_key = decode_cursor(cursor).ok_or(QueryBlockingHistoryError::InvalidCursor)?;
+            request = request.set_exclusive_start_key(Some(start_key));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| QueryBlockingHistoryError::Database(QCError::new(
+                "failed to query blocking history".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+
+        let entries = response
+            .items()
+            .iter()
+            .filter_map(|item| {
+                BlockingHistoryEntry::try_from(item)
+                    .inspect_err(|err| tracing::error!("skipping invalid blocking history entry: {}", err))
+                    .ok()
+            })
+            .collect();
+        Ok(BlockingHistoryPage {
+            entries,
+            next_cursor: response.last_evaluated_key().and_then(encode_cursor),
+        })
+    }
+}
+
+/// Where the history entries live.
+pub enum BlockingHistoryQCCollection {
+    Dynamo(DynamoBlockingHistoryQCCollection),
+    Memory(InMemoryBlockingHistoryQCCollection),
+    Sql(SqlBlockingHistoryQCCollection),
+}
+
+impl IBlockingHistoryQCCollection for BlockingHistoryQCCollection {
+    async fn archive(&self, entry: BlockingHistoryEntry) -> Result<(), ArchiveBlockingError> {
+        match self {
+            BlockingHistoryQCCollection::Dynamo(collection) => collection.archive(entry).await,
+            BlockingHistoryQCCollection::Memory(collection) => collection.archive(entry).await,
+            BlockingHistoryQCCollection::Sql(collection) => collection.archive(entry).await,
+        }
+    }
+
+    async fn query(&self, query: BlockingHistoryQuery) -> Result<BlockingHistoryPage, QueryBlockingHistoryError> {
+        match self {
+            BlockingHistoryQCCollection::Dynamo(collection) => collection.query(query).await,
+            BlockingHistoryQCCollection::Memory(collection) => collection.query(query).await,
+            BlockingHistoryQCCollection::Sql(collection) => collection.query(query).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_sorts_by_start_in_utc() {
+        let entry = |start_date: &str| BlockingHistoryEntry {
+            camera_id: "garage".to_string(),
+            user_id: "auth0|1".to_string(),
+            start_date: Some(start_date.to_string()),
+            end_date: "2025-01-20T00:00:00Z".to_string(),
+            reason: None,
+            created_by: None,
+            created_at: None,
+            archived_at: DateTime::UNIX_EPOCH,
+            archive_reason: "deleted".to_string(),
+            archived_by: None,
+        };
+        // 23:30 in Berlin is earlier than 23:00 UTC the same day
+        let berlin = entry("2025-01-14T23:30:00+01:00");
+        let utc = entry("2025-01-14T23:00:00Z");
+        assert_eq!(berlin.id(), "2025-01-14T22:30:00Z#auth0|1#garage");
+        assert!(berlin.id() < utc.id());
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let key = HashMap::from([
+            ("partitionKey".to_string(), AttributeValue::S("blockingHistory/camera/garage".to_string())),
+            ("sortKey".to_string(), AttributeValue::S("2025-01-14T22:30:00Z#auth0|1#garage".to_string())),
+        ]);
+        let cursor = encode_cursor(&key).unwrap();
+        assert_eq!(decode_cursor(&cursor), Some(key));
+        assert_eq!(decode_cursor("not a cursor"), None);
+    }
+}
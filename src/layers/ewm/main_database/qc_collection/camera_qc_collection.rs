@@ -1,7 +1,9 @@
+use crate::layers::ewm::main_database::qc_collection::sql::camera_qc_collection::SqlCameraQCCollection;
+use crate::layers::ewm::main_database::qc_collection::memory::camera_qc_collection::InMemoryCameraQCCollection;
 use std::collections::HashMap;
 
 use super::error::QCError;
-use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
 use chrono::Utc;
 #[derive(Clone)]
 pub struct CameraListQueryResultItem {
@@ -52,6 +54,12 @@ pub struct FindCamerabyIdError(pub QCError);
 #[derive(Debug, Clone)]
 pub struct CheckIfCameraExistsError(pub QCError);
 
+#[derive(Debug, Clone)]
+pub struct UpdateCameraStreamMetadataError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct UpdateCameraCommandError(pub QCError);
+
 pub trait ICameraQCCollection {
     fn list_cameras(
         &self,
@@ -78,25 +86,100 @@ pub trait ICameraQCCollection {
         &self,
         id: &str,
     ) -> impl std::future::Future<Output = Result<bool, CheckIfCameraExistsError>> + Send;
+
+    fn update_camera_stream_metadata(
+        &self,
+        id: &str,
+        stream_metadata: CameraStreamMetadata,
+    ) -> impl std::future::Future<Output = Result<(), UpdateCameraStreamMetadataError>> + Send;
+
+    fn update_camera(
+        &self,
+        command_input: UpdateCameraCommandInput,
+    ) -> impl std::future::Future<Output = Result<FindCameraByIdResult, UpdateCameraCommandError>> + Send;
 }
 
 #[derive(Clone)]
-
-pub struct CameraQCCollection {
+pub struct DynamoCameraQCCollection {
     client: aws_sdk_dynamodb::Client,
     table: String,
 }
 
-impl CameraQCCollection {
+impl DynamoCameraQCCollection {
     pub fn new(client: aws_sdk_dynamodb::Client, table: String) -> Self {
         Self { client, table }
     }
 }
+#[derive(Debug, Clone)]
+pub struct CameraStreamMetadata {
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub framerate: Option<f64>,
+    pub has_audio: bool,
+    pub audio_codec: Option<String>,
+    pub probed_at: chrono::DateTime<Utc>,
+}
+
+impl CameraStreamMetadata {
+    fn to_attribute_value(&self) -> AttributeValue {
+        let optional_s = |v: &Option<String>| match v {
+            Some(s) => AttributeValue::S(s.clone()),
+            None => AttributeValue::Null(true),
+        };
+        let optional_n = |v: Option<String>| match v {
+            Some(n) => AttributeValue::N(n),
+            None => AttributeValue::Null(true),
+        };
+        let mut map = HashMap::new();
+        map.insert("videoCodec".to_string(), optional_s(&self.video_codec));
+        map.insert("width".to_string(), optional_n(self.width.map(|v| v.to_string())));
+        map.insert("height".to_string(), optional_n(self.height.map(|v| v.to_string())));
+        map.insert("framerate".to_string(), optional_n(self.framerate.map(|v| v.to_string())));
+        map.insert("hasAudio".to_string(), AttributeValue::Bool(self.has_audio));
+        map.insert("audioCodec".to_string(), optional_s(&self.audio_codec));
+        map.insert("probedAt".to_string(), AttributeValue::S(self.probed_at.to_rfc3339()));
+        AttributeValue::M(map)
+    }
+
+    fn from_attribute_value(value: &AttributeValue) -> Option<Self> {
+        let map = value.as_m().ok()?;
+        let s = |k: &str| map.get(k).and_then(|v| v.as_s().ok()).cloned();
+        let n = |k: &str| map.get(k).and_then(|v| v.as_n().ok()).cloned();
+        let probed_at = chrono::DateTime::parse_from_rfc3339(&s("probedAt")?)
+            .ok()?
+            .with_timezone(&Utc);
+        Some(CameraStreamMetadata {
+            video_codec: s("videoCodec"),
+            width: n("width").and_then(|v| v.parse().ok()),
+            height: n("height").and_then(|v| v.parse().ok()),
+            framerate: n("framerate").and_then(|v| v.parse().ok()),
+            has_audio: map
+                .get("hasAudio")
+                .and_then(|v| v.as_bool().ok())
+                .copied()
+                .unwrap_or(false),
+            audio_codec: s("audioCodec"),
+            probed_at,
+        })
+    }
+}
+
 pub struct PutCameraCommandInput {
     pub id: Option<String>,
     pub name: String,
     pub source_url: String,
     pub permanent_stream_url: Option<String>,
+    pub stream_metadata: Option<CameraStreamMetadata>,
+}
+
+/// Partial update of a camera item; `None` fields are left untouched.
+pub struct UpdateCameraCommandInput {
+    pub id: String,
+    pub name: Option<String>,
+    pub source_url: Option<String>,
+    pub permanent_stream_url: Option<String>,
+    pub stream_metadata: Option<Option<CameraStreamMetadata>>,
 }
 
 pub struct CreateCameraCommandOutput {
@@ -104,19 +187,79 @@ pub struct CreateCameraCommandOutput {
     pub name: String,
     pub source_url: String,
     pub permanent_stream_url: Option<String>,
+    pub stream_metadata: Option<CameraStreamMetadata>,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
 }
 
+#[derive(Clone)]
 pub struct FindCameraByIdResult {
     pub id: String,
     pub name: String,
     pub source_url: String,
     pub permanent_stream_url: Option<String>,
+    pub stream_metadata: Option<CameraStreamMetadata>,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
 }
-impl ICameraQCCollection for CameraQCCollection {
+
+impl TryFrom<&HashMap<String, AttributeValue>> for FindCameraByIdResult {
+    type Error = String;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let id = item
+            .get("sortKey")
+            .and_then(|v| v.as_s().ok())
+            .ok_or_else(|| "Missing or invalid 'id' field".to_string())?
+            .to_string();
+
+        let name = item
+            .get("name")
+            .and_then(|v| v.as_s().ok())
+            .ok_or_else(|| "Missing or invalid 'name' field".to_string())?
+            .to_string();
+
+        let source_url = item
+            .get("url")
+            .and_then(|v| v.as_s().ok())
+            .ok_or_else(|| "Missing or invalid 'url' field".to_string())?
+            .to_string();
+
+        let permanent_stream_url = item
+            .get("permanentStreamUrl")
+            .and_then(|v| v.as_s().ok())
+            .map(|s| s.to_string());
+
+        let stream_metadata = item
+            .get("streamMetadata")
+            .and_then(CameraStreamMetadata::from_attribute_value);
+
+        let created_at = item
+            .get("createdAt")
+            .and_then(|v| v.as_s().ok())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok_or_else(|| "Missing or invalid 'createdAt' field".to_string())?;
+
+        let updated_at = item
+            .get("updatedAt")
+            .and_then(|v| v.as_s().ok())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok_or_else(|| "Missing or invalid 'updatedAt' field".to_string())?;
+
+        Ok(FindCameraByIdResult {
+            id,
+            name,
+            source_url,
+            permanent_stream_url,
+            stream_metadata,
+            created_at,
+            updated_at,
+        })
+    }
+}
+impl ICameraQCCollection for DynamoCameraQCCollection {
     async fn list_cameras(&self) -> Result<Vec<CameraListQueryResultItem>, ListCamerasQueryError> {
         let results = self
             .client
@@ -168,6 +311,7 @@ impl ICameraQCCollection for CameraQCCollection {
             name: command_input.name,
             source_url: command_input.source_url,
             permanent_stream_url: command_input.permanent_stream_url,
+            stream_metadata: command_input.stream_metadata,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -175,6 +319,10 @@ impl ICameraQCCollection for CameraQCCollection {
             Some(s) => AttributeValue::S(s.clone()),
             None => AttributeValue::Null(true),
         };
+        let stream_metadata = match &result.stream_metadata {
+            Some(m) => m.to_attribute_value(),
+            None => AttributeValue::Null(true),
+        };
         self.client
             .put_item()
             .table_name(&self.table)
@@ -183,6 +331,7 @@ impl ICameraQCCollection for CameraQCCollection {
             .item("name", AttributeValue::S(result.name.clone()))
             .item("url", AttributeValue::S(result.source_url.clone()))
             .item("permanentStreamUrl", permanent_stream_url)
+            .item("streamMetadata", stream_metadata)
             .item(
                 "createdAt",
                 AttributeValue::S(result.created_at.to_rfc3339()),
@@ -241,76 +390,8 @@ impl ICameraQCCollection for CameraQCCollection {
             })?;
 
         if let Some(item) = result.item {
-            let id = item
-                .get("sortKey")
-                .and_then(|v| v.as_s().ok())
-                .ok_or_else(|| {
-                    FindCamerabyIdError(QCError::new(
-                        "Missing or invalid 'id' field".to_string(),
-                        None,
-                    ))
-                })?
-                .to_string();
-
-            let name = item
-                .get("name")
-                .and_then(|v| v.as_s().ok())
-                .ok_or_else(|| {
-                    FindCamerabyIdError(QCError::new(
-                        "Missing or invalid 'name' field".to_string(),
-                        None,
-                    ))
-                })?
-                .to_string();
-
-            let source_url = item
-                .get("url")
-                .and_then(|v| v.as_s().ok())
-                .ok_or_else(|| {
-                    FindCamerabyIdError(QCError::new(
-                        "Missing or invalid 'url' field".to_string(),
-                        None,
-                    ))
-                })?
-                .to_string();
-
-            let permanent_stream_url = item
-                .get("permanentStreamUrl")
-                .and_then(|v| v.as_s().ok())
-                .map(|s| s.to_string());
-
-            let created_at = item
-                .get("createdAt")
-                .and_then(|v| v.as_s().ok())
-                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                .map(|dt| dt.with_timezone(&Utc))
-                .ok_or_else(|| {
-                    FindCamerabyIdError(QCError::new(
-                        "Missing or invalid 'createdAt' field".to_string(),
-                        None,
-                    ))
-                })?;
-
-            let updated_at = item
-                .get("updatedAt")
-                .and_then(|v| v.as_s().ok())
-                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                .map(|dt| dt.with_timezone(&Utc))
-                .ok_or_else(|| {
-                    FindCamerabyIdError(QCError::new(
-                        "Missing or invalid 'updatedAt' field".to_string(),
-                        None,
-                    ))
-                })?;
-
-            Ok(FindCameraByIdResult {
-                id,
-                name,
-                source_url,
-                permanent_stream_url,
-                created_at,
-                updated_at,
-            })
+            FindCameraByIdResult::try_from(&item)
+                .map_err(|err| FindCamerabyIdError(QCError::new(err, None)))
         } else {
             Err(FindCamerabyIdError(QCError::new(
                 "Camera not found".to_string(),
@@ -342,4 +423,161 @@ impl ICameraQCCollection for CameraQCCollection {
 
         Ok(result.item.is_some())
     }
+
+    async fn update_camera_stream_metadata(
+        &self,
+        id: &str,
+        stream_metadata: CameraStreamMetadata,
+    ) -> Result<(), UpdateCameraStreamMetadataError> {
+        self.client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("camera".to_string()))
+            .key("sortKey", AttributeValue::S(id.to_string()))
+            .update_expression("SET streamMetadata = :streamMetadata")
+            .condition_expression("attribute_exists(sortKey)")
+            .expression_attribute_values(":streamMetadata", stream_metadata.to_attribute_value())
+            .send()
+            .await
+            .map_err(|err| {
+                UpdateCameraStreamMetadataError(QCError::new(
+                    "failed to update camera stream metadata in database".to_string(),
+                    Some(format!("{:?}", err)),
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    async fn update_camera(
+        &self,
+        command_input: UpdateCameraCommandInput,
+    ) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+        let mut request = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("camera".to_string()))
+            .key("sortKey", AttributeValue::S(command_input.id.clone()))
+            .condition_expression("attribute_exists(sortKey)")
+            .return_values(ReturnValue::AllNew);
+
+        let mut assignments = vec!["#updatedAt = :updatedAt".to_string()];
+        request = request
+            .expression_attribute_names("#updatedAt", "updatedAt")
+            .expression_attribute_values(":updatedAt", AttributeValue::S(Utc::now().to_rfc3339()));
+
+        if let Some(name) = command_input.name {
+            assignments.push("#name = :name".to_string());
+            request = request
+                .expression_attribute_names("#name", "name")
+                .expression_attribute_values(":name", AttributeValue::S(name));
+        }
+        if let Some(source_url) = command_input.source_url {
+            assignments.push("#url = :url".to_string());
+            request = request
+                .expression_attribute_names("#url", "url")
+                .expression_attribute_values(":url", AttributeValue::S(source_url));
+        }
+        if let Some(permanent_stream_url) = command_input.permanent_stream_url {
+            assignments.push("#permanentStreamUrl = :permanentStreamUrl".to_string());
+            request = request
+                .expression_attribute_names("#permanentStreamUrl", "permanentStreamUrl")
+                .expression_attribute_values(
+                    ":permanentStreamUrl",
+                    AttributeValue::S(permanent_stream_url),
+                );
+        }
+        if let Some(stream_metadata) = command_input.stream_metadata {
+            let value = match stream_metadata {
+                Some(m) => m.to_attribute_value(),
+                None => AttributeValue::Null(true),
+            };
+            assignments.push("#streamMetadata = :streamMetadata".to_string());
+            request = request
+                .expression_attribute_names("#streamMetadata", "streamMetadata")
+                .expression_attribute_values(":streamMetadata", value);
+        }
+
+        let result = request
+            .update_expression(format!("SET {}", assignments.join(", ")))
+            .send()
+            .await
+            .map_err(|err| {
+                UpdateCameraCommandError(QCError::new(
+                    "failed to update camera in database".to_string(),
+                    Some(format!("{:?}", err)),
+                ))
+            })?;
+
+        let attributes = result.attributes.unwrap_or_default();
+        FindCameraByIdResult::try_from(&attributes)
+            .map_err(|err| UpdateCameraCommandError(QCError::new(err, None)))
+    }
+}
+
+/// Where the cameras live, picked once at startup from `STORAGE_BACKEND`.
+#[derive(Clone)]
+pub enum CameraQCCollection {
+    Dynamo(DynamoCameraQCCollection),
+    Memory(InMemoryCameraQCCollection),
+    Sql(SqlCameraQCCollection),
+}
+
+impl ICameraQCCollection for CameraQCCollection {
+    async fn list_cameras(&self) -> Result<Vec<CameraListQueryResultItem>, ListCamerasQueryError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.list_cameras().await,
+            CameraQCCollection::Memory(collection) => collection.list_cameras().await,
+            CameraQCCollection::Sql(collection) => collection.list_cameras().await,
+        }
+    }
+
+    async fn put_camera(&self, command_input: PutCameraCommandInput) -> Result<CreateCameraCommandOutput, CreateCameraCommandError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.put_camera(command_input).await,
+            CameraQCCollection::Memory(collection) => collection.put_camera(command_input).await,
+            CameraQCCollection::Sql(collection) => collection.put_camera(command_input).await,
+        }
+    }
+
+    async fn delete_camera_by_id(&self, id: &str) -> Result<(), DeleteCameraCommandError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.delete_camera_by_id(id).await,
+            CameraQCCollection::Memory(collection) => collection.delete_camera_by_id(id).await,
+            CameraQCCollection::Sql(collection) => collection.delete_camera_by_id(id).await,
+        }
+    }
+
+    async fn find_camera_by_id(&self, id: &str) -> Result<FindCameraByIdResult, FindCamerabyIdError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.find_camera_by_id(id).await,
+            CameraQCCollection::Memory(collection) => collection.find_camera_by_id(id).await,
+            CameraQCCollection::Sql(collection) => collection.find_camera_by_id(id).await,
+        }
+    }
+
+    async fn camera_exists_by_id(&self, id: &str) -> Result<bool, CheckIfCameraExistsError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.camera_exists_by_id(id).await,
+            CameraQCCollection::Memory(collection) => collection.camera_exists_by_id(id).await,
+            CameraQCCollection::Sql(collection) => collection.camera_exists_by_id(id).await,
+        }
+    }
+
+    async fn update_camera_stream_metadata(&self, id: &str, stream_metadata: CameraStreamMetadata) -> Result<(), UpdateCameraStreamMetadataError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.update_camera_stream_metadata(id, stream_metadata).await,
+            CameraQCCollection::Memory(collection) => collection.update_camera_stream_metadata(id, stream_metadata).await,
+            CameraQCCollection::Sql(collection) => collection.update_camera_stream_metadata(id, stream_metadata).await,
+        }
+    }
+
+    async fn update_camera(&self, command_input: UpdateCameraCommandInput) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+        match self {
+            CameraQCCollection::Dynamo(collection) => collection.update_camera(command_input).await,
+            CameraQCCollection::Memory(collection) => collection.update_camera(command_input).await,
+            CameraQCCollection::Sql(collection) => collection.update_camera(command_input).await,
+        }
+    }
 }
@@ -1,19 +1,187 @@
-use aws_sdk_dynamodb::types::AttributeValue;
+use crate::layers::ewm::main_database::qc_collection::sql::camera_temp_blocking_qc_collection::SqlCameraTempBlockingQCCollection;
+use crate::layers::ewm::main_database::qc_collection::memory::camera_temp_blocking_qc_collection::InMemoryCameraTempBlockingQCCollection;
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use aws_sdk_dynamodb::{
+    error::SdkError,
+    operation::{
+        delete_item::DeleteItemError, transact_write_items::TransactWriteItemsError,
+        update_item::UpdateItemError,
+    },
+    types::{AttributeValue, Put, ReturnValue, TransactWriteItem},
+};
 
 use crate::layers::ewm::main_database::qc_collection::error::QCError;
 
+/// Blockings created before the audit fields existed have no reason,
+/// creator or timestamps.
+#[derive(Debug, Clone)]
 pub struct CameraTempBlocking {
     pub id: String,
     pub camera_id: String,
     pub user_id: String,
-    pub end_date: String
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    /// Whether the blocked user may see `reason`
+    pub share_reason: bool,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for CameraTempBlocking {
+    type Error = String;
+
+    fn try_from(value: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| optional_string(value, name).ok_or(format!("missing {}", name));
+        let camera_id = string("sortKey")?;
+        Ok(CameraTempBlocking {
+            id: camera_id.clone(),
+            camera_id,
+            user_id: string("user_id")?,
+            start_date: optional_string(value, "start_date"),
+            end_date: string("end_date")?,
+            reason: optional_string(value, "reason"),
+            share_reason: value
+                .get("share_reason")
+                .and_then(|v| v.as_bool().ok())
+                .copied()
+                .unwrap_or(false),
+            created_by: optional_string(value, "created_by"),
+            created_at: optional_string(value, "created_at"),
+            updated_at: optional_string(value, "updated_at"),
+        })
+    }
+}
+
+fn parse_blockings(items: &[HashMap<String, AttributeValue>]) -> Vec<CameraTempBlocking> {
+    items
+        .iter()
+        .filter_map(|item| CameraTempBlocking::try_from(item).ok())
+        .collect()
 }
 
+/// DynamoDB's limit on items per transaction.
+pub const MAX_USERS_PER_TEMP_BLOCKING: usize = 100;
+
 pub struct CreateCameraTempBlockingInput {
     pub camera_id: String,
     pub start_time: String,
     pub end_time: String,
     pub user_ids: Vec<String>,
+    pub reason: Option<String>,
+    pub share_reason: bool,
+    pub created_by: String,
+}
+
+const PARTITION_KEY_PREFIX: &str = "cameraTempBlocking/";
+const RECURRING_PARTITION_KEY_PREFIX: &str = "cameraRecurringBlocking/";
+
+/// Global secondary index that lists blockings by camera. The table needs it
+/// with `cameraPartitionKey` (S) as partition key, `cameraSortKey` (S) as sort
+/// key and all attributes projected. Items written before the index existed
+/// are filled in by `backfill_camera_index`.
+pub const CAMERA_INDEX_NAME: &str = "byCamera";
+const CAMERA_INDEX_PARTITION_KEY: &str = "cameraPartitionKey";
+const CAMERA_INDEX_SORT_KEY: &str = "cameraSortKey";
+
+fn camera_index_partition_key(partition_key_prefix: &str, camera_id: &str) -> String {
+    format!("{}camera/{}", partition_key_prefix, camera_id)
+}
+
+/// A blocking that repeats on a schedule. `rrule` is an RRULE subset,
+/// `start_time` and `end_time` are local wall-clock times like `22:00` in
+/// `timezone`. Stored per user, like one-off blockings.
+#[derive(Debug, Clone)]
+pub struct RecurringCameraBlocking {
+    pub id: String,
+    pub camera_id: String,
+    pub user_id: String,
+    pub rrule: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub timezone: String,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for RecurringCameraBlocking {
+    type Error = String;
+
+    fn try_from(value: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| optional_string(value, name).ok_or(format!("missing {}", name));
+        Ok(RecurringCameraBlocking {
+            id: string("sortKey")?,
+            camera_id: string("camera_id")?,
+            user_id: string("user_id")?,
+            rrule: string("rrule")?,
+            start_time: string("start_time")?,
+            end_time: string("end_time")?,
+            timezone: string("timezone")?,
+        })
+    }
+}
+
+fn parse_recurring_blockings(items: &[HashMap<String, AttributeValue>]) -> Vec<RecurringCameraBlocking> {
+    items
+        .iter()
+        .filter_map(|item| {
+            RecurringCameraBlocking::try_from(item)
+                .inspect_err(|err| tracing::error!("skipping invalid recurring blocking: {}", err))
+                .ok()
+        })
+        .collect()
+}
+
+const PRIVACY_SHUTTER_PARTITION_KEY: &str = "cameraPrivacyShutter";
+
+/// Blocks a camera for every non-admin user, including users who sign up
+/// while it is closed. A camera has at most one shutter; setting a new one
+/// replaces it.
+#[derive(Debug, Clone)]
+pub struct CameraPrivacyShutter {
+    pub camera_id: String,
+    /// `None` closes the shutter right away
+    pub start_date: Option<String>,
+    pub end_date: String,
+    pub reason: Option<String>,
+    /// Whether the blocked users may see `reason`
+    pub share_reason: bool,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for CameraPrivacyShutter {
+    type Error = String;
+
+    fn try_from(value: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| optional_string(value, name).ok_or(format!("missing {}", name));
+        Ok(CameraPrivacyShutter {
+            camera_id: string("sortKey")?,
+            start_date: optional_string(value, "start_date"),
+            end_date: string("end_date")?,
+            reason: optional_string(value, "reason"),
+            share_reason: value
+                .get("share_reason")
+                .and_then(|v| v.as_bool().ok())
+                .copied()
+                .unwrap_or(false),
+            created_by: optional_string(value, "created_by"),
+            created_at: optional_string(value, "created_at"),
+        })
+    }
+}
+
+/// Fields left as `None` keep their stored value. The update only applies
+/// while the blocking still has the `expected_*` dates it was read with.
+pub struct UpdateCameraTempBlockingInput {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub reason: Option<String>,
+    pub share_reason: Option<bool>,
+    pub expected_start_date: Option<String>,
+    pub expected_end_date: String,
 }
 
 pub trait ICameraTempBlockingQCCollection {
@@ -21,28 +189,153 @@ pub trait ICameraTempBlockingQCCollection {
     
     fn list_temp_blocking_for_camera(&self, camera_id: &str) -> impl std::future::Future<Output = Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError>> + Send;
     
+    /// Writes the blockings of all users in one transaction: either every
+    /// user is blocked or, if any of them already is, nobody. At most
+    /// `MAX_USERS_PER_TEMP_BLOCKING` users fit in one transaction.
     fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> impl std::future::Future<Output = Result<(), CreateCameraTempBlockingError>> + Send;
 
     fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> impl std::future::Future<Output = Result<(), DeleteCameraTempBlockingError>> + Send;
 
+    /// Every one-off blocking of every user; meant for background jobs.
+    fn list_all_temp_blockings(&self) -> impl std::future::Future<Output = Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError>> + Send;
+
+    /// Deletes the blocking only if it still ends at `end_date`, so a
+    /// blocking extended in the meantime survives. Returns whether it was
+    /// deleted.
+    fn delete_temp_blocking_ending_at(&self, camera_id: &str, user_id: &str, end_date: &str) -> impl std::future::Future<Output = Result<bool, DeleteCameraTempBlockingError>> + Send;
+
     fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> impl std::future::Future<Output = Result<Option<CameraTempBlocking>, GetCameraTempBlockingError>> + Send;
+
+    /// Changes the dates of an existing blocking in a single write, so the
+    /// user is never unblocked in between. Returns `None` when there is no
+    /// blocking with the expected dates to update.
+    fn update_temp_blocking(&self, camera_id: &str, user_id: &str, input: UpdateCameraTempBlockingInput) -> impl std::future::Future<Output = Result<Option<CameraTempBlocking>, UpdateCameraTempBlockingError>> + Send;
+
+    /// Shutters of every camera, including ones that ended or have not
+    /// started yet.
+    fn list_privacy_shutters(&self) -> impl std::future::Future<Output = Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError>> + Send;
+
+    fn get_privacy_shutter(&self, camera_id: &str) -> impl std::future::Future<Output = Result<Option<CameraPrivacyShutter>, GetCameraTempBlockingError>> + Send;
+
+    fn put_privacy_shutter(&self, shutter: CameraPrivacyShutter) -> impl std::future::Future<Output = Result<(), PutCameraPrivacyShutterError>> + Send;
+
+    fn delete_privacy_shutter(&self, camera_id: &str) -> impl std::future::Future<Output = Result<(), DeleteCameraTempBlockingError>> + Send;
+
+    fn list_recurring_blockings_for_user(&self, user_id: &str) -> impl std::future::Future<Output = Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError>> + Send;
+
+    fn list_recurring_blockings_for_camera(&self, camera_id: &str) -> impl std::future::Future<Output = Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError>> + Send;
+
+    fn create_recurring_blocking(&self, blocking: RecurringCameraBlocking) -> impl std::future::Future<Output = Result<(), CreateRecurringCameraBlockingError>> + Send;
+
+    fn delete_recurring_blocking(&self, user_id: &str, id: &str) -> impl std::future::Future<Output = Result<(), DeleteCameraTempBlockingError>> + Send;
 }
 
-pub struct CameraTempBlockingQCCollection {
+pub struct DynamoCameraTempBlockingQCCollection {
     client: aws_sdk_dynamodb::Client,
     table: String,
 }
 
-impl CameraTempBlockingQCCollection {
+impl DynamoCameraTempBlockingQCCollection {
     pub fn new(client: aws_sdk_dynamodb::Client, table: String) -> Self {
         Self { client, table }
     }
+
+    async fn query_camera_index(
+        &self,
+        partition_key_prefix: &str,
+        camera_id: &str,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, QCError> {
+        self.client
+            .query()
+            .table_name(&self.table)
+            .index_name(CAMERA_INDEX_NAME)
+            .key_condition_expression("#pk = :pk")
+            .expression_attribute_names("#pk", CAMERA_INDEX_PARTITION_KEY)
+            .expression_attribute_values(
+                ":pk",
+                AttributeValue::S(camera_index_partition_key(partition_key_prefix, camera_id)),
+            )
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| QCError::new(e.to_string(), Some(format!("{:?}", e))))
+    }
+
+    /// Adds the camera index keys to blockings written before the index
+    /// existed. Only items without the keys are touched, so running it again
+    /// is cheap and safe. Returns the number of updated items.
+    pub async fn backfill_camera_index(&self) -> Result<usize, BackfillCameraIndexError> {
+        let to_error = |message: &str, e: String| {
+            BackfillCameraIndexError(QCError::new(message.to_string(), Some(e)))
+        };
+        let items: Vec<HashMap<String, AttributeValue>> = self.client
+            .scan()
+            .table_name(&self.table)
+            .filter_expression(
+                "(begins_with(partitionKey, :temp_prefix) OR begins_with(partitionKey, :recurring_prefix)) AND attribute_not_exists(#pk)",
+            )
+            .expression_attribute_names("#pk", CAMERA_INDEX_PARTITION_KEY)
+            .expression_attribute_values(":temp_prefix", AttributeValue::S(PARTITION_KEY_PREFIX.to_string()))
+            .expression_attribute_values(":recurring_prefix", AttributeValue::S(RECURRING_PARTITION_KEY_PREFIX.to_string()))
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| to_error("failed to scan blockings to backfill", format!("{:?}", e)))?;
+
+        let mut updated = 0;
+        for item in items {
+            let (Some(partition_key), Some(sort_key)) =
+                (optional_string(&item, "partitionKey"), optional_string(&item, "sortKey"))
+            else {
+                continue;
+            };
+            let (index_pk, index_sk) = if partition_key.starts_with(RECURRING_PARTITION_KEY_PREFIX) {
+                let Some(camera_id) = optional_string(&item, "camera_id") else {
+                    continue;
+                };
+                (camera_index_partition_key(RECURRING_PARTITION_KEY_PREFIX, &camera_id), sort_key.clone())
+            } else {
+                let user_id = partition_key.trim_start_matches(PARTITION_KEY_PREFIX).to_string();
+                (camera_index_partition_key(PARTITION_KEY_PREFIX, &sort_key), user_id)
+            };
+            self.client
+                .update_item()
+                .table_name(&self.table)
+                .key("partitionKey", AttributeValue::S(partition_key))
+                .key("sortKey", AttributeValue::S(sort_key))
+                .condition_expression("attribute_exists(sortKey)")
+                .update_expression("SET #pk = :pk, #sk = :sk")
+                .expression_attribute_names("#pk", CAMERA_INDEX_PARTITION_KEY)
+                .expression_attribute_names("#sk", CAMERA_INDEX_SORT_KEY)
+                .expression_attribute_values(":pk", AttributeValue::S(index_pk))
+                .expression_attribute_values(":sk", AttributeValue::S(index_sk))
+                .send()
+                .await
+                .map_err(|e| to_error("failed to backfill blocking", format!("{:?}", e)))?;
+            updated += 1;
+        }
+        Ok(updated)
+    }
 }
 #[derive(Debug, Clone)]
 pub struct ListCameraTempBlockingsQueryError(pub QCError);
 
 #[derive(Debug, Clone)]
-pub struct CreateCameraTempBlockingError(pub QCError);
+pub enum CreateCameraTempBlockingError {
+    /// Users that already have a blocking for the camera; nothing was written
+    AlreadyBlocked(Vec<String>),
+    Database(QCError),
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateRecurringCameraBlockingError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct PutCameraPrivacyShutterError(pub QCError);
 
 #[derive(Debug, Clone)]
 pub struct DeleteCameraTempBlockingError(pub QCError);
@@ -50,131 +343,525 @@ pub struct DeleteCameraTempBlockingError(pub QCError);
 #[derive(Debug, Clone)]
 pub struct GetCameraTempBlockingError(pub QCError);
 
+#[derive(Debug, Clone)]
+pub struct UpdateCameraTempBlockingError(pub QCError);
 
-impl ICameraTempBlockingQCCollection for CameraTempBlockingQCCollection {
+#[derive(Debug, Clone)]
+pub struct BackfillCameraIndexError(pub QCError);
+
+fn optional_string(item: &HashMap<String, AttributeValue>, name: &str) -> Option<String> {
+    item.get(name).and_then(|v| v.as_s().ok()).cloned()
+}
+
+
+impl ICameraTempBlockingQCCollection for DynamoCameraTempBlockingQCCollection {
     async fn list_temp_blocking_for_user(&self, user_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
         let response = self.client
             .query()
             .table_name(&self.table)
             .key_condition_expression("partitionKey = :pk")
-            .expression_attribute_values(":pk", AttributeValue::S(format!("cameraTempBlocking/{}", user_id)))
+            .expression_attribute_values(":pk", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
             .send()
             .await
             .map_err(|e| ListCameraTempBlockingsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
 
-        let mut results = Vec::new();
-        if let Some(items) = response.items {
-            for item in items {
-                if let (Some(sort_key), Some(end_date), Some(user_id)) = (
-                    item.get("sortKey").and_then(|v| v.as_s().ok()),
-                    item.get("end_date").and_then(|v| v.as_s().ok()),
-                    item.get("user_id").and_then(|v| v.as_s().ok())
-                ) {
-                    results.push(CameraTempBlocking {
-                        id: sort_key.clone(),
-                        camera_id: sort_key.clone(),
-                        user_id: user_id.clone(),
-                        end_date: end_date.clone(),
-                    });
-                }
+        Ok(parse_blockings(response.items()))
+    }
+
+    async fn list_temp_blocking_for_camera(&self, camera_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        let items = self
+            .query_camera_index(PARTITION_KEY_PREFIX, camera_id)
+            .await
+            .map_err(ListCameraTempBlockingsQueryError)?;
+        Ok(parse_blockings(&items))
+    }
+
+    async fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> Result<(), CreateCameraTempBlockingError> {
+        let database_error = |e: String| CreateCameraTempBlockingError::Database(QCError::new(
+            "failed to create camera temp blocking".to_string(),
+            Some(e),
+        ));
+        let now = Utc::now().to_rfc3339();
+        let mut transact_items = Vec::with_capacity(input.user_ids.len());
+        for user_id in &input.user_ids {
+            let mut item = HashMap::from([
+                ("partitionKey".to_string(), AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id))),
+                ("sortKey".to_string(), AttributeValue::S(input.camera_id.clone())),
+                ("start_date".to_string(), AttributeValue::S(input.start_time.clone())),
+                ("end_date".to_string(), AttributeValue::S(input.end_time.clone())),
+                ("camera_id".to_string(), AttributeValue::S(input.camera_id.clone())),
+                ("user_id".to_string(), AttributeValue::S(user_id.clone())),
+                (CAMERA_INDEX_PARTITION_KEY.to_string(), AttributeValue::S(camera_index_partition_key(PARTITION_KEY_PREFIX, &input.camera_id))),
+                (CAMERA_INDEX_SORT_KEY.to_string(), AttributeValue::S(user_id.clone())),
+                ("share_reason".to_string(), AttributeValue::Bool(input.share_reason)),
+                ("created_by".to_string(), AttributeValue::S(input.created_by.clone())),
+                ("created_at".to_string(), AttributeValue::S(now.clone())),
+                ("updated_at".to_string(), AttributeValue::S(now.clone())),
+            ]);
+            if let Some(reason) = &input.reason {
+                item.insert("reason".to_string(), AttributeValue::S(reason.clone()));
             }
+            let put = Put::builder()
+                .table_name(&self.table)
+                .set_item(Some(item))
+                .condition_expression("attribute_not_exists(sortKey)")
+                .build()
+                .map_err(|e| database_error(format!("{:?}", e)))?;
+            transact_items.push(TransactWriteItem::builder().put(put).build());
+        }
+
+        let result = self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(SdkError::ServiceError(err)) => match err.err() {
+                // reasons come back in request order, one per item
+                TransactWriteItemsError::TransactionCanceledException(canceled) => {
+                    let conflicts: Vec<String> = canceled
+                        .cancellation_reasons()
+                        .iter()
+                        .zip(&input.user_ids)
+                        .filter(|(reason, _)| reason.code() == Some("ConditionalCheckFailed"))
+                        .map(|(_, user_id)| user_id.clone())
+                        .collect();
+                    if conflicts.is_empty() {
+                        Err(database_error(format!("{:?}", canceled)))
+                    } else {
+                        Err(CreateCameraTempBlockingError::AlreadyBlocked(conflicts))
+                    }
+                }
+                other => Err(database_error(format!("{:?}", other))),
+            },
+            Err(e) => Err(database_error(format!("{:?}", e))),
         }
-        Ok(results)
     }
 
-    async fn list_temp_blocking_for_camera(&self, camera_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
-        let response = self.client
+    async fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        self.client
+            .delete_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
+            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .send()
+            .await
+            .map_err(|e| DeleteCameraTempBlockingError(QCError::new(
+                "failed to delete camera temp blocking".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+        Ok(())
+    }
+
+    async fn list_all_temp_blockings(&self) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        let items: Vec<HashMap<String, AttributeValue>> = self.client
             .scan()
             .table_name(&self.table)
-            .filter_expression("begins_with(partitionKey, :pk_prefix) AND sortKey = :camera_id")
-            .expression_attribute_values(":pk_prefix", AttributeValue::S("cameraTempBlocking/".to_string()))
-            .expression_attribute_values(":camera_id", AttributeValue::S(camera_id.to_string()))
+            .filter_expression("begins_with(partitionKey, :prefix)")
+            .expression_attribute_values(":prefix", AttributeValue::S(PARTITION_KEY_PREFIX.to_string()))
+            .into_paginator()
+            .items()
             .send()
+            .try_collect()
             .await
             .map_err(|e| ListCameraTempBlockingsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
 
-        let mut results = Vec::new();
-        if let Some(items) = response.items {
-            for item in items {
-                if let (Some(sort_key), Some(end_date), Some(user_id)) = (
-                    item.get("sortKey").and_then(|v| v.as_s().ok()),
-                    item.get("end_date").and_then(|v| v.as_s().ok()),
-                    item.get("user_id").and_then(|v| v.as_s().ok())
-                ) {
-                    results.push(CameraTempBlocking {
-                        id: sort_key.clone(),
-                        camera_id: camera_id.to_string(),
-                        user_id: user_id.clone(),
-                        end_date: end_date.clone(),
-                    });
-                }
+        Ok(parse_blockings(&items))
+    }
+
+    async fn delete_temp_blocking_ending_at(&self, camera_id: &str, user_id: &str, end_date: &str) -> Result<bool, DeleteCameraTempBlockingError> {
+        let result = self.client
+            .delete_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
+            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .condition_expression("end_date = :end_date")
+            .expression_attribute_values(":end_date", AttributeValue::S(end_date.to_string()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(err))
+                if matches!(err.err(), DeleteItemError::ConditionalCheckFailedException(_)) =>
+            {
+                Ok(false)
             }
+            Err(e) => Err(DeleteCameraTempBlockingError(QCError::new(
+                "failed to delete expired camera temp blocking".to_string(),
+                Some(format!("{:?}", e)),
+            ))),
         }
-        Ok(results)
     }
 
-    async fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> Result<(), CreateCameraTempBlockingError> {
-        for user_id in &input.user_ids {
-            self.client
-                .put_item()
-                .table_name(&self.table)
-                .item("partitionKey", AttributeValue::S(format!("cameraTempBlocking/{}", user_id)))
-                .item("sortKey", AttributeValue::S(input.camera_id.clone()))
-                .item("start_date", AttributeValue::S(input.start_time.clone()))
-                .item("end_date", AttributeValue::S(input.end_time.clone()))
-                .item("camera_id", AttributeValue::S(input.camera_id.clone()))
-                .item("user_id", AttributeValue::S(user_id.clone()))
-                .send()
-                .await
-                .map_err(|e| CreateCameraTempBlockingError(QCError::new(
-                    "failed to create camera temp blocking".to_string(),
-                    Some(format!("{:?}", e)),
-                )))?;
+    async fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<Option<CameraTempBlocking>, GetCameraTempBlockingError> {
+        let result = self.client
+            .get_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
+            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .send()
+            .await
+            .map_err(|e| GetCameraTempBlockingError(QCError::new(
+                "failed to get camera temp blocking".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+
+        Ok(result.item.as_ref().and_then(|item| CameraTempBlocking::try_from(item).ok()))
+    }
+
+    async fn update_temp_blocking(&self, camera_id: &str, user_id: &str, input: UpdateCameraTempBlockingInput) -> Result<Option<CameraTempBlocking>, UpdateCameraTempBlockingError> {
+        let mut request = self.client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
+            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .return_values(ReturnValue::AllNew);
+
+        // blockings without a start date are stored without the attribute
+        request = match input.expected_start_date {
+            Some(expected_start_date) => request
+                .condition_expression("end_date = :expected_end_date AND start_date = :expected_start_date")
+                .expression_attribute_values(":expected_start_date", AttributeValue::S(expected_start_date)),
+            None => request.condition_expression("end_date = :expected_end_date AND attribute_not_exists(start_date)"),
+        };
+        request = request.expression_attribute_values(":expected_end_date", AttributeValue::S(input.expected_end_date));
+
+        let mut assignments = vec!["updated_at = :updated_at"];
+        request = request.expression_attribute_values(":updated_at", AttributeValue::S(Utc::now().to_rfc3339()));
+        if let Some(start_time) = input.start_time {
+            assignments.push("start_date = :start_date");
+            request = request.expression_attribute_values(":start_date", AttributeValue::S(start_time));
+        }
+        if let Some(end_time) = input.end_time {
+            assignments.push("end_date = :end_date");
+            request = request.expression_attribute_values(":end_date", AttributeValue::S(end_time));
+        }
+        if let Some(reason) = input.reason {
+            assignments.push("reason = :reason");
+            request = request.expression_attribute_values(":reason", AttributeValue::S(reason));
         }
+        if let Some(share_reason) = input.share_reason {
+            assignments.push("share_reason = :share_reason");
+            request = request.expression_attribute_values(":share_reason", AttributeValue::Bool(share_reason));
+        }
+
+        let result = request
+            .update_expression(format!("SET {}", assignments.join(", ")))
+            .send()
+            .await;
+        let attributes = match result {
+            Ok(output) => output.attributes.unwrap_or_default(),
+            Err(SdkError::ServiceError(err))
+                if matches!(err.err(), UpdateItemError::ConditionalCheckFailedException(_)) =>
+            {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(UpdateCameraTempBlockingError(QCError::new(
+                    "failed to update camera temp blocking".to_string(),
+                    Some(format!("{:?}", e)),
+                )));
+            }
+        };
+
+        CameraTempBlocking::try_from(&attributes)
+            .map(Some)
+            .map_err(|e| UpdateCameraTempBlockingError(QCError::new(
+                "updated camera temp blocking is invalid".to_string(),
+                Some(e),
+            )))
+    }
+
+    async fn list_recurring_blockings_for_user(&self, user_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        let items = self.client
+            .query()
+            .table_name(&self.table)
+            .key_condition_expression("partitionKey = :pk")
+            .expression_attribute_values(":pk", AttributeValue::S(format!("{}{}", RECURRING_PARTITION_KEY_PREFIX, user_id)))
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| ListCameraTempBlockingsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
+        Ok(parse_recurring_blockings(&items))
+    }
+
+    async fn list_recurring_blockings_for_camera(&self, camera_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        let items = self
+            .query_camera_index(RECURRING_PARTITION_KEY_PREFIX, camera_id)
+            .await
+            .map_err(ListCameraTempBlockingsQueryError)?;
+        Ok(parse_recurring_blockings(&items))
+    }
+
+    async fn create_recurring_blocking(&self, blocking: RecurringCameraBlocking) -> Result<(), CreateRecurringCameraBlockingError> {
+        self.client
+            .put_item()
+            .table_name(&self.table)
+            .item("partitionKey", AttributeValue::S(format!("{}{}", RECURRING_PARTITION_KEY_PREFIX, blocking.user_id)))
+            .item(CAMERA_INDEX_PARTITION_KEY, AttributeValue::S(camera_index_partition_key(RECURRING_PARTITION_KEY_PREFIX, &blocking.camera_id)))
+            .item(CAMERA_INDEX_SORT_KEY, AttributeValue::S(blocking.id.clone()))
+            .item("sortKey", AttributeValue::S(blocking.id))
+            .item("camera_id", AttributeValue::S(blocking.camera_id))
+            .item("user_id", AttributeValue::S(blocking.user_id))
+            .item("rrule", AttributeValue::S(blocking.rrule))
+            .item("start_time", AttributeValue::S(blocking.start_time))
+            .item("end_time", AttributeValue::S(blocking.end_time))
+            .item("timezone", AttributeValue::S(blocking.timezone))
+            .send()
+            .await
+            .map_err(|e| CreateRecurringCameraBlockingError(QCError::new(
+                "failed to create recurring camera blocking".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
         Ok(())
     }
 
-    async fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+    async fn delete_recurring_blocking(&self, user_id: &str, id: &str) -> Result<(), DeleteCameraTempBlockingError> {
         self.client
             .delete_item()
             .table_name(&self.table)
-            .key("partitionKey", AttributeValue::S(format!("cameraTempBlocking/{}", user_id)))
-            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .key("partitionKey", AttributeValue::S(format!("{}{}", RECURRING_PARTITION_KEY_PREFIX, user_id)))
+            .key("sortKey", AttributeValue::S(id.to_string()))
             .send()
             .await
             .map_err(|e| DeleteCameraTempBlockingError(QCError::new(
-                "failed to delete camera temp blocking".to_string(),
+                "failed to delete recurring camera blocking".to_string(),
                 Some(format!("{:?}", e)),
             )))?;
         Ok(())
     }
 
-    async fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<Option<CameraTempBlocking>, GetCameraTempBlockingError> {
+    async fn list_privacy_shutters(&self) -> Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError> {
+        let items: Vec<HashMap<String, AttributeValue>> = self.client
+            .query()
+            .table_name(&self.table)
+            .key_condition_expression("partitionKey = :pk")
+            .expression_attribute_values(":pk", AttributeValue::S(PRIVACY_SHUTTER_PARTITION_KEY.to_string()))
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| ListCameraTempBlockingsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                CameraPrivacyShutter::try_from(item)
+                    .inspect_err(|err| tracing::error!("skipping invalid privacy shutter: {}", err))
+                    .ok()
+            })
+            .collect())
+    }
+
+    async fn get_privacy_shutter(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, GetCameraTempBlockingError> {
         let result = self.client
             .get_item()
             .table_name(&self.table)
-            .key("partitionKey", AttributeValue::S(format!("cameraTempBlocking/{}", user_id)))
+            .key("partitionKey", AttributeValue::S(PRIVACY_SHUTTER_PARTITION_KEY.to_string()))
             .key("sortKey", AttributeValue::S(camera_id.to_string()))
             .send()
             .await
             .map_err(|e| GetCameraTempBlockingError(QCError::new(
-                "failed to get camera temp blocking".to_string(),
+                "failed to get camera privacy shutter".to_string(),
                 Some(format!("{:?}", e)),
             )))?;
 
-        if let Some(item) = result.item {
-            if let (Some(end_date), Some(user_id_val)) = (
-                item.get("end_date").and_then(|v| v.as_s().ok()),
-                item.get("user_id").and_then(|v| v.as_s().ok())
-            ) {
-                return Ok(Some(CameraTempBlocking {
-                    id: camera_id.to_string(),
-                    camera_id: camera_id.to_string(),
-                    user_id: user_id_val.clone(),
-                    end_date: end_date.clone(),
-                }));
+        Ok(result.item.as_ref().and_then(|item| CameraPrivacyShutter::try_from(item).ok()))
+    }
+
+    async fn put_privacy_shutter(&self, shutter: CameraPrivacyShutter) -> Result<(), PutCameraPrivacyShutterError> {
+        let optional = [
+            ("start_date", shutter.start_date),
+            ("reason", shutter.reason),
+            ("created_by", shutter.created_by),
+            ("created_at", shutter.created_at),
+        ];
+        let mut request = self.client.put_item();
+        for (name, value) in optional {
+            if let Some(value) = value {
+                request = request.item(name, AttributeValue::S(value));
             }
         }
-        Ok(None)
+        request
+            .table_name(&self.table)
+            .item("partitionKey", AttributeValue::S(PRIVACY_SHUTTER_PARTITION_KEY.to_string()))
+            .item("sortKey", AttributeValue::S(shutter.camera_id))
+            .item("end_date", AttributeValue::S(shutter.end_date))
+            .item("share_reason", AttributeValue::Bool(shutter.share_reason))
+            .send()
+            .await
+            .map_err(|e| PutCameraPrivacyShutterError(QCError::new(
+                "failed to put camera privacy shutter".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+        Ok(())
+    }
+
+    async fn delete_privacy_shutter(&self, camera_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        self.client
+            .delete_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(PRIVACY_SHUTTER_PARTITION_KEY.to_string()))
+            .key("sortKey", AttributeValue::S(camera_id.to_string()))
+            .send()
+            .await
+            .map_err(|e| DeleteCameraTempBlockingError(QCError::new(
+                "failed to delete camera privacy shutter".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+        Ok(())
+    }
+}
+
+/// Where the blockings live, picked once at startup from `STORAGE_BACKEND`.
+pub enum CameraTempBlockingQCCollection {
+    Dynamo(DynamoCameraTempBlockingQCCollection),
+    Memory(InMemoryCameraTempBlockingQCCollection),
+    Sql(SqlCameraTempBlockingQCCollection),
+}
+
+impl CameraTempBlockingQCCollection {
+    /// Only DynamoDB has an index to backfill; the other stores index every
+    /// blocking as it is written.
+    pub async fn backfill_camera_index(&self) -> Result<usize, BackfillCameraIndexError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.backfill_camera_index().await,
+            CameraTempBlockingQCCollection::Memory(_) | CameraTempBlockingQCCollection::Sql(_) => Ok(0),
+        }
+    }
+}
+
+impl ICameraTempBlockingQCCollection for CameraTempBlockingQCCollection {
+    async fn list_temp_blocking_for_user(&self, user_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_temp_blocking_for_user(user_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_temp_blocking_for_user(user_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_temp_blocking_for_user(user_id).await,
+        }
+    }
+
+    async fn list_temp_blocking_for_camera(&self, camera_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_temp_blocking_for_camera(camera_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_temp_blocking_for_camera(camera_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_temp_blocking_for_camera(camera_id).await,
+        }
+    }
+
+    async fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> Result<(), CreateCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.create_temp_blocking(input).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.create_temp_blocking(input).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.create_temp_blocking(input).await,
+        }
+    }
+
+    async fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.delete_temp_blocking(camera_id, user_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.delete_temp_blocking(camera_id, user_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.delete_temp_blocking(camera_id, user_id).await,
+        }
+    }
+
+    async fn list_all_temp_blockings(&self) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_all_temp_blockings().await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_all_temp_blockings().await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_all_temp_blockings().await,
+        }
+    }
+
+    async fn delete_temp_blocking_ending_at(&self, camera_id: &str, user_id: &str, end_date: &str) -> Result<bool, DeleteCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.delete_temp_blocking_ending_at(camera_id, user_id, end_date).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.delete_temp_blocking_ending_at(camera_id, user_id, end_date).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.delete_temp_blocking_ending_at(camera_id, user_id, end_date).await,
+        }
+    }
+
+    async fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<Option<CameraTempBlocking>, GetCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.get_temp_blocking(camera_id, user_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.get_temp_blocking(camera_id, user_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.get_temp_blocking(camera_id, user_id).await,
+        }
+    }
+
+    async fn update_temp_blocking(&self, camera_id: &str, user_id: &str, input: UpdateCameraTempBlockingInput) -> Result<Option<CameraTempBlocking>, UpdateCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.update_temp_blocking(camera_id, user_id, input).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.update_temp_blocking(camera_id, user_id, input).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.update_temp_blocking(camera_id, user_id, input).await,
+        }
+    }
+
+    async fn list_recurring_blockings_for_user(&self, user_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_recurring_blockings_for_user(user_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_recurring_blockings_for_user(user_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_recurring_blockings_for_user(user_id).await,
+        }
+    }
+
+    async fn list_recurring_blockings_for_camera(&self, camera_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_recurring_blockings_for_camera(camera_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_recurring_blockings_for_camera(camera_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_recurring_blockings_for_camera(camera_id).await,
+        }
+    }
+
+    async fn create_recurring_blocking(&self, blocking: RecurringCameraBlocking) -> Result<(), CreateRecurringCameraBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.create_recurring_blocking(blocking).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.create_recurring_blocking(blocking).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.create_recurring_blocking(blocking).await,
+        }
     }
-}
\ No newline at end of file
+
+    async fn delete_recurring_blocking(&self, user_id: &str, id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.delete_recurring_blocking(user_id, id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.delete_recurring_blocking(user_id, id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.delete_recurring_blocking(user_id, id).await,
+        }
+    }
+
+    async fn list_privacy_shutters(&self) -> Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.list_privacy_shutters().await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.list_privacy_shutters().await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.list_privacy_shutters().await,
+        }
+    }
+
+    async fn get_privacy_shutter(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, GetCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.get_privacy_shutter(camera_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.get_privacy_shutter(camera_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.get_privacy_shutter(camera_id).await,
+        }
+    }
+
+    async fn put_privacy_shutter(&self, shutter: CameraPrivacyShutter) -> Result<(), PutCameraPrivacyShutterError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.put_privacy_shutter(shutter).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.put_privacy_shutter(shutter).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.put_privacy_shutter(shutter).await,
+        }
+    }
+
+    async fn delete_privacy_shutter(&self, camera_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        match self {
+            CameraTempBlockingQCCollection::Dynamo(collection) => collection.delete_privacy_shutter(camera_id).await,
+            CameraTempBlockingQCCollection::Memory(collection) => collection.delete_privacy_shutter(camera_id).await,
+            CameraTempBlockingQCCollection::Sql(collection) => collection.delete_privacy_shutter(camera_id).await,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    blocking_history_qc_collection::{
+        parse_date, sortable, ArchiveBlockingError, BlockingHistoryEntry, BlockingHistoryPage, BlockingHistoryQuery,
+        IBlockingHistoryQCCollection, QueryBlockingHistoryError,
+    },
+    error::QCError,
+};
+
+/// History entries kept in process memory, keyed by the entry id like the
+/// SQL table. Pages run newest first and the cursor carries the last id.
+/// Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct InMemoryBlockingHistoryQCCollection {
+    entries: Arc<Mutex<BTreeMap<String, BlockingHistoryEntry>>>,
+}
+
+impl InMemoryBlockingHistoryQCCollection {
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, BlockingHistoryEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl IBlockingHistoryQCCollection for InMemoryBlockingHistoryQCCollection {
+    async fn archive(&self, entry: BlockingHistoryEntry) -> Result<(), ArchiveBlockingError> {
+        self.entries().insert(entry.id(), entry);
+        Ok(())
+    }
+
+    async fn query(&self, query: BlockingHistoryQuery) -> Result<BlockingHistoryPage, QueryBlockingHistoryError> {
+        if query.camera_id.is_none() && query.user_id.is_none() {
+            return Err(QueryBlockingHistoryError::Database(QCError::new(
+                "camera_id or user_id is required".to_string(),
+                None,
+            )));
+        }
+        let last_id = match &query.cursor {
+            Some(cursor) => Some(
+                URL_SAFE_NO_PAD
+                    .decode(cursor)
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .ok_or(QueryBlockingHistoryError::InvalidCursor)?,
+            ),
+            None => None,
+        };
+        // ids start with the start date, so "started before `to`" compares ids
+        let before = query.to.map(|to| sortable(Some(to)));
+        let after = query.from.map(|from| sortable(Some(from)));
+
+        let limit = usize::try_from(query.limit).unwrap_or(0);
+        let entries = self.entries();
+        // one more than asked tells whether another page follows
+        let matches: Vec<(&String, &BlockingHistoryEntry)> = entries
+            .iter()
+            .rev()
+            .filter(|(_, entry)| query.camera_id.as_ref().is_none_or(|camera_id| &entry.camera_id == camera_id))
+            .filter(|(_, entry)| query.user_id.as_ref().is_none_or(|user_id| &entry.user_id == user_id))
+            .filter(|(id, _)| before.as_ref().is_none_or(|before| *id < before))
+            .filter(|(id, _)| last_id.as_ref().is_none_or(|last_id| *id < last_id))
+            .filter(|(_, entry)| {
+                after
+                    .as_ref()
+                    .is_none_or(|after| &sortable(parse_date(&entry.end_date)) > after)
+            })
+            .take(limit + 1)
+            .collect();
+
+        let next_cursor = match matches.len() > limit && limit > 0 {
+            true => Some(URL_SAFE_NO_PAD.encode(matches[limit - 1].0)),
+            false => None,
+        };
+        Ok(BlockingHistoryPage {
+            entries: matches.into_iter().take(limit).map(|(_, entry)| entry.clone()).collect(),
+            next_cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+
+    fn entry(camera_id: &str, user_id: &str, start_date: &str) -> BlockingHistoryEntry {
+        BlockingHistoryEntry {
+            camera_id: camera_id.to_string(),
+            user_id: user_id.to_string(),
+            start_date: Some(start_date.to_string()),
+            end_date: "2025-01-20T00:00:00Z".to_string(),
+            reason: None,
+            created_by: None,
+            created_at: None,
+            archived_at: Utc::now(),
+            archive_reason: "expired".to_string(),
+            archived_by: None,
+        }
+    }
+
+    fn query(camera_id: Option<&str>, user_id: Option<&str>, limit: i32, cursor: Option<String>) -> BlockingHistoryQuery {
+        BlockingHistoryQuery {
+            camera_id: camera_id.map(str::to_string),
+            user_id: user_id.map(str::to_string),
+            from: None,
+            to: None,
+            limit,
+            cursor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_newest_first() {
+        let collection = InMemoryBlockingHistoryQCCollection::default();
+        for (camera_id, user_id, start) in [
+            ("garage", "auth0|1", "2025-01-10T00:00:00Z"),
+            ("garage", "auth0|2", "2025-01-11T00:00:00Z"),
+            ("garage", "auth0|1", "2025-01-12T00:00:00Z"),
+            ("door", "auth0|1", "2025-01-13T00:00:00Z"),
+            // archiving the same blocking again replaces the entry
+            ("garage", "auth0|1", "2025-01-12T00:00:00Z"),
+        ] {
+            collection.archive(entry(camera_id, user_id, start)).await.unwrap();
+        }
+
+        let first = collection.query(query(Some("garage"), None, 2, None)).await.unwrap();
+        let starts: Vec<_> = first.entries.iter().map(|e| e.start_date.clone().unwrap()).collect();
+        assert_eq!(starts, vec!["2025-01-12T00:00:00Z", "2025-01-11T00:00:00Z"]);
+        let second = collection.query(query(Some("garage"), None, 2, first.next_cursor)).await.unwrap();
+        assert_eq!(second.entries.len(), 1);
+        assert!(second.next_cursor.is_none());
+
+        let mut ranged = query(None, Some("auth0|1"), 10, None);
+        ranged.to = Some(DateTime::parse_from_rfc3339("2025-01-12T00:00:00Z").unwrap().with_timezone(&Utc));
+        assert_eq!(collection.query(ranged).await.unwrap().entries.len(), 1);
+
+        assert!(matches!(
+            collection.query(query(Some("garage"), None, 2, Some("%".to_string()))).await,
+            Err(QueryBlockingHistoryError::InvalidCursor)
+        ));
+    }
+}
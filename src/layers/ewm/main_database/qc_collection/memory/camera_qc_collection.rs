@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+
+use crate::layers::ewm::main_database::qc_collection::{
+    camera_qc_collection::{
+        CameraListQueryResultItem, CameraStreamMetadata, CheckIfCameraExistsError, CreateCameraCommandError,
+        CreateCameraCommandOutput, DeleteCameraCommandError, FindCameraByIdResult, FindCamerabyIdError,
+        ICameraQCCollection, ListCamerasQueryError, PutCameraCommandInput, UpdateCameraCommandError,
+        UpdateCameraCommandInput, UpdateCameraStreamMetadataError,
+    },
+    error::QCError,
+};
+
+/// Cameras kept in process memory, ordered by id like the DynamoDB sort key.
+/// Clones share the same cameras.
+#[derive(Clone, Default)]
+pub struct InMemoryCameraQCCollection {
+    cameras: Arc<Mutex<BTreeMap<String, FindCameraByIdResult>>>,
+}
+
+impl InMemoryCameraQCCollection {
+    fn cameras(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, FindCameraByIdResult>> {
+        self.cameras.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ICameraQCCollection for InMemoryCameraQCCollection {
+    async fn list_cameras(&self) -> Result<Vec<CameraListQueryResultItem>, ListCamerasQueryError> {
+        Ok(self
+            .cameras()
+            .values()
+            .map(|camera| CameraListQueryResultItem {
+                id: camera.id.clone(),
+                name: camera.name.clone(),
+                source_url: camera.source_url.clone(),
+            })
+            .collect())
+    }
+
+    async fn put_camera(
+        &self,
+        command_input: PutCameraCommandInput,
+    ) -> Result<CreateCameraCommandOutput, CreateCameraCommandError> {
+        let now = Utc::now();
+        let camera = FindCameraByIdResult {
+            id: command_input.id.unwrap_or(ulid::Ulid::new().to_string()),
+            name: command_input.name,
+            source_url: command_input.source_url,
+            permanent_stream_url: command_input.permanent_stream_url,
+            stream_metadata: command_input.stream_metadata,
+            created_at: now,
+            updated_at: now,
+        };
+        self.cameras().insert(camera.id.clone(), camera.clone());
+        Ok(CreateCameraCommandOutput {
+            id: camera.id,
+            name: camera.name,
+            source_url: camera.source_url,
+            permanent_stream_url: camera.permanent_stream_url,
+            stream_metadata: camera.stream_metadata,
+            created_at: camera.created_at,
+            updated_at: camera.updated_at,
+        })
+    }
+
+    async fn delete_camera_by_id(&self, id: &str) -> Result<(), DeleteCameraCommandError> {
+        self.cameras().remove(id);
+        Ok(())
+    }
+
+    async fn find_camera_by_id(&self, id: &str) -> Result<FindCameraByIdResult, FindCamerabyIdError> {
+        self.cameras()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| FindCamerabyIdError(QCError::new("Camera not found".to_string(), None)))
+    }
+
+    async fn camera_exists_by_id(&self, id: &str) -> Result<bool, CheckIfCameraExistsError> {
+        Ok(self.cameras().contains_key(id))
+    }
+
+    async fn update_camera_stream_metadata(
+        &self,
+        id: &str,
+        stream_metadata: CameraStreamMetadata,
+    ) -> Result<(), UpdateCameraStreamMetadataError> {
+        match self.cameras().get_mut(id) {
+            Some(camera) => {
+                camera.stream_metadata = Some(stream_metadata);
+                Ok(())
+            }
+            None => Err(UpdateCameraStreamMetadataError(QCError::new(
+                "failed to update camera stream metadata in database".to_string(),
+                Some(format!("camera {} not found", id)),
+            ))),
+        }
+    }
+
+    async fn update_camera(
+        &self,
+        command_input: UpdateCameraCommandInput,
+    ) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+        let mut cameras = self.cameras();
+        let Some(camera) = cameras.get_mut(&command_input.id) else {
+            return Err(UpdateCameraCommandError(QCError::new(
+                "failed to update camera in database".to_string(),
+                Some(format!("camera {} not found", command_input.id)),
+            )));
+        };
+        camera.updated_at = Utc::now();
+        if let Some(name) = command_input.name {
+            camera.name = name;
+        }
+        if let Some(source_url) = command_input.source_url {
+            camera.source_url = source_url;
+        }
+        if let Some(permanent_stream_url) = command_input.permanent_stream_url {
+            camera.permanent_stream_url = Some(permanent_stream_url);
+        }
+        if let Some(stream_metadata) = command_input.stream_metadata {
+            camera.stream_metadata = stream_metadata;
+        }
+        Ok(camera.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_input(id: &str, name: &str) -> PutCameraCommandInput {
+        PutCameraCommandInput {
+            id: Some(id.to_string()),
+            name: name.to_string(),
+            source_url: format!("rtsp://{}", id),
+            permanent_stream_url: None,
+            stream_metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cameras_are_shared_between_clones_and_listed_by_id() {
+        let collection = InMemoryCameraQCCollection::default();
+        let other = collection.clone();
+        collection.put_camera(put_input("b", "Garage")).await.unwrap();
+        other.put_camera(put_input("a", "Door")).await.unwrap();
+
+        let ids: Vec<String> = collection.list_cameras().await.unwrap().into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let updated = other
+            .update_camera(UpdateCameraCommandInput {
+                id: "b".to_string(),
+                name: Some("Carport".to_string()),
+                source_url: None,
+                permanent_stream_url: None,
+                stream_metadata: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Carport");
+        assert_eq!(updated.source_url, "rtsp://b");
+
+        collection.delete_camera_by_id("b").await.unwrap();
+        assert!(!other.camera_exists_by_id("b").await.unwrap());
+        assert!(other.find_camera_by_id("b").await.is_err());
+    }
+}
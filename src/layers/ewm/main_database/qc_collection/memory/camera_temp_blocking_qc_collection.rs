@@ -0,0 +1,306 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+
+use crate::layers::ewm::main_database::qc_collection::{
+    camera_temp_blocking_qc_collection::{
+        CameraPrivacyShutter, CameraTempBlocking, CreateCameraTempBlockingError, CreateCameraTempBlockingInput,
+        CreateRecurringCameraBlockingError, DeleteCameraTempBlockingError, GetCameraTempBlockingError,
+        ICameraTempBlockingQCCollection, ListCameraTempBlockingsQueryError, PutCameraPrivacyShutterError,
+        RecurringCameraBlocking, UpdateCameraTempBlockingError, UpdateCameraTempBlockingInput,
+        MAX_USERS_PER_TEMP_BLOCKING,
+    },
+    error::QCError,
+};
+
+/// Keys mirror the DynamoDB layout: blockings by `(user_id, camera_id)`,
+/// recurring blockings by `(user_id, id)` and shutters by camera, so listings
+/// come back in the same order.
+#[derive(Default)]
+struct Blockings {
+    temp: BTreeMap<(String, String), CameraTempBlocking>,
+    recurring: BTreeMap<(String, String), RecurringCameraBlocking>,
+    privacy_shutters: BTreeMap<String, CameraPrivacyShutter>,
+}
+
+/// Blockings kept in process memory. Clones share the same blockings.
+#[derive(Clone, Default)]
+pub struct InMemoryCameraTempBlockingQCCollection {
+    blockings: Arc<Mutex<Blockings>>,
+}
+
+impl InMemoryCameraTempBlockingQCCollection {
+    fn blockings(&self) -> std::sync::MutexGuard<'_, Blockings> {
+        self.blockings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn key(user_id: &str, camera_id: &str) -> (String, String) {
+    (user_id.to_string(), camera_id.to_string())
+}
+
+impl ICameraTempBlockingQCCollection for InMemoryCameraTempBlockingQCCollection {
+    async fn list_temp_blocking_for_user(&self, user_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        Ok(self
+            .blockings()
+            .temp
+            .iter()
+            .filter(|((user, _), _)| user == user_id)
+            .map(|(_, blocking)| blocking.clone())
+            .collect())
+    }
+
+    async fn list_temp_blocking_for_camera(&self, camera_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        // ordered by user like the camera index
+        Ok(self
+            .blockings()
+            .temp
+            .values()
+            .filter(|blocking| blocking.camera_id == camera_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> Result<(), CreateCameraTempBlockingError> {
+        let unique_user_ids: HashSet<&String> = input.user_ids.iter().collect();
+        if input.user_ids.len() > MAX_USERS_PER_TEMP_BLOCKING || unique_user_ids.len() != input.user_ids.len() {
+            return Err(CreateCameraTempBlockingError::Database(QCError::new(
+                "failed to create camera temp blocking".to_string(),
+                Some(format!(
+                    "a transaction takes at most {} distinct users",
+                    MAX_USERS_PER_TEMP_BLOCKING
+                )),
+            )));
+        }
+
+        let mut blockings = self.blockings();
+        let conflicts: Vec<String> = input
+            .user_ids
+            .iter()
+            .filter(|user_id| blockings.temp.contains_key(&key(user_id, &input.camera_id)))
+            .cloned()
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(CreateCameraTempBlockingError::AlreadyBlocked(conflicts));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        for user_id in input.user_ids {
+            let blocking = CameraTempBlocking {
+                id: input.camera_id.clone(),
+                camera_id: input.camera_id.clone(),
+                user_id: user_id.clone(),
+                start_date: Some(input.start_time.clone()),
+                end_date: input.end_time.clone(),
+                reason: input.reason.clone(),
+                share_reason: input.share_reason,
+                created_by: Some(input.created_by.clone()),
+                created_at: Some(now.clone()),
+                updated_at: Some(now.clone()),
+            };
+            blockings.temp.insert(key(&user_id, &input.camera_id), blocking);
+        }
+        Ok(())
+    }
+
+    async fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        self.blockings().temp.remove(&key(user_id, camera_id));
+        Ok(())
+    }
+
+    async fn list_all_temp_blockings(&self) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        Ok(self.blockings().temp.values().cloned().collect())
+    }
+
+    async fn delete_temp_blocking_ending_at(&self, camera_id: &str, user_id: &str, end_date: &str) -> Result<bool, DeleteCameraTempBlockingError> {
+        let mut blockings = self.blockings();
+        let key = key(user_id, camera_id);
+        if blockings.temp.get(&key).is_some_and(|blocking| blocking.end_date == end_date) {
+            blockings.temp.remove(&key);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    async fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<Option<CameraTempBlocking>, GetCameraTempBlockingError> {
+        Ok(self.blockings().temp.get(&key(user_id, camera_id)).cloned())
+    }
+
+    async fn update_temp_blocking(&self, camera_id: &str, user_id: &str, input: UpdateCameraTempBlockingInput) -> Result<Option<CameraTempBlocking>, UpdateCameraTempBlockingError> {
+        let mut blockings = self.blockings();
+        let Some(blocking) = blockings
+            .temp
+            .get_mut(&key(user_id, camera_id))
+            .filter(|b| b.start_date == input.expected_start_date && b.end_date == input.expected_end_date)
+        else {
+            return Ok(None);
+        };
+        blocking.updated_at = Some(Utc::now().to_rfc3339());
+        if let Some(start_time) = input.start_time {
+            blocking.start_date = Some(start_time);
+        }
+        if let Some(end_time) = input.end_time {
+            blocking.end_date = end_time;
+        }
+        if let Some(reason) = input.reason {
+            blocking.reason = Some(reason);
+        }
+        if let Some(share_reason) = input.share_reason {
+            blocking.share_reason = share_reason;
+        }
+        Ok(Some(blocking.clone()))
+    }
+
+    async fn list_privacy_shutters(&self) -> Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError> {
+        Ok(self.blockings().privacy_shutters.values().cloned().collect())
+    }
+
+    async fn get_privacy_shutter(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, GetCameraTempBlockingError> {
+        Ok(self.blockings().privacy_shutters.get(camera_id).cloned())
+    }
+
+    async fn put_privacy_shutter(&self, shutter: CameraPrivacyShutter) -> Result<(), PutCameraPrivacyShutterError> {
+        self.blockings().privacy_shutters.insert(shutter.camera_id.clone(), shutter);
+        Ok(())
+    }
+
+    async fn delete_privacy_shutter(&self, camera_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        self.blockings().privacy_shutters.remove(camera_id);
+        Ok(())
+    }
+
+    async fn list_recurring_blockings_for_user(&self, user_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        Ok(self
+            .blockings()
+            .recurring
+            .iter()
+            .filter(|((user, _), _)| user == user_id)
+            .map(|(_, blocking)| blocking.clone())
+            .collect())
+    }
+
+    async fn list_recurring_blockings_for_camera(&self, camera_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        // the camera index sorts recurring blockings by id
+        let mut blockings: Vec<RecurringCameraBlocking> = self
+            .blockings()
+            .recurring
+            .values()
+            .filter(|blocking| blocking.camera_id == camera_id)
+            .cloned()
+            .collect();
+        blockings.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(blockings)
+    }
+
+    async fn create_recurring_blocking(&self, blocking: RecurringCameraBlocking) -> Result<(), CreateRecurringCameraBlockingError> {
+        self.blockings()
+            .recurring
+            .insert(key(&blocking.user_id, &blocking.id), blocking);
+        Ok(())
+    }
+
+    async fn delete_recurring_blocking(&self, user_id: &str, id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        self.blockings().recurring.remove(&key(user_id, id));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_input(camera_id: &str, user_ids: &[&str], end_time: &str) -> CreateCameraTempBlockingInput {
+        CreateCameraTempBlockingInput {
+            camera_id: camera_id.to_string(),
+            start_time: "2025-01-14T00:00:00Z".to_string(),
+            end_time: end_time.to_string(),
+            user_ids: user_ids.iter().map(|u| u.to_string()).collect(),
+            reason: None,
+            share_reason: false,
+            created_by: "auth0|admin".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_blocks_everyone_or_nobody() {
+        let collection = InMemoryCameraTempBlockingQCCollection::default();
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|2"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+
+        let result = collection
+            .create_temp_blocking(create_input("garage", &["auth0|1", "auth0|2", "auth0|3"], "2025-01-20T00:00:00Z"))
+            .await;
+        assert!(matches!(result, Err(CreateCameraTempBlockingError::AlreadyBlocked(users)) if users == vec!["auth0|2"]));
+        assert_eq!(collection.list_temp_blocking_for_camera("garage").await.unwrap().len(), 1);
+
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|3", "auth0|1"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+        let users: Vec<String> = collection
+            .list_temp_blocking_for_camera("garage")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| b.user_id)
+            .collect();
+        assert_eq!(users, vec!["auth0|1", "auth0|2", "auth0|3"]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_ending_at_spares_extended_blockings() {
+        let collection = InMemoryCameraTempBlockingQCCollection::default();
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|1"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+        collection
+            .update_temp_blocking(
+                "garage",
+                "auth0|1",
+                UpdateCameraTempBlockingInput {
+                    start_time: None,
+                    end_time: Some("2025-02-01T00:00:00Z".to_string()),
+                    reason: None,
+                    share_reason: None,
+                    expected_start_date: Some("2025-01-14T00:00:00Z".to_string()),
+                    expected_end_date: "2025-01-20T00:00:00Z".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        // the end date read before the first update is stale now
+        let stale = collection
+            .update_temp_blocking(
+                "garage",
+                "auth0|1",
+                UpdateCameraTempBlockingInput {
+                    start_time: None,
+                    end_time: Some("2025-01-25T00:00:00Z".to_string()),
+                    reason: None,
+                    share_reason: None,
+                    expected_start_date: Some("2025-01-14T00:00:00Z".to_string()),
+                    expected_end_date: "2025-01-20T00:00:00Z".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(stale.is_none());
+
+        assert!(!collection
+            .delete_temp_blocking_ending_at("garage", "auth0|1", "2025-01-20T00:00:00Z")
+            .await
+            .unwrap());
+        assert!(collection
+            .delete_temp_blocking_ending_at("garage", "auth0|1", "2025-02-01T00:00:00Z")
+            .await
+            .unwrap());
+        assert!(collection.get_temp_blocking("garage", "auth0|1").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,22 @@
+pub mod blocking_history_qc_collection;
+pub mod camera_qc_collection;
+pub mod camera_temp_blocking_qc_collection;
+pub mod temp_stream_session_qc_collection;
+pub mod user_qc_collection;
+
+use blocking_history_qc_collection::InMemoryBlockingHistoryQCCollection;
+use camera_qc_collection::InMemoryCameraQCCollection;
+use camera_temp_blocking_qc_collection::InMemoryCameraTempBlockingQCCollection;
+use temp_stream_session_qc_collection::InMemoryTempStreamSessionQCCollection;
+use user_qc_collection::InMemoryUserQCCollection;
+
+/// The in-memory collections of one process. Cloning shares the data, so the
+/// store can sit in the app state and hand out collections per request.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    pub cameras: InMemoryCameraQCCollection,
+    pub camera_temp_blockings: InMemoryCameraTempBlockingQCCollection,
+    pub users: InMemoryUserQCCollection,
+    pub temp_stream_sessions: InMemoryTempStreamSessionQCCollection,
+    pub blocking_history: InMemoryBlockingHistoryQCCollection,
+}
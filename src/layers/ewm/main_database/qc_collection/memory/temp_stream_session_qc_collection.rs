@@ -0,0 +1,132 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+
+use crate::layers::ewm::main_database::qc_collection::temp_stream_session_qc_collection::{
+    ActiveTempStreamSessionCounts, CountTempStreamSessionsError, CreateTempStreamSessionError,
+    DeleteTempStreamSessionError, FindTempStreamSessionError, ITempStreamSessionQCCollection,
+    ListTempStreamSessionsQueryError, TempStreamSession,
+};
+
+/// Sessions kept in process memory, keyed by `(user_id, id)` like the
+/// DynamoDB user partition. Clones share the same sessions.
+#[derive(Clone, Default)]
+pub struct InMemoryTempStreamSessionQCCollection {
+    sessions: Arc<Mutex<BTreeMap<(String, String), TempStreamSession>>>,
+}
+
+impl InMemoryTempStreamSessionQCCollection {
+    fn sessions(&self) -> std::sync::MutexGuard<'_, BTreeMap<(String, String), TempStreamSession>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ITempStreamSessionQCCollection for InMemoryTempStreamSessionQCCollection {
+    async fn create_session(&self, session: TempStreamSession) -> Result<(), CreateTempStreamSessionError> {
+        self.sessions()
+            .insert((session.user_id.clone(), session.id.clone()), session);
+        Ok(())
+    }
+
+    async fn list_sessions_for_user(&self, user_id: &str) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        Ok(self
+            .sessions()
+            .iter()
+            .filter(|((user, _), _)| user == user_id)
+            .map(|(_, session)| session.clone())
+            .collect())
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        Ok(self.sessions().values().cloned().collect())
+    }
+
+    async fn find_session_by_id(&self, session_id: &str) -> Result<Option<TempStreamSession>, FindTempStreamSessionError> {
+        Ok(self
+            .sessions()
+            .values()
+            .find(|session| session.id == session_id)
+            .cloned())
+    }
+
+    async fn delete_session(&self, user_id: &str, session_id: &str) -> Result<(), DeleteTempStreamSessionError> {
+        self.sessions()
+            .remove(&(user_id.to_string(), session_id.to_string()));
+        Ok(())
+    }
+
+    async fn count_active_sessions(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+    ) -> Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError> {
+        let now = Utc::now();
+        Ok(self
+            .sessions()
+            .values()
+            .filter(|session| session.expires_at > now)
+            .fold(ActiveTempStreamSessionCounts::default(), |counts, session| {
+                ActiveTempStreamSessionCounts {
+                    user: counts.user + usize::from(session.user_id == user_id),
+                    camera: counts.camera + usize::from(session.camera_id == camera_id),
+                    total: counts.total + 1,
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::layers::ewm::temporary_stream_server::StreamQuality;
+
+    fn session(id: &str, user_id: &str, camera_id: &str, expires_in: Duration) -> TempStreamSession {
+        let issued_at = Utc::now();
+        TempStreamSession {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            camera_id: camera_id.to_string(),
+            stream_id: format!("stream-{}", id),
+            stream_url: format!("rtsp://media/{}", id),
+            hls_url: None,
+            whep_url: None,
+            quality: StreamQuality::Original,
+            token: "secret".to_string(),
+            privacy_shutter_exempt: false,
+            issued_at,
+            expires_at: issued_at + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counts_skip_expired_sessions() {
+        let collection = InMemoryTempStreamSessionQCCollection::default();
+        collection.create_session(session("s2", "auth0|1", "garage", Duration::minutes(10))).await.unwrap();
+        collection.create_session(session("s1", "auth0|1", "door", Duration::minutes(10))).await.unwrap();
+        collection.create_session(session("s3", "auth0|2", "garage", Duration::minutes(10))).await.unwrap();
+        collection.create_session(session("s4", "auth0|1", "garage", Duration::minutes(-1))).await.unwrap();
+
+        let ids: Vec<String> = collection
+            .list_sessions_for_user("auth0|1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec!["s1", "s2", "s4"]);
+
+        let counts = collection.count_active_sessions("auth0|1", "garage").await.unwrap();
+        assert_eq!(counts, ActiveTempStreamSessionCounts { user: 2, camera: 2, total: 3 });
+
+        // only the owner deletes a session
+        collection.delete_session("auth0|1", "s3").await.unwrap();
+        assert!(collection.find_session_by_id("s3").await.unwrap().is_some());
+        collection.delete_session("auth0|2", "s3").await.unwrap();
+        assert!(collection.find_session_by_id("s3").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::BTreeMap,
+    ops::Bound,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::layers::ewm::main_database::qc_collection::user_qc_collection::{
+    decode_user_cursor, encode_user_cursor, CreateUserCommandError, CreateUserCommandInput, FindUserByIdQueryError,
+    FindUsersByIdsQueryError, IUserQCCollection, ListUsersQueryError, SearchUsersQuery, SearchUsersQueryError,
+    SetRoleOverridesCommandInput, UpdateUserCommandError, UpdateUserCommandInput, UpsertUserCommandInput,
+    UserListQueryResultItem, UserSearchPage,
+};
+
+/// Users kept in process memory, ordered by id like the DynamoDB sort key.
+/// Clones share the same users.
+#[derive(Clone, Default)]
+pub struct InMemoryUserQCCollection {
+    users: Arc<Mutex<BTreeMap<String, UserListQueryResultItem>>>,
+}
+
+impl InMemoryUserQCCollection {
+    fn users(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, UserListQueryResultItem>> {
+        self.users.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Applies `change` to an existing user and returns the result.
+    fn update(&self, user_id: &str, change: impl FnOnce(&mut UserListQueryResultItem)) -> Option<UserListQueryResultItem> {
+        let mut users = self.users();
+        let user = users.get_mut(user_id)?;
+        change(user);
+        Some(user.clone())
+    }
+}
+
+impl IUserQCCollection for InMemoryUserQCCollection {
+    async fn list_users(&self) -> Result<Vec<UserListQueryResultItem>, ListUsersQueryError> {
+        Ok(self.users().values().cloned().collect())
+    }
+
+    async fn search_users(&self, query: SearchUsersQuery) -> Result<UserSearchPage, SearchUsersQueryError> {
+        let start = match &query.cursor {
+            Some(cursor) => Bound::Excluded(decode_user_cursor(cursor).ok_or(SearchUsersQueryError::InvalidCursor)?),
+            None => Bound::Unbounded,
+        };
+        let users = self.users();
+        let mut matches = Vec::new();
+        for user in users.range((start, Bound::Unbounded)).map(|(_, user)| user) {
+            if !query.matches(user) {
+                continue;
+            }
+            matches.push(user.clone());
+            if matches.len() >= query.limit {
+                let has_more = users
+                    .range((Bound::Excluded(user.user_id.clone()), Bound::Unbounded))
+                    .next()
+                    .is_some();
+                return Ok(UserSearchPage {
+                    users: matches,
+                    next_cursor: has_more.then(|| encode_user_cursor(&user.user_id)),
+                });
+            }
+        }
+        Ok(UserSearchPage {
+            users: matches,
+            next_cursor: None,
+        })
+    }
+
+    async fn find_user_by_id(&self, user_id: &str) -> Result<Option<UserListQueryResultItem>, FindUserByIdQueryError> {
+        Ok(self.users().get(user_id).cloned())
+    }
+
+    async fn find_users_by_ids(&self, user_ids: Vec<String>) -> Result<Vec<UserListQueryResultItem>, FindUsersByIdsQueryError> {
+        let users = self.users();
+        Ok(user_ids.iter().filter_map(|user_id| users.get(user_id).cloned()).collect())
+    }
+
+    async fn create_user(&self, input: CreateUserCommandInput) -> Result<UserListQueryResultItem, CreateUserCommandError> {
+        let mut users = self.users();
+        if users.contains_key(&input.user_id) {
+            return Err(CreateUserCommandError::AlreadyExists);
+        }
+        let user = UserListQueryResultItem {
+            user_id: input.user_id,
+            email: input.email,
+            name: input.name,
+            deactivated_at: None,
+            roles: vec![],
+            removed_at: None,
+            granted_roles: vec![],
+            revoked_roles: vec![],
+        };
+        users.insert(user.user_id.clone(), user.clone());
+        Ok(user)
+    }
+
+    async fn update_user(&self, user_id: &str, input: UpdateUserCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        Ok(self.update(user_id, |user| {
+            if let Some(email) = input.email {
+                user.email = email;
+            }
+            if let Some(name) = input.name {
+                user.name = name;
+            }
+        }))
+    }
+
+    async fn deactivate_user(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        Ok(self.update(user_id, |user| {
+            user.deactivated_at.get_or_insert_with(|| at.to_rfc3339());
+        }))
+    }
+
+    async fn upsert_user(&self, input: UpsertUserCommandInput) -> Result<UserListQueryResultItem, UpdateUserCommandError> {
+        let mut users = self.users();
+        let user = users
+            .entry(input.user_id.clone())
+            .or_insert_with(|| UserListQueryResultItem {
+                user_id: input.user_id,
+                email: String::new(),
+                name: String::new(),
+                deactivated_at: None,
+                roles: vec![],
+                removed_at: None,
+                granted_roles: vec![],
+                revoked_roles: vec![],
+            });
+        user.email = input.email;
+        user.name = input.name;
+        user.roles = input.roles;
+        user.removed_at = None;
+        Ok(user.clone())
+    }
+
+    async fn set_role_overrides(&self, user_id: &str, input: SetRoleOverridesCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        Ok(self.update(user_id, |user| {
+            user.granted_roles = input.granted_roles;
+            user.revoked_roles = input.revoked_roles;
+        }))
+    }
+
+    async fn mark_user_removed(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        Ok(self.update(user_id, |user| {
+            user.removed_at.get_or_insert_with(|| at.to_rfc3339());
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    async fn create(collection: &InMemoryUserQCCollection, user_id: &str, name: &str) {
+        collection
+            .create_user(CreateUserCommandInput {
+                user_id: user_id.to_string(),
+                email: format!("{}@example.com", name.to_lowercase()),
+                name: name.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_matches() {
+        let collection = InMemoryUserQCCollection::default();
+        for (user_id, name) in [("auth0|1", "Ada"), ("auth0|2", "Bob"), ("auth0|3", "Adam"), ("auth0|4", "Alan")] {
+            create(&collection, user_id, name).await;
+        }
+        let query = |cursor: Option<String>| SearchUsersQuery {
+            search: Some("ad".to_string()),
+            roles: vec![],
+            active_only: false,
+            exclude_user_ids: HashSet::new(),
+            limit: 1,
+            cursor,
+        };
+
+        let first = collection.search_users(query(None)).await.unwrap();
+        assert_eq!(first.users[0].user_id, "auth0|1");
+        let second = collection.search_users(query(first.next_cursor)).await.unwrap();
+        assert_eq!(second.users[0].user_id, "auth0|3");
+        // auth0|4 follows but does not match, like a DynamoDB page cut off
+        // before the end
+        let third = collection.search_users(query(second.next_cursor)).await.unwrap();
+        assert!(third.users.is_empty());
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_first_removal_and_deactivation_dates_are_kept() {
+        let collection = InMemoryUserQCCollection::default();
+        create(&collection, "auth0|1", "Ada").await;
+        let first = "2025-01-01T00:00:00Z".parse().unwrap();
+        let later = "2025-02-01T00:00:00Z".parse().unwrap();
+
+        collection.mark_user_removed("auth0|1", first).await.unwrap();
+        let user = collection.mark_user_removed("auth0|1", later).await.unwrap().unwrap();
+        assert_eq!(user.removed_at, Some(first.to_rfc3339()));
+
+        collection.deactivate_user("auth0|1", first).await.unwrap();
+        let user = collection
+            .upsert_user(UpsertUserCommandInput {
+                user_id: "auth0|1".to_string(),
+                email: "ada@example.com".to_string(),
+                name: "Ada Lovelace".to_string(),
+                roles: vec!["Viewer".to_string()],
+                synced_at: later,
+            })
+            .await
+            .unwrap();
+        assert!(user.removed_at.is_none());
+        assert_eq!(user.deactivated_at, Some(first.to_rfc3339()));
+        assert!(collection.mark_user_removed("auth0|9", later).await.unwrap().is_none());
+    }
+}
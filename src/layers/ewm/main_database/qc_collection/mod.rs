@@ -1,4 +1,9 @@
 pub mod error;
 pub mod camera_qc_collection;
 pub mod camera_temp_blocking_qc_collection;
-pub mod user_qc_collection;
\ No newline at end of file
+pub mod user_qc_collection;
+pub mod temp_stream_session_qc_collection;
+pub mod blocking_history_qc_collection;
+
+pub mod memory;
+pub mod sql;
@@ -0,0 +1,216 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sqlx::{any::AnyRow, AnyPool, FromRow, Row};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    blocking_history_qc_collection::{
+        parse_date, sortable, ArchiveBlockingError, BlockingHistoryEntry, BlockingHistoryPage, BlockingHistoryQuery,
+        IBlockingHistoryQCCollection, QueryBlockingHistoryError,
+    },
+    error::QCError,
+    sql::{database_error, get_date},
+};
+
+const ENTRY_COLUMNS: &str = "camera_id, user_id, start_date, end_date, reason, created_by, created_at, archived_at, \
+    archive_reason, archived_by";
+
+impl FromRow<'_, AnyRow> for BlockingHistoryEntry {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(BlockingHistoryEntry {
+            camera_id: row.try_get("camera_id")?,
+            user_id: row.try_get("user_id")?,
+            start_date: row.try_get("start_date")?,
+            end_date: row.try_get("end_date")?,
+            reason: row.try_get("reason")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+            archived_at: get_date(row, "archived_at")?,
+            archive_reason: row.try_get("archive_reason")?,
+            archived_by: row.try_get("archived_by")?,
+        })
+    }
+}
+
+/// Entries in the `blocking_history` table, stored once and found through
+/// the camera and user indexes. Pages are keyed by the entry id, which the
+/// cursor carries.
+#[derive(Clone)]
+pub struct SqlBlockingHistoryQCCollection {
+    pool: AnyPool,
+}
+
+impl SqlBlockingHistoryQCCollection {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl IBlockingHistoryQCCollection for SqlBlockingHistoryQCCollection {
+    async fn archive(&self, entry: BlockingHistoryEntry) -> Result<(), ArchiveBlockingError> {
+        sqlx::query(
+            "INSERT INTO blocking_history (id, camera_id, user_id, start_date, end_date, end_date_utc, reason, \
+             created_by, created_at, archived_at, archive_reason, archived_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT (id) DO UPDATE SET start_date = excluded.start_date, end_date = excluded.end_date, \
+             end_date_utc = excluded.end_date_utc, reason = excluded.reason, created_by = excluded.created_by, \
+             created_at = excluded.created_at, archived_at = excluded.archived_at, \
+             archive_reason = excluded.archive_reason, archived_by = excluded.archived_by",
+        )
+        .bind(entry.id())
+        .bind(&entry.camera_id)
+        .bind(&entry.user_id)
+        .bind(entry.start_date.clone())
+        .bind(&entry.end_date)
+        .bind(sortable(parse_date(&entry.end_date)))
+        .bind(entry.reason.clone())
+        .bind(entry.created_by.clone())
+        .bind(entry.created_at.clone())
+        .bind(entry.archived_at.to_rfc3339())
+        .bind(&entry.archive_reason)
+        .bind(entry.archived_by.clone())
+        .execute(&self.pool)
+        .await
+        .map_err(|err| ArchiveBlockingError(database_error("failed to archive blocking", err)))?;
+        Ok(())
+    }
+
+    async fn query(&self, query: BlockingHistoryQuery) -> Result<BlockingHistoryPage, QueryBlockingHistoryError> {
+        if query.camera_id.is_none() && query.user_id.is_none() {
+            return Err(QueryBlockingHistoryError::Database(QCError::new(
+                "camera_id or user_id is required".to_string(),
+                None,
+            )));
+        }
+
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        let mut condition = |clause: &str, value: String| {
+            values.push(value);
+            conditions.push(format!("{} ${}", clause, values.len()));
+        };
+        if let Some(camera_id) = query.camera_id {
+            condition("camera_id =", camera_id);
+        }
+        if let Some(user_id) = query.user_id {
+            condition("user_id =", user_id);
+        }
+        // ids start with the start date, so "started before `to`" compares ids
+        if let Some(to) = query.to {
+            condition("id <", sortable(Some(to)));
+        }
+        if let Some(from) = query.from {
+            condition("end_date_utc >", sortable(Some(from)));
+        }
+        if let Some(cursor) = &query.cursor {
+            let last_id = URL_SAFE_NO_PAD
+                .decode(cursor)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or(QueryBlockingHistoryError::InvalidCursor)?;
+            condition("id <", last_id);
+        }
+
+        let limit = usize::try_from(query.limit).unwrap_or(0);
+        // one more than asked tells whether another page follows
+        let sql = format!(
+            "SELECT id, {} FROM blocking_history WHERE {} ORDER BY id DESC LIMIT ${}",
+            ENTRY_COLUMNS,
+            conditions.join(" AND "),
+            values.len() + 1
+        );
+        let mut statement = sqlx::query(&sql);
+        for value in values {
+            statement = statement.bind(value);
+        }
+        let rows = statement
+            .bind(limit as i64 + 1)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| QueryBlockingHistoryError::Database(database_error("failed to query blocking history", err)))?;
+
+        let to_error =
+            |err| QueryBlockingHistoryError::Database(database_error("failed to parse blocking history entry", err));
+        let next_cursor = match rows.len() > limit && limit > 0 {
+            true => Some(URL_SAFE_NO_PAD.encode(rows[limit - 1].try_get::<String, _>("id").map_err(to_error)?)),
+            false => None,
+        };
+        let entries = rows
+            .iter()
+            .take(limit)
+            .map(BlockingHistoryEntry::from_row)
+            .collect::<Result<_, _>>()
+            .map_err(to_error)?;
+        Ok(BlockingHistoryPage { entries, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+    use crate::layers::ewm::main_database::qc_collection::sql::connect_in_memory;
+
+    fn entry(camera_id: &str, user_id: &str, start_date: &str, end_date: &str) -> BlockingHistoryEntry {
+        BlockingHistoryEntry {
+            camera_id: camera_id.to_string(),
+            user_id: user_id.to_string(),
+            start_date: Some(start_date.to_string()),
+            end_date: end_date.to_string(),
+            reason: None,
+            created_by: None,
+            created_at: None,
+            archived_at: Utc::now(),
+            archive_reason: "expired".to_string(),
+            archived_by: None,
+        }
+    }
+
+    fn query(camera_id: Option<&str>, user_id: Option<&str>, limit: i32, cursor: Option<String>) -> BlockingHistoryQuery {
+        BlockingHistoryQuery {
+            camera_id: camera_id.map(str::to_string),
+            user_id: user_id.map(str::to_string),
+            from: None,
+            to: None,
+            limit,
+            cursor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_newest_first() {
+        let collection = SqlBlockingHistoryQCCollection::new(connect_in_memory().await);
+        for (camera_id, user_id, start) in [
+            ("garage", "auth0|1", "2025-01-10T00:00:00Z"),
+            ("garage", "auth0|2", "2025-01-11T00:00:00Z"),
+            ("garage", "auth0|1", "2025-01-12T00:00:00Z"),
+            ("door", "auth0|1", "2025-01-13T00:00:00Z"),
+        ] {
+            collection.archive(entry(camera_id, user_id, start, "2025-01-20T00:00:00Z")).await.unwrap();
+        }
+        // archiving the same blocking again replaces the entry
+        collection
+            .archive(entry("garage", "auth0|1", "2025-01-12T00:00:00Z", "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+
+        let first = collection.query(query(Some("garage"), None, 2, None)).await.unwrap();
+        let starts: Vec<_> = first.entries.iter().map(|e| e.start_date.clone().unwrap()).collect();
+        assert_eq!(starts, vec!["2025-01-12T00:00:00Z", "2025-01-11T00:00:00Z"]);
+        let second = collection.query(query(Some("garage"), None, 2, first.next_cursor)).await.unwrap();
+        assert_eq!(second.entries.len(), 1);
+        assert!(second.next_cursor.is_none());
+
+        let for_user = collection.query(query(Some("garage"), Some("auth0|1"), 10, None)).await.unwrap();
+        assert_eq!(for_user.entries.len(), 2);
+
+        let mut ranged = query(None, Some("auth0|1"), 10, None);
+        ranged.to = Some(DateTime::parse_from_rfc3339("2025-01-12T00:00:00Z").unwrap().with_timezone(&Utc));
+        let ranged = collection.query(ranged).await.unwrap();
+        assert_eq!(ranged.entries.len(), 1);
+
+        assert!(matches!(
+            collection.query(query(None, None, 10, None)).await,
+            Err(QueryBlockingHistoryError::Database(_))
+        ));
+    }
+}
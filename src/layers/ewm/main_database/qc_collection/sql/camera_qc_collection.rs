@@ -0,0 +1,315 @@
+use chrono::Utc;
+use sqlx::{any::AnyRow, AnyConnection, AnyPool, FromRow, Row};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    camera_qc_collection::{
+        CameraListQueryResultItem, CameraStreamMetadata, CheckIfCameraExistsError, CreateCameraCommandError,
+        CreateCameraCommandOutput, DeleteCameraCommandError, FindCameraByIdResult, FindCamerabyIdError,
+        ICameraQCCollection, ListCamerasQueryError, PutCameraCommandInput, UpdateCameraCommandError,
+        UpdateCameraCommandInput, UpdateCameraStreamMetadataError,
+    },
+    error::QCError,
+    sql::{database_error, get_date, get_flag},
+};
+
+const CAMERA_COLUMNS: &str = "id, name, source_url, permanent_stream_url, stream_video_codec, stream_width, \
+    stream_height, stream_framerate, stream_has_audio, stream_audio_codec, stream_probed_at, created_at, updated_at";
+
+impl FromRow<'_, AnyRow> for FindCameraByIdResult {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        let stream_metadata = match row.try_get::<Option<String>, _>("stream_probed_at")? {
+            Some(_) => Some(CameraStreamMetadata {
+                video_codec: row.try_get("stream_video_codec")?,
+                width: row.try_get::<Option<i64>, _>("stream_width")?.and_then(|v| v.try_into().ok()),
+                height: row.try_get::<Option<i64>, _>("stream_height")?.and_then(|v| v.try_into().ok()),
+                framerate: row.try_get("stream_framerate")?,
+                has_audio: get_flag(row, "stream_has_audio")?,
+                audio_codec: row.try_get("stream_audio_codec")?,
+                probed_at: get_date(row, "stream_probed_at")?,
+            }),
+            None => None,
+        };
+        Ok(FindCameraByIdResult {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            source_url: row.try_get("source_url")?,
+            permanent_stream_url: row.try_get("permanent_stream_url")?,
+            stream_metadata,
+            created_at: get_date(row, "created_at")?,
+            updated_at: get_date(row, "updated_at")?,
+        })
+    }
+}
+
+/// Cameras in the `cameras` table, listed by id.
+#[derive(Clone)]
+pub struct SqlCameraQCCollection {
+    pool: AnyPool,
+}
+
+impl SqlCameraQCCollection {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Overwrites every metadata column; `None` clears them all. Returns whether
+/// the camera exists.
+async fn write_stream_metadata(
+    connection: &mut AnyConnection,
+    id: &str,
+    stream_metadata: Option<&CameraStreamMetadata>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE cameras SET stream_video_codec = $2, stream_width = $3, stream_height = $4, stream_framerate = $5, \
+         stream_has_audio = $6, stream_audio_codec = $7, stream_probed_at = $8 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(stream_metadata.and_then(|m| m.video_codec.clone()))
+    .bind(stream_metadata.and_then(|m| m.width).map(i64::from))
+    .bind(stream_metadata.and_then(|m| m.height).map(i64::from))
+    .bind(stream_metadata.and_then(|m| m.framerate))
+    .bind(stream_metadata.map(|m| i64::from(m.has_audio)))
+    .bind(stream_metadata.and_then(|m| m.audio_codec.clone()))
+    .bind(stream_metadata.map(|m| m.probed_at.to_rfc3339()))
+    .execute(connection)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+impl ICameraQCCollection for SqlCameraQCCollection {
+    async fn list_cameras(&self) -> Result<Vec<CameraListQueryResultItem>, ListCamerasQueryError> {
+        let rows = sqlx::query("SELECT id, name, source_url FROM cameras ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListCamerasQueryError(database_error("failed to list cameras", err)))?;
+        rows.iter()
+            .map(|row| {
+                Ok(CameraListQueryResultItem {
+                    id: row.try_get("id")?,
+                    name: row.try_get("name")?,
+                    source_url: row.try_get("source_url")?,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(|err| ListCamerasQueryError(database_error("failed to parse camera", err)))
+    }
+
+    async fn put_camera(
+        &self,
+        command_input: PutCameraCommandInput,
+    ) -> Result<CreateCameraCommandOutput, CreateCameraCommandError> {
+        let now = Utc::now();
+        let result = CreateCameraCommandOutput {
+            id: command_input.id.unwrap_or(ulid::Ulid::new().to_string()),
+            name: command_input.name,
+            source_url: command_input.source_url,
+            permanent_stream_url: command_input.permanent_stream_url,
+            stream_metadata: command_input.stream_metadata,
+            created_at: now,
+            updated_at: now,
+        };
+        let to_error = |err| CreateCameraCommandError(database_error("failed to create camera", err));
+
+        let mut transaction = self.pool.begin().await.map_err(to_error)?;
+        // a put replaces the whole camera, like the DynamoDB item
+        sqlx::query(
+            "INSERT INTO cameras (id, name, source_url, permanent_stream_url, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (id) DO UPDATE SET name = excluded.name, source_url = excluded.source_url, \
+             permanent_stream_url = excluded.permanent_stream_url, created_at = excluded.created_at, \
+             updated_at = excluded.updated_at",
+        )
+        .bind(&result.id)
+        .bind(&result.name)
+        .bind(&result.source_url)
+        .bind(result.permanent_stream_url.clone())
+        .bind(result.created_at.to_rfc3339())
+        .bind(result.updated_at.to_rfc3339())
+        .execute(&mut *transaction)
+        .await
+        .map_err(to_error)?;
+        write_stream_metadata(&mut transaction, &result.id, result.stream_metadata.as_ref())
+            .await
+            .map_err(to_error)?;
+        transaction.commit().await.map_err(to_error)?;
+        Ok(result)
+    }
+
+    async fn delete_camera_by_id(&self, id: &str) -> Result<(), DeleteCameraCommandError> {
+        sqlx::query("DELETE FROM cameras WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteCameraCommandError(database_error("failed to delete camera", err)))?;
+        Ok(())
+    }
+
+    async fn find_camera_by_id(&self, id: &str) -> Result<FindCameraByIdResult, FindCamerabyIdError> {
+        sqlx::query_as::<_, FindCameraByIdResult>(&format!("SELECT {} FROM cameras WHERE id = $1", CAMERA_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| FindCamerabyIdError(database_error("failed to find camera", err)))?
+            .ok_or_else(|| FindCamerabyIdError(QCError::new("Camera not found".to_string(), None)))
+    }
+
+    async fn camera_exists_by_id(&self, id: &str) -> Result<bool, CheckIfCameraExistsError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cameras WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| CheckIfCameraExistsError(database_error("failed to check if camera exists", err)))?;
+        Ok(count > 0)
+    }
+
+    async fn update_camera_stream_metadata(
+        &self,
+        id: &str,
+        stream_metadata: CameraStreamMetadata,
+    ) -> Result<(), UpdateCameraStreamMetadataError> {
+        let to_error = |err| {
+            UpdateCameraStreamMetadataError(database_error("failed to update camera stream metadata in database", err))
+        };
+        let mut connection = self.pool.acquire().await.map_err(to_error)?;
+        if !write_stream_metadata(&mut connection, id, Some(&stream_metadata))
+            .await
+            .map_err(to_error)?
+        {
+            return Err(UpdateCameraStreamMetadataError(QCError::new(
+                "failed to update camera stream metadata in database".to_string(),
+                Some(format!("camera {} not found", id)),
+            )));
+        }
+        Ok(())
+    }
+
+    async fn update_camera(
+        &self,
+        command_input: UpdateCameraCommandInput,
+    ) -> Result<FindCameraByIdResult, UpdateCameraCommandError> {
+        let to_error = |err| UpdateCameraCommandError(database_error("failed to update camera in database", err));
+        let not_found = || {
+            UpdateCameraCommandError(QCError::new(
+                "failed to update camera in database".to_string(),
+                Some(format!("camera {} not found", command_input.id)),
+            ))
+        };
+
+        let mut transaction = self.pool.begin().await.map_err(to_error)?;
+        let updated = sqlx::query(
+            "UPDATE cameras SET name = COALESCE($2, name), source_url = COALESCE($3, source_url), \
+             permanent_stream_url = COALESCE($4, permanent_stream_url), updated_at = $5 WHERE id = $1",
+        )
+        .bind(&command_input.id)
+        .bind(command_input.name.clone())
+        .bind(command_input.source_url.clone())
+        .bind(command_input.permanent_stream_url.clone())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *transaction)
+        .await
+        .map_err(to_error)?;
+        if updated.rows_affected() == 0 {
+            return Err(not_found());
+        }
+        if let Some(stream_metadata) = &command_input.stream_metadata {
+            write_stream_metadata(&mut transaction, &command_input.id, stream_metadata.as_ref())
+                .await
+                .map_err(to_error)?;
+        }
+        let camera =
+            sqlx::query_as::<_, FindCameraByIdResult>(&format!("SELECT {} FROM cameras WHERE id = $1", CAMERA_COLUMNS))
+                .bind(&command_input.id)
+                .fetch_one(&mut *transaction)
+                .await
+                .map_err(to_error)?;
+        transaction.commit().await.map_err(to_error)?;
+        Ok(camera)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::ewm::main_database::qc_collection::sql::connect_in_memory;
+
+    fn put_input(id: &str, name: &str) -> PutCameraCommandInput {
+        PutCameraCommandInput {
+            id: Some(id.to_string()),
+            name: name.to_string(),
+            source_url: format!("rtsp://{}", id),
+            permanent_stream_url: None,
+            stream_metadata: None,
+        }
+    }
+
+    fn update_input(id: &str) -> UpdateCameraCommandInput {
+        UpdateCameraCommandInput {
+            id: id.to_string(),
+            name: None,
+            source_url: None,
+            permanent_stream_url: None,
+            stream_metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_update_and_delete_cameras() {
+        let collection = SqlCameraQCCollection::new(connect_in_memory().await);
+        collection.put_camera(put_input("b", "Garage")).await.unwrap();
+        collection.put_camera(put_input("a", "Door")).await.unwrap();
+
+        let ids: Vec<String> = collection.list_cameras().await.unwrap().into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let updated = collection
+            .update_camera(UpdateCameraCommandInput {
+                name: Some("Carport".to_string()),
+                ..update_input("b")
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Carport");
+        assert_eq!(updated.source_url, "rtsp://b");
+
+        collection.delete_camera_by_id("b").await.unwrap();
+        assert!(!collection.camera_exists_by_id("b").await.unwrap());
+        assert!(collection.find_camera_by_id("b").await.is_err());
+        assert!(collection.update_camera(update_input("b")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_metadata_round_trip_and_clear() {
+        let collection = SqlCameraQCCollection::new(connect_in_memory().await);
+        collection.put_camera(put_input("a", "Door")).await.unwrap();
+        collection
+            .update_camera_stream_metadata(
+                "a",
+                CameraStreamMetadata {
+                    video_codec: Some("h264".to_string()),
+                    width: Some(1920),
+                    height: Some(1080),
+                    framerate: Some(25.0),
+                    has_audio: true,
+                    audio_codec: None,
+                    probed_at: Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let metadata = collection.find_camera_by_id("a").await.unwrap().stream_metadata.unwrap();
+        assert_eq!(metadata.video_codec.as_deref(), Some("h264"));
+        assert_eq!((metadata.width, metadata.height, metadata.framerate), (Some(1920), Some(1080), Some(25.0)));
+        assert!(metadata.has_audio);
+
+        let cleared = collection
+            .update_camera(UpdateCameraCommandInput {
+                stream_metadata: Some(None),
+                ..update_input("a")
+            })
+            .await
+            .unwrap();
+        assert!(cleared.stream_metadata.is_none());
+    }
+}
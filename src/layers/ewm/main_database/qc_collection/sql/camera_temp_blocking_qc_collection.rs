@@ -0,0 +1,431 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sqlx::{any::AnyRow, AnyPool, FromRow, Row};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    camera_temp_blocking_qc_collection::{
+        CameraPrivacyShutter, CameraTempBlocking, CreateCameraTempBlockingError, CreateCameraTempBlockingInput,
+        CreateRecurringCameraBlockingError, DeleteCameraTempBlockingError, GetCameraTempBlockingError,
+        ICameraTempBlockingQCCollection, ListCameraTempBlockingsQueryError, PutCameraPrivacyShutterError,
+        RecurringCameraBlocking, UpdateCameraTempBlockingError, UpdateCameraTempBlockingInput,
+        MAX_USERS_PER_TEMP_BLOCKING,
+    },
+    error::QCError,
+    sql::{database_error, get_flag, is_unique_violation, placeholders},
+};
+
+const BLOCKING_COLUMNS: &str =
+    "user_id, camera_id, start_date, end_date, reason, share_reason, created_by, created_at, updated_at";
+const RECURRING_COLUMNS: &str = "user_id, id, camera_id, rrule, start_time, end_time, timezone";
+const SHUTTER_COLUMNS: &str = "camera_id, start_date, end_date, reason, share_reason, created_by, created_at";
+
+impl FromRow<'_, AnyRow> for CameraTempBlocking {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        let camera_id: String = row.try_get("camera_id")?;
+        Ok(CameraTempBlocking {
+            id: camera_id.clone(),
+            camera_id,
+            user_id: row.try_get("user_id")?,
+            start_date: row.try_get("start_date")?,
+            end_date: row.try_get("end_date")?,
+            reason: row.try_get("reason")?,
+            share_reason: get_flag(row, "share_reason")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, AnyRow> for RecurringCameraBlocking {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(RecurringCameraBlocking {
+            id: row.try_get("id")?,
+            camera_id: row.try_get("camera_id")?,
+            user_id: row.try_get("user_id")?,
+            rrule: row.try_get("rrule")?,
+            start_time: row.try_get("start_time")?,
+            end_time: row.try_get("end_time")?,
+            timezone: row.try_get("timezone")?,
+        })
+    }
+}
+
+impl FromRow<'_, AnyRow> for CameraPrivacyShutter {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(CameraPrivacyShutter {
+            camera_id: row.try_get("camera_id")?,
+            start_date: row.try_get("start_date")?,
+            end_date: row.try_get("end_date")?,
+            reason: row.try_get("reason")?,
+            share_reason: get_flag(row, "share_reason")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Blockings in the `camera_temp_blockings`, `recurring_camera_blockings`
+/// and `camera_privacy_shutters` tables. Per-camera listings use the camera
+/// indexes and come back in the same order as the DynamoDB camera index.
+#[derive(Clone)]
+pub struct SqlCameraTempBlockingQCCollection {
+    pool: AnyPool,
+}
+
+impl SqlCameraTempBlockingQCCollection {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+
+    async fn list_blockings(
+        &self,
+        condition: &str,
+        value: Option<&str>,
+    ) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        let sql = format!("SELECT {} FROM camera_temp_blockings {}", BLOCKING_COLUMNS, condition);
+        let mut query = sqlx::query_as::<_, CameraTempBlocking>(&sql);
+        if let Some(value) = value {
+            query = query.bind(value);
+        }
+        query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListCameraTempBlockingsQueryError(database_error("failed to list camera temp blockings", err)))
+    }
+
+    async fn list_recurring_blockings(
+        &self,
+        condition: &str,
+        value: &str,
+    ) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        let sql = format!("SELECT {} FROM recurring_camera_blockings {}", RECURRING_COLUMNS, condition);
+        sqlx::query_as::<_, RecurringCameraBlocking>(&sql)
+            .bind(value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| {
+                ListCameraTempBlockingsQueryError(database_error("failed to list recurring camera blockings", err))
+            })
+    }
+}
+
+impl ICameraTempBlockingQCCollection for SqlCameraTempBlockingQCCollection {
+    async fn list_temp_blocking_for_user(&self, user_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        self.list_blockings("WHERE user_id = $1 ORDER BY camera_id", Some(user_id)).await
+    }
+
+    async fn list_temp_blocking_for_camera(&self, camera_id: &str) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        self.list_blockings("WHERE camera_id = $1 ORDER BY user_id", Some(camera_id)).await
+    }
+
+    async fn create_temp_blocking(&self, input: CreateCameraTempBlockingInput) -> Result<(), CreateCameraTempBlockingError> {
+        let unique_user_ids: HashSet<&String> = input.user_ids.iter().collect();
+        if input.user_ids.len() > MAX_USERS_PER_TEMP_BLOCKING || unique_user_ids.len() != input.user_ids.len() {
+            return Err(CreateCameraTempBlockingError::Database(QCError::new(
+                "failed to create camera temp blocking".to_string(),
+                Some(format!(
+                    "a transaction takes at most {} distinct users",
+                    MAX_USERS_PER_TEMP_BLOCKING
+                )),
+            )));
+        }
+        let to_error =
+            |err| CreateCameraTempBlockingError::Database(database_error("failed to create camera temp blocking", err));
+
+        let mut transaction = self.pool.begin().await.map_err(to_error)?;
+        let sql = format!(
+            "SELECT user_id FROM camera_temp_blockings WHERE camera_id = $1 AND user_id IN ({})",
+            placeholders(2, input.user_ids.len())
+        );
+        let mut query = sqlx::query_scalar::<_, String>(&sql).bind(&input.camera_id);
+        for user_id in &input.user_ids {
+            query = query.bind(user_id);
+        }
+        let blocked: HashSet<String> = query
+            .fetch_all(&mut *transaction)
+            .await
+            .map_err(to_error)?
+            .into_iter()
+            .collect();
+        if !blocked.is_empty() {
+            let conflicts = input.user_ids.into_iter().filter(|user_id| blocked.contains(user_id)).collect();
+            return Err(CreateCameraTempBlockingError::AlreadyBlocked(conflicts));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        for user_id in &input.user_ids {
+            let inserted = sqlx::query(
+                "INSERT INTO camera_temp_blockings \
+                 (user_id, camera_id, start_date, end_date, reason, share_reason, created_by, created_at, updated_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)",
+            )
+            .bind(user_id)
+            .bind(&input.camera_id)
+            .bind(&input.start_time)
+            .bind(&input.end_time)
+            .bind(input.reason.clone())
+            .bind(i64::from(input.share_reason))
+            .bind(&input.created_by)
+            .bind(&now)
+            .execute(&mut *transaction)
+            .await;
+            match inserted {
+                // blocked by a concurrent request since the check above
+                Err(err) if is_unique_violation(&err) => {
+                    return Err(CreateCameraTempBlockingError::AlreadyBlocked(vec![user_id.clone()]))
+                }
+                Err(err) => return Err(to_error(err)),
+                Ok(_) => {}
+            }
+        }
+        transaction.commit().await.map_err(to_error)?;
+        Ok(())
+    }
+
+    async fn delete_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        sqlx::query("DELETE FROM camera_temp_blockings WHERE user_id = $1 AND camera_id = $2")
+            .bind(user_id)
+            .bind(camera_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteCameraTempBlockingError(database_error("failed to delete camera temp blocking", err)))?;
+        Ok(())
+    }
+
+    async fn list_all_temp_blockings(&self) -> Result<Vec<CameraTempBlocking>, ListCameraTempBlockingsQueryError> {
+        self.list_blockings("ORDER BY user_id, camera_id", None).await
+    }
+
+    async fn delete_temp_blocking_ending_at(&self, camera_id: &str, user_id: &str, end_date: &str) -> Result<bool, DeleteCameraTempBlockingError> {
+        let result = sqlx::query("DELETE FROM camera_temp_blockings WHERE user_id = $1 AND camera_id = $2 AND end_date = $3")
+            .bind(user_id)
+            .bind(camera_id)
+            .bind(end_date)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteCameraTempBlockingError(database_error("failed to delete camera temp blocking", err)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_temp_blocking(&self, camera_id: &str, user_id: &str) -> Result<Option<CameraTempBlocking>, GetCameraTempBlockingError> {
+        let sql = format!(
+            "SELECT {} FROM camera_temp_blockings WHERE user_id = $1 AND camera_id = $2",
+            BLOCKING_COLUMNS
+        );
+        sqlx::query_as::<_, CameraTempBlocking>(&sql)
+            .bind(user_id)
+            .bind(camera_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| GetCameraTempBlockingError(database_error("failed to get camera temp blocking", err)))
+    }
+
+    async fn update_temp_blocking(&self, camera_id: &str, user_id: &str, input: UpdateCameraTempBlockingInput) -> Result<Option<CameraTempBlocking>, UpdateCameraTempBlockingError> {
+        let sql = format!(
+            "UPDATE camera_temp_blockings SET start_date = COALESCE($3, start_date), end_date = COALESCE($4, end_date), \
+             reason = COALESCE($5, reason), share_reason = COALESCE($6, share_reason), updated_at = $7 \
+             WHERE user_id = $1 AND camera_id = $2 AND COALESCE(start_date, '') = COALESCE($8, '') AND end_date = $9 \
+             RETURNING {}",
+            BLOCKING_COLUMNS
+        );
+        sqlx::query_as::<_, CameraTempBlocking>(&sql)
+            .bind(user_id)
+            .bind(camera_id)
+            .bind(input.start_time)
+            .bind(input.end_time)
+            .bind(input.reason)
+            .bind(input.share_reason.map(i64::from))
+            .bind(Utc::now().to_rfc3339())
+            .bind(input.expected_start_date)
+            .bind(input.expected_end_date)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| UpdateCameraTempBlockingError(database_error("failed to update camera temp blocking", err)))
+    }
+
+    async fn list_privacy_shutters(&self) -> Result<Vec<CameraPrivacyShutter>, ListCameraTempBlockingsQueryError> {
+        let sql = format!("SELECT {} FROM camera_privacy_shutters ORDER BY camera_id", SHUTTER_COLUMNS);
+        sqlx::query_as::<_, CameraPrivacyShutter>(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListCameraTempBlockingsQueryError(database_error("failed to list privacy shutters", err)))
+    }
+
+    async fn get_privacy_shutter(&self, camera_id: &str) -> Result<Option<CameraPrivacyShutter>, GetCameraTempBlockingError> {
+        let sql = format!("SELECT {} FROM camera_privacy_shutters WHERE camera_id = $1", SHUTTER_COLUMNS);
+        sqlx::query_as::<_, CameraPrivacyShutter>(&sql)
+            .bind(camera_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| GetCameraTempBlockingError(database_error("failed to get privacy shutter", err)))
+    }
+
+    async fn put_privacy_shutter(&self, shutter: CameraPrivacyShutter) -> Result<(), PutCameraPrivacyShutterError> {
+        sqlx::query(
+            "INSERT INTO camera_privacy_shutters (camera_id, start_date, end_date, reason, share_reason, created_by, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (camera_id) DO UPDATE SET start_date = excluded.start_date, end_date = excluded.end_date, \
+             reason = excluded.reason, share_reason = excluded.share_reason, created_by = excluded.created_by, \
+             created_at = excluded.created_at",
+        )
+        .bind(shutter.camera_id)
+        .bind(shutter.start_date)
+        .bind(shutter.end_date)
+        .bind(shutter.reason)
+        .bind(i64::from(shutter.share_reason))
+        .bind(shutter.created_by)
+        .bind(shutter.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| PutCameraPrivacyShutterError(database_error("failed to put privacy shutter", err)))?;
+        Ok(())
+    }
+
+    async fn delete_privacy_shutter(&self, camera_id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        sqlx::query("DELETE FROM camera_privacy_shutters WHERE camera_id = $1")
+            .bind(camera_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteCameraTempBlockingError(database_error("failed to delete privacy shutter", err)))?;
+        Ok(())
+    }
+
+    async fn list_recurring_blockings_for_user(&self, user_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        self.list_recurring_blockings("WHERE user_id = $1 ORDER BY id", user_id).await
+    }
+
+    async fn list_recurring_blockings_for_camera(&self, camera_id: &str) -> Result<Vec<RecurringCameraBlocking>, ListCameraTempBlockingsQueryError> {
+        self.list_recurring_blockings("WHERE camera_id = $1 ORDER BY id", camera_id).await
+    }
+
+    async fn create_recurring_blocking(&self, blocking: RecurringCameraBlocking) -> Result<(), CreateRecurringCameraBlockingError> {
+        sqlx::query(
+            "INSERT INTO recurring_camera_blockings (user_id, id, camera_id, rrule, start_time, end_time, timezone) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (user_id, id) DO UPDATE SET camera_id = excluded.camera_id, rrule = excluded.rrule, \
+             start_time = excluded.start_time, end_time = excluded.end_time, timezone = excluded.timezone",
+        )
+        .bind(blocking.user_id)
+        .bind(blocking.id)
+        .bind(blocking.camera_id)
+        .bind(blocking.rrule)
+        .bind(blocking.start_time)
+        .bind(blocking.end_time)
+        .bind(blocking.timezone)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| CreateRecurringCameraBlockingError(database_error("failed to create recurring camera blocking", err)))?;
+        Ok(())
+    }
+
+    async fn delete_recurring_blocking(&self, user_id: &str, id: &str) -> Result<(), DeleteCameraTempBlockingError> {
+        sqlx::query("DELETE FROM recurring_camera_blockings WHERE user_id = $1 AND id = $2")
+            .bind(user_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteCameraTempBlockingError(database_error("failed to delete recurring camera blocking", err)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::ewm::main_database::qc_collection::sql::connect_in_memory;
+
+    fn create_input(camera_id: &str, user_ids: &[&str], end_time: &str) -> CreateCameraTempBlockingInput {
+        CreateCameraTempBlockingInput {
+            camera_id: camera_id.to_string(),
+            start_time: "2025-01-14T00:00:00Z".to_string(),
+            end_time: end_time.to_string(),
+            user_ids: user_ids.iter().map(|u| u.to_string()).collect(),
+            reason: Some("maintenance".to_string()),
+            share_reason: true,
+            created_by: "auth0|admin".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_blocks_everyone_or_nobody() {
+        let collection = SqlCameraTempBlockingQCCollection::new(connect_in_memory().await);
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|2"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+
+        let result = collection
+            .create_temp_blocking(create_input("garage", &["auth0|1", "auth0|2", "auth0|3"], "2025-01-20T00:00:00Z"))
+            .await;
+        assert!(matches!(result, Err(CreateCameraTempBlockingError::AlreadyBlocked(users)) if users == vec!["auth0|2"]));
+        assert_eq!(collection.list_temp_blocking_for_camera("garage").await.unwrap().len(), 1);
+
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|3", "auth0|1"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+        let blockings = collection.list_temp_blocking_for_camera("garage").await.unwrap();
+        let users: Vec<&str> = blockings.iter().map(|b| b.user_id.as_str()).collect();
+        assert_eq!(users, vec!["auth0|1", "auth0|2", "auth0|3"]);
+        assert!(blockings[0].share_reason);
+        assert_eq!(blockings[0].reason.as_deref(), Some("maintenance"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_ending_at_spares_extended_blockings() {
+        let collection = SqlCameraTempBlockingQCCollection::new(connect_in_memory().await);
+        collection
+            .create_temp_blocking(create_input("garage", &["auth0|1"], "2025-01-20T00:00:00Z"))
+            .await
+            .unwrap();
+        let updated = collection
+            .update_temp_blocking(
+                "garage",
+                "auth0|1",
+                UpdateCameraTempBlockingInput {
+                    start_time: None,
+                    end_time: Some("2025-02-01T00:00:00Z".to_string()),
+                    reason: None,
+                    share_reason: Some(false),
+                    expected_start_date: Some("2025-01-14T00:00:00Z".to_string()),
+                    expected_end_date: "2025-01-20T00:00:00Z".to_string(),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.end_date, "2025-02-01T00:00:00Z");
+        assert_eq!(updated.reason.as_deref(), Some("maintenance"));
+        assert!(!updated.share_reason);
+
+        // the end date read before the first update is stale now
+        let stale = collection
+            .update_temp_blocking(
+                "garage",
+                "auth0|1",
+                UpdateCameraTempBlockingInput {
+                    start_time: None,
+                    end_time: Some("2025-01-25T00:00:00Z".to_string()),
+                    reason: None,
+                    share_reason: None,
+                    expected_start_date: Some("2025-01-14T00:00:00Z".to_string()),
+                    expected_end_date: "2025-01-20T00:00:00Z".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(stale.is_none());
+
+        assert!(!collection
+            .delete_temp_blocking_ending_at("garage", "auth0|1", "2025-01-20T00:00:00Z")
+            .await
+            .unwrap());
+        assert!(collection
+            .delete_temp_blocking_ending_at("garage", "auth0|1", "2025-02-01T00:00:00Z")
+            .await
+            .unwrap());
+        assert!(collection.get_temp_blocking("garage", "auth0|1").await.unwrap().is_none());
+    }
+}
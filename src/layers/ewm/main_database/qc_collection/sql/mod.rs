@@ -0,0 +1,149 @@
+pub mod blocking_history_qc_collection;
+pub mod camera_qc_collection;
+pub mod camera_temp_blocking_qc_collection;
+pub mod temp_stream_session_qc_collection;
+pub mod user_qc_collection;
+
+use std::{borrow::Cow, future::Future, pin::Pin};
+
+use chrono::{DateTime, Utc};
+use sqlx::{
+    any::{install_default_drivers, AnyRow},
+    error::BoxDynError,
+    migrate::{Migration, MigrationSource, MigrationType, Migrator},
+    AnyPool, Row,
+};
+
+use crate::layers::ewm::main_database::qc_collection::error::QCError;
+
+/// Schema changes in the order they are applied. Applied migrations are
+/// checksummed, so changes go into a new file instead of an edited one.
+const MIGRATIONS: &[(i64, &str, &str)] = &[
+    (
+        1,
+        "create tables",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/0001_create_tables.sql")),
+    ),
+    (
+        2,
+        "index temp stream session expiry",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/0002_index_temp_stream_session_expiry.sql")),
+    ),
+];
+
+/// Serves the migrations compiled into the binary, so deployments do not
+/// need the `migrations` directory next to it.
+#[derive(Debug)]
+struct EmbeddedMigrations;
+
+impl MigrationSource<'static> for EmbeddedMigrations {
+    fn resolve(self) -> Pin<Box<dyn Future<Output = Result<Vec<Migration>, BoxDynError>> + Send>> {
+        Box::pin(async {
+            Ok(MIGRATIONS
+                .iter()
+                .map(|(version, description, sql)| {
+                    Migration::new(
+                        *version,
+                        Cow::Borrowed(*description),
+                        MigrationType::Simple,
+                        Cow::Borrowed(*sql),
+                        false,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectSqlDatabaseError(pub QCError);
+
+/// Connects to a `sqlite://` or `postgres://` URL and brings the schema up
+/// to date.
+pub async fn connect(database_url: &str) -> Result<AnyPool, ConnectSqlDatabaseError> {
+    install_default_drivers();
+    let pool = AnyPool::connect(database_url).await.map_err(|err| {
+        ConnectSqlDatabaseError(database_error("failed to connect to sql database", err))
+    })?;
+    migrate(&pool).await?;
+    Ok(pool)
+}
+
+async fn migrate(pool: &AnyPool) -> Result<(), ConnectSqlDatabaseError> {
+    let migrator = Migrator::new(EmbeddedMigrations).await.map_err(|err| {
+        ConnectSqlDatabaseError(QCError::new("failed to load migrations".to_string(), Some(format!("{:?}", err))))
+    })?;
+    migrator.run(pool).await.map_err(|err| {
+        ConnectSqlDatabaseError(QCError::new("failed to run migrations".to_string(), Some(format!("{:?}", err))))
+    })
+}
+
+fn database_error(message: &str, err: sqlx::Error) -> QCError {
+    QCError::new(message.to_string(), Some(format!("{:?}", err)))
+}
+
+fn is_unique_violation(err: &sqlx::Error) -> bool {
+    err.as_database_error().is_some_and(|err| err.is_unique_violation())
+}
+
+/// `$first, $first + 1, ...` for an `IN` list of `count` values.
+fn placeholders(first: usize, count: usize) -> String {
+    (first..first + count)
+        .map(|index| format!("${}", index))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Flags are stored as 0/1 since the Any driver cannot read SQLite booleans.
+fn get_flag(row: &AnyRow, column: &str) -> Result<bool, sqlx::Error> {
+    Ok(row.try_get::<i64, _>(column)? != 0)
+}
+
+fn get_date(row: &AnyRow, column: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+    let value: String = row.try_get(column)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|err| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(err),
+        })
+}
+
+/// A private in-memory SQLite database. A single connection that is never
+/// recycled, since every connection to `sqlite::memory:` gets its own
+/// database.
+#[cfg(test)]
+async fn connect_in_memory() -> AnyPool {
+    install_default_drivers();
+    let pool = sqlx::any::AnyPoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    migrate(&pool).await.unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders(2, 3), "$2, $3, $4");
+        assert_eq!(placeholders(1, 0), "");
+    }
+
+    #[tokio::test]
+    async fn test_migrations_run_once() {
+        let pool = connect_in_memory().await;
+        migrate(&pool).await.unwrap();
+        let applied: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(applied, MIGRATIONS.len() as i64);
+    }
+}
@@ -0,0 +1,198 @@
+use chrono::{SecondsFormat, Utc};
+use sqlx::{any::AnyRow, AnyPool, FromRow, Row};
+
+use crate::layers::ewm::{
+    main_database::qc_collection::{
+        sql::{database_error, get_date, get_flag},
+        temp_stream_session_qc_collection::{
+            ActiveTempStreamSessionCounts, CountTempStreamSessionsError, CreateTempStreamSessionError,
+            DeleteTempStreamSessionError, FindTempStreamSessionError, ITempStreamSessionQCCollection,
+            ListTempStreamSessionsQueryError, TempStreamSession,
+        },
+    },
+    temporary_stream_server::StreamQuality,
+};
+
+const SESSION_COLUMNS: &str = "id, user_id, camera_id, stream_id, stream_url, hls_url, whep_url, quality, token, \
+    privacy_shutter_exempt, issued_at, expires_at";
+
+impl FromRow<'_, AnyRow> for TempStreamSession {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(TempStreamSession {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            camera_id: row.try_get("camera_id")?,
+            stream_id: row.try_get("stream_id")?,
+            stream_url: row.try_get("stream_url")?,
+            hls_url: row.try_get("hls_url")?,
+            whep_url: row.try_get("whep_url")?,
+            quality: row
+                .try_get::<String, _>("quality")?
+                .parse()
+                .unwrap_or(StreamQuality::Original),
+            token: row.try_get("token")?,
+            privacy_shutter_exempt: get_flag(row, "privacy_shutter_exempt")?,
+            issued_at: get_date(row, "issued_at")?,
+            expires_at: get_date(row, "expires_at")?,
+        })
+    }
+}
+
+/// Sessions in the `temp_stream_sessions` table; per-user listings use the
+/// user index.
+#[derive(Clone)]
+pub struct SqlTempStreamSessionQCCollection {
+    pool: AnyPool,
+}
+
+impl SqlTempStreamSessionQCCollection {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl ITempStreamSessionQCCollection for SqlTempStreamSessionQCCollection {
+    async fn create_session(&self, session: TempStreamSession) -> Result<(), CreateTempStreamSessionError> {
+        sqlx::query(
+            "INSERT INTO temp_stream_sessions (id, user_id, camera_id, stream_id, stream_url, hls_url, whep_url, \
+             quality, token, privacy_shutter_exempt, issued_at, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(session.id)
+        .bind(session.user_id)
+        .bind(session.camera_id)
+        .bind(session.stream_id)
+        .bind(session.stream_url)
+        .bind(session.hls_url)
+        .bind(session.whep_url)
+        .bind(session.quality.as_str())
+        .bind(session.token)
+        .bind(i64::from(session.privacy_shutter_exempt))
+        .bind(session.issued_at.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(session.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .execute(&self.pool)
+        .await
+        .map_err(|err| CreateTempStreamSessionError(database_error("failed to create temp stream session", err)))?;
+        Ok(())
+    }
+
+    async fn list_sessions_for_user(&self, user_id: &str) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        let sql = format!("SELECT {} FROM temp_stream_sessions WHERE user_id = $1 ORDER BY id", SESSION_COLUMNS);
+        sqlx::query_as::<_, TempStreamSession>(&sql)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListTempStreamSessionsQueryError(database_error("failed to list temp stream sessions", err)))
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        let sql = format!("SELECT {} FROM temp_stream_sessions ORDER BY user_id, id", SESSION_COLUMNS);
+        sqlx::query_as::<_, TempStreamSession>(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListTempStreamSessionsQueryError(database_error("failed to list temp stream sessions", err)))
+    }
+
+    async fn find_session_by_id(&self, session_id: &str) -> Result<Option<TempStreamSession>, FindTempStreamSessionError> {
+        let sql = format!("SELECT {} FROM temp_stream_sessions WHERE id = $1", SESSION_COLUMNS);
+        sqlx::query_as::<_, TempStreamSession>(&sql)
+            .bind(session_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| FindTempStreamSessionError(database_error("failed to find temp stream session", err)))
+    }
+
+    async fn delete_session(&self, user_id: &str, session_id: &str) -> Result<(), DeleteTempStreamSessionError> {
+        sqlx::query("DELETE FROM temp_stream_sessions WHERE user_id = $1 AND id = $2")
+            .bind(user_id)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| DeleteTempStreamSessionError(database_error("failed to delete temp stream session", err)))?;
+        Ok(())
+    }
+
+    async fn count_active_sessions(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+    ) -> Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS total, \
+             COALESCE(SUM(CASE WHEN user_id = $1 THEN 1 ELSE 0 END), 0) AS user_count, \
+             COALESCE(SUM(CASE WHEN camera_id = $2 THEN 1 ELSE 0 END), 0) AS camera_count \
+             FROM temp_stream_sessions WHERE expires_at > $3",
+        )
+        .bind(user_id)
+        .bind(camera_id)
+        .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| CountTempStreamSessionsError(database_error("failed to count temp stream sessions", err)))?;
+        let count = |name: &str| {
+            row.try_get::<i64, _>(name)
+                .map(|count| count as usize)
+                .map_err(|err| CountTempStreamSessionsError(database_error("failed to count temp stream sessions", err)))
+        };
+        Ok(ActiveTempStreamSessionCounts {
+            user: count("user_count")?,
+            camera: count("camera_count")?,
+            total: count("total")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::layers::ewm::main_database::qc_collection::sql::connect_in_memory;
+
+    fn session(id: &str, user_id: &str) -> TempStreamSession {
+        let issued_at = Utc::now();
+        TempStreamSession {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            camera_id: "garage".to_string(),
+            stream_id: format!("stream-{}", id),
+            stream_url: format!("rtsp://media/{}", id),
+            hls_url: Some(format!("https://media/{}/index.m3u8", id)),
+            whep_url: None,
+            quality: StreamQuality::Original,
+            token: "secret".to_string(),
+            privacy_shutter_exempt: true,
+            issued_at,
+            expires_at: issued_at + Duration::minutes(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_find_and_delete_sessions() {
+        let collection = SqlTempStreamSessionQCCollection::new(connect_in_memory().await);
+        collection.create_session(session("s2", "auth0|1")).await.unwrap();
+        collection.create_session(session("s1", "auth0|1")).await.unwrap();
+        collection.create_session(session("s3", "auth0|2")).await.unwrap();
+
+        let ids: Vec<String> = collection
+            .list_sessions_for_user("auth0|1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec!["s1", "s2"]);
+
+        let found = collection.find_session_by_id("s3").await.unwrap().unwrap();
+        assert_eq!(found.user_id, "auth0|2");
+        assert!(found.privacy_shutter_exempt);
+        assert!(found.whep_url.is_none());
+
+        let counts = collection.count_active_sessions("auth0|1", "garage").await.unwrap();
+        assert_eq!(counts, ActiveTempStreamSessionCounts { user: 2, camera: 3, total: 3 });
+
+        collection.delete_session("auth0|2", "s3").await.unwrap();
+        assert!(collection.find_session_by_id("s3").await.unwrap().is_none());
+        assert_eq!(collection.list_sessions().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{any::AnyRow, AnyPool, FromRow, Row};
+
+use crate::layers::ewm::main_database::qc_collection::{
+    sql::{database_error, is_unique_violation, placeholders},
+    user_qc_collection::{
+        decode_user_cursor, encode_user_cursor, CreateUserCommandError, CreateUserCommandInput, FindUserByIdQueryError,
+        FindUsersByIdsQueryError, IUserQCCollection, ListUsersQueryError, SearchUsersQuery, SearchUsersQueryError,
+        SetRoleOverridesCommandInput, UpdateUserCommandError, UpdateUserCommandInput, UpsertUserCommandInput,
+        UserListQueryResultItem, UserSearchPage,
+    },
+};
+
+const USER_COLUMNS: &str = "user_id, email, name, roles, granted_roles, revoked_roles, deactivated_at, removed_at";
+
+/// Users read per query while searching; matching happens in
+/// `SearchUsersQuery::matches`, like for DynamoDB.
+const SEARCH_READ_PAGE_SIZE: usize = 200;
+
+fn get_roles(row: &AnyRow, column: &str) -> Result<Vec<String>, sqlx::Error> {
+    let value: String = row.try_get(column)?;
+    serde_json::from_str(&value).map_err(|err| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(err),
+    })
+}
+
+fn roles_to_json(roles: &[String]) -> String {
+    serde_json::to_string(roles).unwrap_or_else(|_| "[]".to_string())
+}
+
+impl FromRow<'_, AnyRow> for UserListQueryResultItem {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(UserListQueryResultItem {
+            user_id: row.try_get("user_id")?,
+            email: row.try_get("email")?,
+            name: row.try_get("name")?,
+            deactivated_at: row.try_get("deactivated_at")?,
+            roles: get_roles(row, "roles")?,
+            removed_at: row.try_get("removed_at")?,
+            granted_roles: get_roles(row, "granted_roles")?,
+            revoked_roles: get_roles(row, "revoked_roles")?,
+        })
+    }
+}
+
+/// Users in the `users` table, ordered by id. Role lists are stored as JSON
+/// arrays.
+#[derive(Clone)]
+pub struct SqlUserQCCollection {
+    pool: AnyPool,
+}
+
+impl SqlUserQCCollection {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+
+    /// Runs an `UPDATE ... WHERE user_id = $1` and returns the updated user.
+    async fn update(
+        &self,
+        assignments: &str,
+        user_id: &str,
+        values: Vec<Option<String>>,
+    ) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        let sql = format!("UPDATE users SET {} WHERE user_id = $1 RETURNING {}", assignments, USER_COLUMNS);
+        let mut query = sqlx::query_as::<_, UserListQueryResultItem>(&sql).bind(user_id);
+        for value in values {
+            query = query.bind(value);
+        }
+        query
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| UpdateUserCommandError(database_error("failed to update user in database", err)))
+    }
+}
+
+impl IUserQCCollection for SqlUserQCCollection {
+    async fn list_users(&self) -> Result<Vec<UserListQueryResultItem>, ListUsersQueryError> {
+        sqlx::query_as::<_, UserListQueryResultItem>(&format!("SELECT {} FROM users ORDER BY user_id", USER_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| ListUsersQueryError(database_error("failed to list users from database", err)))
+    }
+
+    async fn search_users(&self, query: SearchUsersQuery) -> Result<UserSearchPage, SearchUsersQueryError> {
+        // the cursor is the id of the last returned user; no id is empty
+        let mut after = match &query.cursor {
+            Some(cursor) => decode_user_cursor(cursor).ok_or(SearchUsersQueryError::InvalidCursor)?,
+            None => String::new(),
+        };
+        let sql = format!("SELECT {} FROM users WHERE user_id > $1 ORDER BY user_id LIMIT $2", USER_COLUMNS);
+
+        let mut users = Vec::new();
+        loop {
+            let batch = sqlx::query_as::<_, UserListQueryResultItem>(&sql)
+                .bind(&after)
+                .bind(SEARCH_READ_PAGE_SIZE as i64)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|err| SearchUsersQueryError::Database(database_error("failed to search users in database", err)))?;
+            let is_last_batch = batch.len() < SEARCH_READ_PAGE_SIZE;
+            let batch_len = batch.len();
+            if let Some(last) = batch.last() {
+                after = last.user_id.clone();
+            }
+
+            for (index, user) in batch.into_iter().enumerate() {
+                if !query.matches(&user) {
+                    continue;
+                }
+                users.push(user);
+                if users.len() >= query.limit {
+                    let is_last = is_last_batch && index + 1 == batch_len;
+                    let next_cursor = (!is_last).then(|| encode_user_cursor(&users[users.len() - 1].user_id));
+                    return Ok(UserSearchPage { users, next_cursor });
+                }
+            }
+
+            if is_last_batch {
+                return Ok(UserSearchPage { users, next_cursor: None });
+            }
+        }
+    }
+
+    async fn find_user_by_id(&self, user_id: &str) -> Result<Option<UserListQueryResultItem>, FindUserByIdQueryError> {
+        sqlx::query_as::<_, UserListQueryResultItem>(&format!("SELECT {} FROM users WHERE user_id = $1", USER_COLUMNS))
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| FindUserByIdQueryError(database_error("failed to find user in database", err)))
+    }
+
+    async fn find_users_by_ids(&self, user_ids: Vec<String>) -> Result<Vec<UserListQueryResultItem>, FindUsersByIdsQueryError> {
+        if user_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let sql = format!(
+            "SELECT {} FROM users WHERE user_id IN ({})",
+            USER_COLUMNS,
+            placeholders(1, user_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, UserListQueryResultItem>(&sql);
+        for user_id in &user_ids {
+            query = query.bind(user_id);
+        }
+        let mut found: HashMap<String, UserListQueryResultItem> = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| FindUsersByIdsQueryError(database_error("failed to find users in database", err)))?
+            .into_iter()
+            .map(|user| (user.user_id.clone(), user))
+            .collect();
+        Ok(user_ids.iter().filter_map(|user_id| found.remove(user_id)).collect())
+    }
+
+    async fn create_user(&self, input: CreateUserCommandInput) -> Result<UserListQueryResultItem, CreateUserCommandError> {
+        let sql = format!(
+            "INSERT INTO users (user_id, email, name) VALUES ($1, $2, $3) RETURNING {}",
+            USER_COLUMNS
+        );
+        sqlx::query_as::<_, UserListQueryResultItem>(&sql)
+            .bind(input.user_id)
+            .bind(input.email)
+            .bind(input.name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| match is_unique_violation(&err) {
+                true => CreateUserCommandError::AlreadyExists,
+                false => CreateUserCommandError::Database(database_error("failed to create user in database", err)),
+            })
+    }
+
+    async fn update_user(&self, user_id: &str, input: UpdateUserCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        self.update(
+            "email = COALESCE($2, email), name = COALESCE($3, name)",
+            user_id,
+            vec![input.email, input.name],
+        )
+        .await
+    }
+
+    async fn deactivate_user(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        self.update(
+            "deactivated_at = COALESCE(deactivated_at, $2)",
+            user_id,
+            vec![Some(at.to_rfc3339())],
+        )
+        .await
+    }
+
+    async fn upsert_user(&self, input: UpsertUserCommandInput) -> Result<UserListQueryResultItem, UpdateUserCommandError> {
+        let sql = format!(
+            "INSERT INTO users (user_id, email, name, roles, synced_at) VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (user_id) DO UPDATE SET email = excluded.email, name = excluded.name, \
+             roles = excluded.roles, synced_at = excluded.synced_at, removed_at = NULL \
+             RETURNING {}",
+            USER_COLUMNS
+        );
+        sqlx::query_as::<_, UserListQueryResultItem>(&sql)
+            .bind(input.user_id)
+            .bind(input.email)
+            .bind(input.name)
+            .bind(roles_to_json(&input.roles))
+            .bind(input.synced_at.to_rfc3339())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| UpdateUserCommandError(database_error("failed to upsert user in database", err)))
+    }
+
+    async fn set_role_overrides(&self, user_id: &str, input: SetRoleOverridesCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        self.update(
+            "granted_roles = $2, revoked_roles = $3",
+            user_id,
+            vec![
+                Some(roles_to_json(&input.granted_roles)),
+                Some(roles_to_json(&input.revoked_roles)),
+            ],
+        )
+        .await
+    }
+
+    async fn mark_user_removed(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        self.update("removed_at = COALESCE(removed_at, $2)", user_id, vec![Some(at.to_rfc3339())])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::layers::ewm::main_database::qc_collection::sql::connect_in_memory;
+
+    async fn create(collection: &SqlUserQCCollection, user_id: &str, name: &str) {
+        collection
+            .create_user(CreateUserCommandInput {
+                user_id: user_id.to_string(),
+                email: format!("{}@example.com", name.to_lowercase()),
+                name: name.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+
+    fn search(search: &str, limit: usize, cursor: Option<String>) -> SearchUsersQuery {
+        SearchUsersQuery {
+            search: Some(search.to_string()),
+            roles: vec![],
+            active_only: true,
+            exclude_user_ids: HashSet::new(),
+            limit,
+            cursor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_matches() {
+        let collection = SqlUserQCCollection::new(connect_in_memory().await);
+        for (user_id, name) in [("auth0|1", "Ann Lee"), ("auth0|2", "Bob"), ("auth0|3", "Anna"), ("auth0|4", "Lee Ann")] {
+            create(&collection, user_id, name).await;
+        }
+        collection.deactivate_user("auth0|3", Utc::now()).await.unwrap();
+
+        let first = collection.search_users(search("ann", 1, None)).await.unwrap();
+        assert_eq!(first.users[0].user_id, "auth0|1");
+        let second = collection.search_users(search("ann", 1, first.next_cursor)).await.unwrap();
+        assert_eq!(second.users[0].user_id, "auth0|4");
+        assert!(second.next_cursor.is_none());
+
+        assert!(matches!(
+            collection.search_users(search("ann", 1, Some("%%%".to_string()))).await,
+            Err(SearchUsersQueryError::InvalidCursor)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_keeps_overrides_and_clears_removal() {
+        let collection = SqlUserQCCollection::new(connect_in_memory().await);
+        create(&collection, "auth0|1", "Ann").await;
+        assert!(matches!(
+            collection
+                .create_user(CreateUserCommandInput {
+                    user_id: "auth0|1".to_string(),
+                    email: "other@example.com".to_string(),
+                    name: "Other".to_string(),
+                })
+                .await,
+            Err(CreateUserCommandError::AlreadyExists)
+        ));
+        collection
+            .set_role_overrides(
+                "auth0|1",
+                SetRoleOverridesCommandInput {
+                    granted_roles: vec!["Admin".to_string()],
+                    revoked_roles: vec![],
+                },
+            )
+            .await
+            .unwrap();
+        collection.mark_user_removed("auth0|1", Utc::now()).await.unwrap();
+
+        let user = collection
+            .upsert_user(UpsertUserCommandInput {
+                user_id: "auth0|1".to_string(),
+                email: "ann@example.org".to_string(),
+                name: "Ann Lee".to_string(),
+                roles: vec!["Viewer".to_string()],
+                synced_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(user.email, "ann@example.org");
+        assert_eq!(user.roles, vec!["Viewer"]);
+        assert_eq!(user.granted_roles, vec!["Admin"]);
+        assert!(user.is_active());
+
+        let found = collection
+            .find_users_by_ids(vec!["auth0|2".to_string(), "auth0|1".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(collection.update_user("auth0|2", UpdateUserCommandInput { email: None, name: None }).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,442 @@
+use crate::layers::ewm::main_database::qc_collection::memory::temp_stream_session_qc_collection::InMemoryTempStreamSessionQCCollection;
+use crate::layers::ewm::main_database::qc_collection::sql::temp_stream_session_qc_collection::SqlTempStreamSessionQCCollection;
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::{AttributeValue, Delete, Put, Select, TransactWriteItem};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::layers::ewm::{
+    main_database::qc_collection::error::QCError, temporary_stream_server::StreamQuality,
+};
+
+const PARTITION_KEY_PREFIX: &str = "tempStreamSession/";
+const ID_PARTITION_KEY_PREFIX: &str = "tempStreamSessionById/";
+const CAMERA_PARTITION_KEY_PREFIX: &str = "tempStreamSessionByCamera/";
+const EXPIRY_PARTITION_KEY: &str = "tempStreamSessionByExpiry/";
+
+/// A temporary stream handed out to a user. Sessions are stored under the
+/// user's partition so a user's own sessions are a single query, and once
+/// more under their own id so the media layer can look them up by key.
+/// Counting entries under the camera's partition and a shared partition are
+/// sorted by expiry, so active sessions are counted without reading expired
+/// ones.
+#[derive(Debug, Clone)]
+pub struct TempStreamSession {
+    pub id: String,
+    pub user_id: String,
+    pub camera_id: String,
+    pub stream_id: String,
+    pub stream_url: String,
+    pub hls_url: Option<String>,
+    pub whep_url: Option<String>,
+    pub quality: StreamQuality,
+    /// Secret the media layer checks before serving the stream
+    pub token: String,
+    /// Whether the user could see cameras behind a privacy shutter when the
+    /// session was issued
+    pub privacy_shutter_exempt: bool,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for TempStreamSession {
+    type Error = String;
+
+    fn try_from(value: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> Result<String, String> {
+            value
+                .get(name)
+                .and_then(|v| v.as_s().ok())
+                .cloned()
+                .ok_or(format!("missing {}", name))
+        };
+        let date = |name: &str| -> Result<DateTime<Utc>, String> {
+            DateTime::parse_from_rfc3339(&string(name)?)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|err| format!("invalid {}: {}", name, err))
+        };
+        Ok(TempStreamSession {
+            id: string("sortKey")?,
+            user_id: string("user_id")?,
+            camera_id: string("camera_id")?,
+            stream_id: string("stream_id")?,
+            stream_url: string("stream_url").unwrap_or_default(),
+            hls_url: string("hls_url").ok(),
+            whep_url: string("whep_url").ok(),
+            quality: string("quality")
+                .and_then(|q| q.parse())
+                .unwrap_or(StreamQuality::Original),
+            token: string("token").unwrap_or_default(),
+            privacy_shutter_exempt: value
+                .get("privacy_shutter_exempt")
+                .and_then(|v| v.as_bool().ok())
+                .copied()
+                .unwrap_or(false),
+            issued_at: date("issued_at")?,
+            expires_at: date("expires_at")?,
+        })
+    }
+}
+
+pub trait ITempStreamSessionQCCollection {
+    fn create_session(
+        &self,
+        session: TempStreamSession,
+    ) -> impl std::future::Future<Output = Result<(), CreateTempStreamSessionError>> + Send;
+
+    fn list_sessions_for_user(
+        &self,
+        user_id: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError>>
+           + Send;
+
+    fn list_sessions(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError>>
+           + Send;
+
+    fn find_session_by_id(
+        &self,
+        session_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<TempStreamSession>, FindTempStreamSessionError>>
+           + Send;
+
+    fn delete_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), DeleteTempStreamSessionError>> + Send;
+
+    /// Unexpired sessions of the user, of the camera and overall.
+    fn count_active_sessions(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+    ) -> impl std::future::Future<Output = Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError>>
+           + Send;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ActiveTempStreamSessionCounts {
+    pub user: usize,
+    pub camera: usize,
+    pub total: usize,
+}
+
+pub struct DynamoTempStreamSessionQCCollection {
+    client: aws_sdk_dynamodb::Client,
+    table: String,
+}
+
+impl DynamoTempStreamSessionQCCollection {
+    pub fn new(client: aws_sdk_dynamodb::Client, table: String) -> Self {
+        Self { client, table }
+    }
+
+    /// Counts the items of a partition matching the key condition and, when
+    /// given, the filter.
+    async fn count(
+        &self,
+        key_condition_expression: &str,
+        filter_expression: Option<&str>,
+        values: Vec<(&str, AttributeValue)>,
+    ) -> Result<usize, QCError> {
+        let mut request = self
+            .client
+            .query()
+            .table_name(&self.table)
+            .select(Select::Count)
+            .consistent_read(true)
+            .key_condition_expression(key_condition_expression)
+            .set_filter_expression(filter_expression.map(str::to_string));
+        for (name, value) in values {
+            request = request.expression_attribute_values(name, value);
+        }
+        let pages: Vec<_> = request
+            .into_paginator()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| QCError::new(e.to_string(), Some(format!("{:?}", e))))?;
+        Ok(pages.iter().map(|page| page.count() as usize).sum())
+    }
+}
+
+/// Keys of the full session items: under the owner's partition and its own.
+fn session_keys(user_id: &str, session_id: &str) -> [(String, String); 2] {
+    [
+        (format!("{}{}", PARTITION_KEY_PREFIX, user_id), session_id.to_string()),
+        (format!("{}{}", ID_PARTITION_KEY_PREFIX, session_id), session_id.to_string()),
+    ]
+}
+
+/// Keys of the counting entries, sorted by expiry within the camera's and
+/// the shared partition.
+fn counting_keys(camera_id: &str, session_id: &str, expires_at: &DateTime<Utc>) -> [(String, String); 2] {
+    let sort_key = format!("{}#{}", expiry_sort_key(expires_at), session_id);
+    [
+        (format!("{}{}", CAMERA_PARTITION_KEY_PREFIX, camera_id), sort_key.clone()),
+        (EXPIRY_PARTITION_KEY.to_string(), sort_key),
+    ]
+}
+
+fn expiry_sort_key(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_sessions(items: &[HashMap<String, AttributeValue>]) -> Vec<TempStreamSession> {
+    items
+        .iter()
+        .filter_map(|item| {
+            TempStreamSession::try_from(item)
+                .inspect_err(|err| tracing::error!("skipping invalid temp stream session: {}", err))
+                .ok()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTempStreamSessionError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct ListTempStreamSessionsQueryError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct FindTempStreamSessionError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct DeleteTempStreamSessionError(pub QCError);
+
+#[derive(Debug, Clone)]
+pub struct CountTempStreamSessionsError(pub QCError);
+
+impl ITempStreamSessionQCCollection for DynamoTempStreamSessionQCCollection {
+    async fn create_session(&self, session: TempStreamSession) -> Result<(), CreateTempStreamSessionError> {
+        let to_error = |message: &str, e: String| CreateTempStreamSessionError(QCError::new(message.to_string(), Some(e)));
+        let mut item = HashMap::from([
+            ("user_id".to_string(), AttributeValue::S(session.user_id.clone())),
+            ("camera_id".to_string(), AttributeValue::S(session.camera_id.clone())),
+            ("stream_id".to_string(), AttributeValue::S(session.stream_id)),
+            ("stream_url".to_string(), AttributeValue::S(session.stream_url)),
+            ("quality".to_string(), AttributeValue::S(session.quality.as_str().to_string())),
+            ("token".to_string(), AttributeValue::S(session.token)),
+            ("privacy_shutter_exempt".to_string(), AttributeValue::Bool(session.privacy_shutter_exempt)),
+            ("issued_at".to_string(), AttributeValue::S(session.issued_at.to_rfc3339_opts(SecondsFormat::Secs, true))),
+            ("expires_at".to_string(), AttributeValue::S(expiry_sort_key(&session.expires_at))),
+        ]);
+        let optional_urls = [("hls_url", session.hls_url), ("whep_url", session.whep_url)];
+        for (name, url) in optional_urls {
+            if let Some(url) = url {
+                item.insert(name.to_string(), AttributeValue::S(url));
+            }
+        }
+
+        let counting_item = HashMap::from([
+            ("user_id".to_string(), AttributeValue::S(session.user_id.clone())),
+            ("camera_id".to_string(), AttributeValue::S(session.camera_id.clone())),
+            ("expires_at".to_string(), AttributeValue::S(expiry_sort_key(&session.expires_at))),
+        ]);
+        let items = session_keys(&session.user_id, &session.id)
+            .into_iter()
+            .map(|keys| (keys, item.clone()))
+            .chain(
+                counting_keys(&session.camera_id, &session.id, &session.expires_at)
+                    .into_iter()
+                    .map(|keys| (keys, counting_item.clone())),
+            );
+
+        let mut transact_items = Vec::new();
+        for ((partition_key, sort_key), mut item) in items {
+            item.insert("partitionKey".to_string(), AttributeValue::S(partition_key));
+            item.insert("sortKey".to_string(), AttributeValue::S(sort_key));
+            let put = Put::builder()
+                .table_name(&self.table)
+                .set_item(Some(item))
+                .build()
+                .map_err(|e| to_error("failed to build temp stream session item", format!("{:?}", e)))?;
+            transact_items.push(TransactWriteItem::builder().put(put).build());
+        }
+
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await
+            .map_err(|e| to_error("failed to create temp stream session", format!("{:?}", e)))?;
+        Ok(())
+    }
+
+    async fn list_sessions_for_user(&self, user_id: &str) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        let items: Vec<HashMap<String, AttributeValue>> = self.client
+            .query()
+            .table_name(&self.table)
+            .key_condition_expression("partitionKey = :pk")
+            .expression_attribute_values(":pk", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id)))
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| ListTempStreamSessionsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
+
+        Ok(parse_sessions(&items))
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        let items: Vec<HashMap<String, AttributeValue>> = self.client
+            .scan()
+            .table_name(&self.table)
+            .filter_expression("begins_with(partitionKey, :pk_prefix)")
+            .expression_attribute_values(":pk_prefix", AttributeValue::S(PARTITION_KEY_PREFIX.to_string()))
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await
+            .map_err(|e| ListTempStreamSessionsQueryError(QCError::new(e.to_string(), Some(format!("{:?}", e)))))?;
+
+        Ok(parse_sessions(&items))
+    }
+
+    async fn find_session_by_id(&self, session_id: &str) -> Result<Option<TempStreamSession>, FindTempStreamSessionError> {
+        let response = self.client
+            .get_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S(format!("{}{}", ID_PARTITION_KEY_PREFIX, session_id)))
+            .key("sortKey", AttributeValue::S(session_id.to_string()))
+            .send()
+            .await
+            .map_err(|e| FindTempStreamSessionError(QCError::new(
+                "failed to find temp stream session".to_string(),
+                Some(format!("{:?}", e)),
+            )))?;
+        Ok(response.item().and_then(|item| {
+            TempStreamSession::try_from(item)
+                .inspect_err(|err| tracing::error!("skipping invalid temp stream session: {}", err))
+                .ok()
+        }))
+    }
+
+    async fn delete_session(&self, user_id: &str, session_id: &str) -> Result<(), DeleteTempStreamSessionError> {
+        let to_error = |message: &str, e: String| DeleteTempStreamSessionError(QCError::new(message.to_string(), Some(e)));
+        let session = self
+            .find_session_by_id(session_id)
+            .await
+            .map_err(|e| to_error("failed to find temp stream session to delete", format!("{:?}", e.0)))?;
+        let mut keys = session_keys(user_id, session_id).to_vec();
+        if let Some(session) = session.filter(|s| s.user_id == user_id) {
+            keys.extend(counting_keys(&session.camera_id, session_id, &session.expires_at));
+        }
+
+        let mut transact_items = Vec::new();
+        for (partition_key, sort_key) in keys {
+            let delete = Delete::builder()
+                .table_name(&self.table)
+                .key("partitionKey", AttributeValue::S(partition_key))
+                .key("sortKey", AttributeValue::S(sort_key))
+                .build()
+                .map_err(|e| to_error("failed to build temp stream session key", format!("{:?}", e)))?;
+            transact_items.push(TransactWriteItem::builder().delete(delete).build());
+        }
+
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await
+            .map_err(|e| to_error("failed to delete temp stream session", format!("{:?}", e)))?;
+        Ok(())
+    }
+
+    async fn count_active_sessions(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+    ) -> Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError> {
+        let now = AttributeValue::S(expiry_sort_key(&Utc::now()));
+        let user = self.count(
+            "partitionKey = :pk",
+            Some("expires_at > :now"),
+            vec![
+                (":pk", AttributeValue::S(format!("{}{}", PARTITION_KEY_PREFIX, user_id))),
+                (":now", now.clone()),
+            ],
+        );
+        let camera = self.count(
+            "partitionKey = :pk AND sortKey > :now",
+            None,
+            vec![
+                (":pk", AttributeValue::S(format!("{}{}", CAMERA_PARTITION_KEY_PREFIX, camera_id))),
+                (":now", now.clone()),
+            ],
+        );
+        let total = self.count(
+            "partitionKey = :pk AND sortKey > :now",
+            None,
+            vec![(":pk", AttributeValue::S(EXPIRY_PARTITION_KEY.to_string())), (":now", now)],
+        );
+        let (user, camera, total) = tokio::try_join!(user, camera, total).map_err(CountTempStreamSessionsError)?;
+        Ok(ActiveTempStreamSessionCounts { user, camera, total })
+    }
+}
+
+/// Where the sessions live.
+pub enum TempStreamSessionQCCollection {
+    Dynamo(DynamoTempStreamSessionQCCollection),
+    Memory(InMemoryTempStreamSessionQCCollection),
+    Sql(SqlTempStreamSessionQCCollection),
+}
+
+impl ITempStreamSessionQCCollection for TempStreamSessionQCCollection {
+    async fn create_session(&self, session: TempStreamSession) -> Result<(), CreateTempStreamSessionError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.create_session(session).await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.create_session(session).await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.create_session(session).await,
+        }
+    }
+
+    async fn list_sessions_for_user(&self, user_id: &str) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.list_sessions_for_user(user_id).await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.list_sessions_for_user(user_id).await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.list_sessions_for_user(user_id).await,
+        }
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<TempStreamSession>, ListTempStreamSessionsQueryError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.list_sessions().await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.list_sessions().await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.list_sessions().await,
+        }
+    }
+
+    async fn find_session_by_id(&self, session_id: &str) -> Result<Option<TempStreamSession>, FindTempStreamSessionError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.find_session_by_id(session_id).await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.find_session_by_id(session_id).await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.find_session_by_id(session_id).await,
+        }
+    }
+
+    async fn delete_session(&self, user_id: &str, session_id: &str) -> Result<(), DeleteTempStreamSessionError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.delete_session(user_id, session_id).await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.delete_session(user_id, session_id).await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.delete_session(user_id, session_id).await,
+        }
+    }
+
+    async fn count_active_sessions(
+        &self,
+        user_id: &str,
+        camera_id: &str,
+    ) -> Result<ActiveTempStreamSessionCounts, CountTempStreamSessionsError> {
+        match self {
+            TempStreamSessionQCCollection::Dynamo(collection) => collection.count_active_sessions(user_id, camera_id).await,
+            TempStreamSessionQCCollection::Memory(collection) => collection.count_active_sessions(user_id, camera_id).await,
+            TempStreamSessionQCCollection::Sql(collection) => collection.count_active_sessions(user_id, camera_id).await,
+        }
+    }
+}
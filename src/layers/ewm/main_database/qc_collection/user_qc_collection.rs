@@ -1,6 +1,14 @@
-use std::collections::HashMap;
+use crate::layers::ewm::main_database::qc_collection::sql::user_qc_collection::SqlUserQCCollection;
+use crate::layers::ewm::main_database::qc_collection::memory::user_qc_collection::InMemoryUserQCCollection;
+use std::collections::{HashMap, HashSet};
 
-use aws_sdk_dynamodb::types::{AttributeValue, KeysAndAttributes};
+use aws_sdk_dynamodb::{
+    error::SdkError,
+    operation::{put_item::PutItemError, update_item::UpdateItemError},
+    types::{AttributeValue, KeysAndAttributes, ReturnValue},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
 
 use super::error::QCError;
 
@@ -9,6 +17,40 @@ pub struct UserListQueryResultItem {
     pub user_id: String,
     pub email: String,
     pub name: String,
+    /// Set once an admin deactivated the user
+    pub deactivated_at: Option<String>,
+    /// Roles as last synced from the identity provider
+    pub roles: Vec<String>,
+    /// Set when the user no longer exists at the identity provider. Only a
+    /// flag for admins; it does not lock the user out.
+    pub removed_at: Option<String>,
+    /// Roles granted locally on top of the token roles
+    pub granted_roles: Vec<String>,
+    /// Roles taken away locally even when the token carries them
+    pub revoked_roles: Vec<String>,
+}
+
+impl UserListQueryResultItem {
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+
+    /// The synced roles with the local grants and revocations applied.
+    pub fn effective_roles(&self) -> Vec<String> {
+        merge_roles(self.roles.clone(), &self.granted_roles, &self.revoked_roles)
+    }
+}
+
+/// Revocations win over grants, so a role listed in both stays removed.
+pub fn merge_roles(roles: Vec<String>, granted_roles: &[String], revoked_roles: &[String]) -> Vec<String> {
+    let mut roles = roles;
+    for role in granted_roles {
+        if !roles.contains(role) {
+            roles.push(role.clone());
+        }
+    }
+    roles.retain(|role| !revoked_roles.contains(role));
+    roles
 }
 
 impl TryFrom<&HashMap<String, AttributeValue>> for UserListQueryResultItem {
@@ -33,10 +75,33 @@ impl TryFrom<&HashMap<String, AttributeValue>> for UserListQueryResultItem {
             .ok_or_else(|| "Missing or invalid 'name' field".to_string())?
             .to_string();
 
+        let deactivated_at = value
+            .get("deactivated_at")
+            .and_then(|v| v.as_s().ok())
+            .cloned();
+
+        let string_list = |name: &str| -> Vec<String> {
+            value
+                .get(name)
+                .and_then(|v| v.as_l().ok())
+                .map(|items| items.iter().filter_map(|item| item.as_s().ok()).cloned().collect())
+                .unwrap_or_default()
+        };
+
+        let removed_at = value
+            .get("removed_at")
+            .and_then(|v| v.as_s().ok())
+            .cloned();
+
         Ok(UserListQueryResultItem {
             user_id,
             email,
             name,
+            deactivated_at,
+            roles: string_list("roles"),
+            removed_at,
+            granted_roles: string_list("granted_roles"),
+            revoked_roles: string_list("revoked_roles"),
         })
     }
 }
@@ -50,12 +115,105 @@ pub struct FindUserByIdQueryError(pub QCError);
 #[derive(Debug, Clone)]
 pub struct FindUsersByIdsQueryError(pub QCError);
 
+#[derive(Debug, Clone)]
+pub enum CreateUserCommandError {
+    AlreadyExists,
+    Database(QCError),
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateUserCommandError(pub QCError);
+
+pub struct CreateUserCommandInput {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+}
+
+/// Fields left as `None` keep their stored value.
+pub struct UpdateUserCommandInput {
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+pub struct SetRoleOverridesCommandInput {
+    pub granted_roles: Vec<String>,
+    pub revoked_roles: Vec<String>,
+}
+
+pub struct UpsertUserCommandInput {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+    pub roles: Vec<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Users ordered by id, narrowed down by every given criterion.
+pub struct SearchUsersQuery {
+    /// Case-insensitive prefix of the email, the name or any word of the name
+    pub search: Option<String>,
+    /// Users holding at least one of these roles once local grants and
+    /// revocations are applied, compared case-insensitively
+    pub roles: Vec<String>,
+    pub active_only: bool,
+    pub exclude_user_ids: HashSet<String>,
+    pub limit: usize,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl SearchUsersQuery {
+    pub fn matches(&self, user: &UserListQueryResultItem) -> bool {
+        if self.active_only && !user.is_active() {
+            return false;
+        }
+        if self.exclude_user_ids.contains(&user.user_id) {
+            return false;
+        }
+        if !self.roles.is_empty()
+            && !user
+                .effective_roles()
+                .iter()
+                .any(|role| self.roles.iter().any(|wanted| wanted.eq_ignore_ascii_case(role)))
+        {
+            return false;
+        }
+        let Some(search) = self.search.as_deref().map(str::to_lowercase) else {
+            return true;
+        };
+        let name = user.name.to_lowercase();
+        user.email.to_lowercase().starts_with(&search)
+            || name.starts_with(&search)
+            || name.split_whitespace().any(|word| word.starts_with(&search))
+    }
+}
+
+/// A missing `next_cursor` marks the last page.
+pub struct UserSearchPage {
+    pub users: Vec<UserListQueryResultItem>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchUsersQueryError {
+    InvalidCursor,
+    Database(QCError),
+}
+
 pub trait IUserQCCollection {
     fn list_users(
         &self,
     ) -> impl std::future::Future<Output = Result<Vec<UserListQueryResultItem>, ListUsersQueryError>>
            + Send;
 
+    /// Stops reading once `limit` matches are found, so early pages do not
+    /// load the whole user list.
+    fn search_users(
+        &self,
+        query: SearchUsersQuery,
+    ) -> impl std::future::Future<Output = Result<UserSearchPage, SearchUsersQueryError>> + Send;
+
     fn find_user_by_id(
         &self,
         user_id: &str,
@@ -67,21 +225,167 @@ pub trait IUserQCCollection {
         user_ids: Vec<String>,
     ) -> impl std::future::Future<Output = Result<Vec<UserListQueryResultItem>, FindUsersByIdsQueryError>>
            + Send;
+
+    fn create_user(
+        &self,
+        input: CreateUserCommandInput,
+    ) -> impl std::future::Future<Output = Result<UserListQueryResultItem, CreateUserCommandError>> + Send;
+
+    /// Returns `None` when there is no such user.
+    fn update_user(
+        &self,
+        user_id: &str,
+        input: UpdateUserCommandInput,
+    ) -> impl std::future::Future<Output = Result<Option<UserListQueryResultItem>, UpdateUserCommandError>>
+           + Send;
+
+    /// Marks the user as deactivated; deactivating twice keeps the first
+    /// date. Returns `None` when there is no such user.
+    fn deactivate_user(
+        &self,
+        user_id: &str,
+        at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<Option<UserListQueryResultItem>, UpdateUserCommandError>>
+           + Send;
+
+    /// Creates or overwrites the user's profile and roles as the identity
+    /// provider reports them, and clears an earlier removal mark.
+    /// Deactivation is left alone.
+    fn upsert_user(
+        &self,
+        input: UpsertUserCommandInput,
+    ) -> impl std::future::Future<Output = Result<UserListQueryResultItem, UpdateUserCommandError>> + Send;
+
+    /// Replaces the user's local role grants and revocations. Returns `None`
+    /// when there is no such user.
+    fn set_role_overrides(
+        &self,
+        user_id: &str,
+        input: SetRoleOverridesCommandInput,
+    ) -> impl std::future::Future<Output = Result<Option<UserListQueryResultItem>, UpdateUserCommandError>>
+           + Send;
+
+    /// Flags a user the identity provider no longer knows; flagging twice
+    /// keeps the first date. Returns `None` when there is no such user.
+    fn mark_user_removed(
+        &self,
+        user_id: &str,
+        at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<Option<UserListQueryResultItem>, UpdateUserCommandError>>
+           + Send;
 }
 
 #[derive(Clone)]
-pub struct UserQCCollection {
+pub struct DynamoUserQCCollection {
     client: aws_sdk_dynamodb::Client,
     table: String,
 }
 
-impl UserQCCollection {
+impl DynamoUserQCCollection {
     pub fn new(client: aws_sdk_dynamodb::Client, table: String) -> Self {
         Self { client, table }
     }
+
+    /// Maps the result of a conditional update to the updated user, or
+    /// `None` when the condition found no user.
+    fn updated_user<R>(
+        &self,
+        result: Result<aws_sdk_dynamodb::operation::update_item::UpdateItemOutput, SdkError<UpdateItemError, R>>,
+    ) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError>
+    where
+        R: std::fmt::Debug,
+    {
+        let attributes = match result {
+            Ok(output) => output.attributes.unwrap_or_default(),
+            Err(SdkError::ServiceError(err))
+                if matches!(err.err(), UpdateItemError::ConditionalCheckFailedException(_)) =>
+            {
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(UpdateUserCommandError(QCError::new(
+                    "failed to update user".to_string(),
+                    Some(format!("{:?}", err)),
+                )))
+            }
+        };
+        UserListQueryResultItem::try_from(&attributes)
+            .map(Some)
+            .map_err(|err| UpdateUserCommandError(QCError::new("failed to parse user item".to_string(), Some(err))))
+    }
 }
 
-impl IUserQCCollection for UserQCCollection {
+/// Number of stored users read per request while searching.
+const SEARCH_READ_PAGE_SIZE: i32 = 200;
+
+pub(super) fn encode_user_cursor(user_id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(user_id)
+}
+
+pub(super) fn decode_user_cursor(cursor: &str) -> Option<String> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+impl IUserQCCollection for DynamoUserQCCollection {
+    async fn search_users(&self, query: SearchUsersQuery) -> Result<UserSearchPage, SearchUsersQueryError> {
+        let mut start_key = match &query.cursor {
+            Some(cursor) => {
+                let user_id = decode_user_cursor(cursor).ok_or(SearchUsersQueryError::InvalidCursor)?;
+                Some(HashMap::from([
+                    ("partitionKey".to_string(), AttributeValue::S("user".to_string())),
+                    ("sortKey".to_string(), AttributeValue::S(user_id)),
+                ]))
+            }
+            None => None,
+        };
+
+        // the cursor is the id of the last returned user
+        let mut users = Vec::new();
+        loop {
+            let response = self
+                .client
+                .query()
+                .table_name(&self.table)
+                .key_condition_expression("#partitionKey = :partitionKeyVal")
+                .expression_attribute_names("#partitionKey", "partitionKey")
+                .expression_attribute_values(":partitionKeyVal", AttributeValue::S("user".to_string()))
+                .limit(SEARCH_READ_PAGE_SIZE)
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(|err| {
+                    SearchUsersQueryError::Database(QCError::new(
+                        "failed to search users in database".to_string(),
+                        Some(format!("{:?}", err)),
+                    ))
+                })?;
+
+            let items = response.items();
+            for (index, item) in items.iter().enumerate() {
+                let user = UserListQueryResultItem::try_from(item).map_err(|err| {
+                    SearchUsersQueryError::Database(QCError::new("failed to parse user item".to_string(), Some(err)))
+                })?;
+                if !query.matches(&user) {
+                    continue;
+                }
+                users.push(user);
+                if users.len() >= query.limit {
+                    let is_last = index + 1 == items.len() && response.last_evaluated_key().is_none();
+                    let next_cursor = (!is_last).then(|| encode_user_cursor(&users[users.len() - 1].user_id));
+                    return Ok(UserSearchPage { users, next_cursor });
+                }
+            }
+
+            start_key = response.last_evaluated_key().cloned();
+            if start_key.is_none() {
+                return Ok(UserSearchPage { users, next_cursor: None });
+            }
+        }
+    }
+
     async fn find_user_by_id(&self, user_id: &str) -> Result<Option<UserListQueryResultItem>, FindUserByIdQueryError> {
         let result = self
             .client
@@ -116,51 +420,73 @@ impl IUserQCCollection for UserQCCollection {
             return Ok(vec![]);
         }
 
-        let keys: Vec<HashMap<String, AttributeValue>> = user_ids
-            .iter()
-            .map(|user_id| {
-                let mut key = HashMap::new();
-                key.insert("partitionKey".to_string(), AttributeValue::S("user".to_string()));
-                key.insert("sortKey".to_string(), AttributeValue::S(user_id.clone()));
-                key
-            })
-            .collect();
+        let mut users = Vec::new();
+        // batch_get_item takes at most 100 keys and may hand some back as
+        // unprocessed under load, which must be asked for again
+        for chunk in user_ids.chunks(100) {
+            let mut pending: Vec<HashMap<String, AttributeValue>> = chunk
+                .iter()
+                .map(|user_id| {
+                    let mut key = HashMap::new();
+                    key.insert("partitionKey".to_string(), AttributeValue::S("user".to_string()));
+                    key.insert("sortKey".to_string(), AttributeValue::S(user_id.clone()));
+                    key
+                })
+                .collect();
 
-        let keys_and_attributes = KeysAndAttributes::builder()
-            .set_keys(Some(keys))
-            .build()
-            .map_err(|err| {
-                FindUsersByIdsQueryError(QCError::new(
-                    "failed to build keys and attributes".to_string(),
-                    Some(format!("{:?}", err)),
-                ))
-            })?;
+            for attempt in 0.. {
+                if pending.is_empty() {
+                    break;
+                }
+                if attempt >= 5 {
+                    return Err(FindUsersByIdsQueryError(QCError::new(
+                        "failed to batch fetch users from database".to_string(),
+                        Some(format!("{} keys still unprocessed", pending.len())),
+                    )));
+                }
+                if attempt > 0 {
+                    tokio::time::sleep(std::time::Duration::from_millis(50 << attempt)).await;
+                }
 
-        let result = self
-            .client
-            .batch_get_item()
-            .request_items(&self.table, keys_and_attributes)
-            .send()
-            .await
-            .map_err(|err| {
-                FindUsersByIdsQueryError(QCError::new(
-                    "failed to batch fetch users from database".to_string(),
-                    Some(format!("{:?}", err)),
-                ))
-            })?;
+                let keys_and_attributes = KeysAndAttributes::builder()
+                    .set_keys(Some(pending))
+                    .build()
+                    .map_err(|err| {
+                        FindUsersByIdsQueryError(QCError::new(
+                            "failed to build keys and attributes".to_string(),
+                            Some(format!("{:?}", err)),
+                        ))
+                    })?;
 
-        let mut users = Vec::new();
-        if let Some(responses) = result.responses {
-            if let Some(items) = responses.get(&self.table) {
-                for item in items {
-                    let user = UserListQueryResultItem::try_from(item).map_err(|err| {
+                let result = self
+                    .client
+                    .batch_get_item()
+                    .request_items(&self.table, keys_and_attributes)
+                    .send()
+                    .await
+                    .map_err(|err| {
                         FindUsersByIdsQueryError(QCError::new(
-                            "failed to parse user item".to_string(),
-                            Some(err),
+                            "failed to batch fetch users from database".to_string(),
+                            Some(format!("{:?}", err)),
                         ))
                     })?;
-                    users.push(user);
+
+                if let Some(items) = result.responses.as_ref().and_then(|r| r.get(&self.table)) {
+                    for item in items {
+                        let user = UserListQueryResultItem::try_from(item).map_err(|err| {
+                            FindUsersByIdsQueryError(QCError::new(
+                                "failed to parse user item".to_string(),
+                                Some(err),
+                            ))
+                        })?;
+                        users.push(user);
+                    }
                 }
+                pending = result
+                    .unprocessed_keys
+                    .and_then(|mut unprocessed| unprocessed.remove(&self.table))
+                    .map(|keys| keys.keys)
+                    .unwrap_or_default();
             }
         }
 
@@ -168,7 +494,9 @@ impl IUserQCCollection for UserQCCollection {
     }
 
     async fn list_users(&self) -> Result<Vec<UserListQueryResultItem>, ListUsersQueryError> {
-        let results = self
+        // the user sync compares against every stored user, so follow all
+        // pages instead of stopping at the first megabyte
+        let items: Vec<HashMap<String, AttributeValue>> = self
             .client
             .query()
             .table_name(&self.table)
@@ -178,7 +506,10 @@ impl IUserQCCollection for UserQCCollection {
                 ":partitionKeyVal",
                 AttributeValue::S("user".to_string()),
             )
+            .into_paginator()
+            .items()
             .send()
+            .try_collect()
             .await
             .map_err(|err| {
                 ListUsersQueryError(QCError::new(
@@ -187,21 +518,331 @@ impl IUserQCCollection for UserQCCollection {
                 ))
             })?;
 
-        if let Some(items) = results.items {
-            let results = items
-                .iter()
-                .map(|v| {
-                    UserListQueryResultItem::try_from(v).map_err(|err| {
-                        ListUsersQueryError(QCError::new(
-                            "failed to parse user item".to_string(),
-                            Some(err),
-                        ))
-                    })
+        items
+            .iter()
+            .map(|v| {
+                UserListQueryResultItem::try_from(v).map_err(|err| {
+                    ListUsersQueryError(QCError::new(
+                        "failed to parse user item".to_string(),
+                        Some(err),
+                    ))
                 })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(results)
-        } else {
-            Ok(vec![])
+            })
+            .collect()
+    }
+
+    async fn create_user(&self, input: CreateUserCommandInput) -> Result<UserListQueryResultItem, CreateUserCommandError> {
+        let result = self
+            .client
+            .put_item()
+            .table_name(&self.table)
+            .item("partitionKey", AttributeValue::S("user".to_string()))
+            .item("sortKey", AttributeValue::S(input.user_id.clone()))
+            .item("email", AttributeValue::S(input.email.clone()))
+            .item("name", AttributeValue::S(input.name.clone()))
+            .condition_expression("attribute_not_exists(sortKey)")
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(UserListQueryResultItem {
+                user_id: input.user_id,
+                email: input.email,
+                name: input.name,
+                deactivated_at: None,
+                roles: vec![],
+                removed_at: None,
+                granted_roles: vec![],
+                revoked_roles: vec![],
+            }),
+            Err(SdkError::ServiceError(err))
+                if matches!(err.err(), PutItemError::ConditionalCheckFailedException(_)) =>
+            {
+                Err(CreateUserCommandError::AlreadyExists)
+            }
+            Err(err) => Err(CreateUserCommandError::Database(QCError::new(
+                "failed to create user".to_string(),
+                Some(format!("{:?}", err)),
+            ))),
+        }
+    }
+
+    async fn update_user(&self, user_id: &str, input: UpdateUserCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        let mut assignments = Vec::new();
+        let mut request = self.client.update_item();
+        if let Some(email) = input.email {
+            assignments.push("email = :email");
+            request = request.expression_attribute_values(":email", AttributeValue::S(email));
         }
+        if let Some(name) = input.name {
+            assignments.push("#name = :name");
+            request = request
+                .expression_attribute_names("#name", "name")
+                .expression_attribute_values(":name", AttributeValue::S(name));
+        }
+        if assignments.is_empty() {
+            return self
+                .find_user_by_id(user_id)
+                .await
+                .map_err(|FindUserByIdQueryError(err)| UpdateUserCommandError(err));
+        }
+        let result = request
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("user".to_string()))
+            .key("sortKey", AttributeValue::S(user_id.to_string()))
+            .condition_expression("attribute_exists(sortKey)")
+            .update_expression(format!("SET {}", assignments.join(", ")))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+        self.updated_user(result)
+    }
+
+    async fn deactivate_user(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("user".to_string()))
+            .key("sortKey", AttributeValue::S(user_id.to_string()))
+            .condition_expression("attribute_exists(sortKey)")
+            .update_expression("SET deactivated_at = if_not_exists(deactivated_at, :at)")
+            .expression_attribute_values(":at", AttributeValue::S(at.to_rfc3339()))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+        self.updated_user(result)
+    }
+
+    async fn upsert_user(&self, input: UpsertUserCommandInput) -> Result<UserListQueryResultItem, UpdateUserCommandError> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("user".to_string()))
+            .key("sortKey", AttributeValue::S(input.user_id.clone()))
+            .update_expression("SET email = :email, #name = :name, #roles = :roles, synced_at = :synced_at REMOVE removed_at")
+            .expression_attribute_names("#name", "name")
+            .expression_attribute_names("#roles", "roles")
+            .expression_attribute_values(":email", AttributeValue::S(input.email))
+            .expression_attribute_values(":name", AttributeValue::S(input.name))
+            .expression_attribute_values(
+                ":roles",
+                AttributeValue::L(input.roles.into_iter().map(AttributeValue::S).collect()),
+            )
+            .expression_attribute_values(":synced_at", AttributeValue::S(input.synced_at.to_rfc3339()))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+        // without a condition the update always finds (or creates) the user
+        self.updated_user(result)?.ok_or_else(|| {
+            UpdateUserCommandError(QCError::new("upserted user was not returned".to_string(), None))
+        })
+    }
+
+    async fn set_role_overrides(&self, user_id: &str, input: SetRoleOverridesCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        let list = |roles: Vec<String>| AttributeValue::L(roles.into_iter().map(AttributeValue::S).collect());
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("user".to_string()))
+            .key("sortKey", AttributeValue::S(user_id.to_string()))
+            .condition_expression("attribute_exists(sortKey)")
+            .update_expression("SET granted_roles = :granted, revoked_roles = :revoked")
+            .expression_attribute_values(":granted", list(input.granted_roles))
+            .expression_attribute_values(":revoked", list(input.revoked_roles))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+        self.updated_user(result)
+    }
+
+    async fn mark_user_removed(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table)
+            .key("partitionKey", AttributeValue::S("user".to_string()))
+            .key("sortKey", AttributeValue::S(user_id.to_string()))
+            .condition_expression("attribute_exists(sortKey)")
+            .update_expression("SET removed_at = if_not_exists(removed_at, :at)")
+            .expression_attribute_values(":at", AttributeValue::S(at.to_rfc3339()))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+        self.updated_user(result)
+    }
+}
+
+/// Where the users live, picked once at startup from `STORAGE_BACKEND`.
+#[derive(Clone)]
+pub enum UserQCCollection {
+    Dynamo(DynamoUserQCCollection),
+    Memory(InMemoryUserQCCollection),
+    Sql(SqlUserQCCollection),
+}
+
+impl IUserQCCollection for UserQCCollection {
+    async fn search_users(&self, query: SearchUsersQuery) -> Result<UserSearchPage, SearchUsersQueryError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.search_users(query).await,
+            UserQCCollection::Memory(collection) => collection.search_users(query).await,
+            UserQCCollection::Sql(collection) => collection.search_users(query).await,
+        }
+    }
+
+    async fn find_user_by_id(&self, user_id: &str) -> Result<Option<UserListQueryResultItem>, FindUserByIdQueryError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.find_user_by_id(user_id).await,
+            UserQCCollection::Memory(collection) => collection.find_user_by_id(user_id).await,
+            UserQCCollection::Sql(collection) => collection.find_user_by_id(user_id).await,
+        }
+    }
+
+    async fn find_users_by_ids(&self, user_ids: Vec<String>) -> Result<Vec<UserListQueryResultItem>, FindUsersByIdsQueryError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.find_users_by_ids(user_ids).await,
+            UserQCCollection::Memory(collection) => collection.find_users_by_ids(user_ids).await,
+            UserQCCollection::Sql(collection) => collection.find_users_by_ids(user_ids).await,
+        }
+    }
+
+    async fn list_users(&self) -> Result<Vec<UserListQueryResultItem>, ListUsersQueryError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.list_users().await,
+            UserQCCollection::Memory(collection) => collection.list_users().await,
+            UserQCCollection::Sql(collection) => collection.list_users().await,
+        }
+    }
+
+    async fn create_user(&self, input: CreateUserCommandInput) -> Result<UserListQueryResultItem, CreateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.create_user(input).await,
+            UserQCCollection::Memory(collection) => collection.create_user(input).await,
+            UserQCCollection::Sql(collection) => collection.create_user(input).await,
+        }
+    }
+
+    async fn update_user(&self, user_id: &str, input: UpdateUserCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.update_user(user_id, input).await,
+            UserQCCollection::Memory(collection) => collection.update_user(user_id, input).await,
+            UserQCCollection::Sql(collection) => collection.update_user(user_id, input).await,
+        }
+    }
+
+    async fn deactivate_user(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.deactivate_user(user_id, at).await,
+            UserQCCollection::Memory(collection) => collection.deactivate_user(user_id, at).await,
+            UserQCCollection::Sql(collection) => collection.deactivate_user(user_id, at).await,
+        }
+    }
+
+    async fn upsert_user(&self, input: UpsertUserCommandInput) -> Result<UserListQueryResultItem, UpdateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.upsert_user(input).await,
+            UserQCCollection::Memory(collection) => collection.upsert_user(input).await,
+            UserQCCollection::Sql(collection) => collection.upsert_user(input).await,
+        }
+    }
+
+    async fn set_role_overrides(&self, user_id: &str, input: SetRoleOverridesCommandInput) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.set_role_overrides(user_id, input).await,
+            UserQCCollection::Memory(collection) => collection.set_role_overrides(user_id, input).await,
+            UserQCCollection::Sql(collection) => collection.set_role_overrides(user_id, input).await,
+        }
+    }
+
+    async fn mark_user_removed(&self, user_id: &str, at: DateTime<Utc>) -> Result<Option<UserListQueryResultItem>, UpdateUserCommandError> {
+        match self {
+            UserQCCollection::Dynamo(collection) => collection.mark_user_removed(user_id, at).await,
+            UserQCCollection::Memory(collection) => collection.mark_user_removed(user_id, at).await,
+            UserQCCollection::Sql(collection) => collection.mark_user_removed(user_id, at).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(user_id: &str, name: &str, email: &str, roles: &[&str]) -> UserListQueryResultItem {
+        UserListQueryResultItem {
+            user_id: user_id.to_string(),
+            email: email.to_string(),
+            name: name.to_string(),
+            deactivated_at: None,
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            removed_at: None,
+            granted_roles: vec![],
+            revoked_roles: vec![],
+        }
+    }
+
+    fn query() -> SearchUsersQuery {
+        SearchUsersQuery {
+            search: None,
+            roles: vec![],
+            active_only: false,
+            exclude_user_ids: HashSet::new(),
+            limit: 50,
+            cursor: None,
+        }
+    }
+
+    #[test]
+    fn test_search_matches_name_words_and_email_prefix() {
+        let ada = user("auth0|1", "Ada Lovelace", "countess@example.com", &["Viewer"]);
+        let search = |text: &str| SearchUsersQuery { search: Some(text.to_string()), ..query() };
+        assert!(search("ada").matches(&ada));
+        assert!(search("LOVE").matches(&ada));
+        assert!(search("Count").matches(&ada));
+        assert!(!search("example").matches(&ada));
+        assert!(!search("lace").matches(&ada));
+    }
+
+    #[test]
+    fn test_search_filters_roles_activity_and_exclusions() {
+        let mut ada = user("auth0|1", "Ada Lovelace", "ada@example.com", &["Viewer"]);
+        assert!(SearchUsersQuery { roles: vec!["viewer".to_string(), "Admin".to_string()], ..query() }.matches(&ada));
+        assert!(!SearchUsersQuery { roles: vec!["Admin".to_string()], ..query() }.matches(&ada));
+        assert!(!SearchUsersQuery { exclude_user_ids: HashSet::from(["auth0|1".to_string()]), ..query() }.matches(&ada));
+        // users flagged as removed by the sync stay active
+        ada.removed_at = Some("2025-01-01T00:00:00+00:00".to_string());
+        assert!(SearchUsersQuery { active_only: true, ..query() }.matches(&ada));
+        ada.deactivated_at = Some("2025-01-02T00:00:00+00:00".to_string());
+        assert!(query().matches(&ada));
+        assert!(!SearchUsersQuery { active_only: true, ..query() }.matches(&ada));
+    }
+
+    #[test]
+    fn test_role_filter_applies_local_overrides() {
+        let mut ada = user("auth0|1", "Ada Lovelace", "ada@example.com", &["Viewer"]);
+        let admins = || SearchUsersQuery { roles: vec!["admin".to_string()], ..query() };
+        ada.granted_roles = vec!["Admin".to_string()];
+        assert!(admins().matches(&ada));
+        ada.roles = vec!["Admin".to_string()];
+        ada.granted_roles = vec![];
+        ada.revoked_roles = vec!["Admin".to_string()];
+        assert!(!admins().matches(&ada));
+    }
+
+    #[test]
+    fn test_merge_roles() {
+        let roles = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            merge_roles(roles(&["Viewer", "Operator"]), &roles(&["Admin", "Viewer"]), &roles(&["Operator"])),
+            roles(&["Viewer", "Admin"])
+        );
+        assert_eq!(merge_roles(roles(&[]), &roles(&["Admin"]), &roles(&["Admin"])), roles(&[]));
+    }
+
+    #[test]
+    fn test_user_cursor_round_trip() {
+        assert_eq!(decode_user_cursor(&encode_user_cursor("auth0|1")).as_deref(), Some("auth0|1"));
+        assert_eq!(decode_user_cursor("not a cursor!"), None);
     }
 }
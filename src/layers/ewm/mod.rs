@@ -1,3 +1,6 @@
 pub mod main_database;
+pub mod onvif;
 pub mod permanent_stream_server;
-pub mod temporary_stream_server;
\ No newline at end of file
+pub mod rtsp_prober;
+pub mod temporary_stream_server;
+pub mod identity_provider;
\ No newline at end of file
@@ -0,0 +1,473 @@
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use reqwest::StatusCode;
+use sha1::{Digest, Sha1};
+use tokio::net::UdpSocket;
+
+const SOAP_ENVELOPE_NS: &str = "http://www.w3.org/2003/05/soap-envelope";
+const DEVICE_WSDL_NS: &str = "http://www.onvif.org/ver10/device/wsdl";
+const MEDIA_WSDL_NS: &str = "http://www.onvif.org/ver10/media/wsdl";
+const SCHEMA_NS: &str = "http://www.onvif.org/ver10/schema";
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredDevice {
+    pub endpoint_reference: String,
+    pub xaddrs: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInformation {
+    pub manufacturer: String,
+    pub model: String,
+    pub firmware_version: String,
+    pub serial_number: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnvifStreamUri {
+    pub profile_token: String,
+    pub profile_name: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnvifCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnvifError {
+    pub message: String,
+    pub debug_message: String,
+}
+
+impl OnvifError {
+    fn new(message: &str, debug_message: String) -> Self {
+        Self {
+            message: message.to_owned(),
+            debug_message,
+        }
+    }
+}
+
+pub trait IOnvifClient {
+    fn probe(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<DiscoveredDevice>, OnvifError>> + Send;
+
+    fn get_device_information(
+        &self,
+        device_address: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> impl std::future::Future<Output = Result<DeviceInformation, OnvifError>> + Send;
+
+    fn get_stream_uris(
+        &self,
+        device_address: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> impl std::future::Future<Output = Result<Vec<OnvifStreamUri>, OnvifError>> + Send;
+}
+
+#[derive(Clone)]
+pub struct OnvifClient {
+    discovery_address: SocketAddr,
+    probe_timeout: Duration,
+    http_client: reqwest::Client,
+}
+
+impl OnvifClient {
+    pub fn new(discovery_address: SocketAddr, probe_timeout: Duration) -> Self {
+        Self {
+            discovery_address,
+            probe_timeout,
+            http_client: reqwest::Client::builder()
+                .timeout(probe_timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    async fn soap_call(
+        &self,
+        address: &str,
+        body: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> Result<String, OnvifError> {
+        let envelope = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><s:Envelope xmlns:s=\"{}\"><s:Header>{}</s:Header><s:Body>{}</s:Body></s:Envelope>",
+            SOAP_ENVELOPE_NS,
+            credentials.map(security_header).unwrap_or_default(),
+            body
+        );
+        let response = self
+            .http_client
+            .post(address)
+            .header("Content-Type", "application/soap+xml; charset=utf-8")
+            .body(envelope)
+            .send()
+            .await
+            .map_err(|err| OnvifError::new("Failed to reach ONVIF device", err.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|err| OnvifError::new("Failed to read ONVIF response", err.to_string()))?;
+        if status != StatusCode::OK {
+            let reason = first_element_text(&text, "Text").unwrap_or_default();
+            return Err(OnvifError::new(
+                "ONVIF device rejected request",
+                format!("request failed with: {} {}", status, reason),
+            ));
+        }
+        Ok(text)
+    }
+
+    async fn media_address(
+        &self,
+        device_address: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> String {
+        let body = format!(
+            "<tds:GetCapabilities xmlns:tds=\"{}\"><tds:Category>Media</tds:Category></tds:GetCapabilities>",
+            DEVICE_WSDL_NS
+        );
+        match self.soap_call(device_address, &body, credentials).await {
+            Ok(response) => elements(&response, "Media")
+                .first()
+                .and_then(|(_, inner)| first_element_text(inner, "XAddr"))
+                .unwrap_or_else(|| device_address.to_owned()),
+            Err(err) => {
+                tracing::warn!("GetCapabilities failed, using device service for media: {:?}", err);
+                device_address.to_owned()
+            }
+        }
+    }
+}
+
+impl IOnvifClient for OnvifClient {
+    async fn probe(&self) -> Result<Vec<DiscoveredDevice>, OnvifError> {
+        let bind_address = if self.discovery_address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_address)
+            .await
+            .map_err(|err| OnvifError::new("Failed to open discovery socket", err.to_string()))?;
+        socket
+            .send_to(probe_message().as_bytes(), self.discovery_address)
+            .await
+            .map_err(|err| OnvifError::new("Failed to send discovery probe", err.to_string()))?;
+
+        let deadline = tokio::time::Instant::now() + self.probe_timeout;
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut devices = Vec::new();
+        let mut buffer = vec![0u8; 65535];
+        loop {
+            let received =
+                tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await;
+            let (size, _) = match received {
+                Err(_) => break,
+                Ok(Err(err)) => {
+                    return Err(OnvifError::new(
+                        "Failed to read discovery response",
+                        err.to_string(),
+                    ))
+                }
+                Ok(Ok(received)) => received,
+            };
+            let message = String::from_utf8_lossy(&buffer[..size]).to_string();
+            for device in parse_probe_matches(&message) {
+                if seen.insert(device.endpoint_reference.clone()) {
+                    devices.push(device);
+                }
+            }
+        }
+        Ok(devices)
+    }
+
+    async fn get_device_information(
+        &self,
+        device_address: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> Result<DeviceInformation, OnvifError> {
+        let body = format!("<tds:GetDeviceInformation xmlns:tds=\"{}\"/>", DEVICE_WSDL_NS);
+        let response = self.soap_call(device_address, &body, credentials).await?;
+        let field = |name: &str| first_element_text(&response, name).unwrap_or_default();
+        Ok(DeviceInformation {
+            manufacturer: field("Manufacturer"),
+            model: field("Model"),
+            firmware_version: field("FirmwareVersion"),
+            serial_number: field("SerialNumber"),
+        })
+    }
+
+    async fn get_stream_uris(
+        &self,
+        device_address: &str,
+        credentials: Option<&OnvifCredentials>,
+    ) -> Result<Vec<OnvifStreamUri>, OnvifError> {
+        let media_address = self.media_address(device_address, credentials).await;
+        let body = format!("<trt:GetProfiles xmlns:trt=\"{}\"/>", MEDIA_WSDL_NS);
+        let response = self.soap_call(&media_address, &body, credentials).await?;
+
+        let mut stream_uris = Vec::new();
+        for (attributes, inner) in elements(&response, "Profiles") {
+            let Some(token) = attribute(&attributes, "token") else {
+                continue;
+            };
+            let profile_name = first_element_text(&inner, "Name").unwrap_or_else(|| token.clone());
+            let body = format!(
+                "<trt:GetStreamUri xmlns:trt=\"{}\" xmlns:tt=\"{}\"><trt:StreamSetup><tt:Stream>RTP-Unicast</tt:Stream><tt:Transport><tt:Protocol>RTSP</tt:Protocol></tt:Transport></trt:StreamSetup><trt:ProfileToken>{}</trt:ProfileToken></trt:GetStreamUri>",
+                MEDIA_WSDL_NS,
+                SCHEMA_NS,
+                xml_escape(&token)
+            );
+            let response = self.soap_call(&media_address, &body, credentials).await?;
+            if let Some(uri) = first_element_text(&response, "Uri") {
+                stream_uris.push(OnvifStreamUri {
+                    profile_token: token,
+                    profile_name,
+                    uri,
+                });
+            }
+        }
+        Ok(stream_uris)
+    }
+}
+
+fn probe_message() -> String {
+    let id = format!("{:032x}", ulid::Ulid::new().0);
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+        <e:Envelope xmlns:e=\"{}\" xmlns:w=\"http://schemas.xmlsoap.org/ws/2004/08/addressing\" xmlns:d=\"http://schemas.xmlsoap.org/ws/2005/04/discovery\" xmlns:dn=\"http://www.onvif.org/ver10/network/wsdl\">\
+        <e:Header>\
+        <w:MessageID>uuid:{}-{}-{}-{}-{}</w:MessageID>\
+        <w:To e:mustUnderstand=\"true\">urn:schemas-xmlsoap-org:ws:2005:04:discovery</w:To>\
+        <w:Action e:mustUnderstand=\"true\">http://schemas.xmlsoap.org/ws/2005/04/discovery/Probe</w:Action>\
+        </e:Header>\
+        <e:Body><d:Probe><d:Types>dn:NetworkVideoTransmitter</d:Types></d:Probe></e:Body>\
+        </e:Envelope>",
+        SOAP_ENVELOPE_NS,
+        &id[0..8],
+        &id[8..12],
+        &id[12..16],
+        &id[16..20],
+        &id[20..32]
+    )
+}
+
+/// WS-Security UsernameToken with a password digest, as required by most
+/// ONVIF devices for authenticated calls.
+fn security_header(credentials: &OnvifCredentials) -> String {
+    let nonce = ulid::Ulid::new().0.to_be_bytes();
+    let created = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    let mut hasher = Sha1::new();
+    hasher.update(nonce);
+    hasher.update(created.as_bytes());
+    hasher.update(credentials.password.as_bytes());
+    let digest = STANDARD.encode(hasher.finalize());
+    format!(
+        "<wsse:Security s:mustUnderstand=\"1\" xmlns:wsse=\"http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd\" xmlns:wsu=\"http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd\">\
+        <wsse:UsernameToken>\
+        <wsse:Username>{}</wsse:Username>\
+        <wsse:Password Type=\"http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest\">{}</wsse:Password>\
+        <wsse:Nonce EncodingType=\"http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary\">{}</wsse:Nonce>\
+        <wsu:Created>{}</wsu:Created>\
+        </wsse:UsernameToken>\
+        </wsse:Security>",
+        xml_escape(&credentials.username),
+        digest,
+        STANDARD.encode(nonce),
+        created
+    )
+}
+
+fn parse_probe_matches(message: &str) -> Vec<DiscoveredDevice> {
+    elements(message, "ProbeMatch")
+        .into_iter()
+        .filter_map(|(_, inner)| {
+            let endpoint_reference = elements(&inner, "EndpointReference")
+                .first()
+                .and_then(|(_, reference)| first_element_text(reference, "Address"))?;
+            let split = |name: &str| -> Vec<String> {
+                first_element_text(&inner, name)
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(str::to_owned)
+                    .collect()
+            };
+            Some(DiscoveredDevice {
+                endpoint_reference,
+                xaddrs: split("XAddrs"),
+                scopes: split("Scopes"),
+            })
+        })
+        .collect()
+}
+
+/// Returns `(attributes, inner_xml)` for every element with the given local
+/// name, regardless of namespace prefix. Nested elements with the same name
+/// are not supported, which is fine for the ONVIF responses read here.
+fn elements(xml: &str, local_name: &str) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut cursor = 0;
+    while let Some(offset) = xml[cursor..].find('<') {
+        let start = cursor + offset;
+        let Some(tag_end) = xml[start..].find('>').map(|i| start + i) else {
+            break;
+        };
+        let tag = &xml[start + 1..tag_end];
+        cursor = tag_end + 1;
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        let (qualified_name, attributes) = tag
+            .trim_end_matches('/')
+            .split_once(char::is_whitespace)
+            .unwrap_or((tag.trim_end_matches('/'), ""));
+        let name = qualified_name.rsplit(':').next().unwrap_or_default();
+        if name != local_name {
+            continue;
+        }
+        if tag.ends_with('/') {
+            found.push((attributes.to_owned(), String::new()));
+            continue;
+        }
+        let closing = format!("</{}>", qualified_name);
+        let Some(close_start) = xml[cursor..].find(&closing).map(|i| cursor + i) else {
+            break;
+        };
+        found.push((attributes.to_owned(), xml[cursor..close_start].to_owned()));
+        cursor = close_start + closing.len();
+    }
+    found
+}
+
+fn first_element_text(xml: &str, local_name: &str) -> Option<String> {
+    elements(xml, local_name)
+        .into_iter()
+        .next()
+        .map(|(_, inner)| xml_unescape(inner.trim()))
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = attributes
+        .match_indices(&pattern)
+        .find(|(i, _)| *i == 0 || attributes[..*i].ends_with(char::is_whitespace))?
+        .0
+        + pattern.len();
+    let end = attributes[start..].find('"')? + start;
+    Some(xml_unescape(&attributes[start..end]))
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::HeaderMap, routing::post, Router};
+
+    use super::*;
+
+    async fn soap_stand_in(headers: HeaderMap, body: String) -> String {
+        let host = headers.get("host").and_then(|h| h.to_str().ok()).unwrap_or_default();
+        let content = if body.contains("GetDeviceInformation") {
+            "<tds:GetDeviceInformationResponse><tds:Manufacturer>Acme</tds:Manufacturer><tds:Model>Cam 2</tds:Model><tds:FirmwareVersion>1.0</tds:FirmwareVersion><tds:SerialNumber>SN1</tds:SerialNumber></tds:GetDeviceInformationResponse>".to_owned()
+        } else if body.contains("GetCapabilities") {
+            format!("<tds:GetCapabilitiesResponse><tds:Capabilities><tt:Media><tt:XAddr>http://{}/onvif/media</tt:XAddr></tt:Media></tds:Capabilities></tds:GetCapabilitiesResponse>", host)
+        } else if body.contains("GetProfiles") {
+            "<trt:GetProfilesResponse><trt:Profiles token=\"main\" fixed=\"true\"><tt:Name>MainStream</tt:Name></trt:Profiles><trt:Profiles token=\"sub\"><tt:Name>SubStream</tt:Name></trt:Profiles></trt:GetProfilesResponse>".to_owned()
+        } else {
+            let token = if body.contains(">main<") { "main" } else { "sub" };
+            format!("<trt:GetStreamUriResponse><trt:MediaUri><tt:Uri>rtsp://10.0.0.5:554/{}?a=1&amp;b=2</tt:Uri></trt:MediaUri></trt:GetStreamUriResponse>", token)
+        };
+        format!("<s:Envelope xmlns:s=\"{}\"><s:Body>{}</s:Body></s:Envelope>", SOAP_ENVELOPE_NS, content)
+    }
+
+    async fn start_stand_ins() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/onvif/device_service", post(soap_stand_in))
+            .route("/onvif/media", post(soap_stand_in));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let udp_address = udp.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 65535];
+            let (_, from) = udp.recv_from(&mut buffer).await.unwrap();
+            let reply = format!(
+                "<e:Envelope><e:Body><d:ProbeMatches><d:ProbeMatch>\
+                <w:EndpointReference><w:Address>urn:uuid:device-1</w:Address></w:EndpointReference>\
+                <d:Scopes>onvif://www.onvif.org/name/Garage onvif://www.onvif.org/hardware/Cam2</d:Scopes>\
+                <d:XAddrs>http://{}/onvif/device_service</d:XAddrs>\
+                </d:ProbeMatch></d:ProbeMatches></e:Body></e:Envelope>",
+                http_address
+            );
+            udp.send_to(reply.as_bytes(), from).await.unwrap();
+            udp.send_to(reply.as_bytes(), from).await.unwrap();
+        });
+        udp_address
+    }
+
+    #[tokio::test]
+    async fn test_probe_and_fetch_stream_uris_from_stand_in() {
+        let discovery_address = start_stand_ins().await;
+        let client = OnvifClient::new(discovery_address, Duration::from_millis(300));
+
+        let devices = client.probe().await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].endpoint_reference, "urn:uuid:device-1");
+        assert_eq!(devices[0].scopes.len(), 2);
+
+        let credentials = OnvifCredentials {
+            username: "admin".to_owned(),
+            password: "secret".to_owned(),
+        };
+        let information = client
+            .get_device_information(&devices[0].xaddrs[0], Some(&credentials))
+            .await
+            .unwrap();
+        assert_eq!(information.manufacturer, "Acme");
+        assert_eq!(information.model, "Cam 2");
+
+        let stream_uris = client
+            .get_stream_uris(&devices[0].xaddrs[0], Some(&credentials))
+            .await
+            .unwrap();
+        assert_eq!(stream_uris.len(), 2);
+        assert_eq!(stream_uris[0].profile_name, "MainStream");
+        assert_eq!(stream_uris[0].uri, "rtsp://10.0.0.5:554/main?a=1&b=2");
+        assert_eq!(stream_uris[1].profile_token, "sub");
+    }
+
+    #[test]
+    fn test_elements_reads_attributes_and_self_closing_tags() {
+        let xml = "<a:List><a:Item token=\"x\"/><a:Item token=\"y\"><b:Name>Y</b:Name></a:Item></a:List>";
+        let items = elements(xml, "Item");
+        assert_eq!(items.len(), 2);
+        assert_eq!(attribute(&items[0].0, "token").as_deref(), Some("x"));
+        assert_eq!(first_element_text(&items[1].1, "Name").as_deref(), Some("Y"));
+    }
+}
@@ -0,0 +1,599 @@
+use std::{net::SocketAddr, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::{Digest, Md5};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use url::Url;
+
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamDescription {
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub framerate: Option<f64>,
+    pub has_audio: bool,
+    pub audio_codec: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RtspProbeError {
+    pub message: String,
+    pub debug_message: String,
+}
+
+impl RtspProbeError {
+    fn new(message: &str, debug_message: String) -> Self {
+        Self {
+            message: message.to_owned(),
+            debug_message,
+        }
+    }
+}
+
+pub trait IRtspProber {
+    /// Sends DESCRIBE for `source_url` over a connection to one of
+    /// `addresses`, which the caller resolved and vetted; the url's host is
+    /// not resolved again.
+    fn describe(
+        &self,
+        source_url: &str,
+        addresses: &[SocketAddr],
+    ) -> impl std::future::Future<Output = Result<StreamDescription, RtspProbeError>> + Send;
+}
+
+#[derive(Clone)]
+pub struct RtspProber {
+    timeout: Duration,
+}
+
+impl RtspProber {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+struct RtspResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl RtspResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl IRtspProber for RtspProber {
+    async fn describe(&self, source_url: &str, addresses: &[SocketAddr]) -> Result<StreamDescription, RtspProbeError> {
+        tokio::time::timeout(self.timeout, describe_inner(source_url, addresses))
+            .await
+            .map_err(|_| {
+                RtspProbeError::new(
+                    "Timed out while probing stream",
+                    format!("no DESCRIBE response after {:?}", self.timeout),
+                )
+            })?
+    }
+}
+
+async fn describe_inner(source_url: &str, addresses: &[SocketAddr]) -> Result<StreamDescription, RtspProbeError> {
+    let url = Url::parse(source_url)
+        .map_err(|err| RtspProbeError::new("Invalid stream url", format!("{:?}", err)))?;
+    if url.scheme() != "rtsp" {
+        return Err(RtspProbeError::new(
+            "Only plain rtsp urls can be probed",
+            format!("unsupported scheme {}", url.scheme()),
+        ));
+    }
+    if url.host_str().is_none() || addresses.is_empty() {
+        return Err(RtspProbeError::new("Stream url has no host", source_url.to_owned()));
+    }
+    let username = url.username().to_owned();
+    let password = url.password().unwrap_or_default().to_owned();
+
+    let mut request_url = url.clone();
+    let _ = request_url.set_username("");
+    let _ = request_url.set_password(None);
+    let request_url = request_url.to_string();
+
+    let mut stream = TcpStream::connect(addresses)
+        .await
+        .map_err(|err| RtspProbeError::new("Failed to connect to stream", format!("{:?}", err)))?;
+
+    let mut response = send_describe(&mut stream, &request_url, 1, None).await?;
+    if response.status == 401 && !username.is_empty() {
+        let challenge = response.header("WWW-Authenticate").unwrap_or_default().to_owned();
+        let authorization =
+            build_authorization(&challenge, &username, &password, &request_url)?;
+        response = send_describe(&mut stream, &request_url, 2, Some(&authorization)).await?;
+    }
+
+    if response.status != 200 {
+        return Err(RtspProbeError::new(
+            "Stream server rejected DESCRIBE request",
+            format!("DESCRIBE failed with status {}", response.status),
+        ));
+    }
+
+    Ok(parse_sdp(&response.body))
+}
+
+async fn send_describe(
+    stream: &mut TcpStream,
+    request_url: &str,
+    cseq: u32,
+    authorization: Option<&str>,
+) -> Result<RtspResponse, RtspProbeError> {
+    let mut request = format!(
+        "DESCRIBE {} RTSP/1.0\r\nCSeq: {}\r\nAccept: application/sdp\r\nUser-Agent: user_api_server\r\n",
+        request_url, cseq
+    );
+    if let Some(authorization) = authorization {
+        request.push_str(&format!("Authorization: {}\r\n", authorization));
+    }
+    request.push_str("\r\n");
+
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|err| RtspProbeError::new("Failed to send DESCRIBE request", format!("{:?}", err)))?;
+
+    read_response(stream).await
+}
+
+async fn read_response(stream: &mut TcpStream) -> Result<RtspResponse, RtspProbeError> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buffer.len() > MAX_RESPONSE_SIZE {
+            return Err(RtspProbeError::new(
+                "Stream server response is too large",
+                format!("no end of headers after {} bytes", buffer.len()),
+            ));
+        }
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|err| RtspProbeError::new("Failed to read DESCRIBE response", format!("{:?}", err)))?;
+        if read == 0 {
+            return Err(RtspProbeError::new(
+                "Stream server closed the connection",
+                "connection closed before end of headers".to_owned(),
+            ));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| RtspProbeError::new("Invalid RTSP response", status_line.to_owned()))?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_RESPONSE_SIZE);
+
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|err| RtspProbeError::new("Failed to read DESCRIBE response", format!("{:?}", err)))?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(RtspResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+fn build_authorization(
+    challenge: &str,
+    username: &str,
+    password: &str,
+    request_url: &str,
+) -> Result<String, RtspProbeError> {
+    if challenge.get(..6).is_some_and(|scheme| scheme.eq_ignore_ascii_case("digest")) {
+        let realm = challenge_param(challenge, "realm").unwrap_or_default();
+        let nonce = challenge_param(challenge, "nonce").ok_or_else(|| {
+            RtspProbeError::new("Invalid digest challenge", challenge.to_owned())
+        })?;
+        let ha1 = md5_hex(&format!("{}:{}:{}", username, realm, password));
+        let ha2 = md5_hex(&format!("DESCRIBE:{}", request_url));
+        let response = md5_hex(&format!("{}:{}:{}", ha1, nonce, ha2));
+        return Ok(format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", response=\"{}\"",
+            username, realm, nonce, request_url, response
+        ));
+    }
+    if challenge.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("basic")) {
+        let credentials = STANDARD.encode(format!("{}:{}", username, password));
+        return Ok(format!("Basic {}", credentials));
+    }
+    Err(RtspProbeError::new(
+        "Unsupported authentication scheme",
+        challenge.to_owned(),
+    ))
+}
+
+fn challenge_param(challenge: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = challenge.find(&pattern)? + pattern.len();
+    let end = challenge[start..].find('"')? + start;
+    Some(challenge[start..end].to_owned())
+}
+
+fn md5_hex(input: &str) -> String {
+    Md5::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Extracts codec, resolution, framerate and audio presence from an SDP
+/// session description. Only the first video and audio media sections are
+/// considered.
+pub fn parse_sdp(sdp: &str) -> StreamDescription {
+    let mut description = StreamDescription::default();
+    let mut current_media: Option<&str> = None;
+    let mut media_payloads: Vec<String> = Vec::new();
+    let mut seen_video = false;
+    let mut seen_audio = false;
+
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            let mut parts = media.split_whitespace();
+            let kind = parts.next().unwrap_or_default();
+            media_payloads = parts.skip(2).map(str::to_owned).collect();
+            current_media = match kind {
+                "video" if !seen_video => {
+                    seen_video = true;
+                    description.video_codec = media_payloads
+                        .first()
+                        .and_then(|pt| static_payload_codec(pt))
+                        .map(str::to_owned);
+                    Some("video")
+                }
+                "audio" if !seen_audio => {
+                    seen_audio = true;
+                    description.has_audio = true;
+                    description.audio_codec = media_payloads
+                        .first()
+                        .and_then(|pt| static_payload_codec(pt))
+                        .map(str::to_owned);
+                    Some("audio")
+                }
+                _ => None,
+            };
+            continue;
+        }
+
+        let Some(media) = current_media else {
+            continue;
+        };
+        let Some(attribute) = line.strip_prefix("a=") else {
+            continue;
+        };
+        let (name, value) = attribute.split_once(':').unwrap_or((attribute, ""));
+
+        match (media, name) {
+            (_, "rtpmap") => {
+                let Some((payload, encoding)) = value.split_once(' ') else {
+                    continue;
+                };
+                if media_payloads.first().map(String::as_str) != Some(payload) {
+                    continue;
+                }
+                let codec = encoding.split('/').next().unwrap_or_default().to_uppercase();
+                if media == "video" {
+                    description.video_codec = Some(codec);
+                } else {
+                    description.audio_codec = Some(codec);
+                }
+            }
+            ("video", "framerate") | ("video", "x-framerate") => {
+                description.framerate = value.trim().parse::<f64>().ok();
+            }
+            ("video", "framesize") => {
+                let size = value.split_whitespace().nth(1).unwrap_or_default();
+                if let Some((w, h)) = size.split_once('-') {
+                    set_resolution(&mut description, w, h);
+                }
+            }
+            ("video", "x-dimensions") => {
+                if let Some((w, h)) = value.split_once(',') {
+                    set_resolution(&mut description, w, h);
+                }
+            }
+            ("video", "fmtp") if description.width.is_none() => {
+                if let Some((w, h)) = sprop_resolution(value) {
+                    description.width = Some(w);
+                    description.height = Some(h);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    description
+}
+
+fn set_resolution(description: &mut StreamDescription, width: &str, height: &str) {
+    if let (Ok(w), Ok(h)) = (width.trim().parse::<u32>(), height.trim().parse::<u32>()) {
+        description.width = Some(w);
+        description.height = Some(h);
+    }
+}
+
+fn static_payload_codec(payload_type: &str) -> Option<&'static str> {
+    match payload_type {
+        "0" => Some("PCMU"),
+        "8" => Some("PCMA"),
+        "14" => Some("MPA"),
+        "26" => Some("JPEG"),
+        "32" => Some("MPV"),
+        _ => None,
+    }
+}
+
+/// Reads the picture size from the H.264 sequence parameter set carried in
+/// `sprop-parameter-sets`, for cameras that do not advertise it otherwise.
+fn sprop_resolution(fmtp: &str) -> Option<(u32, u32)> {
+    let sets = fmtp
+        .split(';')
+        .filter_map(|p| p.trim().split_once('='))
+        .find(|(k, _)| k.ends_with("sprop-parameter-sets"))?
+        .1;
+    let sps = STANDARD.decode(sets.split(',').next()?.trim()).ok()?;
+    parse_h264_sps_resolution(&sps)
+}
+
+struct BitReader {
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl BitReader {
+    fn bit(&mut self) -> Option<u32> {
+        let byte = *self.data.get(self.position / 8)?;
+        let bit = (byte >> (7 - (self.position % 8))) & 1;
+        self.position += 1;
+        Some(bit as u32)
+    }
+
+    fn bits(&mut self, count: usize) -> Option<u32> {
+        (0..count).try_fold(0u32, |acc, _| Some((acc << 1) | self.bit()?))
+    }
+
+    fn ue(&mut self) -> Option<u32> {
+        let mut leading_zeros = 0;
+        while self.bit()? == 0 {
+            leading_zeros += 1;
+            if leading_zeros > 31 {
+                return None;
+            }
+        }
+        Some((1u32 << leading_zeros) - 1 + self.bits(leading_zeros)?)
+    }
+
+    fn se(&mut self) -> Option<i32> {
+        let value = self.ue()?;
+        Some(if value % 2 == 1 {
+            value.div_ceil(2) as i32
+        } else {
+            -((value / 2) as i32)
+        })
+    }
+}
+
+fn parse_h264_sps_resolution(nal: &[u8]) -> Option<(u32, u32)> {
+    if nal.first()? & 0x1f != 7 {
+        return None;
+    }
+    let mut rbsp = Vec::with_capacity(nal.len());
+    let mut zeros = 0;
+    for &byte in &nal[1..] {
+        if zeros >= 2 && byte == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if byte == 0 { zeros + 1 } else { 0 };
+        rbsp.push(byte);
+    }
+    let mut r = BitReader {
+        data: rbsp,
+        position: 0,
+    };
+
+    let profile_idc = r.bits(8)?;
+    r.bits(16)?;
+    r.ue()?;
+    let mut chroma_format_idc = 1;
+    if [100, 110, 122, 244, 44, 83, 86, 118, 128, 138, 139, 134, 135].contains(&profile_idc) {
+        chroma_format_idc = r.ue()?;
+        if chroma_format_idc == 3 {
+            r.bit()?;
+        }
+        r.ue()?;
+        r.ue()?;
+        r.bit()?;
+        if r.bit()? == 1 {
+            let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if r.bit()? == 1 {
+                    let size = if i < 6 { 16 } else { 64 };
+                    let (mut last, mut next) = (8i32, 8i32);
+                    for _ in 0..size {
+                        if next != 0 {
+                            next = last.checked_add(r.se()?)?.rem_euclid(256);
+                        }
+                        if next != 0 {
+                            last = next;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    r.ue()?;
+    let pic_order_cnt_type = r.ue()?;
+    if pic_order_cnt_type == 0 {
+        r.ue()?;
+    } else if pic_order_cnt_type == 1 {
+        r.bit()?;
+        r.se()?;
+        r.se()?;
+        for _ in 0..r.ue()? {
+            r.se()?;
+        }
+    }
+    r.ue()?;
+    r.bit()?;
+    let width_in_mbs = r.ue()?.checked_add(1)?;
+    let height_in_map_units = r.ue()?.checked_add(1)?;
+    let frame_mbs_only = r.bit()?;
+    if frame_mbs_only == 0 {
+        r.bit()?;
+    }
+    r.bit()?;
+
+    // the fields come from the camera, so oversized values are rejected
+    // rather than trusted to fit
+    let mut width = width_in_mbs.checked_mul(16)?;
+    let mut height = (2 - frame_mbs_only).checked_mul(height_in_map_units)?.checked_mul(16)?;
+    if r.bit()? == 1 {
+        let (left, right, top, bottom) = (r.ue()?, r.ue()?, r.ue()?, r.ue()?);
+        let crop_x = if chroma_format_idc == 0 || chroma_format_idc == 3 { 1 } else { 2 };
+        let crop_y = (if chroma_format_idc == 1 { 2 } else { 1 }) * (2 - frame_mbs_only);
+        width = width.checked_sub(left.checked_add(right)?.checked_mul(crop_x)?)?;
+        height = height.checked_sub(top.checked_add(bottom)?.checked_mul(crop_y)?)?;
+    }
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sdp_with_explicit_attributes() {
+        let sdp = "v=0\r\n\
+            o=- 0 0 IN IP4 127.0.0.1\r\n\
+            s=Camera\r\n\
+            m=video 0 RTP/AVP 96\r\n\
+            a=rtpmap:96 H265/90000\r\n\
+            a=framerate:25\r\n\
+            a=framesize:96 2560-1440\r\n\
+            m=audio 0 RTP/AVP 97\r\n\
+            a=rtpmap:97 MPEG4-GENERIC/16000/1\r\n";
+
+        let description = parse_sdp(sdp);
+        assert_eq!(description.video_codec.as_deref(), Some("H265"));
+        assert_eq!(description.width, Some(2560));
+        assert_eq!(description.height, Some(1440));
+        assert_eq!(description.framerate, Some(25.0));
+        assert!(description.has_audio);
+        assert_eq!(description.audio_codec.as_deref(), Some("MPEG4-GENERIC"));
+    }
+
+    #[test]
+    fn test_parse_sdp_reads_resolution_from_sps() {
+        let sdp = "v=0\n\
+            m=video 0 RTP/AVP 96\n\
+            a=rtpmap:96 H264/90000\n\
+            a=fmtp:96 packetization-mode=1;profile-level-id=640028;sprop-parameter-sets=Z2QAKKzoB4AiflQ=,aO48sA==\n";
+
+        let description = parse_sdp(sdp);
+        assert_eq!(description.video_codec.as_deref(), Some("H264"));
+        assert_eq!(description.width, Some(1920));
+        assert_eq!(description.height, Some(1080));
+        assert!(!description.has_audio);
+    }
+
+    #[test]
+    fn test_oversized_sps_fields_are_rejected() {
+        let ue = |value: u32| {
+            let code = format!("{:b}", u64::from(value) + 1);
+            format!("{}{}", "0".repeat(code.len() - 1), code)
+        };
+        // baseline profile, pic_order_cnt_type 2, one map unit high, no cropping
+        let sps = |pic_width_in_mbs_minus1: u32| {
+            let bits = format!(
+                "{:08b}{}{}{}{}{}0{}{}11",
+                66,
+                "0".repeat(16),
+                ue(0),
+                ue(0),
+                ue(2),
+                ue(0),
+                ue(pic_width_in_mbs_minus1),
+                ue(0)
+            );
+            let mut nal = vec![0x67];
+            nal.extend(bits.as_bytes().chunks(8).map(|chunk| {
+                chunk.iter().fold(0u8, |acc, bit| (acc << 1) | (bit - b'0')) << (8 - chunk.len())
+            }));
+            nal
+        };
+        assert_eq!(parse_h264_sps_resolution(&sps(119)), Some((1920, 16)));
+        assert_eq!(parse_h264_sps_resolution(&sps(1 << 30)), None);
+    }
+
+    #[test]
+    fn test_non_ascii_challenge_is_unsupported() {
+        assert!(build_authorization("Basié realm=\"cam\"", "admin", "secret", "rtsp://10.0.0.2/stream").is_err());
+        assert!(build_authorization("Digesé realm=\"cam\"", "admin", "secret", "rtsp://10.0.0.2/stream").is_err());
+    }
+
+    #[test]
+    fn test_parse_sdp_static_payload_types() {
+        let sdp = "m=video 0 RTP/AVP 26\nm=audio 0 RTP/AVP 0\n";
+
+        let description = parse_sdp(sdp);
+        assert_eq!(description.video_codec.as_deref(), Some("JPEG"));
+        assert_eq!(description.audio_codec.as_deref(), Some("PCMU"));
+        assert!(description.has_audio);
+    }
+
+    #[test]
+    fn test_digest_authorization() {
+        let authorization = build_authorization(
+            "Digest realm=\"cam\", nonce=\"abc\"",
+            "admin",
+            "secret",
+            "rtsp://10.0.0.2/stream",
+        )
+        .unwrap();
+        assert!(authorization.starts_with("Digest username=\"admin\""));
+        assert!(authorization.contains("nonce=\"abc\""));
+    }
+}
@@ -1,36 +1,149 @@
+use std::{str::FromStr, time::Duration};
+
 use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::layers::ewm::permanent_stream_server::AddCreationOutput;
-
+/// `url` is the RTSP url; the relay may also offer HLS and WebRTC (WHEP)
+/// playback of the same stream.
 pub struct TemporaryStreamOutput {
     pub id: String,
     pub name: String,
     pub url: String,
+    pub hls_url: Option<String>,
+    pub whep_url: Option<String>,
     pub expiration_date: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamProtocol {
+    Rtsp,
+    Hls,
+    Whep,
+}
+
+impl StreamProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamProtocol::Rtsp => "rtsp",
+            StreamProtocol::Hls => "hls",
+            StreamProtocol::Whep => "whep",
+        }
+    }
+}
+
+impl FromStr for StreamProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rtsp" => Ok(StreamProtocol::Rtsp),
+            "hls" => Ok(StreamProtocol::Hls),
+            "whep" => Ok(StreamProtocol::Whep),
+            other => Err(format!("unknown stream protocol {:?}", other)),
+        }
+    }
+}
+
+/// Ordered from lowest to highest so role limits can be compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StreamQuality {
+    Downscaled,
+    Original,
+}
+
+impl StreamQuality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamQuality::Downscaled => "downscaled",
+            StreamQuality::Original => "original",
+        }
+    }
+}
+
+impl FromStr for StreamQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "downscaled" => Ok(StreamQuality::Downscaled),
+            "original" => Ok(StreamQuality::Original),
+            other => Err(format!("unknown stream quality {:?}", other)),
+        }
+    }
+}
+
+/// `ttl_seconds: None` leaves the lifetime up to the temporary stream server.
+#[derive(Debug, Clone)]
+pub struct TemporaryStreamOptions {
+    pub quality: StreamQuality,
+    pub ttl_seconds: Option<u64>,
+}
+
 #[derive(Debug)]
 pub struct TemporaryStreamServerError {
-    message: String,
-    debug_message: String,
+    pub message: String,
+    pub debug_message: String,
 }
 pub trait ITemporaryStreamServer {
     fn get_stream(
         &self,
         camera_id: &str,
         source_camera_stream_url: &str,
+        options: &TemporaryStreamOptions,
     ) -> impl std::future::Future<Output = Result<TemporaryStreamOutput, TemporaryStreamServerError>>
            + Send;
+
+    fn delete_stream(
+        &self,
+        stream_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), TemporaryStreamServerError>> + Send;
+
+    fn list_streams(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<TemporaryStreamOutput>, TemporaryStreamServerError>>
+           + Send;
+
+    /// Fetches a playback resource (playlist, segment) served by the relay.
+    /// Non-success statuses are returned as they are so callers can pass
+    /// them on.
+    fn fetch_resource(
+        &self,
+        url: &str,
+    ) -> impl std::future::Future<Output = Result<StreamResource, TemporaryStreamServerError>> + Send;
+}
+
+pub struct StreamResource {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest wait for the next bytes of a response, so a stalled relay
+/// releases the request instead of holding it forever
+const READ_TIMEOUT: Duration = Duration::from_secs(15);
+/// Playlists and segments are buffered before they are passed on, so
+/// anything larger is refused
+const MAX_RESOURCE_BYTES: usize = 32 * 1024 * 1024;
+
+/// The client every request to the relay goes through; clones share its
+/// connection pool.
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .expect("failed to build the temporary stream server client")
 }
 
 pub struct TemporaryStreamServer {
     base_url: String,
+    client: reqwest::Client,
 }
 impl TemporaryStreamServer {
-    pub fn new(base_url: String) -> Self {
-        Self { base_url }
+    pub fn new(base_url: String, client: reqwest::Client) -> Self {
+        Self { base_url, client }
     }
 }
 #[derive(Serialize)]
@@ -39,12 +152,16 @@ struct CreateStreamRequestBody {
     source_url: String,
     down_scale: bool,
     expirable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ttl_seconds: Option<u64>,
 }
 #[derive(Deserialize)]
 pub struct AddStreamOutput {
     id: String,
     name: String,
     url: String,
+    hls_url: Option<String>,
+    whep_url: Option<String>,
     expiration_date: Option<String>,
 }
 impl ITemporaryStreamServer for TemporaryStreamServer {
@@ -52,16 +169,18 @@ impl ITemporaryStreamServer for TemporaryStreamServer {
         &self,
         camera_id: &str,
         source_camera_stream_url: &str,
+        options: &TemporaryStreamOptions,
     ) -> Result<TemporaryStreamOutput, TemporaryStreamServerError> {
         let request_body = CreateStreamRequestBody {
             name: camera_id.to_owned(),
             source_url: source_camera_stream_url.to_owned(),
-            down_scale: false,
+            down_scale: options.quality == StreamQuality::Downscaled,
             expirable: true,
+            ttl_seconds: options.ttl_seconds,
         };
         let url = format!("{}/streams", self.base_url);
-        let client = reqwest::Client::new();
-        let response = client
+        let response = self
+            .client
             .post(&url)
             .json(&request_body)
             .send()
@@ -86,6 +205,106 @@ impl ITemporaryStreamServer for TemporaryStreamServer {
                     message: "Failed to parse stream creation output".to_string(),
                     debug_message: format!("{:?}", err),
                 })?;
+        stream_output.try_into()
+    }
+
+    async fn delete_stream(&self, stream_id: &str) -> Result<(), TemporaryStreamServerError> {
+        let url = format!("{}/streams/{}", self.base_url, stream_id);
+        let response = self
+            .client
+            .delete(&url)
+            .send()
+            .await
+            .map_err(|err| TemporaryStreamServerError {
+                message: "Failed to delete temporary stream".to_string(),
+                debug_message: err.to_string(),
+            })?;
+        let status = response.status();
+        tracing::info!("delete stream status code: {}", status);
+        // a stream that already expired on the server is as good as deleted
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(TemporaryStreamServerError {
+                message: format!("request failed with: {}", status),
+                debug_message: format!("request failed with: {}", status),
+            });
+        }
+        Ok(())
+    }
+
+    async fn list_streams(&self) -> Result<Vec<TemporaryStreamOutput>, TemporaryStreamServerError> {
+        let url = format!("{}/streams", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|err| TemporaryStreamServerError {
+                message: "Failed to list temporary streams".to_string(),
+                debug_message: err.to_string(),
+            })?;
+        let status = response.status();
+        if status != StatusCode::OK {
+            return Err(TemporaryStreamServerError {
+                message: format!("request failed with: {}", status),
+                debug_message: format!("request failed with: {}", status),
+            });
+        }
+        let streams: Vec<AddStreamOutput> =
+            response
+                .json()
+                .await
+                .map_err(|err| TemporaryStreamServerError {
+                    message: "Failed to parse stream list".to_string(),
+                    debug_message: format!("{:?}", err),
+                })?;
+        streams.into_iter().map(|stream| stream.try_into()).collect()
+    }
+
+    async fn fetch_resource(&self, url: &str) -> Result<StreamResource, TemporaryStreamServerError> {
+        let mut response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| TemporaryStreamServerError {
+                message: "Failed to fetch stream resource".to_string(),
+                debug_message: err.to_string(),
+            })?;
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let too_large = |size: u64| TemporaryStreamServerError {
+            message: "Stream resource is too large".to_string(),
+            debug_message: format!("{} exceeds {} bytes", size, MAX_RESOURCE_BYTES),
+        };
+        if let Some(length) = response.content_length().filter(|length| *length > MAX_RESOURCE_BYTES as u64) {
+            return Err(too_large(length));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|err| TemporaryStreamServerError {
+            message: "Failed to read stream resource".to_string(),
+            debug_message: err.to_string(),
+        })? {
+            if body.len() + chunk.len() > MAX_RESOURCE_BYTES {
+                return Err(too_large((body.len() + chunk.len()) as u64));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(StreamResource {
+            status,
+            content_type,
+            body,
+        })
+    }
+}
+
+impl TryFrom<AddStreamOutput> for TemporaryStreamOutput {
+    type Error = TemporaryStreamServerError;
+
+    fn try_from(stream_output: AddStreamOutput) -> Result<Self, Self::Error> {
         let expiration_date = match stream_output.expiration_date {
             Some(ed) => Some(DateTime::parse_from_rfc3339(&ed).map_err(|err| TemporaryStreamServerError {
                 message: "Failed to parse expiration date".to_string(),
@@ -100,8 +319,113 @@ impl ITemporaryStreamServer for TemporaryStreamServer {
             id: stream_output.id,
             name: stream_output.name,
             url: stream_output.url,
+            hls_url: stream_output.hls_url,
+            whep_url: stream_output.whep_url,
             expiration_date,
         };
         Ok(stream_output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode as HttpStatusCode,
+        routing::{delete, get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    type Requests = Arc<Mutex<Vec<Value>>>;
+
+    async fn create_stream(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+        requests.lock().unwrap().push(body);
+        Json(json!({
+            "id": "stream-1",
+            "name": "camera-1",
+            "url": "rtsp://relay/stream-1",
+            "hls_url": "http://relay/stream-1/index.m3u8",
+            "whep_url": "http://relay/stream-1/whep",
+            "expiration_date": "2030-01-01T00:00:00Z"
+        }))
+    }
+
+    async fn list_streams() -> Json<Value> {
+        Json(json!([
+            { "id": "stream-1", "name": "camera-1", "url": "rtsp://relay/stream-1", "expiration_date": "2030-01-01T00:00:00Z" }
+        ]))
+    }
+
+    async fn delete_stream(Path(id): Path<String>) -> HttpStatusCode {
+        match id.as_str() {
+            "stream-1" => HttpStatusCode::NO_CONTENT,
+            _ => HttpStatusCode::NOT_FOUND,
+        }
+    }
+
+    async fn segment(Path(size): Path<usize>) -> Vec<u8> {
+        vec![0; size]
+    }
+
+    async fn start_mock_server() -> (String, Requests) {
+        let requests: Requests = Arc::default();
+        let app = Router::new()
+            .route("/streams", post(create_stream).get(list_streams))
+            .route("/streams/{id}", delete(delete_stream))
+            .route("/segments/{size}", get(segment))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", address), requests)
+    }
+
+    #[tokio::test]
+    async fn test_stream_server_contract() {
+        let (base_url, requests) = start_mock_server().await;
+        let server = TemporaryStreamServer::new(base_url, http_client());
+
+        let options = TemporaryStreamOptions {
+            quality: StreamQuality::Downscaled,
+            ttl_seconds: Some(120),
+        };
+        let stream = server
+            .get_stream("camera-1", "rtsp://relay/permanent/camera-1", &options)
+            .await
+            .unwrap();
+        assert_eq!(stream.url, "rtsp://relay/stream-1");
+        assert_eq!(stream.hls_url.as_deref(), Some("http://relay/stream-1/index.m3u8"));
+        assert_eq!(stream.whep_url.as_deref(), Some("http://relay/stream-1/whep"));
+        assert!(stream.expiration_date.is_some());
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert_eq!(request["down_scale"], json!(true));
+        assert_eq!(request["ttl_seconds"], json!(120));
+
+        let streams = server.list_streams().await.unwrap();
+        assert_eq!(streams.len(), 1);
+        assert!(streams[0].hls_url.is_none());
+
+        server.delete_stream("stream-1").await.unwrap();
+        server.delete_stream("already-gone").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_resource_refuses_oversized_bodies() {
+        let (base_url, _) = start_mock_server().await;
+        let server = TemporaryStreamServer::new(base_url.clone(), http_client());
+
+        let resource = server.fetch_resource(&format!("{}/segments/{}", base_url, 1024)).await.unwrap();
+        assert_eq!(resource.status, 200);
+        assert_eq!(resource.body.len(), 1024);
+        assert!(server
+            .fetch_resource(&format!("{}/segments/{}", base_url, MAX_RESOURCE_BYTES + 1))
+            .await
+            .is_err());
+    }
+}